@@ -0,0 +1,78 @@
+using System;
+
+using Steit.Builtins;
+using Steit.Codec;
+using Steit.Collections;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Steit.Builtins {
+    public sealed partial class RngState : IState {
+        public Path Path { get; }
+
+        public UInt64 Seed { get; private set; }
+        public UInt64 Counter { get; private set; }
+
+        public RngState(Path path = null) {
+            this.Path = path ?? Path.Root;
+        }
+
+        public static event EventHandler<FieldUpdateEventArgs<UInt64, RngState>> OnSeedUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<UInt64, RngState>> OnCounterUpdate;
+
+        public static void ClearSeedUpdateHandlers() { OnSeedUpdate = null; }
+        public static void ClearCounterUpdateHandlers() { OnCounterUpdate = null; }
+
+        public static void ClearUpdateHandlers() {
+            OnSeedUpdate = null;
+            OnCounterUpdate = null;
+        }
+
+        public static RngState Deserialize(IReader reader, Path path = null) {
+            var rngState = new RngState(path);
+            rngState.Replace(reader, shouldNotify: false);
+            return rngState;
+        }
+
+        public WireType? GetWireType(UInt32 tag) {
+            switch (tag) {
+                case 0: return WireType.Varint;
+                case 1: return WireType.Varint;
+                default: return null;
+            }
+        }
+
+        public IState GetNested(UInt32 tag) {
+            switch (tag) {
+                default: return null;
+            }
+        }
+
+        public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+            switch (tag) {
+                case 0: this.Seed = this.MaybeNotify(0, reader.ReadUInt64(), this.Seed, OnSeedUpdate, shouldNotify); break;
+                case 1: this.Counter = this.MaybeNotify(1, reader.ReadUInt64(), this.Counter, OnCounterUpdate, shouldNotify); break;
+                default: reader.SkipField(wireType); break;
+            }
+        }
+
+        public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+        public void ReplayListPop() { throw new NotSupportedException(); }
+        public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+        private TValue MaybeNotify<TValue>(
+            UInt32 tag,
+            TValue newValue,
+            TValue oldValue,
+            EventHandler<FieldUpdateEventArgs<TValue, RngState>> handler,
+            bool shouldNotify
+        ) {
+            if (shouldNotify) {
+                var args = new FieldUpdateEventArgs<TValue, RngState>(tag, newValue, oldValue, this);
+                handler?.Invoke(this, args);
+            }
+
+            return newValue;
+        }
+    }
+}
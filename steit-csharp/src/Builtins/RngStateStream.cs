@@ -0,0 +1,24 @@
+using System;
+
+namespace Steit.Builtins {
+    // Mirrors the SplitMix64 stream of `RngState` on the server.
+    public sealed partial class RngState {
+        private const UInt64 Gamma = 0x9e3779b97f4a7c15UL;
+
+        // Returns the value `offset` draws ahead of the server's counter.
+        public UInt64 Peek(UInt64 offset = 0) {
+            unchecked {
+                var index = this.Counter + offset + 1;
+                return Mix(this.Seed + index * Gamma);
+            }
+        }
+
+        private static UInt64 Mix(UInt64 z) {
+            unchecked {
+                z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9UL;
+                z = (z ^ (z >> 27)) * 0x94d049bb133111ebUL;
+                return z ^ (z >> 31);
+            }
+        }
+    }
+}
@@ -66,6 +66,25 @@ namespace Steit.Codec {
             }
         }
 
+        public static Single ReadSingle(this IReader reader) {
+            return BitConverter.Int32BitsToSingle(BitConverter.ToInt32(reader.ReadFixed(4), 0));
+        }
+
+        public static Double ReadDouble(this IReader reader) {
+            return BitConverter.Int64BitsToDouble(BitConverter.ToInt64(reader.ReadFixed(8), 0));
+        }
+
+        // Fixed values are little-endian, whatever the byte order of the host.
+        private static byte[] ReadFixed(this IReader reader, int count) {
+            var bytes = reader.Read(count);
+
+            if (!BitConverter.IsLittleEndian) {
+                Array.Reverse(bytes);
+            }
+
+            return bytes;
+        }
+
         public static String ReadString(this IReader reader) {
             return Encoding.UTF8.GetString(reader.ReadToEnd());
         }
@@ -93,6 +112,8 @@ namespace Steit.Codec {
             switch (wireType) {
                 case WireType.Varint: reader.ReadBoolean(); break;
                 case WireType.Sized: reader.SkipToEnd(); break;
+                case WireType.Fixed32: reader.Skip(4); break;
+                case WireType.Fixed64: reader.Skip(8); break;
                 default: throw new NotSupportedException(String.Format("Unsupported wire type: {0}", wireType));
             }
         }
@@ -3,14 +3,18 @@ using System;
 namespace Steit.Codec {
     public enum WireType : Byte {
         Varint = 0,
+        Fixed64 = 1,
         Sized = 2,
+        Fixed32 = 5,
     }
 
     public static class WireTypeHelper {
         public static WireType New(UInt32 value) {
             switch (value) {
                 case (byte) WireType.Varint:
+                case (byte) WireType.Fixed64:
                 case (byte) WireType.Sized:
+                case (byte) WireType.Fixed32:
                     return (WireType) value;
 
                 default:
@@ -0,0 +1,78 @@
+using System;
+
+using Steit.Codec;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Steit.Collections {
+    public sealed partial class StateFlags<T> : IState where T : struct {
+        public Path Path { get; }
+        public UInt64 Bits { get; private set; }
+
+        public T Value { get { return (T) Enum.ToObject(typeof(T), this.Bits); } }
+
+        // public StateFlags(Path? path = null, UInt64 bits = 0) {
+        public StateFlags(Path path = null, UInt64 bits = 0) {
+            if (!typeof(T).IsEnum) {
+                throw new InvalidOperationException(String.Format("{0} is expected to be an enum type.", typeof(T).FullName));
+            }
+
+            this.Path = path ?? Path.Root;
+            this.Bits = bits;
+        }
+
+        // public event EventHandler<FieldUpdateEventArgs<Boolean, StateFlags<T>>>? OnUpdate;
+        public event EventHandler<FieldUpdateEventArgs<Boolean, StateFlags<T>>> OnUpdate;
+
+        public void ClearUpdateHandlers() { this.OnUpdate = null; }
+
+        public bool Has(T flag) {
+            var mask = Convert.ToUInt64(flag);
+            return (this.Bits & mask) == mask;
+        }
+
+        // public static StateFlags<T> Deserialize(IReader reader, Path? path = null) {
+        public static StateFlags<T> Deserialize(IReader reader, Path path = null) {
+            return new StateFlags<T>(path, reader.ReadUInt64());
+        }
+
+        public WireType? GetWireType(UInt32 tag) {
+            return tag < 64 ? WireType.Varint : (WireType?) null;
+        }
+
+        // public IState? GetNested(UInt32 tag) {
+        public IState GetNested(UInt32 tag) {
+            return null;
+        }
+
+        public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+            if (tag >= 64) {
+                reader.SkipField(wireType);
+                return;
+            }
+
+            var mask = 1UL << (int) tag;
+            var newValue = reader.ReadBoolean();
+            var oldValue = (this.Bits & mask) != 0;
+
+            if (shouldNotify) {
+                var args = new FieldUpdateEventArgs<Boolean, StateFlags<T>>(tag, newValue, oldValue, this);
+                this.OnUpdate?.Invoke(this, args);
+            }
+
+            this.Bits = newValue ? this.Bits | mask : this.Bits & ~mask;
+        }
+
+        public void ReplayListPush(IReader reader) {
+            throw new NotSupportedException();
+        }
+
+        public void ReplayListPop() {
+            throw new NotSupportedException();
+        }
+
+        public void ReplayMapRemove(UInt32 key) {
+            throw new NotSupportedException();
+        }
+    }
+}
@@ -7,7 +7,7 @@ using Steit.State;
 using Steit.State.Event;
 
 namespace Steit.Collections {
-    public sealed partial class StateList<T> : ReadOnlyCollection<T>, IState {
+    public sealed partial class StateList<T> : ReadOnlyCollection<T>, IListState {
         public Path Path { get; }
 
         // public StateList(Path? path = null, IList<T>? items = null) : base(items ?? new List<T>()) {
@@ -22,10 +22,13 @@ namespace Steit.Collections {
         public event EventHandler<ListPushEventArgs<T, StateList<T>>> OnPush;
         // public event EventHandler<ListPopEventArgs<T, StateList<T>>>? OnPop;
         public event EventHandler<ListPopEventArgs<T, StateList<T>>> OnPop;
+        // public event EventHandler<ListMoveEventArgs<T, StateList<T>>>? OnMove;
+        public event EventHandler<ListMoveEventArgs<T, StateList<T>>> OnMove;
 
         public void ClearUpdateHandlers() { this.OnUpdate = null; }
         public void ClearPushHandlers() { this.OnPush = null; }
         public void ClearPopHandlers() { this.OnPop = null; }
+        public void ClearMoveHandlers() { this.OnMove = null; }
 
         // public static StateList<T> Deserialize(IReader reader, Path? path = null) {
         public static StateList<T> Deserialize(IReader reader, Path path = null) {
@@ -77,6 +80,33 @@ namespace Steit.Collections {
             this.Items.Add(item);
         }
 
+        public void ReplayListExtend(IReader reader) {
+            while (!reader.EndOfStream()) {
+                var tag = (UInt32) this.Count;
+                var item = reader.ReadValue<T>(this.Path, tag);
+
+                var args = new ListPushEventArgs<T, StateList<T>>(tag, item, this);
+                this.OnPush?.Invoke(this, args);
+
+                this.Items.Add(item);
+            }
+        }
+
+        public void ReplayListMove(UInt32 from, UInt32 to) {
+            if (from >= this.Count || to >= this.Count) {
+                throw new IndexOutOfRangeException();
+            }
+
+            var item = this[(int) from];
+
+            var args = new ListMoveEventArgs<T, StateList<T>>(from, to, item, this);
+            this.OnMove?.Invoke(this, args);
+
+            // Paths of the shifted items are kept, as they can't be changed.
+            this.Items.RemoveAt((int) from);
+            this.Items.Insert((int) to, item);
+        }
+
         public void ReplayListPop() {
             if (this.Count <= 0) {
                 throw new InvalidOperationException("Cannot pop from an empty `StateList`.");
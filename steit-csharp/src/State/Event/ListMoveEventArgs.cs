@@ -0,0 +1,18 @@
+using System;
+using System.Collections.Generic;
+
+namespace Steit.State.Event {
+    public sealed class ListMoveEventArgs<TItem, TList> : EventArgs where TList : IList<TItem>, IState {
+        public UInt32 From { get; }
+        public UInt32 To { get; }
+        public TItem Item { get; }
+        public TList List { get; }
+
+        public ListMoveEventArgs(UInt32 from, UInt32 to, TItem item, TList list) {
+            this.From = from;
+            this.To = to;
+            this.Item = item;
+            this.List = list;
+        }
+    }
+}
@@ -0,0 +1,10 @@
+using System;
+
+using Steit.Codec;
+
+namespace Steit.State {
+    public interface IListState : IState {
+        void ReplayListExtend(IReader reader);
+        void ReplayListMove(UInt32 from, UInt32 to);
+    }
+}
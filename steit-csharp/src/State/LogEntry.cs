@@ -9,8 +9,13 @@ using Steit.State.Event;
 namespace Steit.State {
     public sealed partial class LogEntry : IEnumState {
         public const UInt32 UpdateTag = 0;
+        public const UInt32 InternTag = 1;
+        public const UInt32 UpdateInternedTag = 2;
+        public const UInt32 ReplaceTag = 3;
         public const UInt32 ListPushTag = 8;
         public const UInt32 ListPopTag = 9;
+        public const UInt32 ListExtendTag = 10;
+        public const UInt32 ListMoveTag = 11;
         public const UInt32 MapRemoveTag = 12;
 
         public Path Path { get; }
@@ -19,8 +24,13 @@ namespace Steit.State {
         public IState Variant { get; private set; }
 
         public Update UpdateVariant { get { return this.Variant as Update; } }
+        public Intern InternVariant { get { return this.Variant as Intern; } }
+        public UpdateInterned UpdateInternedVariant { get { return this.Variant as UpdateInterned; } }
+        public Replace ReplaceVariant { get { return this.Variant as Replace; } }
         public ListPush ListPushVariant { get { return this.Variant as ListPush; } }
         public ListPop ListPopVariant { get { return this.Variant as ListPop; } }
+        public ListExtend ListExtendVariant { get { return this.Variant as ListExtend; } }
+        public ListMove ListMoveVariant { get { return this.Variant as ListMove; } }
         public MapRemove MapRemoveVariant { get { return this.Variant as MapRemove; } }
 
         public LogEntry(Path path = null) : this(path, 0) { }
@@ -31,16 +41,26 @@ namespace Steit.State {
 
             switch (tag) {
                 case 0: this.Variant = new Update(this.Path.GetNested(0)); break;
+                case 1: this.Variant = new Intern(this.Path.GetNested(1)); break;
+                case 2: this.Variant = new UpdateInterned(this.Path.GetNested(2)); break;
+                case 3: this.Variant = new Replace(this.Path.GetNested(3)); break;
                 case 8: this.Variant = new ListPush(this.Path.GetNested(8)); break;
                 case 9: this.Variant = new ListPop(this.Path.GetNested(9)); break;
+                case 10: this.Variant = new ListExtend(this.Path.GetNested(10)); break;
+                case 11: this.Variant = new ListMove(this.Path.GetNested(11)); break;
                 case 12: this.Variant = new MapRemove(this.Path.GetNested(12)); break;
                 default: this.Variant = new Update(this.Path.GetNested(0)); break;
             }
         }
 
         public static LogEntry NewUpdate(Path path = null) { return new LogEntry(path, 0); }
+        public static LogEntry NewIntern(Path path = null) { return new LogEntry(path, 1); }
+        public static LogEntry NewUpdateInterned(Path path = null) { return new LogEntry(path, 2); }
+        public static LogEntry NewReplace(Path path = null) { return new LogEntry(path, 3); }
         public static LogEntry NewListPush(Path path = null) { return new LogEntry(path, 8); }
         public static LogEntry NewListPop(Path path = null) { return new LogEntry(path, 9); }
+        public static LogEntry NewListExtend(Path path = null) { return new LogEntry(path, 10); }
+        public static LogEntry NewListMove(Path path = null) { return new LogEntry(path, 11); }
         public static LogEntry NewMapRemove(Path path = null) { return new LogEntry(path, 12); }
 
         public static event EventHandler<VariantUpdateEventArgs<LogEntry>> OnUpdate;
@@ -58,8 +78,13 @@ namespace Steit.State {
         public WireType? GetWireType(UInt32 tag) {
             switch (tag) {
                 case 0: return WireType.Sized;
+                case 1: return WireType.Sized;
+                case 2: return WireType.Sized;
+                case 3: return WireType.Sized;
                 case 8: return WireType.Sized;
                 case 9: return WireType.Sized;
+                case 10: return WireType.Sized;
+                case 11: return WireType.Sized;
                 case 12: return WireType.Sized;
                 default: return null;
             }
@@ -72,8 +97,13 @@ namespace Steit.State {
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
                 case 0: this.UpdateAndNotify(0, Update.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
+                case 1: this.UpdateAndNotify(1, Intern.Deserialize(reader, this.Path.GetNested(1)), shouldNotify); break;
+                case 2: this.UpdateAndNotify(2, UpdateInterned.Deserialize(reader, this.Path.GetNested(2)), shouldNotify); break;
+                case 3: this.UpdateAndNotify(3, Replace.Deserialize(reader, this.Path.GetNested(3)), shouldNotify); break;
                 case 8: this.UpdateAndNotify(8, ListPush.Deserialize(reader, this.Path.GetNested(8)), shouldNotify); break;
                 case 9: this.UpdateAndNotify(9, ListPop.Deserialize(reader, this.Path.GetNested(9)), shouldNotify); break;
+                case 10: this.UpdateAndNotify(10, ListExtend.Deserialize(reader, this.Path.GetNested(10)), shouldNotify); break;
+                case 11: this.UpdateAndNotify(11, ListMove.Deserialize(reader, this.Path.GetNested(11)), shouldNotify); break;
                 case 12: this.UpdateAndNotify(12, MapRemove.Deserialize(reader, this.Path.GetNested(12)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
@@ -168,6 +198,225 @@ namespace Steit.State {
             }
         }
 
+        // Variant (1): Intern
+
+        public sealed partial class Intern : IState {
+            public Path Path { get; }
+
+            public UInt32 Index { get; private set; }
+            public String Value { get; private set; }
+
+            internal Intern(Path path = null) {
+                this.Path = path ?? Path.Root;
+            }
+
+            public static event EventHandler<FieldUpdateEventArgs<UInt32, Intern>> OnIndexUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<String, Intern>> OnValueUpdate;
+
+            public static void ClearIndexUpdateHandlers() { OnIndexUpdate = null; }
+            public static void ClearValueUpdateHandlers() { OnValueUpdate = null; }
+
+            public static void ClearUpdateHandlers() {
+                OnIndexUpdate = null;
+                OnValueUpdate = null;
+            }
+
+            internal static Intern Deserialize(IReader reader, Path path = null) {
+                var intern = new Intern(path);
+                intern.Replace(reader, shouldNotify: false);
+                return intern;
+            }
+
+            public WireType? GetWireType(UInt32 tag) {
+                switch (tag) {
+                    case 0: return WireType.Varint;
+                    case 1: return WireType.Sized;
+                    default: return null;
+                }
+            }
+
+            public IState GetNested(UInt32 tag) {
+                switch (tag) {
+                    default: return null;
+                }
+            }
+
+            public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+                switch (tag) {
+                    case 0: this.Index = this.MaybeNotify(0, reader.ReadUInt32(), this.Index, OnIndexUpdate, shouldNotify); break;
+                    case 1: this.Value = this.MaybeNotify(1, reader.ReadString(), this.Value, OnValueUpdate, shouldNotify); break;
+                    default: reader.SkipField(wireType); break;
+                }
+            }
+
+            public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+            public void ReplayListPop() { throw new NotSupportedException(); }
+            public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+            private TValue MaybeNotify<TValue>(
+                UInt32 tag,
+                TValue newValue,
+                TValue oldValue,
+                EventHandler<FieldUpdateEventArgs<TValue, Intern>> handler,
+                bool shouldNotify
+            ) {
+                if (shouldNotify) {
+                    var args = new FieldUpdateEventArgs<TValue, Intern>(tag, newValue, oldValue, this);
+                    handler?.Invoke(this, args);
+                }
+
+                return newValue;
+            }
+        }
+
+        // Variant (2): UpdateInterned
+
+        public sealed partial class UpdateInterned : IState {
+            public Path Path { get; }
+
+            public Vector<UInt32> FlattenPath { get; private set; }
+            public UInt32 Index { get; private set; }
+
+            internal UpdateInterned(Path path = null) {
+                this.Path = path ?? Path.Root;
+                this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
+            }
+
+            public static event EventHandler<FieldUpdateEventArgs<Vector<UInt32>, UpdateInterned>> OnFlattenPathUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<UInt32, UpdateInterned>> OnIndexUpdate;
+
+            public static void ClearFlattenPathUpdateHandlers() { OnFlattenPathUpdate = null; }
+            public static void ClearIndexUpdateHandlers() { OnIndexUpdate = null; }
+
+            public static void ClearUpdateHandlers() {
+                OnFlattenPathUpdate = null;
+                OnIndexUpdate = null;
+            }
+
+            internal static UpdateInterned Deserialize(IReader reader, Path path = null) {
+                var updateInterned = new UpdateInterned(path);
+                updateInterned.Replace(reader, shouldNotify: false);
+                return updateInterned;
+            }
+
+            public WireType? GetWireType(UInt32 tag) {
+                switch (tag) {
+                    case 0: return WireType.Sized;
+                    case 1: return WireType.Varint;
+                    default: return null;
+                }
+            }
+
+            public IState GetNested(UInt32 tag) {
+                switch (tag) {
+                    case 0: return this.FlattenPath;
+                    default: return null;
+                }
+            }
+
+            public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+                switch (tag) {
+                    case 0: this.FlattenPath = this.MaybeNotify(0, Vector<UInt32>.Deserialize(reader, this.Path.GetNested(0)), this.FlattenPath, OnFlattenPathUpdate, shouldNotify); break;
+                    case 1: this.Index = this.MaybeNotify(1, reader.ReadUInt32(), this.Index, OnIndexUpdate, shouldNotify); break;
+                    default: reader.SkipField(wireType); break;
+                }
+            }
+
+            public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+            public void ReplayListPop() { throw new NotSupportedException(); }
+            public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+            private TValue MaybeNotify<TValue>(
+                UInt32 tag,
+                TValue newValue,
+                TValue oldValue,
+                EventHandler<FieldUpdateEventArgs<TValue, UpdateInterned>> handler,
+                bool shouldNotify
+            ) {
+                if (shouldNotify) {
+                    var args = new FieldUpdateEventArgs<TValue, UpdateInterned>(tag, newValue, oldValue, this);
+                    handler?.Invoke(this, args);
+                }
+
+                return newValue;
+            }
+        }
+
+        // Variant (3): Replace
+
+        public sealed partial class Replace : IState {
+            public Path Path { get; }
+
+            public Vector<UInt32> FlattenPath { get; private set; }
+            public Bytes Value { get; private set; }
+
+            internal Replace(Path path = null) {
+                this.Path = path ?? Path.Root;
+                this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
+                this.Value = new Bytes(this.Path.GetNested(1));
+            }
+
+            public static event EventHandler<FieldUpdateEventArgs<Vector<UInt32>, Replace>> OnFlattenPathUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<Bytes, Replace>> OnValueUpdate;
+
+            public static void ClearFlattenPathUpdateHandlers() { OnFlattenPathUpdate = null; }
+            public static void ClearValueUpdateHandlers() { OnValueUpdate = null; }
+
+            public static void ClearUpdateHandlers() {
+                OnFlattenPathUpdate = null;
+                OnValueUpdate = null;
+            }
+
+            internal static Replace Deserialize(IReader reader, Path path = null) {
+                var replace = new Replace(path);
+                replace.Replace(reader, shouldNotify: false);
+                return replace;
+            }
+
+            public WireType? GetWireType(UInt32 tag) {
+                switch (tag) {
+                    case 0: return WireType.Sized;
+                    case 1: return WireType.Sized;
+                    default: return null;
+                }
+            }
+
+            public IState GetNested(UInt32 tag) {
+                switch (tag) {
+                    case 0: return this.FlattenPath;
+                    case 1: return this.Value;
+                    default: return null;
+                }
+            }
+
+            public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+                switch (tag) {
+                    case 0: this.FlattenPath = this.MaybeNotify(0, Vector<UInt32>.Deserialize(reader, this.Path.GetNested(0)), this.FlattenPath, OnFlattenPathUpdate, shouldNotify); break;
+                    case 1: this.Value = this.MaybeNotify(1, Bytes.Deserialize(reader, this.Path.GetNested(1)), this.Value, OnValueUpdate, shouldNotify); break;
+                    default: reader.SkipField(wireType); break;
+                }
+            }
+
+            public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+            public void ReplayListPop() { throw new NotSupportedException(); }
+            public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+            private TValue MaybeNotify<TValue>(
+                UInt32 tag,
+                TValue newValue,
+                TValue oldValue,
+                EventHandler<FieldUpdateEventArgs<TValue, Replace>> handler,
+                bool shouldNotify
+            ) {
+                if (shouldNotify) {
+                    var args = new FieldUpdateEventArgs<TValue, Replace>(tag, newValue, oldValue, this);
+                    handler?.Invoke(this, args);
+                }
+
+                return newValue;
+            }
+        }
+
         // Variant (8): ListPush
 
         public sealed partial class ListPush : IState {
@@ -311,6 +560,160 @@ namespace Steit.State {
             }
         }
 
+        // Variant (10): ListExtend
+
+        public sealed partial class ListExtend : IState {
+            public Path Path { get; }
+
+            public Vector<UInt32> FlattenPath { get; private set; }
+            public Bytes Items { get; private set; }
+
+            internal ListExtend(Path path = null) {
+                this.Path = path ?? Path.Root;
+                this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
+                this.Items = new Bytes(this.Path.GetNested(1));
+            }
+
+            public static event EventHandler<FieldUpdateEventArgs<Vector<UInt32>, ListExtend>> OnFlattenPathUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<Bytes, ListExtend>> OnItemsUpdate;
+
+            public static void ClearFlattenPathUpdateHandlers() { OnFlattenPathUpdate = null; }
+            public static void ClearItemsUpdateHandlers() { OnItemsUpdate = null; }
+
+            public static void ClearUpdateHandlers() {
+                OnFlattenPathUpdate = null;
+                OnItemsUpdate = null;
+            }
+
+            internal static ListExtend Deserialize(IReader reader, Path path = null) {
+                var listExtend = new ListExtend(path);
+                listExtend.Replace(reader, shouldNotify: false);
+                return listExtend;
+            }
+
+            public WireType? GetWireType(UInt32 tag) {
+                switch (tag) {
+                    case 0: return WireType.Sized;
+                    case 1: return WireType.Sized;
+                    default: return null;
+                }
+            }
+
+            public IState GetNested(UInt32 tag) {
+                switch (tag) {
+                    case 0: return this.FlattenPath;
+                    case 1: return this.Items;
+                    default: return null;
+                }
+            }
+
+            public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+                switch (tag) {
+                    case 0: this.FlattenPath = this.MaybeNotify(0, Vector<UInt32>.Deserialize(reader, this.Path.GetNested(0)), this.FlattenPath, OnFlattenPathUpdate, shouldNotify); break;
+                    case 1: this.Items = this.MaybeNotify(1, Bytes.Deserialize(reader, this.Path.GetNested(1)), this.Items, OnItemsUpdate, shouldNotify); break;
+                    default: reader.SkipField(wireType); break;
+                }
+            }
+
+            public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+            public void ReplayListPop() { throw new NotSupportedException(); }
+            public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+            private TValue MaybeNotify<TValue>(
+                UInt32 tag,
+                TValue newValue,
+                TValue oldValue,
+                EventHandler<FieldUpdateEventArgs<TValue, ListExtend>> handler,
+                bool shouldNotify
+            ) {
+                if (shouldNotify) {
+                    var args = new FieldUpdateEventArgs<TValue, ListExtend>(tag, newValue, oldValue, this);
+                    handler?.Invoke(this, args);
+                }
+
+                return newValue;
+            }
+        }
+
+        // Variant (11): ListMove
+
+        public sealed partial class ListMove : IState {
+            public Path Path { get; }
+
+            public Vector<UInt32> FlattenPath { get; private set; }
+            public UInt32 From { get; private set; }
+            public UInt32 To { get; private set; }
+
+            internal ListMove(Path path = null) {
+                this.Path = path ?? Path.Root;
+                this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
+            }
+
+            public static event EventHandler<FieldUpdateEventArgs<Vector<UInt32>, ListMove>> OnFlattenPathUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<UInt32, ListMove>> OnFromUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<UInt32, ListMove>> OnToUpdate;
+
+            public static void ClearFlattenPathUpdateHandlers() { OnFlattenPathUpdate = null; }
+            public static void ClearFromUpdateHandlers() { OnFromUpdate = null; }
+            public static void ClearToUpdateHandlers() { OnToUpdate = null; }
+
+            public static void ClearUpdateHandlers() {
+                OnFlattenPathUpdate = null;
+                OnFromUpdate = null;
+                OnToUpdate = null;
+            }
+
+            internal static ListMove Deserialize(IReader reader, Path path = null) {
+                var listMove = new ListMove(path);
+                listMove.Replace(reader, shouldNotify: false);
+                return listMove;
+            }
+
+            public WireType? GetWireType(UInt32 tag) {
+                switch (tag) {
+                    case 0: return WireType.Sized;
+                    case 1: return WireType.Varint;
+                    case 2: return WireType.Varint;
+                    default: return null;
+                }
+            }
+
+            public IState GetNested(UInt32 tag) {
+                switch (tag) {
+                    case 0: return this.FlattenPath;
+                    default: return null;
+                }
+            }
+
+            public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+                switch (tag) {
+                    case 0: this.FlattenPath = this.MaybeNotify(0, Vector<UInt32>.Deserialize(reader, this.Path.GetNested(0)), this.FlattenPath, OnFlattenPathUpdate, shouldNotify); break;
+                    case 1: this.From = this.MaybeNotify(1, reader.ReadUInt32(), this.From, OnFromUpdate, shouldNotify); break;
+                    case 2: this.To = this.MaybeNotify(2, reader.ReadUInt32(), this.To, OnToUpdate, shouldNotify); break;
+                    default: reader.SkipField(wireType); break;
+                }
+            }
+
+            public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+            public void ReplayListPop() { throw new NotSupportedException(); }
+            public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+            private TValue MaybeNotify<TValue>(
+                UInt32 tag,
+                TValue newValue,
+                TValue oldValue,
+                EventHandler<FieldUpdateEventArgs<TValue, ListMove>> handler,
+                bool shouldNotify
+            ) {
+                if (shouldNotify) {
+                    var args = new FieldUpdateEventArgs<TValue, ListMove>(tag, newValue, oldValue, this);
+                    handler?.Invoke(this, args);
+                }
+
+                return newValue;
+            }
+        }
+
         // Variant (12): MapRemove
 
         public sealed partial class MapRemove : IState {
@@ -21,6 +21,8 @@ namespace Steit.State {
             Deserializers["System.Int16"] = reader => reader.ReadInt16();
             Deserializers["System.Int32"] = reader => reader.ReadInt32();
             Deserializers["System.Int64"] = reader => reader.ReadInt64();
+            Deserializers["System.Single"] = reader => reader.ReadSingle();
+            Deserializers["System.Double"] = reader => reader.ReadDouble();
             Deserializers["System.Boolean"] = reader => reader.ReadBoolean();
             Deserializers["System.String"] = reader => reader.ReadString();
 
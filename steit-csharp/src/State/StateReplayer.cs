@@ -1,29 +1,44 @@
 using System;
 using System.Collections.Generic;
+using System.Text;
 
 using Steit.Codec;
 using Steit.Collections;
 
 namespace Steit.State {
     public static class StateReplayer {
-        public static void Replay<T>(ref T root, IReader reader) where T : IState {
+        // A `StringTable` should be kept per stream so that interned strings can be resolved across calls.
+        // public static void Replay<T>(ref T root, IReader reader, StringTable? strings = null) where T : IState {
+        public static void Replay<T>(ref T root, IReader reader, StringTable strings = null) where T : IState {
+            strings = strings ?? new StringTable();
+
             while (!reader.EndOfStream()) {
                 var entry = LogEntry.Deserialize(reader.GetNested());
-                Replay(ref root, entry);
+                Replay(ref root, entry, strings);
             }
         }
 
-        public static void Replay<T>(ref T root, LogEntry entry) where T : IState {
+        // public static void Replay<T>(ref T root, LogEntry entry, StringTable? strings = null) where T : IState {
+        public static void Replay<T>(ref T root, LogEntry entry, StringTable strings = null) where T : IState {
+            if (entry.Tag == LogEntry.InternTag) {
+                if (strings == null) {
+                    throw new InvalidOperationException("Cannot replay interned strings without a string table");
+                }
+
+                // strings.Insert(entry.InternVariant!.Index, entry.InternVariant!.Value);
+                strings.Insert(entry.InternVariant.Index, entry.InternVariant.Value);
+                return;
+            }
+
             var path = new List<UInt32>(GetPath(entry));
             var tag = 0U;
 
-            if (entry.Tag == LogEntry.UpdateTag) {
+            if (entry.Tag == LogEntry.UpdateTag || entry.Tag == LogEntry.UpdateInternedTag || entry.Tag == LogEntry.ReplaceTag) {
                 if (path.Count > 0) {
                     tag = path[path.Count - 1];
                     path.RemoveAt(path.Count - 1);
                 } else {
-                    // var reader = new ByteReader(entry.UpdateVariant!.Value);
-                    var reader = new ByteReader(entry.UpdateVariant.Value);
+                    var reader = new ByteReader(GetUpdateValue(entry, strings));
                     root = StateFactory.Deserialize<T>(reader, root.Path);
                     return;
                 }
@@ -36,11 +51,12 @@ namespace Steit.State {
             }
 
             switch (entry.Tag) {
-                case LogEntry.UpdateTag: {
+                case LogEntry.UpdateTag:
+                case LogEntry.UpdateInternedTag:
+                case LogEntry.ReplaceTag: {
                         var wireType = container.GetWireType(tag);
                         if (wireType == null) { return; }
-                        // var reader = new ByteReader(entry.UpdateVariant!.Value);
-                        var reader = new ByteReader(entry.UpdateVariant.Value);
+                        var reader = new ByteReader(GetUpdateValue(entry, strings));
                         container.ReplaceAt(tag, wireType.Value, reader, shouldNotify: true);
                         break;
                     }
@@ -57,6 +73,23 @@ namespace Steit.State {
                         break;
                     }
 
+                case LogEntry.ListExtendTag: {
+                        var list = container as IListState;
+                        if (list == null) { throw new NotSupportedException(); }
+                        // var reader = new ByteReader(entry.ListExtendVariant!.Items);
+                        var reader = new ByteReader(entry.ListExtendVariant.Items);
+                        list.ReplayListExtend(reader);
+                        break;
+                    }
+
+                case LogEntry.ListMoveTag: {
+                        var list = container as IListState;
+                        if (list == null) { throw new NotSupportedException(); }
+                        // list.ReplayListMove(entry.ListMoveVariant!.From, entry.ListMoveVariant!.To);
+                        list.ReplayListMove(entry.ListMoveVariant.From, entry.ListMoveVariant.To);
+                        break;
+                    }
+
                 case LogEntry.MapRemoveTag: {
                         // var key = entry.MapRemoveVariant!.Key;
                         var key = entry.MapRemoveVariant.Key;
@@ -68,14 +101,42 @@ namespace Steit.State {
             }
         }
 
+        // private static IReadOnlyList<Byte> GetUpdateValue(LogEntry entry, StringTable? strings) {
+        private static IReadOnlyList<Byte> GetUpdateValue(LogEntry entry, StringTable strings) {
+            if (entry.Tag == LogEntry.UpdateInternedTag) {
+                if (strings == null) {
+                    throw new InvalidOperationException("Cannot replay interned strings without a string table");
+                }
+
+                // return Array.AsReadOnly(Encoding.UTF8.GetBytes(strings.Get(entry.UpdateInternedVariant!.Index)));
+                return Array.AsReadOnly(Encoding.UTF8.GetBytes(strings.Get(entry.UpdateInternedVariant.Index)));
+            }
+
+            if (entry.Tag == LogEntry.ReplaceTag) {
+                // return entry.ReplaceVariant!.Value;
+                return entry.ReplaceVariant.Value;
+            }
+
+            // return entry.UpdateVariant!.Value;
+            return entry.UpdateVariant.Value;
+        }
+
         private static Vector<UInt32> GetPath(LogEntry entry) {
             switch (entry.Tag) {
                 // case LogEntry.UpdateTag: return entry.UpdateVariant!.FlattenPath;
                 case LogEntry.UpdateTag: return entry.UpdateVariant.FlattenPath;
+                // case LogEntry.UpdateInternedTag: return entry.UpdateInternedVariant!.FlattenPath;
+                case LogEntry.UpdateInternedTag: return entry.UpdateInternedVariant.FlattenPath;
+                // case LogEntry.ReplaceTag: return entry.ReplaceVariant!.FlattenPath;
+                case LogEntry.ReplaceTag: return entry.ReplaceVariant.FlattenPath;
                 // case LogEntry.ListPushTag: return entry.ListPushVariant!.FlattenPath;
                 case LogEntry.ListPushTag: return entry.ListPushVariant.FlattenPath;
                 // case LogEntry.ListPopTag: return entry.ListPopVariant!.FlattenPath;
                 case LogEntry.ListPopTag: return entry.ListPopVariant.FlattenPath;
+                // case LogEntry.ListExtendTag: return entry.ListExtendVariant!.FlattenPath;
+                case LogEntry.ListExtendTag: return entry.ListExtendVariant.FlattenPath;
+                // case LogEntry.ListMoveTag: return entry.ListMoveVariant!.FlattenPath;
+                case LogEntry.ListMoveTag: return entry.ListMoveVariant.FlattenPath;
                 // case LogEntry.MapRemoveTag: return entry.MapRemoveVariant!.FlattenPath;
                 case LogEntry.MapRemoveTag: return entry.MapRemoveVariant.FlattenPath;
                 default: throw new InvalidOperationException(String.Format("Unknown log entry tag {0}", entry.Tag));
@@ -0,0 +1,31 @@
+using System;
+using System.Collections.Generic;
+
+namespace Steit.State {
+    // Mirrors the per-stream table of interned strings kept by the server logger.
+    public sealed class StringTable {
+        private readonly List<String> strings = new List<String>();
+
+        public int Count { get { return this.strings.Count; } }
+
+        public void Insert(UInt32 index, String value) {
+            if (index != this.strings.Count) {
+                throw new InvalidOperationException(String.Format("Expected interned string index {0}, got {1}", this.strings.Count, index));
+            }
+
+            this.strings.Add(value);
+        }
+
+        public String Get(UInt32 index) {
+            if (index >= this.strings.Count) {
+                throw new InvalidOperationException(String.Format("Interned string {0} not found", index));
+            }
+
+            return this.strings[(int) index];
+        }
+
+        public void Clear() {
+            this.strings.Clear();
+        }
+    }
+}
@@ -0,0 +1,15 @@
+using System;
+
+using Steit.Builtins;
+using Steit.Codec;
+using Steit.Collections;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Just.To.Test {
+    [Flags]
+    public enum Formation : UInt64 {
+        Tight = 1UL << 0,
+        Hidden = 1UL << 3,
+    }
+}
@@ -0,0 +1,99 @@
+using System;
+
+using Steit.Builtins;
+using Steit.Codec;
+using Steit.Collections;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Just.To.Test {
+    public sealed partial class Squad : IState {
+        public Path Path { get; }
+
+        public Transform Leader { get; private set; }
+        public StateList<Transform> Members { get; private set; }
+        public StateFlags<Formation> Formation { get; private set; }
+        public Byte Morale { get; private set; }
+
+        public const Byte MoraleMin = 0;
+        public const Byte MoraleMax = 100;
+
+        public Squad(Path path = null) {
+            this.Path = path ?? Path.Root;
+            this.Leader = new Transform(this.Path.GetNested(0));
+            this.Members = new StateList<Transform>(this.Path.GetNested(1));
+            this.Formation = new StateFlags<Formation>(this.Path.GetNested(2));
+        }
+
+        public static event EventHandler<FieldUpdateEventArgs<Transform, Squad>> OnLeaderUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<StateList<Transform>, Squad>> OnMembersUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<StateFlags<Formation>, Squad>> OnFormationUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<Byte, Squad>> OnMoraleUpdate;
+
+        public static void ClearLeaderUpdateHandlers() { OnLeaderUpdate = null; }
+        public static void ClearMembersUpdateHandlers() { OnMembersUpdate = null; }
+        public static void ClearFormationUpdateHandlers() { OnFormationUpdate = null; }
+        public static void ClearMoraleUpdateHandlers() { OnMoraleUpdate = null; }
+
+        public static void ClearUpdateHandlers() {
+            OnLeaderUpdate = null;
+            OnMembersUpdate = null;
+            OnFormationUpdate = null;
+            OnMoraleUpdate = null;
+        }
+
+        public static Squad Deserialize(IReader reader, Path path = null) {
+            var squad = new Squad(path);
+            squad.Replace(reader, shouldNotify: false);
+            return squad;
+        }
+
+        public WireType? GetWireType(UInt32 tag) {
+            switch (tag) {
+                case 0: return WireType.Sized;
+                case 1: return WireType.Sized;
+                case 2: return WireType.Varint;
+                case 3: return WireType.Varint;
+                default: return null;
+            }
+        }
+
+        public IState GetNested(UInt32 tag) {
+            switch (tag) {
+                case 0: return this.Leader;
+                case 1: return this.Members;
+                case 2: return this.Formation;
+                default: return null;
+            }
+        }
+
+        public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+            switch (tag) {
+                case 0: this.Leader = this.MaybeNotify(0, Transform.Deserialize(reader, this.Path.GetNested(0)), this.Leader, OnLeaderUpdate, shouldNotify); break;
+                case 1: this.Members = this.MaybeNotify(1, StateList<Transform>.Deserialize(reader, this.Path.GetNested(1)), this.Members, OnMembersUpdate, shouldNotify); break;
+                case 2: this.Formation = this.MaybeNotify(2, StateFlags<Formation>.Deserialize(reader, this.Path.GetNested(2)), this.Formation, OnFormationUpdate, shouldNotify); break;
+                case 3: this.Morale = this.MaybeNotify(3, reader.ReadByte(), this.Morale, OnMoraleUpdate, shouldNotify); break;
+                default: reader.SkipField(wireType); break;
+            }
+        }
+
+        public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+        public void ReplayListPop() { throw new NotSupportedException(); }
+        public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+        private TValue MaybeNotify<TValue>(
+            UInt32 tag,
+            TValue newValue,
+            TValue oldValue,
+            EventHandler<FieldUpdateEventArgs<TValue, Squad>> handler,
+            bool shouldNotify
+        ) {
+            if (shouldNotify) {
+                var args = new FieldUpdateEventArgs<TValue, Squad>(tag, newValue, oldValue, this);
+                handler?.Invoke(this, args);
+            }
+
+            return newValue;
+        }
+    }
+}
@@ -0,0 +1,78 @@
+using System;
+
+using Steit.Builtins;
+using Steit.Codec;
+using Steit.Collections;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Just.To.Test {
+    public sealed partial class Transform : IState {
+        public Path Path { get; }
+
+        public Int32 X { get; private set; }
+        public Int32 Y { get; private set; }
+
+        public Transform(Path path = null) {
+            this.Path = path ?? Path.Root;
+        }
+
+        public static event EventHandler<FieldUpdateEventArgs<Int32, Transform>> OnXUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<Int32, Transform>> OnYUpdate;
+
+        public static void ClearXUpdateHandlers() { OnXUpdate = null; }
+        public static void ClearYUpdateHandlers() { OnYUpdate = null; }
+
+        public static void ClearUpdateHandlers() {
+            OnXUpdate = null;
+            OnYUpdate = null;
+        }
+
+        public static Transform Deserialize(IReader reader, Path path = null) {
+            var transform = new Transform(path);
+            transform.Replace(reader, shouldNotify: false);
+            return transform;
+        }
+
+        public WireType? GetWireType(UInt32 tag) {
+            switch (tag) {
+                case 0: return WireType.Varint;
+                case 1: return WireType.Varint;
+                default: return null;
+            }
+        }
+
+        public IState GetNested(UInt32 tag) {
+            switch (tag) {
+                default: return null;
+            }
+        }
+
+        public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+            switch (tag) {
+                case 0: this.X = this.MaybeNotify(0, reader.ReadInt32(), this.X, OnXUpdate, shouldNotify); break;
+                case 1: this.Y = this.MaybeNotify(1, reader.ReadInt32(), this.Y, OnYUpdate, shouldNotify); break;
+                default: reader.SkipField(wireType); break;
+            }
+        }
+
+        public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+        public void ReplayListPop() { throw new NotSupportedException(); }
+        public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+        private TValue MaybeNotify<TValue>(
+            UInt32 tag,
+            TValue newValue,
+            TValue oldValue,
+            EventHandler<FieldUpdateEventArgs<TValue, Transform>> handler,
+            bool shouldNotify
+        ) {
+            if (shouldNotify) {
+                var args = new FieldUpdateEventArgs<TValue, Transform>(tag, newValue, oldValue, this);
+                handler?.Invoke(this, args);
+            }
+
+            return newValue;
+        }
+    }
+}
@@ -104,6 +104,15 @@ impl Attribute<'_, String> {
     }
 }
 
+impl Attribute<'_, syn::ExprPath> {
+    pub fn parse_expr_path(&mut self, meta: &syn::MetaNameValue) -> bool {
+        self.parse_name_value(meta, |lit| match lit {
+            syn::Lit::Str(lit) => lit.parse().map_err(|_| "a path"),
+            _ => Err("a path"),
+        })
+    }
+}
+
 pub struct VecAttribute<'a, T> {
     ctx: &'a Context,
     name: &'static str,
@@ -262,7 +271,7 @@ fn parse_meta(
 
         syn::NestedMeta::Lit(lit) => {
             if error_on_unknown {
-                ctx.error(&lit, "unexpected literal in steit attributes");
+                ctx.error(lit, "unexpected literal in steit attributes");
             }
         }
     }
@@ -1,18 +1,46 @@
 use std::{cell::RefCell, fmt, thread};
 
+use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
 
 pub struct Context {
     errors: RefCell<Option<Vec<syn::Error>>>,
+    warnings: RefCell<TokenStream>,
 }
 
 impl Context {
     pub fn new() -> Self {
         Self {
             errors: RefCell::new(Some(Vec::new())),
+            warnings: RefCell::new(TokenStream::new()),
         }
     }
 
+    /// Proc macros can't emit warnings on stable, so a deprecated constant
+    /// is used at the span of `tokens` to get one from the compiler.
+    pub fn warn(&self, tokens: impl ToTokens, message: impl fmt::Display) {
+        let span = tokens
+            .into_token_stream()
+            .into_iter()
+            .next()
+            .map_or_else(Span::call_site, |token| token.span());
+
+        let note = format!("steit lint: {}", message);
+
+        self.warnings.borrow_mut().extend(quote_spanned! { span=>
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const lint: () = ();
+                lint
+            };
+        });
+    }
+
+    pub fn warnings(&self) -> TokenStream {
+        self.warnings.borrow().clone()
+    }
+
     pub fn error(&self, tokens: impl ToTokens, message: impl fmt::Display) {
         self.syn_error(syn::Error::new_spanned(tokens, message));
     }
@@ -5,7 +5,6 @@ use crate::{
     attr::{Attribute, AttributeParse},
     ctx::Context,
     r#impl::Implementer,
-    str_util,
 };
 
 use super::{r#enum::Enum, r#struct::Struct};
@@ -33,6 +32,11 @@ pub struct DeriveSetting {
     pub derive_wire_type: bool,
     pub derive_meta: bool,
 
+    pub skip_unchanged: bool,
+    pub non_exhaustive: bool,
+    pub paths: bool,
+    pub lint: bool,
+
     pub ctor_prefix: String,
     pub size_cache_renamed: Option<(String, TokenStream)>,
     pub runtime_renamed: Option<(String, TokenStream)>,
@@ -66,9 +70,11 @@ impl DeriveSetting {
             _ => false,
         });
 
-        let derive_state = derive_state.get().unwrap_or_default();
-        let derive_serialize = derive_state || derive_serialize.get().unwrap_or_default();
-        let derive_deserialize = derive_state || derive_deserialize.get().unwrap_or_default();
+        // Without the `runtime` feature, there are no states, so `State` derives the codecs only.
+        let wants_state = derive_state.get().unwrap_or_default();
+        let derive_state = wants_state && cfg!(feature = "runtime");
+        let derive_serialize = wants_state || derive_serialize.get().unwrap_or_default();
+        let derive_deserialize = wants_state || derive_deserialize.get().unwrap_or_default();
 
         let derive_default = derive_deserialize || derive_default.get().unwrap_or_default();
 
@@ -81,6 +87,9 @@ impl DeriveSetting {
         let mut derive_ctors = Attribute::new(ctx, "derive_ctors");
         let mut derive_setters = Attribute::new(ctx, "derive_setters");
         let mut no_meta = Attribute::new(ctx, "no_meta");
+        let mut skip_unchanged = Attribute::new(ctx, "skip_unchanged");
+        let mut paths = Attribute::new(ctx, "paths");
+        let mut lint = Attribute::new(ctx, "lint");
 
         let mut ctor_prefix = Attribute::new(ctx, "ctor_prefix");
         let mut size_cache_renamed = Attribute::new(ctx, "size_cache_renamed");
@@ -102,6 +111,15 @@ impl DeriveSetting {
             syn::Meta::Path(path) if no_meta.parse_path(path) => true,
             syn::Meta::NameValue(meta) if no_meta.parse_bool(meta) => true,
 
+            syn::Meta::Path(path) if skip_unchanged.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if skip_unchanged.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if paths.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if paths.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if lint.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if lint.parse_bool(meta) => true,
+
             syn::Meta::NameValue(path) if ctor_prefix.parse_str(path) => true,
             syn::Meta::NameValue(meta) if size_cache_renamed.parse_str(meta) => true,
             syn::Meta::NameValue(meta) if runtime_renamed.parse_str(meta) => true,
@@ -109,6 +127,7 @@ impl DeriveSetting {
             _ => false,
         });
 
+        let non_exhaustive = is_non_exhaustive(attrs);
         let has_size_cache = derive_serialize && !no_size_cache.get().unwrap_or_default();
         let has_runtime = derive_state;
 
@@ -117,6 +136,11 @@ impl DeriveSetting {
         let derive_wire_type = derive_serialize || derive_deserialize;
         let derive_meta = derive_deserialize && !no_meta.get().unwrap_or_default();
 
+        // `STEIT_LINT=1` lints every derived type, which is handy for a one-off audit.
+        let lint = lint.get().unwrap_or_else(|| {
+            std::env::var("STEIT_LINT").is_ok_and(|lint| !lint.is_empty() && lint != "0")
+        });
+
         (
             Self {
                 derive_serialize,
@@ -139,6 +163,11 @@ impl DeriveSetting {
                 derive_wire_type,
                 derive_meta,
 
+                skip_unchanged: skip_unchanged.get().unwrap_or_default(),
+                non_exhaustive,
+                paths: paths.get().unwrap_or_default() && cfg!(feature = "runtime"),
+                lint,
+
                 ctor_prefix: ctor_prefix.get().unwrap_or_else(|| "new".to_string()),
                 size_cache_renamed: size_cache_renamed.get_with_tokens(),
                 runtime_renamed: runtime_renamed.get_with_tokens(),
@@ -169,6 +198,7 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
     let impler = Implementer::new(&input.ident, &input.generics);
     let (setting, unknown_attrs) = DeriveSetting::parse(&ctx, args, &mut input.attrs);
     let type_params = parse_type_params(&ctx, &input.generics);
+    let mut decls = TokenStream::new();
 
     let output = match &mut input.data {
         syn::Data::Struct(data) => Struct::parse(
@@ -181,6 +211,11 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
             None,
         )
         .ok()
+        .map(|r#struct| {
+            decls = r#struct.accessor_decl(&input.vis);
+            decls.extend(path_builder(&setting, &input, r#struct.path_steps()));
+            r#struct.into_token_stream()
+        })
         .into_token_stream(),
 
         syn::Data::Enum(data) => Enum::parse(
@@ -192,6 +227,11 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
             &mut data.variants,
         )
         .ok()
+        .map(|r#enum| {
+            decls = r#enum.view_decls(&input.vis);
+            decls.extend(path_builder(&setting, &input, r#enum.path_steps()));
+            r#enum.into_token_stream()
+        })
         .into_token_stream(),
 
         syn::Data::Union(data) => {
@@ -200,14 +240,17 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
         }
     };
 
-    let output = wrap_in_const(&setting, &input.ident, output);
+    let output = wrap_in_const(&setting, output);
     let derives = setting.derives;
+    let warnings = ctx.warnings();
     let errors = ctx.check().err().map(to_compile_errors);
 
     let derived = quote! {
         #[derive(#(#derives),*)]
         #input
+        #decls
         #output
+        #warnings
         #errors
     };
 
@@ -215,6 +258,153 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
     derived
 }
 
+/// Companion types follow `#[non_exhaustive]` on what they are derived from,
+/// so they can grow along with it.
+pub fn is_non_exhaustive(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("non_exhaustive"))
+}
+
+pub fn non_exhaustive_attr(non_exhaustive: bool) -> Option<TokenStream> {
+    if non_exhaustive {
+        Some(quote!(#[non_exhaustive]))
+    } else {
+        None
+    }
+}
+
+/// A method of a path builder, going down `tags` to a field of type `ty`.
+pub struct PathStep<'a> {
+    pub name: syn::Ident,
+    pub tags: Vec<u32>,
+    pub ty: &'a syn::Type,
+}
+
+/// `<Name>Path`, implemented for `Path<Name>` to build paths with field names instead of tags.
+fn path_builder(
+    setting: &DeriveSetting,
+    input: &syn::DeriveInput,
+    steps: Vec<PathStep>,
+) -> TokenStream {
+    if !setting.paths {
+        return quote!();
+    }
+
+    let name = &input.ident;
+
+    if !setting.derive_state {
+        return syn::Error::new_spanned(name, "`paths` requires `State`").to_compile_error();
+    }
+
+    if !input.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &input.generics,
+            "`paths` is not supported on generic types",
+        )
+        .to_compile_error();
+    }
+
+    let krate = setting.krate();
+    let vis = &input.vis;
+    let trait_name = format_ident!("{}Path", name);
+    let doc = format!("Builds paths into `{}` by field names.", name);
+
+    let signatures = steps
+        .iter()
+        .map(|PathStep { name, ty, .. }| quote!(fn #name(&self) -> #krate::rt::Path<#ty>));
+
+    let methods = steps.iter().map(|PathStep { name, tags, ty }| {
+        quote! {
+            fn #name(&self) -> #krate::rt::Path<#ty> {
+                let mut segments = self.segments().to_vec();
+                segments.extend_from_slice(&[#(#tags),*]);
+                #krate::rt::Path::from_segments(segments)
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #vis trait #trait_name {
+            #(#signatures;)*
+        }
+
+        impl #trait_name for #krate::rt::Path<#name> {
+            #(#methods)*
+        }
+
+        impl #name {
+            pub fn path() -> #krate::rt::Path<Self> {
+                #krate::rt::Path::root()
+            }
+        }
+    }
+}
+
+/// `<ctor>_for_test()`, building fixtures whose logs go nowhere.
+pub fn test_ctor(ctor_name: &syn::Ident) -> TokenStream {
+    let test_ctor_name = format_ident!("{}_for_test", ctor_name);
+
+    quote! {
+        /// Builds a fixture with a runtime which discards its logs, for tests.
+        pub fn #test_ctor_name() -> Self {
+            Self::#ctor_name(Runtime::noop())
+        }
+    }
+}
+
+/// Std conversions between values and their bytes, for generic code built on them.
+pub fn impl_conversions(impler: &Implementer, setting: &DeriveSetting) -> TokenStream {
+    let name = impler.name();
+    let mut tokens = TokenStream::new();
+
+    // Type parameters of states are bounded by `State` throughout, as in other impls.
+    let bounds = |fallback| {
+        if setting.derive_state {
+            "State"
+        } else {
+            fallback
+        }
+    };
+
+    if setting.derive_serialize {
+        let generics = impler.generics_with(&[bounds("Serialize")]);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        tokens.extend(quote! {
+            impl #impl_generics From<#name #ty_generics> for Vec<u8> #where_clause {
+                fn from(value: #name #ty_generics) -> Self {
+                    value.to_bytes()
+                }
+            }
+
+            impl #impl_generics From<&#name #ty_generics> for Vec<u8> #where_clause {
+                fn from(value: &#name #ty_generics) -> Self {
+                    value.to_bytes()
+                }
+            }
+        });
+    }
+
+    if setting.derive_deserialize {
+        let generics = impler.generics_with(&[bounds("Deserialize")]);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        tokens.extend(quote! {
+            impl #impl_generics ::std::convert::TryFrom<&[u8]> for #name #ty_generics #where_clause {
+                type Error = io::Error;
+
+                fn try_from(bytes: &[u8]) -> io::Result<Self> {
+                    Self::deserialize(&mut Reader::new(bytes))
+                }
+            }
+        });
+    }
+
+    tokens
+}
+
 fn parse_type_params<'a>(ctx: &Context, generics: &'a syn::Generics) -> Vec<&'a syn::TypeParam> {
     let mut type_params = Vec::new();
 
@@ -236,17 +426,20 @@ fn to_compile_errors(errors: Vec<syn::Error>) -> TokenStream {
     quote!(#(#compile_errors)*)
 }
 
-fn wrap_in_const(setting: &DeriveSetting, name: &syn::Ident, tokens: TokenStream) -> TokenStream {
-    let dummy_const = format_ident!(
-        "_IMPL_STEIT_FOR_{}",
-        str_util::to_snake_case(name.to_string()).to_uppercase(),
-    );
-
+fn wrap_in_const(setting: &DeriveSetting, tokens: TokenStream) -> TokenStream {
     let extern_crate = setting.extern_crate();
     let krate = setting.krate();
 
+    let runtime_imports = if cfg!(feature = "runtime") {
+        Some(quote! {
+            use #krate::{log::LogEntryKind, rt::Runtime, state::State};
+        })
+    } else {
+        None
+    };
+
     quote! {
-        const #dummy_const: () = {
+        const _: () = {
             #extern_crate
 
             use std::{
@@ -256,14 +449,15 @@ fn wrap_in_const(setting: &DeriveSetting, name: &syn::Ident, tokens: TokenStream
 
             use #krate::{
                 de::{Deserialize, Reader},
-                log::LogEntryKind,
                 meta::*,
-                rt::{Runtime, SizeCache},
+                rt::SizeCache,
                 ser::Serialize,
-                state::State,
+                types::Keyed,
                 wire_fmt::{HasWireType, WireType},
             };
 
+            #runtime_imports
+
             #tokens
         };
     }
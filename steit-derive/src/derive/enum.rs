@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use proc_macro2::TokenStream;
 use quote::ToTokens;
@@ -17,22 +17,30 @@ use super::{
 
 struct EnumAttrs {
     reserved_tags: Vec<u32>,
+    on_deserialize: Option<syn::ExprPath>,
+    on_serialize: Option<syn::ExprPath>,
     csharp_name: Option<String>,
 }
 
 impl EnumAttrs {
     pub fn parse(ctx: &Context, attrs: impl AttributeParse) -> Self {
         let mut reserved_tags = VecAttribute::new(ctx, "reserved_tags");
+        let mut on_deserialize = Attribute::new(ctx, "on_deserialize");
+        let mut on_serialize = Attribute::new(ctx, "on_serialize");
         let mut csharp_name = Attribute::new(ctx, "csharp_name");
 
         attrs.parse(ctx, true, |meta| match meta {
             syn::Meta::List(meta) if reserved_tags.parse_int_list(meta) => true,
+            syn::Meta::NameValue(meta) if on_deserialize.parse_expr_path(meta) => true,
+            syn::Meta::NameValue(meta) if on_serialize.parse_expr_path(meta) => true,
             syn::Meta::NameValue(meta) if csharp_name.parse_str(meta) => true,
             _ => false,
         });
 
         Self {
             reserved_tags: reserved_tags.get(),
+            on_deserialize: on_deserialize.get(),
+            on_serialize: on_serialize.get(),
             csharp_name: csharp_name.get(),
         }
     }
@@ -107,6 +115,19 @@ impl<'a> Enum<'a> {
 
         let ctors = self.variants.iter().map(|r#struct| r#struct.ctor());
 
+        let from_tag_arms = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let tag = variant.tag();
+            let ctor_name = variant.ctor_name();
+            quote!(#tag => Some(Self::#ctor_name(#default_ctor_args)))
+        });
+
+        let ctor_for_test = if self.setting.derive_state {
+            Some(derive::test_ctor(&default_ctor_name))
+        } else {
+            None
+        };
+
         self.impler.impl_with(
             self.trait_bounds(&["Default"]),
             quote! {
@@ -114,11 +135,63 @@ impl<'a> Enum<'a> {
                     #default_ctor
                 }
 
+                #ctor_for_test
                 #(#ctors)*
+
+                pub fn from_tag(tag: u32, #default_ctor_params) -> Option<Self> {
+                    match tag {
+                        #(#from_tag_arms,)*
+                        _ => None,
+                    }
+                }
             },
         )
     }
 
+    fn impl_variant_info(&self) -> TokenStream {
+        let name = self.impler.name();
+
+        let tags = self.variants.iter().map(|r#struct| {
+            let tag = r#struct.variant().unwrap().tag();
+            quote!(#tag)
+        });
+
+        let (tag_arms, name_arms): (Vec<_>, Vec<_>) = self
+            .variants
+            .iter()
+            .map(|r#struct| {
+                let variant = r#struct.variant().unwrap();
+                let qual = variant.qual();
+                let tag = variant.tag();
+                let variant_name = variant.name().to_string();
+
+                (
+                    quote!(#name #qual { .. } => #tag),
+                    quote!(#name #qual { .. } => #variant_name),
+                )
+            })
+            .unzip();
+
+        let accessors = self
+            .variants
+            .iter()
+            .map(|r#struct| r#struct.variant_accessors());
+
+        self.impler.r#impl(quote! {
+            pub const VARIANT_TAGS: &'static [u32] = &[#(#tags,)*];
+
+            pub fn variant_tag(&self) -> u32 {
+                match self { #(#tag_arms,)* }
+            }
+
+            pub fn variant_name(&self) -> &'static str {
+                match self { #(#name_arms,)* }
+            }
+
+            #(#accessors)*
+        })
+    }
+
     fn impl_setters(&self) -> TokenStream {
         let setters = self.variants.iter().map(|r#struct| r#struct.setters());
 
@@ -126,6 +199,106 @@ impl<'a> Enum<'a> {
             .impl_with(self.trait_bounds(&["Default"]), quote!(#(#setters)*))
     }
 
+    pub fn path_steps(&self) -> Vec<derive::PathStep<'_>> {
+        self.variants
+            .iter()
+            .flat_map(|r#struct| r#struct.path_steps())
+            .collect()
+    }
+
+    fn viewed_variants(&self) -> impl Iterator<Item = &Struct<'a>> {
+        self.variants
+            .iter()
+            .filter(|r#struct| r#struct.has_fields())
+    }
+
+    fn view_name(&self, variant: &Variant) -> syn::Ident {
+        format_ident!("{}{}Mut", self.impler.name(), variant.name())
+    }
+
+    fn view_generics(&self, bounds: &[&str]) -> syn::Generics {
+        let mut generics = self.impler.generics().clone();
+
+        for type_param in generics.type_params_mut() {
+            for bound in bounds {
+                type_param.bounds.push(syn::parse_str(bound).unwrap());
+            }
+        }
+
+        generics.params.insert(0, syn::parse_quote!('view));
+        generics
+    }
+
+    /// Declares views that borrow a variant mutably. These have to live outside the wrapping
+    /// const block so that they can be named by users.
+    pub fn view_decls(&self, vis: &syn::Visibility) -> TokenStream {
+        if !self.setting.derive_state {
+            return quote!();
+        }
+
+        let name = self.impler.name();
+        let (_, ty_generics, _) = self.impler.generics().split_for_impl();
+        let generics = self.view_generics(&[]);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        let decls = self.viewed_variants().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let view_name = self.view_name(variant);
+            let doc = format!("Mutable view of `{}::{}`.", name, variant.name());
+            let non_exhaustive = derive::non_exhaustive_attr(
+                self.setting.non_exhaustive || variant.is_non_exhaustive(),
+            );
+
+            quote! {
+                #[doc = #doc]
+                #non_exhaustive
+                #vis struct #view_name #impl_generics #where_clause {
+                    inner: &'view mut #name #ty_generics,
+                }
+            }
+        });
+
+        quote!(#(#decls)*)
+    }
+
+    fn impl_views(&self) -> TokenStream {
+        let name = self.impler.name();
+        let generics = self.view_generics(self.trait_bounds(&[]));
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let mut as_muts = Vec::new();
+        let mut views = Vec::new();
+
+        for r#struct in self.viewed_variants() {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+            let view_name = self.view_name(variant);
+            let as_mut_name = format_ident!("as_{}_mut", variant.snake_case_name());
+            let accessors = r#struct.view_accessors();
+
+            as_muts.push(quote! {
+                pub fn #as_mut_name<'view>(&'view mut self) -> Option<#view_name #ty_generics> {
+                    if let #name #qual { .. } = self {
+                        Some(#view_name { inner: self })
+                    } else {
+                        None
+                    }
+                }
+            });
+
+            views.push(quote! {
+                impl #impl_generics #view_name #ty_generics #where_clause {
+                    #accessors
+                }
+            });
+        }
+
+        let as_muts = self
+            .impler
+            .impl_with(self.trait_bounds(&[]), quote!(#(#as_muts)*));
+        quote!(#as_muts #(#views)*)
+    }
+
     fn impl_partial_eq(&self) -> TokenStream {
         let name = self.impler.name();
 
@@ -226,10 +399,11 @@ impl<'a> Enum<'a> {
             let tag = variant.tag();
 
             let destructure = r#struct.destructure();
+            let encoded_fields = r#struct.destructure_encoded_fields();
             let sizer = r#struct.sizer();
 
             quote! {
-                #name #qual { #destructure .. } => {
+                #name #qual { #destructure #encoded_fields .. } => {
                     size += #tag.cache_size();
                     #sizer
                 }
@@ -242,10 +416,11 @@ impl<'a> Enum<'a> {
             let tag = variant.tag();
 
             let destructure = r#struct.destructure();
+            let encoded_fields = r#struct.destructure_encoded_fields();
             let serializer = r#struct.serializer();
 
             quote! {
-                #name #qual { #destructure .. } => {
+                #name #qual { #destructure #encoded_fields .. } => {
                     #tag.serialize_cached(writer)?;
                     #serializer
                 }
@@ -287,6 +462,7 @@ impl<'a> Enum<'a> {
 
     fn impl_deserialize(&self) -> TokenStream {
         let name = self.impler.name();
+        let hook = self.on_deserialize_hook();
 
         let mergers = self.variants.iter().map(|r#struct| {
             let variant = r#struct.variant().unwrap();
@@ -335,15 +511,22 @@ impl<'a> Enum<'a> {
                         }
                     }
 
+                    #hook
                     Ok(())
                 }
             },
         )
     }
 
+    fn on_deserialize_hook(&self) -> Option<TokenStream> {
+        let hook = self.attrs.on_deserialize.as_ref()?;
+        Some(quote! { #hook(self); })
+    }
+
     fn impl_state(&self) -> TokenStream {
         let ctor_name = format_ident!("{}", &self.setting.ctor_prefix);
         let name = self.impler.name();
+        let hook = self.on_deserialize_hook();
 
         let runtimes = self.variants.iter().map(|r#struct| {
             let variant = r#struct.variant().unwrap();
@@ -397,6 +580,64 @@ impl<'a> Enum<'a> {
             }
         });
 
+        let path_serializers = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+            let tag = variant.tag();
+
+            let destructure = r#struct.destructure();
+            let path_serializer = r#struct.path_serializer();
+
+            quote! {
+                #tag => {
+                    if let #name #qual { #destructure .. } = self {
+                        #path_serializer
+                    } else {
+                        Ok(false)
+                    }
+                }
+            }
+        });
+
+        let aggregates_refreshers = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+
+            let destructure = r#struct.destructure();
+            let aggregates_refresher = r#struct.aggregates_refresher();
+
+            quote! {
+                #name #qual { #destructure .. } => { #aggregates_refresher }
+            }
+        });
+
+        let serialize_preparers = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+
+            let destructure = r#struct.destructure();
+            let serialize_preparer = r#struct.serialize_preparer();
+
+            quote! {
+                #name #qual { #destructure .. } => { #serialize_preparer }
+            }
+        });
+
+        let deserialize_finishers = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+
+            let destructure = r#struct.destructure();
+            let deserialize_finisher = r#struct.deserialize_finisher();
+
+            quote! {
+                #name #qual { #destructure .. } => { #deserialize_finisher }
+            }
+        });
+
+        let serialize_hook = self.attrs.on_serialize.as_ref();
+        let serialize_hook = serialize_hook.map(|hook| quote! { #hook(self); });
+
         self.impler.impl_for(
             "State",
             quote! {
@@ -416,6 +657,20 @@ impl<'a> Enum<'a> {
                     self.runtime().parent().is_root()
                 }
 
+                fn refresh_aggregates(&mut self) {
+                    match self { #(#aggregates_refreshers)* }
+                }
+
+                fn prepare_serialize(&mut self) {
+                    match self { #(#serialize_preparers)* }
+                    #serialize_hook
+                }
+
+                fn finish_deserialize(&mut self) {
+                    match self { #(#deserialize_finishers)* }
+                    #hook
+                }
+
                 fn handle_update(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
                     *self = Self::with_runtime(self.runtime().parent());
                     self.merge(reader)
@@ -428,7 +683,7 @@ impl<'a> Enum<'a> {
                     key: Option<u32>,
                     reader: &mut Reader<impl io::Read>,
                 ) -> io::Result<()> {
-                    if let Some(tag) = path.next() {
+                    let result = if let Some(tag) = path.next() {
                         match tag {
                             #(#replayers,)*
 
@@ -446,6 +701,22 @@ impl<'a> Enum<'a> {
                                 format!("{:?} is not supported on `{}`", kind, stringify!(#name)),
                             )),
                         }
+                    };
+
+                    #hook
+                    result
+                }
+
+                fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+                    match path.split_first() {
+                        Some((&tag, path)) => match tag {
+                            #(#path_serializers,)*
+                            _ => Ok(false),
+                        },
+                        None => {
+                            self.steit_serialize(writer)?;
+                            Ok(true)
+                        }
                     }
                 }
             },
@@ -462,9 +733,10 @@ impl<'a> Enum<'a> {
 
         let builtin = self.setting.steit_owned;
 
-        let variants = self.variants.iter().map(|r#struct| {
+        let variants = self.variants.iter().enumerate().map(|(index, r#struct)| {
             let variant = r#struct.variant().unwrap();
             let tag = variant.tag();
+            let is_default = Some(index) == self.default_variant_index;
 
             let meta = r#struct.meta();
 
@@ -472,6 +744,7 @@ impl<'a> Enum<'a> {
                 VariantMeta {
                     ty: #meta,
                     tag: #tag,
+                    is_default: #is_default,
                 }
             }
         });
@@ -524,10 +797,12 @@ fn parse_variants<'a>(
     let mut parsed_variants = Vec::with_capacity(variants.iter().len());
 
     let reserved_tags: HashSet<_> = attrs.reserved_tags.iter().collect();
-    let mut tags = HashSet::new();
+    let mut tags = HashMap::new();
     let mut unique_tags = true;
 
-    let mut default_variant_index = None;
+    // Set by `#[steit(default)]`, or else the variant with tag 0.
+    let mut marked_default: Option<(usize, syn::Ident)> = None;
+    let mut zero_variant_index = None;
 
     for variant in variants.iter_mut() {
         if let Ok((parsed_variant, unknown_attrs)) = Variant::parse(ctx, setting, variant) {
@@ -537,11 +812,33 @@ fn parse_variants<'a>(
                 ctx.error(tag_tokens, format!("tag {} has been reserved", tag));
             }
 
-            if !tags.insert(tag) {
-                ctx.error(tag_tokens, format!("duplicate tag {}", tag));
+            if let Some(other) = tags.get(&tag) {
+                ctx.error(
+                    tag_tokens,
+                    format!("duplicate tag {}, already used by variant `{}`", tag, other),
+                );
                 unique_tags = false;
+            } else {
+                tags.insert(tag, parsed_variant.name().clone());
             }
 
+            let name = parsed_variant.name().clone();
+            let is_default = match (parsed_variant.default_tokens(), &marked_default) {
+                (Some(tokens), Some((_, other))) => {
+                    ctx.error(
+                        tokens,
+                        format!(
+                            "duplicate default variant, `{}` is the default already",
+                            other
+                        ),
+                    );
+                    false
+                }
+
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
             if let Ok(r#struct) = Struct::parse(
                 ctx,
                 impler,
@@ -551,8 +848,12 @@ fn parse_variants<'a>(
                 &mut variant.fields,
                 Some(parsed_variant),
             ) {
-                if tag == 0 && default_variant_index.is_none() {
-                    default_variant_index = Some(parsed_variants.len());
+                if is_default {
+                    marked_default = Some((parsed_variants.len(), name));
+                }
+
+                if tag == 0 && zero_variant_index.is_none() {
+                    zero_variant_index = Some(parsed_variants.len());
                 }
 
                 parsed_variants.push(r#struct);
@@ -560,10 +861,14 @@ fn parse_variants<'a>(
         }
     }
 
+    let default_variant_index = marked_default
+        .map(|(index, _)| index)
+        .or(zero_variant_index);
+
     if default_variant_index.is_none() {
         ctx.error(
             impler.name(),
-            "expected a variant with tag 0 as the default variant `#[steit(tag = 0)]`",
+            "expected a default variant, marked `#[steit(default)]` or else with `#[steit(tag = 0)]`",
         );
     }
 
@@ -576,6 +881,12 @@ fn parse_variants<'a>(
 
 impl<'a> ToTokens for Enum<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.impl_variant_info());
+
+        if self.setting.derive_state {
+            tokens.extend(self.impl_views());
+        }
+
         if self.setting.derive_ctors {
             tokens.extend(self.impl_ctors());
         }
@@ -608,6 +919,8 @@ impl<'a> ToTokens for Enum<'a> {
             tokens.extend(self.impl_deserialize());
         }
 
+        tokens.extend(derive::impl_conversions(self.impler, self.setting));
+
         if self.setting.derive_state {
             tokens.extend(self.impl_state());
         }
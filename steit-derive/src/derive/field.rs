@@ -14,6 +14,24 @@ use super::{
     variant::Variant,
 };
 
+/// Inclusive bounds of `#[steit(range = "…")]`.
+type Bounds = (Option<i128>, Option<i128>);
+
+#[derive(Clone, Copy)]
+enum AggregateFn {
+    Sum,
+    Count,
+    Min,
+    Max,
+}
+
+/// `#[steit(aggregate = "sum(children.hp)")]`, over the items of a collection field.
+struct Aggregate {
+    function: AggregateFn,
+    collection: syn::Ident,
+    path: Vec<syn::Ident>,
+}
+
 struct FieldAttrs {
     tag: u32,
     tag_tokens: TokenStream,
@@ -21,6 +39,20 @@ struct FieldAttrs {
     no_hash: bool,
     no_eq_hash: bool,
     no_state: bool,
+    string_intern: bool,
+    skip_unchanged: bool,
+    no_log: bool,
+    client_writable: bool,
+    key: bool,
+    no_getter: bool,
+
+    max_len: Option<(usize, TokenStream)>,
+    range: Option<(Bounds, TokenStream)>,
+    clamp: bool,
+    aggregate: Option<(Aggregate, TokenStream)>,
+
+    serialize_with: Option<(syn::ExprPath, TokenStream)>,
+    deserialize_with: Option<(syn::ExprPath, TokenStream)>,
 
     csharp_name: Option<String>,
 }
@@ -32,6 +64,20 @@ impl FieldAttrs {
         let mut no_hash = Attribute::new(ctx, "no_hash");
         let mut no_eq_hash = Attribute::new(ctx, "no_eq_hash");
         let mut no_state = Attribute::new(ctx, "no_state");
+        let mut string_intern = Attribute::new(ctx, "string_intern");
+        let mut skip_unchanged = Attribute::new(ctx, "skip_unchanged");
+        let mut no_log = Attribute::new(ctx, "no_log");
+        let mut client_writable = Attribute::new(ctx, "client_writable");
+        let mut key = Attribute::new(ctx, "key");
+        let mut no_getter = Attribute::new(ctx, "no_getter");
+
+        let mut max_len = Attribute::new(ctx, "max_len");
+        let mut range = Attribute::new(ctx, "range");
+        let mut clamp = Attribute::new(ctx, "clamp");
+        let mut aggregate = Attribute::new(ctx, "aggregate");
+
+        let mut serialize_with = Attribute::new(ctx, "serialize_with");
+        let mut deserialize_with = Attribute::new(ctx, "deserialize_with");
 
         let mut csharp_name = Attribute::new(ctx, "csharp_name");
 
@@ -47,6 +93,35 @@ impl FieldAttrs {
             syn::Meta::Path(path) if no_state.parse_path(path) => true,
             syn::Meta::NameValue(meta) if no_state.parse_bool(meta) => true,
 
+            syn::Meta::Path(path) if string_intern.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if string_intern.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if skip_unchanged.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if skip_unchanged.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if no_log.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if no_log.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if client_writable.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if client_writable.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if key.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if key.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if no_getter.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if no_getter.parse_bool(meta) => true,
+
+            syn::Meta::NameValue(meta) if max_len.parse_int(meta) => true,
+            syn::Meta::NameValue(meta) if range.parse_str(meta) => true,
+
+            syn::Meta::Path(path) if clamp.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if clamp.parse_bool(meta) => true,
+
+            syn::Meta::NameValue(meta) if aggregate.parse_str(meta) => true,
+
+            syn::Meta::NameValue(meta) if serialize_with.parse_expr_path(meta) => true,
+            syn::Meta::NameValue(meta) if deserialize_with.parse_expr_path(meta) => true,
+
             syn::Meta::NameValue(meta) if csharp_name.parse_str(meta) => true,
 
             _ => false,
@@ -60,6 +135,37 @@ impl FieldAttrs {
             ctx.error(&tag_tokens, message);
         })?;
 
+        let range = match range.get_with_tokens() {
+            Some((range, tokens)) => match parse_range(&range) {
+                Ok(bounds) => Some((bounds, tokens)),
+                Err(message) => {
+                    ctx.error(&tokens, message);
+                    return Err(());
+                }
+            },
+
+            None => None,
+        };
+
+        let (clamp, clamp_tokens) = clamp.get_with_tokens().unwrap_or_default();
+
+        if clamp && range.is_none() {
+            ctx.error(clamp_tokens, "`clamp` is only supported along with `range`");
+            return Err(());
+        }
+
+        let aggregate = match aggregate.get_with_tokens() {
+            Some((aggregate, tokens)) => match parse_aggregate(&aggregate) {
+                Ok(aggregate) => Some((aggregate, tokens)),
+                Err(message) => {
+                    ctx.error(&tokens, message);
+                    return Err(());
+                }
+            },
+
+            None => None,
+        };
+
         Ok(Self {
             tag,
             tag_tokens,
@@ -67,6 +173,20 @@ impl FieldAttrs {
             no_hash: no_hash.get().unwrap_or_default(),
             no_eq_hash: no_eq_hash.get().unwrap_or_default(),
             no_state: no_state.get().unwrap_or_default(),
+            string_intern: string_intern.get().unwrap_or_default(),
+            skip_unchanged: skip_unchanged.get().unwrap_or_default(),
+            no_log: no_log.get().unwrap_or_default(),
+            client_writable: client_writable.get().unwrap_or_default(),
+            key: key.get().unwrap_or_default(),
+            no_getter: no_getter.get().unwrap_or_default(),
+
+            max_len: max_len.get_with_tokens(),
+            range,
+            clamp,
+            aggregate,
+
+            serialize_with: serialize_with.get_with_tokens(),
+            deserialize_with: deserialize_with.get_with_tokens(),
 
             csharp_name: csharp_name.get(),
         })
@@ -183,11 +303,64 @@ impl Field {
     }
 }
 
+/// A field marked with `#[steit(computed = "…")]`, which is never on the wire
+/// but recomputed from the other fields by a `fn(&Self) -> T`.
+pub struct ComputedField {
+    field: Field,
+    compute: syn::ExprPath,
+}
+
+impl ComputedField {
+    pub fn is_computed(field: &syn::Field) -> bool {
+        field.attrs.iter().any(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(meta)) if meta.path.is_ident("steit") => {
+                meta.nested.iter().any(|meta| match meta {
+                    syn::NestedMeta::Meta(meta) => meta.path().is_ident("computed"),
+                    _ => false,
+                })
+            }
+
+            _ => false,
+        })
+    }
+
+    pub fn parse(ctx: &Context, field: &mut syn::Field, index: usize) -> derive::Result<Self> {
+        let mut compute = Attribute::new(ctx, "computed");
+
+        (&mut field.attrs).parse(
+            ctx,
+            true,
+            |meta| matches!(meta, syn::Meta::NameValue(meta) if compute.parse_expr_path(meta)),
+        );
+
+        let compute = compute.get().ok_or_else(|| {
+            ctx.error(&*field, "expected a function `#[steit(computed = \"…\")]`")
+        })?;
+
+        Ok(Self {
+            field: Field::from_field(field, index),
+            compute,
+        })
+    }
+
+    pub fn init_default(&self) -> TokenStream {
+        self.field.init(quote!(Default::default()))
+    }
+
+    pub fn refresher(&self, value: TokenStream) -> TokenStream {
+        let access = self.field.access();
+        let compute = &self.compute;
+        quote! { #value.#access = #compute(&#value); }
+    }
+}
+
 pub struct DeriveField<'a> {
     setting: &'a DeriveSetting,
     attrs: FieldAttrs,
     field: Field,
     type_meta: Option<TokenStream>,
+    // Whether the field is a state logging its own changes, so it can be borrowed mutably.
+    is_nested: bool,
 }
 
 impl<'a> Deref for DeriveField<'a> {
@@ -207,7 +380,65 @@ impl<'a> DeriveField<'a> {
         index: usize,
     ) -> derive::Result<Self> {
         let attrs = FieldAttrs::parse(ctx, field)?;
+
+        if attrs.string_intern {
+            if !setting.derive_state || attrs.no_state {
+                ctx.error(field, "`string_intern` is only supported on `State` fields");
+                return Err(());
+            }
+
+            if !is_string(&field.ty) {
+                ctx.error(
+                    &field.ty,
+                    "`string_intern` is only supported on `String` fields",
+                );
+                return Err(());
+            }
+        }
+
+        if attrs.skip_unchanged && (!setting.derive_state || attrs.no_state) {
+            ctx.error(
+                field,
+                "`skip_unchanged` is only supported on `State` fields",
+            );
+            return Err(());
+        }
+
+        if attrs.no_log && (!setting.derive_state || attrs.no_state) {
+            ctx.error(field, "`no_log` is only supported on `State` fields");
+            return Err(());
+        }
+
+        // Writable paths are read from metadata by `WritePolicy::client_writable`.
+        if attrs.client_writable && !setting.derive_meta {
+            ctx.error(
+                field,
+                "`client_writable` is only supported on types with metadata",
+            );
+            return Err(());
+        }
+
+        if let (Some((_, tokens)), Some(_)) = (&attrs.max_len, &attrs.deserialize_with) {
+            ctx.error(
+                tokens,
+                "`max_len` is not supported along with `deserialize_with`",
+            );
+            return Err(());
+        }
+
+        if let (Some((_, tokens)), Some(_)) = (&attrs.range, &attrs.deserialize_with) {
+            ctx.error(
+                tokens,
+                "`range` is not supported along with `deserialize_with`",
+            );
+            return Err(());
+        }
+
+        validate_custom_codec(ctx, setting, &attrs, field)?;
+
         let field = Field::from_field(field, index);
+        let is_nested =
+            setting.derive_state && !attrs.no_state && !is_plain(&field.ty, type_params);
 
         let type_meta = if setting.derive_meta {
             Some(field_type_meta(ctx, &field.ty, type_params)?)
@@ -220,6 +451,7 @@ impl<'a> DeriveField<'a> {
             attrs,
             field,
             type_meta,
+            is_nested,
         })
     }
 
@@ -231,6 +463,118 @@ impl<'a> DeriveField<'a> {
         (self.attrs.tag, &self.attrs.tag_tokens)
     }
 
+    pub fn is_key(&self) -> bool {
+        self.attrs.key
+    }
+
+    /// Whether the field may occur more than once in a message, like `Vec` items written unpacked.
+    pub fn is_repeated(&self) -> bool {
+        match self.ty() {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => {
+                path.segments.last().unwrap().ident == "Vec"
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Name of the field as written in its struct, or its index for tuple structs.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.to_string(),
+            None => self.index.to_string(),
+        }
+    }
+
+    /// Warns about encodings which take more bytes than they could.
+    pub fn lint(&self, ctx: &Context) {
+        let (tag, tag_tokens) = self.tag_with_tokens();
+
+        if tag > 15 {
+            ctx.warn(
+                tag_tokens,
+                format!(
+                    "tag {} encodes in 2 bytes or more, only tags under 16 take 1",
+                    tag
+                ),
+            );
+        }
+
+        if is_vec_of(&self.field.ty, "u8") {
+            ctx.warn(
+                &self.field.ty,
+                "`Vec<u8>` encodes bytes over 127 in 2 bytes each, `Bytes` keeps them raw",
+            );
+        }
+    }
+
+    pub fn aggregate_collection(&self) -> Option<(&syn::Ident, &TokenStream)> {
+        self.attrs
+            .aggregate
+            .as_ref()
+            .map(|(aggregate, tokens)| (&aggregate.collection, tokens))
+    }
+
+    pub fn refresh_nested(&self, is_variant: bool) -> Option<TokenStream> {
+        if self.is_state() && self.attrs.aggregate.is_none() {
+            let field = self.field(is_variant);
+            Some(quote! { #field.refresh_aggregates(); })
+        } else {
+            None
+        }
+    }
+
+    pub fn prepare_nested(&self, is_variant: bool) -> Option<TokenStream> {
+        if self.is_state() {
+            let field = self.field(is_variant);
+            Some(quote! { #field.prepare_serialize(); })
+        } else {
+            None
+        }
+    }
+
+    pub fn finish_nested(&self, is_variant: bool) -> Option<TokenStream> {
+        if self.is_state() {
+            let field = self.field(is_variant);
+            Some(quote! { #field.finish_deserialize(); })
+        } else {
+            None
+        }
+    }
+
+    pub fn refresh_aggregate(&self) -> Option<TokenStream> {
+        let (aggregate, _) = self.attrs.aggregate.as_ref()?;
+
+        let ty = &self.ty;
+        let field = self.field(false);
+        let setter_name = self.alias_prefixed(format_ident!("set"));
+        let collection = &aggregate.collection;
+        let path = &aggregate.path;
+
+        let items = quote!(self.#collection.iter());
+        let values = if path.is_empty() {
+            quote!(#items.map(|item| *item as #ty))
+        } else {
+            quote!(#items.map(|item| item.#(#path).* as #ty))
+        };
+
+        let value = match aggregate.function {
+            AggregateFn::Sum => quote!(#values.sum::<#ty>()),
+            AggregateFn::Count if path.is_empty() => quote!(#items.count() as #ty),
+            AggregateFn::Count => quote!(#items.filter(|item| item.#(#path).*).count() as #ty),
+            AggregateFn::Min => quote!(#values.min().unwrap_or_default()),
+            AggregateFn::Max => quote!(#values.max().unwrap_or_default()),
+        };
+
+        Some(quote! {
+            let value = #value;
+
+            if #field != value {
+                self.#setter_name(value);
+            }
+        })
+    }
+
     pub fn is_state(&self) -> bool {
         self.setting.derive_state && !self.attrs.no_state
     }
@@ -255,17 +599,19 @@ impl<'a> DeriveField<'a> {
         let ty = &self.ty;
         let tag = self.tag();
 
-        let (reset_variant, set_value) = if let Some(variant) = variant {
+        let (reset_variant, set_value, current_value) = if let Some(variant) = variant {
             let qual = variant.qual();
             let ctor_name = variant.ctor_name();
 
             let destructure = self.destructure(format_ident!("self_value"));
 
             let new_variant = if self.setting.derive_state {
+                let krate = self.setting.krate();
+
                 quote! {{
                     let runtime = self.runtime().parent();
                     let value = Self::#ctor_name(runtime.clone());
-                    runtime.log_update(&value).unwrap();
+                    #krate::__private::expect_logged(runtime.log_update(&value));
                     value
                 }}
             } else {
@@ -284,19 +630,112 @@ impl<'a> DeriveField<'a> {
                         *self_value = value;
                     }
                 },
+                quote!(if let #struct_name #qual { #destructure, .. } = &*self { Some(self_value) } else { None }),
             )
         } else {
             let field = self.field(false);
-            (None, quote! { #field = value; })
+            (None, quote! { #field = value; }, quote!(Some(&#field)))
         };
 
-        let (setter, setter_with) = if self.is_state() {
-            let declare_runtime = quote! { let runtime = self.runtime(); };
-            let log_update = quote! { runtime.log_update_child(#tag, &value).unwrap(); };
+        // Nothing gets logged nor set if the setter is called with the current value.
+        let skip_unchanged = if self.attrs.skip_unchanged || self.setting.skip_unchanged {
+            Some(quote! {
+                if #current_value == Some(&value) {
+                    return self;
+                }
+            })
+        } else {
+            None
+        };
+
+        let krate = self.setting.krate();
+
+        let validate_max_len = self.attrs.max_len.as_ref().map(|(max_len, _)| {
+            let name = self.alias().to_string();
+            let len = quote!(#krate::de::HasLen::len(&value));
+            quote!(#krate::__private::validate_max_len(#name, #len, #max_len))
+        });
+
+        // Setters leave values rejected by constraints to `expect_valid`,
+        // which doesn't panic under `no-panic` but leaves the field as it was.
+        // States report them to the error handler of their tree as well.
+        let expect_valid = if self.setting.derive_state {
+            quote!(self.runtime().expect_valid)
+        } else {
+            quote!(#krate::__private::expect_valid)
+        };
+
+        let check_max_len = validate_max_len.as_ref().map(|validate_max_len| {
+            quote! {
+                if #expect_valid(#validate_max_len).is_none() {
+                    return self;
+                }
+            }
+        });
 
+        let check_range = self.check_range(quote!(value));
+        let expect_range = check_range.as_ref().map(|check_range| {
+            quote! {
+                match #expect_valid(#check_range) {
+                    Some(value) => value,
+                    None => return self,
+                }
+            }
+        });
+        let assign_range = expect_range
+            .as_ref()
+            .map(|expect_range| quote! { value = #expect_range; });
+        let let_range = expect_range
+            .as_ref()
+            .map(|expect_range| quote! { let value = #expect_range; });
+
+        // Constrained fields also get fallible setters, returning rejected values as errors.
+        let try_setter = if validate_max_len.is_some() || check_range.is_some() {
+            let try_setter_name = format_ident!("try_{}", setter_name);
+            let validate_max_len = validate_max_len
+                .as_ref()
+                .map(|validate_max_len| quote! { #validate_max_len?; });
+            let let_range = check_range
+                .as_ref()
+                .map(|check_range| quote! { let value = #check_range?; });
+
+            Some(quote! {
+                pub fn #try_setter_name(&mut self, value: #ty) -> io::Result<&mut Self> {
+                    #validate_max_len
+                    #let_range
+                    Ok(self.#setter_name(value))
+                }
+            })
+        } else {
+            None
+        };
+
+        let declare_runtime = quote! { let runtime = self.runtime(); };
+        // `no_log` fields only reach clients through snapshots.
+        // Mutations made inside them, like pushing to a nested list, are still logged.
+        let log_update = if !self.is_state() || self.attrs.no_log {
+            None
+        } else {
+            let krate = self.setting.krate();
+
+            if self.attrs.string_intern {
+                Some(quote! {
+                    #krate::__private::expect_logged(runtime.log_update_child_interned(#tag, &value));
+                })
+            } else {
+                Some(
+                    quote! { #krate::__private::expect_logged(runtime.log_update_child(#tag, &value)); },
+                )
+            }
+        };
+
+        let (setter, setter_with) = if self.is_state() {
             (
                 quote! {
                     pub fn #setter_name(&mut self, mut value: #ty) -> &mut Self {
+                        #check_max_len
+                        #assign_range
+                        #skip_unchanged
                         #reset_variant
                         #declare_runtime
                         value.set_runtime(runtime.nested(#tag));
@@ -312,6 +751,9 @@ impl<'a> DeriveField<'a> {
                         runtime.pause_logger();
                         let value = get_value(runtime.nested(#tag));
                         runtime.unpause_logger();
+                        #check_max_len
+                        #let_range
+                        #skip_unchanged
                         #log_update
                         #set_value
                         self
@@ -322,6 +764,8 @@ impl<'a> DeriveField<'a> {
             (
                 quote! {
                     pub fn #setter_name(&mut self, value: #ty) -> &mut Self {
+                        #check_max_len
+                        #let_range
                         #reset_variant
                         #set_value
                         self
@@ -331,8 +775,202 @@ impl<'a> DeriveField<'a> {
             )
         };
 
+        // Optional fields get a shorthand for setting them to `None`.
+        let clear = if is_option(ty) {
+            let clear_name =
+                format_ident!("clear{}", setter_name.to_string().trim_start_matches("set"));
+
+            Some(quote! {
+                pub fn #clear_name(&mut self) -> &mut Self {
+                    self.#setter_name(None)
+                }
+            })
+        } else {
+            None
+        };
+
+        // `String` fields can also be set from a `&str`, reusing their allocation.
+        let setter_str = if is_string(ty) {
+            let setter_str_name = format_ident!("{}_str", setter_name);
+
+            let skip_unchanged = skip_unchanged.as_ref().map(|_| {
+                quote! {
+                    if #current_value.map(String::as_str) == Some(value) {
+                        return self;
+                    }
+                }
+            });
+
+            let declare_runtime = log_update.as_ref().map(|_| &declare_runtime);
+
+            let set_value = match variant {
+                Some(variant) => {
+                    let qual = variant.qual();
+                    let destructure = self.destructure(format_ident!("self_value"));
+
+                    quote! {
+                        if let #struct_name #qual { #destructure, .. } = self {
+                            self_value.clear();
+                            self_value.push_str(value);
+                        }
+                    }
+                }
+
+                None => {
+                    let field = self.field(false);
+                    quote! { #field.clear(); #field.push_str(value); }
+                }
+            };
+
+            Some(quote! {
+                pub fn #setter_str_name(&mut self, value: &str) -> &mut Self {
+                    #check_max_len
+                    #skip_unchanged
+                    #reset_variant
+                    #declare_runtime
+                    #log_update
+                    #set_value
+                    self
+                }
+            })
+        } else {
+            None
+        };
+
         quote! {
             #setter
+            #try_setter
+            #setter_with
+            #setter_str
+            #clear
+        }
+    }
+
+    pub fn getter(&self, struct_name: &syn::Ident, variant: Option<&Variant>) -> TokenStream {
+        // `no_getter` leaves the name free for a hand-written getter.
+        if self.attrs.no_getter {
+            return quote!();
+        }
+
+        let getter_name = match variant {
+            Some(variant) => self.alias_prefixed(format_ident!("{}", variant.snake_case_name())),
+            None => self.alias(),
+        };
+
+        let getter_mut_name = format_ident!("{}_mut", getter_name);
+        let ty = &self.ty;
+
+        if let Some(variant) = variant {
+            let qual = variant.qual();
+            let destructure = self.destructure_alias();
+            let alias = self.alias();
+
+            let getter_mut = if self.is_nested {
+                Some(quote! {
+                    pub fn #getter_mut_name(&mut self) -> Option<&mut #ty> {
+                        #[allow(unreachable_patterns)]
+                        match self {
+                            #struct_name #qual { #destructure, .. } => Some(#alias),
+                            _ => None,
+                        }
+                    }
+                })
+            } else {
+                None
+            };
+
+            quote! {
+                pub fn #getter_name(&self) -> Option<&#ty> {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #struct_name #qual { #destructure, .. } => Some(#alias),
+                        _ => None,
+                    }
+                }
+
+                #getter_mut
+            }
+        } else {
+            let field = self.field(false);
+
+            // Plain values are changed through setters only, so the change gets logged.
+            let getter_mut = if self.is_nested {
+                Some(quote! {
+                    pub fn #getter_mut_name(&mut self) -> &mut #ty {
+                        &mut #field
+                    }
+                })
+            } else {
+                None
+            };
+
+            quote! {
+                pub fn #getter_name(&self) -> &#ty {
+                    &#field
+                }
+
+                #getter_mut
+            }
+        }
+    }
+
+    pub fn view_accessors(&self, struct_name: &syn::Ident, variant: &Variant) -> TokenStream {
+        let qual = variant.qual();
+        let ty = &self.ty;
+
+        let alias = self.alias();
+        let alias_mut = format_ident!("{}_mut", alias);
+        let destructure = self.destructure_alias();
+
+        let setter_name = format_ident!("set_{}", alias);
+        let variant_setter_name =
+            self.alias_prefixed(format_ident!("set_{}", variant.snake_case_name()));
+
+        let setter_with = if self.is_state() {
+            let setter_with_name = format_ident!("{}_with", setter_name);
+            let variant_setter_with_name = format_ident!("{}_with", variant_setter_name);
+
+            Some(quote! {
+                pub fn #setter_with_name(&mut self, get_value: impl FnOnce(Runtime) -> #ty) -> &mut Self {
+                    self.inner.#variant_setter_with_name(get_value);
+                    self
+                }
+            })
+        } else {
+            None
+        };
+
+        // Like getters, plain values are changed through the logged setter only.
+        let getter_mut = if self.is_nested {
+            Some(quote! {
+                pub fn #alias_mut(&mut self) -> &mut #ty {
+                    #[allow(unreachable_patterns)]
+                    match &mut *self.inner {
+                        #struct_name #qual { #destructure, .. } => #alias,
+                        _ => unreachable!(),
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
+        quote! {
+            pub fn #alias(&self) -> &#ty {
+                #[allow(unreachable_patterns)]
+                match &*self.inner {
+                    #struct_name #qual { #destructure, .. } => #alias,
+                    _ => unreachable!(),
+                }
+            }
+
+            #getter_mut
+
+            pub fn #setter_name(&mut self, value: #ty) -> &mut Self {
+                self.inner.#variant_setter_name(value);
+                self
+            }
+
             #setter_with
         }
     }
@@ -361,22 +999,161 @@ impl<'a> DeriveField<'a> {
         }
     }
 
-    pub fn sizer(&self, is_variant: bool) -> TokenStream {
-        let tag = self.tag();
+    fn field_ref(&self, is_variant: bool) -> TokenStream {
         let field = self.field(is_variant);
-        quote! { size += #field.compute_size_nested(#tag, true).unwrap(); }
+
+        if is_variant {
+            field
+        } else {
+            quote!(&#field)
+        }
+    }
+
+    pub fn has_serialize_with(&self) -> bool {
+        self.attrs.serialize_with.is_some()
     }
 
-    pub fn serializer(&self, is_variant: bool) -> TokenStream {
+    /// Custom-serialized fields are encoded once, into `encoded_fields`, while sizes are computed.
+    pub fn sizer(&self, is_variant: bool, encoded_fields: Option<&TokenStream>) -> TokenStream {
         let tag = self.tag();
-        let field = self.field(is_variant);
-        quote! { #field.serialize_nested(#tag, true, writer)?; }
+
+        let krate = self.setting.krate();
+
+        if let (Some((serialize_with, _)), Some(encoded_fields)) =
+            (&self.attrs.serialize_with, encoded_fields)
+        {
+            let field = self.field_ref(is_variant);
+
+            quote! {
+                size += #encoded_fields.cache_size_with(#tag, #field, |value, writer| {
+                    #serialize_with(value, writer)
+                });
+            }
+        } else {
+            let field = self.field(is_variant);
+            quote! { size += #krate::__private::expect_written(#field.compute_size_nested(#tag, true)); }
+        }
     }
 
-    pub fn merger(&self, is_variant: bool) -> TokenStream {
+    pub fn serializer(
+        &self,
+        is_variant: bool,
+        encoded_fields: Option<&TokenStream>,
+    ) -> TokenStream {
         let tag = self.tag();
+
+        if let (Some((serialize_with, _)), Some(encoded_fields)) =
+            (&self.attrs.serialize_with, encoded_fields)
+        {
+            let field = self.field_ref(is_variant);
+
+            quote! {
+                #encoded_fields.serialize_with(#tag, #field, |value, writer| {
+                    #serialize_with(value, writer)
+                }, writer)?;
+            }
+        } else {
+            let field = self.field(is_variant);
+            quote! { #field.serialize_nested(#tag, true, writer)?; }
+        }
+    }
+
+    /// Merges the field, with errors traced to it under `type_name`.
+    pub fn merger(&self, is_variant: bool, type_name: &str) -> TokenStream {
+        let krate = self.setting.krate();
         let field = self.field(is_variant);
-        quote! { #tag => #field.merge_nested(wire_type, reader)? }
+        let name = self.display_name();
+
+        let merge = if let Some((deserialize_with, _)) = &self.attrs.deserialize_with {
+            let field = if is_variant { quote!(*#field) } else { field };
+
+            quote! {
+                #field = match wire_type {
+                    WireType::Sized => #deserialize_with(wire_type, &mut reader.nested()?)?,
+                    _ => #deserialize_with(wire_type, reader)?,
+                };
+            }
+        } else if let Some((max_len, _)) = &self.attrs.max_len {
+            let field_name = self.alias().to_string();
+            let field = if is_variant {
+                field
+            } else {
+                quote!(&mut #field)
+            };
+
+            quote! { #krate::__private::merge_max_len(#field, #field_name, #max_len, wire_type, reader)?; }
+        } else if self.attrs.range.is_some() {
+            let field = if is_variant { quote!(*#field) } else { field };
+            let check_range = self.check_range(field.clone());
+
+            quote! {
+                #field.merge_nested(wire_type, reader)?;
+                #field = #check_range?;
+            }
+        } else {
+            quote! { #field.merge_nested(wire_type, reader)?; }
+        };
+
+        quote! {
+            #krate::__private::trace_field(#type_name, #name, || {
+                #merge
+                Ok(())
+            })?
+        }
+    }
+
+    /// Reads the field from an `Accessor` the way `merger` does.
+    pub fn accessor_getter(&self) -> TokenStream {
+        let tag = self.tag();
+        let alias = self.alias();
+        let ty = self.ty();
+
+        let value = if let Some((deserialize_with, _)) = &self.attrs.deserialize_with {
+            quote! {
+                self.inner.get_with(#tag, <#ty>::default(), |value, wire_type, reader| {
+                    *value = match wire_type {
+                        WireType::Sized => #deserialize_with(wire_type, &mut reader.nested()?)?,
+                        _ => #deserialize_with(wire_type, reader)?,
+                    };
+                    Ok(())
+                })
+            }
+        } else if let Some((max_len, _)) = &self.attrs.max_len {
+            let krate = self.setting.krate();
+            let name = alias.to_string();
+
+            quote! {
+                self.inner.get_with(#tag, <#ty>::default(), |value, wire_type, reader| {
+                    #krate::__private::merge_max_len(value, #name, #max_len, wire_type, reader)
+                })
+            }
+        } else {
+            quote!(self.inner.get::<#ty>(#tag))
+        };
+
+        let value = match self.check_range(quote!(value)) {
+            Some(check_range) => quote! {
+                let value = #value?;
+                #check_range
+            },
+            None => value,
+        };
+
+        quote! {
+            pub fn #alias(&self) -> io::Result<#ty> {
+                #value
+            }
+        }
+    }
+
+    fn check_range(&self, value: TokenStream) -> Option<TokenStream> {
+        let ((min, max), _) = self.attrs.range.as_ref()?;
+        let krate = self.setting.krate();
+        let name = self.alias().to_string();
+        let (min, max) = (bound(*min), bound(*max));
+        let clamp = self.attrs.clamp;
+
+        Some(quote!(#krate::__private::check_range(#name, #value, #min, #max, #clamp)))
     }
 
     pub fn runtime_setter(&self, is_variant: bool) -> Option<TokenStream> {
@@ -393,13 +1170,51 @@ impl<'a> DeriveField<'a> {
         let tag = self.attrs.tag;
         let field = self.field(is_variant);
 
-        if self.is_state() {
+        if let (true, Some((max_len, _))) = (self.is_state(), &self.attrs.max_len) {
+            let krate = self.setting.krate();
+            let name = self.alias().to_string();
+            let field = if is_variant {
+                field
+            } else {
+                quote!(&mut #field)
+            };
+
+            quote!(#tag => #krate::__private::handle_max_len(#field, #name, #max_len, path, kind, key, reader))
+        } else if let (true, Some(((min, max), _))) = (self.is_state(), &self.attrs.range) {
+            let krate = self.setting.krate();
+            let name = self.alias().to_string();
+            let field = if is_variant {
+                field
+            } else {
+                quote!(&mut #field)
+            };
+            let (min, max) = (bound(*min), bound(*max));
+            let clamp = self.attrs.clamp;
+
+            quote!(#tag => #krate::__private::handle_range(#field, #name, #min, #max, #clamp, path, kind, key, reader))
+        } else if self.is_state() {
             quote!(#tag => #field.handle(path, kind, key, reader))
         } else {
             quote!(#tag => Ok(()))
         }
     }
 
+    pub fn path_serializer(&self, is_variant: bool) -> TokenStream {
+        let tag = self.attrs.tag;
+        let field = self.field(is_variant);
+
+        if self.is_state() {
+            quote!(#tag => #field.serialize_at(path, writer))
+        } else {
+            quote! {
+                #tag if path.is_empty() => {
+                    #field.steit_serialize(writer)?;
+                    Ok(true)
+                }
+            }
+        }
+    }
+
     pub fn meta(&self) -> TokenStream {
         let rust_name = self.alias().to_string();
 
@@ -411,6 +1226,23 @@ impl<'a> DeriveField<'a> {
         let type_meta = self.type_meta.as_ref().unwrap();
         let tag = self.tag();
 
+        let max_len = match &self.attrs.max_len {
+            Some((max_len, _)) => quote!(Some(#max_len)),
+            None => quote!(None),
+        };
+
+        let client_writable = self.attrs.client_writable;
+
+        let range = match &self.attrs.range {
+            Some(((min, max), _)) => {
+                let (min, max) = (bound(*min), bound(*max));
+                let clamp = self.attrs.clamp;
+                quote!(Some(RangeMeta { min: #min, max: #max, clamp: #clamp }))
+            }
+
+            None => quote!(None),
+        };
+
         quote! {
             FieldMeta {
                 name: &NameMeta {
@@ -419,11 +1251,207 @@ impl<'a> DeriveField<'a> {
                 },
                 ty: &#type_meta,
                 tag: #tag,
+                max_len: #max_len,
+                range: #range,
+                client_writable: #client_writable,
             }
         }
     }
 }
 
+fn validate_custom_codec(
+    ctx: &Context,
+    setting: &DeriveSetting,
+    attrs: &FieldAttrs,
+    field: &syn::Field,
+) -> derive::Result<()> {
+    let custom = attrs
+        .serialize_with
+        .as_ref()
+        .or(attrs.deserialize_with.as_ref());
+
+    let (_, tokens) = match custom {
+        Some(custom) => custom,
+        None => return Ok(()),
+    };
+
+    if setting.derive_state && !attrs.no_state {
+        ctx.error(
+            tokens,
+            "`serialize_with` and `deserialize_with` are only supported on `no_state` fields",
+        );
+        return Err(());
+    }
+
+    if setting.derive_meta {
+        ctx.error(
+            tokens,
+            "`serialize_with` and `deserialize_with` cannot be described by meta, consider `#[steit(no_meta)]`",
+        );
+        return Err(());
+    }
+
+    if setting.derive_serialize && attrs.serialize_with.is_none() {
+        ctx.error(
+            field,
+            "expected `serialize_with` to go with `deserialize_with`",
+        );
+        return Err(());
+    }
+
+    if setting.derive_deserialize && attrs.deserialize_with.is_none() {
+        ctx.error(
+            field,
+            "expected `deserialize_with` to go with `serialize_with`",
+        );
+        return Err(());
+    }
+
+    Ok(())
+}
+
+fn bound(bound: Option<i128>) -> TokenStream {
+    match bound {
+        Some(bound) => {
+            let bound = proc_macro2::Literal::i128_unsuffixed(bound);
+            quote!(Some(#bound))
+        }
+
+        None => quote!(None),
+    }
+}
+
+/// Parses `a..=b`, `a..b` and their half-open forms into inclusive bounds.
+fn parse_range(range: &str) -> Result<Bounds, &'static str> {
+    let message = "expected `range` to be an integer range like \"0..=100\"";
+
+    let (start, end, inclusive) = if let Some(index) = range.find("..=") {
+        (&range[..index], &range[index + 3..], true)
+    } else if let Some(index) = range.find("..") {
+        (&range[..index], &range[index + 2..], false)
+    } else {
+        return Err(message);
+    };
+
+    let parse = |bound: &str| match bound.trim() {
+        "" => Ok(None),
+        bound => bound
+            .replace('_', "")
+            .parse::<i128>()
+            .map(Some)
+            .map_err(|_| message),
+    };
+
+    let min = parse(start)?;
+    let max = match parse(end)? {
+        Some(end) if !inclusive => Some(end - 1),
+        None if inclusive => return Err(message),
+        end => end,
+    };
+
+    match (min, max) {
+        (None, None) => Err("expected `range` to have at least one bound"),
+        (Some(min), Some(max)) if min > max => Err("expected `range` not to be empty"),
+        bounds => Ok(bounds),
+    }
+}
+
+/// Parses `function(collection.field…)`, where the field path is optional for `count`.
+fn parse_aggregate(aggregate: &str) -> Result<Aggregate, &'static str> {
+    let message = "expected `aggregate` to be like \"sum(children.hp)\", \
+                   with one of `sum`, `count`, `min` or `max`";
+
+    let aggregate = aggregate.trim();
+    let open = aggregate.find('(').ok_or(message)?;
+
+    if !aggregate.ends_with(')') {
+        return Err(message);
+    }
+
+    let function = match aggregate[..open].trim() {
+        "sum" => AggregateFn::Sum,
+        "count" => AggregateFn::Count,
+        "min" => AggregateFn::Min,
+        "max" => AggregateFn::Max,
+        _ => return Err(message),
+    };
+
+    let mut segments = aggregate[open + 1..aggregate.len() - 1]
+        .split('.')
+        .map(|segment| syn::parse_str::<syn::Ident>(segment.trim()).map_err(|_| message));
+
+    let collection = segments.next().ok_or(message)??;
+    let path = segments.collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Aggregate {
+        function,
+        collection,
+        path,
+    })
+}
+
+fn is_string(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+            segment.ident == "String" && segment.arguments.is_empty()
+        }
+
+        _ => false,
+    }
+}
+
+// Types which don't log changes made through `&mut`, with type parameters as they might be plain.
+fn is_plain(ty: &syn::Type, type_params: &[&syn::TypeParam]) -> bool {
+    const PLAIN: &[&str] = &[
+        "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+        "i128", "isize", "f32", "f64", "String", "Bytes", "Option", "Vec", "Box",
+    ];
+
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+            PLAIN.iter().any(|plain| segment.ident == plain)
+                || type_params.iter().any(|param| path.is_ident(&param.ident))
+        }
+
+        _ => true,
+    }
+}
+
+fn is_vec_of(ty: &syn::Type, item: &str) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+                    matches!(
+                        args.args.first(),
+                        Some(syn::GenericArgument::Type(syn::Type::Path(arg))) if arg.path.is_ident(item)
+                    )
+                }
+
+                _ => false,
+            }
+        }
+
+        _ => false,
+    }
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+            segment.ident == "Option"
+                && matches!(segment.arguments, syn::PathArguments::AngleBracketed(_))
+        }
+
+        _ => false,
+    }
+}
+
 fn field_type_meta(
     ctx: &Context,
     ty: &syn::Type,
@@ -457,7 +1485,7 @@ fn field_type_meta(
     match ty {
         syn::Type::Paren(syn::TypeParen { elem, .. })
         | syn::Type::Reference(syn::TypeReference { elem, .. }) => {
-            field_type_meta(&ctx, &*elem, type_params)
+            field_type_meta(ctx, elem, type_params)
         }
 
         syn::Type::Array(syn::TypeArray { elem, .. }) => {
@@ -533,6 +1561,14 @@ fn field_type_meta(
             }
         }
 
+        syn::Type::Tuple(_) => {
+            ctx.error(
+                ty,
+                "tuples have no meta, use a struct or `#[steit(no_meta)]` on the type",
+            );
+            Err(())
+        }
+
         _ => type_not_supported(),
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use proc_macro2::TokenStream;
 use quote::ToTokens;
@@ -11,54 +11,118 @@ use crate::{
 
 use super::{
     derive::{self, DeriveSetting},
-    field::{DeriveField, Field},
+    field::{ComputedField, DeriveField, Field},
     variant::Variant,
 };
 
+/// `#[steit(repeated_tags = "…")]`, what to do when a field occurs more than once in a message.
+/// `Vec` fields are exempt, as items of numbers may be written one per occurrence.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RepeatedTags {
+    /// Later occurrences are merged into the field, so the last one wins for primitives,
+    /// like protobuf.
+    Last,
+    /// Later occurrences are skipped.
+    First,
+    /// Later occurrences fail deserialization.
+    Error,
+}
+
 struct StructAttrs {
     reserved_tags: Vec<u32>,
+    repeated_tags: RepeatedTags,
 
     no_size_cache: bool,
+    preserve_unknown: bool,
+    component: bool,
+    accessor: bool,
 
     size_cache_renamed: Option<(String, TokenStream)>,
     runtime_renamed: Option<(String, TokenStream)>,
 
+    on_deserialize: Option<syn::ExprPath>,
+    on_serialize: Option<syn::ExprPath>,
+
     csharp_name: Option<String>,
 }
 
 impl StructAttrs {
     pub fn parse(ctx: &Context, attrs: impl AttributeParse) -> Self {
         let mut reserved_tags = VecAttribute::new(ctx, "reserved_tags");
+        let mut repeated_tags = Attribute::new(ctx, "repeated_tags");
 
         let mut no_size_cache = Attribute::new(ctx, "no_size_cache");
+        let mut preserve_unknown = Attribute::new(ctx, "preserve_unknown");
+        let mut component = Attribute::new(ctx, "component");
+        let mut accessor = Attribute::new(ctx, "accessor");
 
         let mut size_cache_renamed = Attribute::new(ctx, "size_cache_renamed");
         let mut runtime_renamed = Attribute::new(ctx, "runtime_renamed");
 
+        let mut on_deserialize = Attribute::new(ctx, "on_deserialize");
+        let mut on_serialize = Attribute::new(ctx, "on_serialize");
+
         let mut csharp_name = Attribute::new(ctx, "csharp_name");
 
         attrs.parse(ctx, true, |meta| match meta {
             syn::Meta::List(meta) if reserved_tags.parse_int_list(meta) => true,
+            syn::Meta::NameValue(meta) if repeated_tags.parse_str(meta) => true,
 
             syn::Meta::Path(path) if no_size_cache.parse_path(path) => true,
             syn::Meta::NameValue(meta) if no_size_cache.parse_bool(meta) => true,
 
+            syn::Meta::Path(path) if preserve_unknown.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if preserve_unknown.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if component.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if component.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if accessor.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if accessor.parse_bool(meta) => true,
+
             syn::Meta::NameValue(meta) if size_cache_renamed.parse_str(meta) => true,
             syn::Meta::NameValue(meta) if runtime_renamed.parse_str(meta) => true,
 
+            syn::Meta::NameValue(meta) if on_deserialize.parse_expr_path(meta) => true,
+            syn::Meta::NameValue(meta) if on_serialize.parse_expr_path(meta) => true,
+
             syn::Meta::NameValue(meta) if csharp_name.parse_str(meta) => true,
 
             _ => false,
         });
 
+        let repeated_tags = match repeated_tags.get_with_tokens() {
+            Some((repeated_tags, tokens)) => match &*repeated_tags {
+                "last" => RepeatedTags::Last,
+                "first" => RepeatedTags::First,
+                "error" => RepeatedTags::Error,
+                _ => {
+                    ctx.error(
+                        tokens,
+                        "expected `repeated_tags` to be one of \"last\", \"first\" or \"error\"",
+                    );
+                    RepeatedTags::Last
+                }
+            },
+
+            None => RepeatedTags::Last,
+        };
+
         Self {
             reserved_tags: reserved_tags.get(),
+            repeated_tags,
 
             no_size_cache: no_size_cache.get().unwrap_or_default(),
+            preserve_unknown: preserve_unknown.get().unwrap_or_default(),
+            component: component.get().unwrap_or_default(),
+            accessor: accessor.get().unwrap_or_default(),
 
             size_cache_renamed: size_cache_renamed.get_with_tokens(),
             runtime_renamed: runtime_renamed.get_with_tokens(),
 
+            on_deserialize: on_deserialize.get(),
+            on_serialize: on_serialize.get(),
+
             csharp_name: csharp_name.get(),
         }
     }
@@ -70,8 +134,11 @@ pub struct Struct<'a> {
     attrs: StructAttrs,
     type_params: &'a [&'a syn::TypeParam],
     fields: Vec<DeriveField<'a>>,
+    computed: Vec<ComputedField>,
     size_cache: Option<Field>,
     runtime: Option<Field>,
+    unknown_fields: Option<Field>,
+    encoded_fields: Option<Field>,
     variant: Option<Variant<'a>>,
 }
 
@@ -92,10 +159,91 @@ impl<'a> Struct<'a> {
         variant: Option<Variant<'a>>,
     ) -> derive::Result<Self> {
         let attrs = StructAttrs::parse(ctx, attrs);
-        let parsed_fields = parse_fields(ctx, setting, &attrs, type_params, fields)?;
+
+        if let (true, Some(variant)) = (attrs.component, &variant) {
+            ctx.error(variant.name(), "variants cannot be components");
+            return Err(());
+        }
+
+        if let (true, Some(variant)) = (attrs.accessor, &variant) {
+            ctx.error(variant.name(), "`accessor` is not supported on variants");
+            return Err(());
+        }
+
+        if attrs.accessor && !type_params.is_empty() {
+            ctx.error(
+                impler.name(),
+                "`accessor` is not supported on generic structs",
+            );
+            return Err(());
+        }
+
+        if attrs.accessor && !setting.derive_deserialize {
+            ctx.error(impler.name(), "`accessor` requires `Deserialize`");
+            return Err(());
+        }
+
+        if let (true, Some(variant)) = (attrs.preserve_unknown, &variant) {
+            ctx.error(
+                variant.name(),
+                "`preserve_unknown` is not supported on variants",
+            );
+            return Err(());
+        }
+
+        if let (Some(_), Some(variant)) = (&attrs.on_deserialize, &variant) {
+            ctx.error(
+                variant.name(),
+                "`on_deserialize` is not supported on variants but their enums",
+            );
+            return Err(());
+        }
+
+        if let (Some(_), Some(variant)) = (&attrs.on_serialize, &variant) {
+            ctx.error(
+                variant.name(),
+                "`on_serialize` is not supported on variants but their enums",
+            );
+            return Err(());
+        }
+
+        let (parsed_fields, computed) = parse_fields(ctx, setting, &attrs, type_params, fields)?;
+
+        if let (false, Some(variant)) = (computed.is_empty(), &variant) {
+            ctx.error(
+                variant.name(),
+                "`computed` fields are not supported on variants",
+            );
+            return Err(());
+        }
+
+        if let (true, Some(variant)) = (parsed_fields.iter().any(DeriveField::is_key), &variant) {
+            ctx.error(variant.name(), "`key` fields are not supported on variants");
+            return Err(());
+        }
+
+        for field in &parsed_fields {
+            if let Some((collection, tokens)) = field.aggregate_collection() {
+                if variant.is_some() || !setting.derive_state {
+                    ctx.error(tokens, "`aggregate` is only supported on fields of states");
+                    return Err(());
+                }
+
+                if !parsed_fields
+                    .iter()
+                    .any(|other| other.alias() == *collection)
+                {
+                    ctx.error(
+                        tokens,
+                        format!("unknown field `{}` in `aggregate`", collection),
+                    );
+                    return Err(());
+                }
+            }
+        }
 
         let krate = setting.krate();
-        let mut field_index = parsed_fields.len();
+        let mut field_index = fields.iter().len();
 
         let size_cache = if setting.has_size_cache && !attrs.no_size_cache {
             Some(add_field(
@@ -133,19 +281,49 @@ impl<'a> Struct<'a> {
             None
         };
 
+        let unknown_fields = if attrs.preserve_unknown {
+            Some(add_field(
+                fields,
+                "unknown_fields".to_string(),
+                syn::parse_quote!(#krate::rt::UnknownFields),
+                {
+                    field_index += 1;
+                    field_index - 1
+                },
+            ))
+        } else {
+            None
+        };
+
+        let encoded_fields = if setting.derive_serialize
+            && parsed_fields.iter().any(DeriveField::has_serialize_with)
+        {
+            Some(add_field(
+                fields,
+                "encoded_fields".to_string(),
+                syn::parse_quote!(#krate::rt::EncodedFields),
+                field_index,
+            ))
+        } else {
+            None
+        };
+
         Ok(Self {
             impler,
             setting,
             attrs,
             type_params,
             fields: parsed_fields,
+            computed,
             size_cache,
             runtime,
+            unknown_fields,
+            encoded_fields,
             variant,
         })
     }
 
-    pub fn variant(&self) -> Option<&Variant> {
+    pub fn variant(&self) -> Option<&Variant<'_>> {
         self.variant.as_ref()
     }
 
@@ -188,11 +366,22 @@ impl<'a> Struct<'a> {
         let name = self.impler.name();
         let qual = self.variant().map(|variant| variant.qual());
         let mut inits: Vec<_> = map_fields!(self, _.init_default()).collect();
+        inits.extend(self.computed.iter().map(ComputedField::init_default));
 
         if let Some(size_cache) = self.size_cache() {
             inits.push(size_cache.init(quote!(SizeCache::new())));
         }
 
+        if let Some(unknown_fields) = &self.unknown_fields {
+            let krate = self.setting.krate();
+            inits.push(unknown_fields.init(quote!(#krate::rt::UnknownFields::new())));
+        }
+
+        if let Some(encoded_fields) = &self.encoded_fields {
+            let krate = self.setting.krate();
+            inits.push(encoded_fields.init(quote!(#krate::rt::EncodedFields::new())));
+        }
+
         let (params, set_variant_runtime) = if let Some(runtime) = self.runtime() {
             inits.push(runtime.init(quote!(runtime)));
 
@@ -207,23 +396,64 @@ impl<'a> Struct<'a> {
             Default::default()
         };
 
+        let body = if self.computed.is_empty() {
+            quote!(#name #qual { #(#inits,)* })
+        } else {
+            let computer = self.computer(quote!(value));
+
+            quote! {
+                let mut value = #name #qual { #(#inits,)* };
+                #computer
+                value
+            }
+        };
+
         quote! {
             pub fn #ctor_name(#params) -> Self {
                 #set_variant_runtime
-                #name #qual { #(#inits,)* }
+                #body
             }
         }
     }
 
+    /// Recomputes `computed` fields, which never go through the wire.
+    fn computer(&self, value: TokenStream) -> TokenStream {
+        let refreshers = self
+            .computed
+            .iter()
+            .map(|field| field.refresher(value.clone()));
+
+        quote!(#(#refreshers)*)
+    }
+
+    /// Runs after the struct is merged or replayed into.
+    fn deserialized(&self) -> TokenStream {
+        let computer = self.computer(quote!(self));
+        let hook = self.attrs.on_deserialize.as_ref();
+        let hook = hook.map(|hook| quote! { #hook(self); });
+        quote!(#computer #hook)
+    }
+
     fn impl_ctor(&self) -> TokenStream {
-        self.impler
-            .impl_with(self.trait_bounds(&["Default"]), self.ctor())
+        let ctor_for_test = if self.setting.derive_state {
+            Some(derive::test_ctor(&self.ctor_name()))
+        } else {
+            None
+        };
+
+        let ctor = self.ctor();
+
+        self.impler.impl_with(
+            self.trait_bounds(&["Default"]),
+            quote!(#ctor #ctor_for_test),
+        )
     }
 
     pub fn setters(&self) -> TokenStream {
         let name = self.impler.name();
+        let getters = map_fields!(self, _.getter(name, self.variant()));
         let setters = map_fields!(self, _.setter(name, self.variant()));
-        quote!(#(#setters)*)
+        quote!(#(#getters)* #(#setters)*)
     }
 
     fn impl_setters(&self) -> TokenStream {
@@ -237,6 +467,92 @@ impl<'a> Struct<'a> {
         )
     }
 
+    pub fn path_steps(&self) -> Vec<derive::PathStep<'_>> {
+        self.fields
+            .iter()
+            .filter(|field| field.is_state())
+            .map(|field| match &self.variant {
+                Some(variant) => derive::PathStep {
+                    name: format_ident!("{}_{}", variant.snake_case_name(), field.alias()),
+                    tags: vec![variant.tag(), field.tag()],
+                    ty: field.ty(),
+                },
+
+                None => derive::PathStep {
+                    name: field.alias(),
+                    tags: vec![field.tag()],
+                    ty: field.ty(),
+                },
+            })
+            .collect()
+    }
+
+    pub fn has_fields(&self) -> bool {
+        !self.fields.is_empty()
+    }
+
+    /// `is_*`, and for variants with fields `as_*` and `try_into_*`,
+    /// which give the fields as a tuple, or as they are if there is only one.
+    pub fn variant_accessors(&self) -> TokenStream {
+        let name = self.impler.name();
+        let variant = self.variant().unwrap();
+        let qual = variant.qual();
+        let snake_case_name = variant.snake_case_name();
+        let is_name = format_ident!("is_{}", snake_case_name);
+
+        let is = quote! {
+            pub fn #is_name(&self) -> bool {
+                matches!(self, #name #qual { .. })
+            }
+        };
+
+        if !self.has_fields() {
+            return is;
+        }
+
+        let as_name = format_ident!("as_{}", snake_case_name);
+        let try_into_name = format_ident!("try_into_{}", snake_case_name);
+        let destructure: Vec<_> = map_fields!(self, _.destructure_alias()).collect();
+        let aliases: Vec<_> = map_fields!(self, _.alias()).collect();
+        let tys: Vec<_> = map_fields!(self, _.ty()).collect();
+
+        let (values, ref_tys, value_tys) = match (&*aliases, &*tys) {
+            ([alias], [ty]) => (quote!(#alias), quote!(&#ty), quote!(#ty)),
+            _ => (
+                quote!((#(#aliases,)*)),
+                quote!((#(&#tys,)*)),
+                quote!((#(#tys,)*)),
+            ),
+        };
+
+        quote! {
+            #is
+
+            pub fn #as_name(&self) -> Option<#ref_tys> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #name #qual { #(#destructure,)* .. } => Some(#values),
+                    _ => None,
+                }
+            }
+
+            pub fn #try_into_name(self) -> Result<#value_tys, Self> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #name #qual { #(#destructure,)* .. } => Ok(#values),
+                    _ => Err(self),
+                }
+            }
+        }
+    }
+
+    pub fn view_accessors(&self) -> TokenStream {
+        let name = self.impler.name();
+        let variant = self.variant().unwrap();
+        let accessors = map_fields!(self, _.view_accessors(name, variant));
+        quote!(#(#accessors)*)
+    }
+
     pub fn eq(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
         let eqs = map_fields!(self, _.eq(is_variant));
@@ -309,16 +625,60 @@ impl<'a> Struct<'a> {
         )
     }
 
+    // Fields are always serialized in ascending tag order so that output doesn't depend on
+    // the order they are declared in.
+    fn fields_by_tag(&self) -> Vec<&DeriveField<'a>> {
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        fields.sort_by_key(|field| field.tag());
+        fields
+    }
+
+    /// Binds the cache of custom-serialized fields in patterns of variants, if there is one.
+    pub fn destructure_encoded_fields(&self) -> Option<TokenStream> {
+        self.encoded_fields.as_ref().map(|encoded_fields| {
+            let destructure = encoded_fields.destructure_alias();
+            quote!(#destructure,)
+        })
+    }
+
+    fn encoded_fields_ref(&self, is_variant: bool) -> Option<TokenStream> {
+        self.encoded_fields
+            .as_ref()
+            .map(|encoded_fields| encoded_fields.field(is_variant))
+    }
+
     pub fn sizer(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
-        let sizers = map_fields!(self, _.sizer(is_variant));
-        quote!(#(#sizers)*)
+        let encoded_fields = self.encoded_fields_ref(is_variant);
+        let sizers = self
+            .fields_by_tag()
+            .into_iter()
+            .map(|field| field.sizer(is_variant, encoded_fields.as_ref()));
+
+        let unknown_fields = self.unknown_fields.as_ref().map(|unknown_fields| {
+            let unknown_fields = unknown_fields.field(false);
+            quote! { size += #unknown_fields.size(); }
+        });
+
+        quote!(#(#sizers)* #unknown_fields)
     }
 
     pub fn serializer(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
-        let serializers = map_fields!(self, _.serializer(is_variant));
-        quote!(#(#serializers)*)
+
+        let encoded_fields = self.encoded_fields_ref(is_variant);
+        let serializers = self
+            .fields_by_tag()
+            .into_iter()
+            .map(|field| field.serializer(is_variant, encoded_fields.as_ref()));
+
+        // Unknown fields go last, as they were read after the known ones they followed.
+        let unknown_fields = self.unknown_fields.as_ref().map(|unknown_fields| {
+            let unknown_fields = unknown_fields.field(false);
+            quote! { #unknown_fields.serialize(writer)?; }
+        });
+
+        quote!(#(#serializers)* #unknown_fields)
     }
 
     fn impl_serialize(&self) -> TokenStream {
@@ -354,16 +714,79 @@ impl<'a> Struct<'a> {
     }
 
     pub fn merger(&self) -> TokenStream {
+        let krate = self.setting.krate();
         let is_variant = self.variant.is_some();
-        let mergers = map_fields!(self, _.merger(is_variant));
+        let type_name = match &self.variant {
+            Some(variant) => format!("{}::{}", self.impler.name(), variant.name()),
+            None => self.impler.name().to_string(),
+        };
+
+        let repeated_tags = self.attrs.repeated_tags;
+        let mut tracked = 0usize;
+
+        let mergers: Vec<_> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let tag = field.tag();
+                let merge = field.merger(is_variant, &type_name);
+
+                if repeated_tags == RepeatedTags::Last || field.is_repeated() {
+                    return quote! { #tag => #merge };
+                }
+
+                let index = tracked;
+                tracked += 1;
+
+                let repeated = if repeated_tags == RepeatedTags::First {
+                    quote!(#krate::de::skip_field(wire_type, reader)?)
+                } else {
+                    let message = format!(
+                        "{}.{} occurs more than once, with tag {}",
+                        type_name,
+                        field.display_name(),
+                        tag,
+                    );
+
+                    quote! {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, #message))
+                    }
+                };
+
+                quote! {
+                    #tag if seen_fields[#index] => #repeated,
+                    #tag => {
+                        seen_fields[#index] = true;
+                        #merge
+                    }
+                }
+            })
+            .collect();
+
+        let skip = match &self.unknown_fields {
+            Some(unknown_fields) => {
+                let unknown_fields = unknown_fields.field(false);
+                quote!(#unknown_fields.merge_field(field_number, wire_type, reader)?)
+            }
+
+            None => quote!(#krate::de::skip_field(wire_type, reader)?),
+        };
+
+        let seen_fields = if tracked > 0 {
+            Some(quote! { let mut seen_fields = [false; #tracked]; })
+        } else {
+            None
+        };
 
         quote! {
+            #seen_fields
+
             while !reader.eof()? {
                 let (field_number, wire_type) = reader.read_tag()?;
 
                 match field_number {
                     #(#mergers,)*
-                    _ => reader.skip_field(wire_type)?,
+                    _ => #skip,
                 }
             }
         }
@@ -371,12 +794,14 @@ impl<'a> Struct<'a> {
 
     fn impl_deserialize(&self) -> TokenStream {
         let merger = self.merger();
+        let deserialized = self.deserialized();
 
         self.impler.impl_for(
             "Deserialize",
             quote! {
                 fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
                     #merger
+                    #deserialized
                     Ok(())
                 }
             },
@@ -441,11 +866,137 @@ impl<'a> Struct<'a> {
         }
     }
 
+    pub fn path_serializer(&self) -> TokenStream {
+        let is_variant = self.variant.is_some();
+        let serializers = map_fields!(self, _.path_serializer(is_variant));
+
+        // Variants are only serialized as part of their enums.
+        let whole = if is_variant {
+            quote!(Ok(false))
+        } else {
+            quote! {{
+                self.steit_serialize(writer)?;
+                Ok(true)
+            }}
+        };
+
+        quote! {
+            match path.split_first() {
+                Some((&tag, path)) => match tag {
+                    #(#serializers,)*
+                    _ => Ok(false),
+                },
+                None => #whole,
+            }
+        }
+    }
+
+    fn key_fields(&self) -> Vec<&DeriveField<'a>> {
+        self.fields.iter().filter(|field| field.is_key()).collect()
+    }
+
+    fn impl_keyed(&self) -> TokenStream {
+        let key_fields = self.key_fields();
+
+        let (key_type, key) = if let [field] = key_fields.as_slice() {
+            let ty = field.ty();
+            let field = field.field(false);
+            (quote!(#ty), quote!(#field.clone()))
+        } else {
+            let types = key_fields.iter().map(|field| field.ty());
+            let fields = key_fields.iter().map(|field| field.field(false));
+            (quote!((#(#types,)*)), quote!((#(#fields.clone(),)*)))
+        };
+
+        self.impler.impl_for_with(
+            "Keyed",
+            self.trait_bounds(&[]),
+            quote! {
+                type Key = #key_type;
+
+                fn key(&self) -> Self::Key {
+                    #key
+                }
+            },
+        )
+    }
+
+    /// Refreshes nested states first, so aggregates can be built upon each other.
+    pub fn aggregates_refresher(&self) -> TokenStream {
+        let is_variant = self.variant.is_some();
+        let nested = map_fields!(self, _.refresh_nested(is_variant));
+        let aggregates = map_fields!(self, _.refresh_aggregate());
+
+        quote! {
+            #(#nested)*
+            #(#aggregates)*
+        }
+    }
+
+    pub fn serialize_preparer(&self) -> TokenStream {
+        let is_variant = self.variant.is_some();
+        let nested = map_fields!(self, _.prepare_nested(is_variant));
+        let hook = self.attrs.on_serialize.as_ref();
+        let hook = hook.map(|hook| quote! { #hook(self); });
+
+        quote! {
+            #(#nested)*
+            #hook
+        }
+    }
+
+    pub fn deserialize_finisher(&self) -> TokenStream {
+        let is_variant = self.variant.is_some();
+        let nested = map_fields!(self, _.finish_nested(is_variant));
+        let deserialized = self.deserialized();
+
+        quote! {
+            #(#nested)*
+            #deserialized
+        }
+    }
+
     fn impl_state(&self) -> TokenStream {
         let ctor_name = self.ctor_name();
         let runtime = self.runtime().unwrap().field(false);
         let runtime_setter = self.runtime_setter();
         let replayer = self.replayer();
+        let path_serializer = self.path_serializer();
+        let aggregates_refresher = self.aggregates_refresher();
+        let serialize_preparer = self.serialize_preparer();
+        let deserialize_finisher = self.deserialize_finisher();
+
+        let replayer = if self.computed.is_empty() && self.attrs.on_deserialize.is_none() {
+            replayer
+        } else {
+            let deserialized = self.deserialized();
+
+            quote! {
+                let result = { #replayer };
+                #deserialized
+                result
+            }
+        };
+
+        let validate_list_items = if !self.key_fields().is_empty() {
+            let krate = self.setting.krate();
+
+            Some(quote! {
+                fn validate_list_items(
+                    items: &[Self],
+                    from: usize,
+                    keys: &mut #krate::__private::KeyIndex,
+                ) -> io::Result<()> {
+                    #krate::__private::validate_unique_keys(items, from, keys)
+                }
+
+                fn list_key_hash(&self) -> Option<u64> {
+                    Some(#krate::__private::KeyIndex::hash_key(&Keyed::key(self)))
+                }
+            })
+        } else {
+            None
+        };
 
         self.impler.impl_for(
             "State",
@@ -471,10 +1022,81 @@ impl<'a> Struct<'a> {
                 ) -> io::Result<()> {
                     #replayer
                 }
+
+                fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+                    #path_serializer
+                }
+
+                fn refresh_aggregates(&mut self) {
+                    #aggregates_refresher
+                }
+
+                fn prepare_serialize(&mut self) {
+                    #serialize_preparer
+                }
+
+                fn finish_deserialize(&mut self) {
+                    #deserialize_finisher
+                }
+
+                #validate_list_items
             },
         )
     }
 
+    fn accessor_name(&self) -> syn::Ident {
+        format_ident!("{}Accessor", self.impler.name())
+    }
+
+    pub fn accessor_decl(&self, vis: &syn::Visibility) -> TokenStream {
+        if !self.attrs.accessor {
+            return quote!();
+        }
+
+        let krate = self.setting.krate();
+        let accessor_name = self.accessor_name();
+        let doc = format!(
+            "Reads fields of an encoded `{}` in place.",
+            self.impler.name()
+        );
+
+        let non_exhaustive = derive::non_exhaustive_attr(self.setting.non_exhaustive);
+
+        quote! {
+            #[doc = #doc]
+            #non_exhaustive
+            #vis struct #accessor_name<'a> {
+                inner: #krate::de::Accessor<'a>,
+            }
+        }
+    }
+
+    fn impl_accessor(&self) -> TokenStream {
+        let krate = self.setting.krate();
+        let accessor_name = self.accessor_name();
+        let getters = map_fields!(self, _.accessor_getter());
+
+        quote! {
+            impl<'a> #accessor_name<'a> {
+                pub fn new(bytes: &'a [u8]) -> Self {
+                    Self::from(#krate::de::Accessor::new(bytes))
+                }
+
+                pub fn inner(&self) -> &#krate::de::Accessor<'a> {
+                    &self.inner
+                }
+
+                #(#getters)*
+            }
+
+            impl<'a> From<#krate::de::Accessor<'a>> for #accessor_name<'a> {
+                fn from(inner: #krate::de::Accessor<'a>) -> Self {
+                    Self { inner }
+                }
+            }
+        }
+    }
+
     pub fn meta(&self) -> TokenStream {
         let rust_name = match &self.variant {
             Some(variant) => variant.name().to_string(),
@@ -488,6 +1110,7 @@ impl<'a> Struct<'a> {
 
         let fields = map_fields!(self, _.meta());
         let builtin = self.setting.steit_owned;
+        let component = self.attrs.component;
 
         let type_params = if self.variant.is_none() {
             let type_params = self.type_params.iter().map(|type_param| {
@@ -509,6 +1132,7 @@ impl<'a> Struct<'a> {
                 type_params: &[#type_params],
                 fields: &[#(#fields,)*],
                 builtin: #builtin,
+                component: #component,
             }
         }
     }
@@ -564,14 +1188,25 @@ fn parse_fields<'a>(
     attrs: &StructAttrs,
     type_params: &'a [&'a syn::TypeParam],
     fields: &mut syn::Fields,
-) -> derive::Result<Vec<DeriveField<'a>>> {
+) -> derive::Result<(Vec<DeriveField<'a>>, Vec<ComputedField>)> {
     let mut parsed_fields = Vec::with_capacity(fields.iter().len());
+    let mut computed = Vec::new();
+    let mut valid = true;
 
     let reserved_tags: HashSet<_> = attrs.reserved_tags.iter().collect();
-    let mut tags = HashSet::new();
+    let mut tags = HashMap::new();
     let mut unique_tags = true;
 
     for (index, field) in fields.iter_mut().enumerate() {
+        if ComputedField::is_computed(field) {
+            match ComputedField::parse(ctx, field, index) {
+                Ok(field) => computed.push(field),
+                Err(()) => valid = false,
+            }
+
+            continue;
+        }
+
         if let Ok(parsed_field) = DeriveField::parse(ctx, setting, type_params, field, index) {
             let (tag, tag_tokens) = parsed_field.tag_with_tokens();
 
@@ -579,22 +1214,73 @@ fn parse_fields<'a>(
                 ctx.error(tag_tokens, format!("tag {} has been reserved", tag));
             }
 
-            if !tags.insert(tag) {
-                ctx.error(tag_tokens, format!("duplicate tag {}", tag));
+            if let Some(other) = tags.get(&tag) {
+                ctx.error(
+                    tag_tokens,
+                    format!("duplicate tag {}, already used by field `{}`", tag, other),
+                );
                 unique_tags = false;
+            } else {
+                tags.insert(tag, parsed_field.access().to_string());
             }
 
             parsed_fields.push(parsed_field);
+        } else {
+            valid = false;
         }
     }
 
-    if parsed_fields.len() == parsed_fields.capacity() && unique_tags {
-        Ok(parsed_fields)
+    if valid && unique_tags && setting.lint {
+        lint_fields(ctx, &parsed_fields, &reserved_tags);
+    }
+
+    if valid && unique_tags {
+        Ok((parsed_fields, computed))
     } else {
         Err(())
     }
 }
 
+// Tags skipped without being reserved are likely from removed fields, which may be reused by mistake.
+fn lint_fields(ctx: &Context, fields: &[DeriveField], reserved_tags: &HashSet<&u32>) {
+    let mut fields: Vec<_> = fields.iter().collect();
+    fields.sort_by_key(|field| field.tag());
+
+    for pair in fields.windows(2) {
+        let (tag, tag_tokens) = pair[1].tag_with_tokens();
+        let mut skipped: Vec<(u32, u32)> = Vec::new();
+
+        for tag in (pair[0].tag() + 1..tag).filter(|tag| !reserved_tags.contains(tag)) {
+            match skipped.last_mut() {
+                Some((_, end)) if *end + 1 == tag => *end = tag,
+                _ => skipped.push((tag, tag)),
+            }
+        }
+
+        if !skipped.is_empty() {
+            let skipped: Vec<_> = skipped
+                .into_iter()
+                .map(|(start, end)| match start == end {
+                    true => start.to_string(),
+                    false => format!("{}..={}", start, end),
+                })
+                .collect();
+
+            ctx.warn(
+                tag_tokens,
+                format!(
+                    "skipped tags {} should be in `reserved_tags` if their fields were removed",
+                    skipped.join(", "),
+                ),
+            );
+        }
+    }
+
+    for field in fields {
+        field.lint(ctx);
+    }
+}
+
 fn add_field(fields: &mut syn::Fields, name: String, ty: syn::Type, index: usize) -> Field {
     if let syn::Fields::Unit = fields {
         *fields = syn::Fields::Named(syn::parse_quote!({}));
@@ -651,6 +1337,14 @@ impl<'a> ToTokens for Struct<'a> {
             tokens.extend(self.impl_deserialize());
         }
 
+        if self.variant.is_none() {
+            tokens.extend(derive::impl_conversions(self.impler, self.setting));
+        }
+
+        if self.variant.is_none() && !self.key_fields().is_empty() {
+            tokens.extend(self.impl_keyed());
+        }
+
         if self.setting.derive_state {
             tokens.extend(self.impl_state());
         }
@@ -658,5 +1352,9 @@ impl<'a> ToTokens for Struct<'a> {
         if self.setting.derive_meta {
             tokens.extend(self.impl_meta());
         }
+
+        if self.attrs.accessor {
+            tokens.extend(self.impl_accessor());
+        }
     }
 }
@@ -11,6 +11,7 @@ use super::{derive, derive::DeriveSetting, tag};
 struct VariantAttrs {
     tag: u32,
     tag_tokens: TokenStream,
+    default: Option<TokenStream>,
 }
 
 impl VariantAttrs {
@@ -19,9 +20,12 @@ impl VariantAttrs {
         variant: &mut syn::Variant,
     ) -> derive::Result<(Self, syn::AttributeArgs)> {
         let mut tag = Attribute::new(ctx, "tag");
+        let mut default = Attribute::new(ctx, "default");
 
         let unknown_attrs = (&mut variant.attrs).parse(ctx, false, |meta| match meta {
             syn::Meta::NameValue(meta) if tag.parse_int(meta) => true,
+            syn::Meta::Path(path) if default.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if default.parse_bool(meta) => true,
             _ => false,
         });
 
@@ -33,7 +37,19 @@ impl VariantAttrs {
             ctx.error(&tag_tokens, message);
         })?;
 
-        Ok((Self { tag, tag_tokens }, unknown_attrs))
+        let default = match default.get_with_tokens() {
+            Some((true, tokens)) => Some(tokens),
+            _ => None,
+        };
+
+        Ok((
+            Self {
+                tag,
+                tag_tokens,
+                default,
+            },
+            unknown_attrs,
+        ))
     }
 }
 
@@ -41,6 +57,7 @@ pub struct Variant<'a> {
     setting: &'a DeriveSetting,
     attrs: VariantAttrs,
     name: syn::Ident,
+    non_exhaustive: bool,
 }
 
 impl<'a> Variant<'a> {
@@ -56,6 +73,7 @@ impl<'a> Variant<'a> {
                 setting,
                 attrs,
                 name: variant.ident.clone(),
+                non_exhaustive: derive::is_non_exhaustive(&variant.attrs),
             },
             unknown_attrs,
         ))
@@ -65,10 +83,19 @@ impl<'a> Variant<'a> {
         &self.name
     }
 
+    pub fn is_non_exhaustive(&self) -> bool {
+        self.non_exhaustive
+    }
+
     pub fn tag(&self) -> u32 {
         self.attrs.tag
     }
 
+    /// Tokens of `#[steit(default)]`, if the variant is marked as the default one.
+    pub fn default_tokens(&self) -> Option<&TokenStream> {
+        self.attrs.default.as_ref()
+    }
+
     pub fn tag_with_tokens(&self) -> (u32, &TokenStream) {
         (self.attrs.tag, &self.attrs.tag_tokens)
     }
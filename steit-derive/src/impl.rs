@@ -14,7 +14,23 @@ impl<'a> Implementer<'a> {
         self.name
     }
 
-    #[allow(dead_code)]
+    pub fn generics(&self) -> &syn::Generics {
+        self.generics
+    }
+
+    /// Generics with `bounds` added to every type parameter.
+    pub fn generics_with(&self, bounds: &[&str]) -> syn::Generics {
+        let mut generics = self.generics.clone();
+
+        for type_param in &mut generics.type_params_mut() {
+            for bound in bounds {
+                type_param.bounds.push(syn::parse_str(bound).unwrap());
+            }
+        }
+
+        generics
+    }
+
     pub fn r#impl(&self, tokens: TokenStream) -> TokenStream {
         self.impl_for(None, tokens)
     }
@@ -30,14 +46,7 @@ impl<'a> Implementer<'a> {
         tokens: TokenStream,
     ) -> TokenStream {
         let r#trait = r#trait.into();
-        let mut generics = self.generics.clone();
-
-        for type_param in &mut generics.type_params_mut() {
-            for bound in bounds {
-                type_param.bounds.push(syn::parse_str(bound).unwrap());
-            }
-        }
-
+        let generics = self.generics_with(bounds);
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
         let r#for = r#trait.map(|r#trait| {
@@ -8,6 +8,7 @@ mod attr;
 mod ctx;
 mod derive;
 mod r#impl;
+mod schema;
 mod str_util;
 
 #[proc_macro_attribute]
@@ -16,3 +17,26 @@ pub fn steit_derive(args: TokenStream, input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
     derive::do_it(args, input).into()
 }
+
+/// Generates states from a schema file, so it can be shared with client generators
+/// as the single source of truth.
+///
+/// The path is relative to the manifest directory. Each type is derived as a `State`:
+///
+/// ```text
+/// struct Hero {
+///     name: String = 0,
+///     #[steit(range = "0..=100")]
+///     hp: u8 = 1,
+/// }
+///
+/// enum Action {
+///     Idle = 0,
+///     Attack = 1 { target: u32 = 0 },
+/// }
+/// ```
+#[proc_macro]
+pub fn include_schema(input: TokenStream) -> TokenStream {
+    let path = syn::parse_macro_input!(input as syn::LitStr);
+    schema::include(path).into()
+}
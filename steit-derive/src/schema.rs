@@ -0,0 +1,243 @@
+use std::{env, fs, path::Path};
+
+use proc_macro2::TokenStream;
+use syn::{
+    braced,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Token,
+};
+
+/// `name: Type = tag`, after its attributes.
+struct SchemaField {
+    attrs: Vec<syn::Attribute>,
+    name: syn::Ident,
+    ty: syn::Type,
+    tag: syn::LitInt,
+}
+
+impl Parse for SchemaField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let tag = input.parse()?;
+        Ok(Self {
+            attrs,
+            name,
+            ty,
+            tag,
+        })
+    }
+}
+
+impl SchemaField {
+    // Fields of variants take the visibility of their enum.
+    fn to_field(&self, is_variant: bool) -> TokenStream {
+        let Self {
+            attrs,
+            name,
+            ty,
+            tag,
+        } = self;
+
+        let vis = if is_variant { None } else { Some(quote!(pub)) };
+
+        quote! {
+            #(#attrs)*
+            #[steit(tag = #tag)]
+            #vis #name: #ty
+        }
+    }
+}
+
+fn parse_fields(input: ParseStream) -> syn::Result<Punctuated<SchemaField, Token![,]>> {
+    let content;
+    braced!(content in input);
+    content.parse_terminated(SchemaField::parse)
+}
+
+/// `Name = tag`, optionally followed by fields in braces.
+struct SchemaVariant {
+    attrs: Vec<syn::Attribute>,
+    name: syn::Ident,
+    tag: syn::LitInt,
+    fields: Option<Punctuated<SchemaField, Token![,]>>,
+}
+
+impl Parse for SchemaVariant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let tag = input.parse()?;
+
+        let fields = if input.peek(syn::token::Brace) {
+            Some(parse_fields(input)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            attrs,
+            name,
+            tag,
+            fields,
+        })
+    }
+}
+
+enum SchemaItem {
+    Struct {
+        attrs: Vec<syn::Attribute>,
+        name: syn::Ident,
+        fields: Punctuated<SchemaField, Token![,]>,
+    },
+
+    Enum {
+        attrs: Vec<syn::Attribute>,
+        name: syn::Ident,
+        variants: Punctuated<SchemaVariant, Token![,]>,
+    },
+}
+
+impl Parse for SchemaItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(Token![struct]) {
+            input.parse::<Token![struct]>()?;
+
+            Ok(SchemaItem::Struct {
+                attrs,
+                name: input.parse()?,
+                fields: parse_fields(input)?,
+            })
+        } else if lookahead.peek(Token![enum]) {
+            input.parse::<Token![enum]>()?;
+            let name = input.parse()?;
+            let content;
+            braced!(content in input);
+
+            Ok(SchemaItem::Enum {
+                attrs,
+                name,
+                variants: content.parse_terminated(SchemaVariant::parse)?,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl SchemaItem {
+    fn to_item(&self) -> TokenStream {
+        let derive = quote!(#[steit::steit_derive(PartialEq, Debug, State)]);
+
+        match self {
+            SchemaItem::Struct {
+                attrs,
+                name,
+                fields,
+            } => {
+                let fields = fields.iter().map(|field| field.to_field(false));
+
+                quote! {
+                    #derive
+                    #(#attrs)*
+                    pub struct #name {
+                        #(#fields,)*
+                    }
+                }
+            }
+
+            SchemaItem::Enum {
+                attrs,
+                name,
+                variants,
+            } => {
+                let variants = variants.iter().map(|variant| {
+                    let SchemaVariant {
+                        attrs,
+                        name,
+                        tag,
+                        fields,
+                    } = variant;
+
+                    let fields = fields.as_ref().map(|fields| {
+                        let fields = fields.iter().map(|field| field.to_field(true));
+                        quote!({ #(#fields,)* })
+                    });
+
+                    quote! {
+                        #(#attrs)*
+                        #[steit(tag = #tag)]
+                        #name #fields
+                    }
+                });
+
+                quote! {
+                    #derive
+                    #(#attrs)*
+                    pub enum #name {
+                        #(#variants,)*
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct Schema {
+    items: Vec<SchemaItem>,
+}
+
+impl Parse for Schema {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut items = Vec::new();
+
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+
+        Ok(Self { items })
+    }
+}
+
+/// Generates states from a schema file, found relative to the manifest directory of the crate.
+pub fn include(path: syn::LitStr) -> TokenStream {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = Path::new(&manifest_dir).join(path.value());
+
+    let source = match fs::read_to_string(&full_path) {
+        Ok(source) => source,
+        Err(error) => {
+            let message = format!("couldn't read `{}`: {}", full_path.display(), error);
+            return syn::Error::new(path.span(), message).to_compile_error();
+        }
+    };
+
+    // Spans inside the file are lost, so errors point at the path instead.
+    let schema = syn::parse_str::<Schema>(&source).map_err(|error| {
+        let message = format!("{}: {}", path.value(), error);
+        syn::Error::new(path.span(), message)
+    });
+
+    let schema = match schema {
+        Ok(schema) => schema,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    let items = schema.items.iter().map(SchemaItem::to_item);
+    let full_path = full_path.to_string_lossy();
+
+    quote! {
+        // Rebuilds whenever the schema changes.
+        const _: &str = include_str!(#full_path);
+
+        #(#items)*
+    }
+}
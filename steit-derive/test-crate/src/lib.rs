@@ -3,18 +3,25 @@ mod tests {
     use std::path::Path;
 
     use steit::{
+        de::{Deserialize, Reader},
+        flags,
         gen::{
             generators::{CSharpGenerator, CSharpSetting},
             Generator, Setting,
         },
-        log::{loggers::WriterLogger, LogEntry},
+        log::{
+            loggers::{BufferLogger, WriterLogger},
+            LogEntry,
+        },
+        meta::{decode_value, export_schema, Value},
         rt::Runtime,
         ser::Serialize,
         state::State,
         steit_derive,
-        types::{List, Map},
+        types::{Flags, List, Map, RngState},
     };
 
+    #[allow(dead_code)]
     #[steit_derive(Debug, State)]
     enum Foo {
         #[steit(tag = 0)]
@@ -55,6 +62,7 @@ mod tests {
         Some(#[steit(tag = 0)] T),
     }
 
+    #[allow(dead_code)]
     #[steit_derive(Debug, State)]
     enum Animal<A, B, C> {
         #[steit(tag = 0)]
@@ -243,6 +251,530 @@ mod tests {
         map: Map<u16, i32>,
     }
 
+    #[steit_derive(Debug, State)]
+    #[steit(component)]
+    struct Transform {
+        #[steit(tag = 0)]
+        x: i32,
+        #[steit(tag = 1)]
+        y: i32,
+    }
+
+    #[steit_derive(Debug, State)]
+    struct Squad {
+        #[steit(tag = 0)]
+        leader: Transform,
+        #[steit(tag = 1)]
+        members: List<Transform>,
+        #[steit(tag = 2)]
+        formation: Flags<Formation>,
+        #[steit(tag = 3, range = "0..=100", clamp)]
+        morale: u8,
+    }
+
+    mod game {
+        use steit::types::List;
+
+        steit::include_schema!("game.steit");
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(paths)]
+    #[non_exhaustive]
+    pub enum Weapon {
+        #[steit(tag = 0)]
+        #[non_exhaustive]
+        Sword {
+            #[steit(tag = 0)]
+            damage: u16,
+        },
+        #[steit(tag = 1)]
+        Bow,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    enum Stance {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1)]
+        Aiming(#[steit(tag = 0)] u8),
+        #[steit(tag = 2, default)]
+        Guard {
+            #[steit(tag = 0)]
+            shield: u16,
+            #[steit(tag = 1)]
+            facing: i8,
+        },
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Guardian {
+        #[steit(tag = 0)]
+        stance: Stance,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(accessor, paths)]
+    #[non_exhaustive]
+    pub struct Loadout {
+        #[steit(tag = 0)]
+        pub weapon: Weapon,
+        #[steit(tag = 1)]
+        pub arrows: u8,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(paths)]
+    pub struct Armory {
+        #[steit(tag = 0)]
+        pub loadouts: List<Loadout>,
+        #[steit(tag = 1)]
+        pub spare: Weapon,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Profile {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        level: u8,
+    }
+
+    // Linted, so warnings here fail the build with `-D warnings`.
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(lint)]
+    struct Motion {
+        #[steit(tag = 0)]
+        x: f32,
+        #[steit(tag = 1)]
+        speed: f64,
+        #[steit(tag = 2)]
+        frame: u32,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Pet {
+        #[steit(tag = 0)]
+        hp: u32,
+        #[steit(tag = 1)]
+        tricks: List<u8>,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Stable {
+        #[steit(tag = 0)]
+        stalls: Option<u8>,
+        #[steit(tag = 1)]
+        pet: Option<Pet>,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(no_meta)]
+    struct Loot {
+        #[steit(tag = 0)]
+        key: Option<u8>,
+        #[steit(tag = 1)]
+        coins: Vec<i32>,
+        #[steit(tag = 2)]
+        slots: [u8; 3],
+        #[steit(tag = 3)]
+        drop: (u8, bool),
+    }
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(no_meta)]
+    struct LastWins {
+        #[steit(tag = 0)]
+        level: u8,
+        #[steit(tag = 1)]
+        coins: Vec<i32>,
+    }
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(no_meta, repeated_tags = "first")]
+    struct FirstWins {
+        #[steit(tag = 0)]
+        level: u8,
+        #[steit(tag = 1)]
+        coins: Vec<i32>,
+    }
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(no_meta, repeated_tags = "error")]
+    struct Strict {
+        #[steit(tag = 0)]
+        level: u8,
+        #[steit(tag = 1)]
+        coins: Vec<i32>,
+    }
+
+    flags! {
+        #[allow(dead_code)]
+        enum Formation {
+            Tight = 0,
+            Hidden = 3,
+        }
+    }
+
+    #[test]
+    fn include_schema() {
+        use game::{Action, Hero};
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut hero = Hero::new(runtime);
+        hero.set_name("Lina".to_string()).set_hp(120);
+        hero.actions.push_with(Action::new_attack);
+        hero.actions[0].set_attack_target(3);
+
+        let mut replayed = Hero::new(Runtime::new());
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, hero);
+
+        let bytes = hero.to_bytes();
+        let hero = Hero::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(hero.hp, 100);
+        assert_eq!(hero.actions[0], replayed.actions[0]);
+
+        let schema = include_str!("../game.steit");
+        let (_, schema) = schema.split_once("\n\n").unwrap();
+        assert_eq!(export_schema::<Hero>(true), schema);
+    }
+
+    #[test]
+    fn string_fields() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut profile = Profile::new(runtime);
+        profile.set_name("Lina".to_string()).set_level(3);
+
+        let mut replayed = Profile::new(Runtime::new());
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, profile);
+
+        let entries = logger.lock().unwrap().pluck();
+        assert_eq!(entries[0].path(), Some(&[0][..]));
+
+        let bytes = profile.to_bytes();
+        assert_eq!(&bytes[..6], &[2, 4, b'L', b'i', b'n', b'a']);
+        assert_eq!(
+            Profile::deserialize(&mut Reader::new(&*bytes)).unwrap(),
+            profile
+        );
+
+        let error = Profile::deserialize(&mut Reader::new(&[2, 1, 0xff][..])).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn str_setters() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut profile = Profile::new(runtime);
+        profile.set_name("Lina".to_string());
+        let bytes = logger.lock().unwrap().pluck_bytes();
+
+        let allocation = profile.name.as_ptr();
+        profile.set_name_str("Kael").set_name_str("Lina");
+        assert_eq!(profile.name.as_ptr(), allocation);
+        assert_eq!(profile.name, "Lina");
+
+        let entries = logger.lock().unwrap().pluck_bytes();
+        assert_eq!(entries[entries.len() / 2..], *bytes);
+
+        let mut replayed = Profile::new(Runtime::new());
+        replayed.replay(&mut Reader::new(&*entries)).unwrap();
+        assert_eq!(replayed, profile);
+    }
+
+    #[test]
+    fn facades() {
+        use steit::{runtime, wire};
+
+        let (runtime, logger) = runtime::Runtime::with_logger_returned(BufferLogger::new());
+        let mut motion = Motion::new(runtime);
+        motion.set_frame(9);
+
+        let bytes = wire::Serialize::to_bytes(&motion);
+        let mut reader = wire::Reader::new(&*bytes);
+        assert_eq!(reader.read_tag().unwrap(), (2, wire::WireType::Varint));
+        assert_eq!(
+            <u32 as wire::Deserialize>::deserialize(&mut reader).unwrap(),
+            9
+        );
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+    }
+
+    #[test]
+    fn conversions() {
+        use std::convert::TryFrom;
+
+        let mut profile = Profile::new(Runtime::new());
+        profile.set_name("Lina".to_string()).set_level(7);
+
+        let bytes = Vec::from(&profile);
+        assert_eq!(bytes, profile.to_bytes());
+        assert_eq!(Profile::try_from(&*bytes).unwrap(), profile);
+        assert!(Profile::try_from(&[2, 1, 0xff][..]).is_err());
+
+        let hex = profile.to_hex();
+        assert_eq!(hex, "02044c696e610807");
+        assert_eq!(Profile::from_hex(&hex.to_uppercase()).unwrap(), profile);
+        assert!(Profile::from_hex("0").is_err());
+        assert!(Profile::from_hex("+1").is_err());
+
+        let entry = LogEntry::new_update(Runtime::new().nested(1).path(), &7u8);
+        let bytes: Vec<u8> = entry.clone().into();
+        assert_eq!(LogEntry::try_from(&*bytes).unwrap().path(), entry.path());
+
+        let path = Armory::path().loadouts().item(1).weapon();
+        let bytes = Vec::from(&path);
+        assert_eq!(steit::rt::Path::try_from(&*bytes).unwrap(), path);
+    }
+
+    #[test]
+    fn float_fields() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut motion = Motion::new(runtime);
+        motion.set_x(-1.25).set_speed(0.1).set_frame(3);
+
+        let mut replayed = Motion::new(Runtime::new());
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, motion);
+
+        let bytes = motion.to_bytes();
+        assert_eq!(&bytes[..5], &[5, 0, 0, 160, 191]);
+        assert_eq!(bytes.len(), 5 + 9 + 2);
+        assert_eq!(
+            Motion::deserialize(&mut Reader::new(&*bytes)).unwrap(),
+            motion
+        );
+
+        match decode_value::<Motion>(&bytes).unwrap() {
+            Value::Struct(fields) => {
+                assert_eq!(fields[&0], Value::Float(-1.25));
+                assert_eq!(fields[&1], Value::Float(0.1));
+            }
+            value => panic!("expected a struct, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn option_fields() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut stable = Stable::new(runtime);
+        assert!(stable.to_bytes().is_empty());
+
+        stable
+            .set_stalls(Some(0))
+            .set_pet_with(|runtime| Some(Pet::new(runtime)));
+        let pet = stable.pet.as_mut().unwrap();
+        pet.set_hp(5);
+        pet.tricks.push(1);
+
+        let mut replayed = Stable::new(Runtime::new());
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, stable);
+
+        let entries = logger.lock().unwrap().pluck();
+        assert_eq!(entries[2].path(), Some(&[1, 0][..]));
+        assert_eq!(entries[3].path(), Some(&[1, 1][..]));
+
+        stable.clear_stalls().clear_pet();
+        assert!(stable.to_bytes().is_empty());
+
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, stable);
+
+        // Entries below a cleared option have nothing to apply to.
+        let mut bytes = Vec::new();
+        entries[2].compute_size_nested(None, false).unwrap();
+        entries[2]
+            .serialize_nested(None, false, &mut bytes)
+            .unwrap();
+        assert!(replayed.replay(&mut Reader::new(&*bytes)).is_err());
+    }
+
+    #[test]
+    fn core_fields() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut loot = Loot::new(runtime);
+        loot.set_key(Some(3))
+            .set_coins(vec![10, -5])
+            .set_slots([1, 0, 2])
+            .set_drop((7, true))
+            .set_key(None);
+
+        let mut replayed = Loot::new(Runtime::new());
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, loot);
+
+        let bytes = loot.to_bytes();
+        assert_eq!(Loot::deserialize(&mut Reader::new(&*bytes)).unwrap(), loot);
+    }
+
+    #[test]
+    fn packed_fields() {
+        let mut loot = Loot::new(Runtime::new());
+        loot.set_coins(vec![1, -1, 300]);
+
+        let bytes = loot.to_bytes();
+        assert_eq!(bytes[..6], [10, 4, 2, 1, 216, 4]);
+
+        // Repeated occurrences append, and unpacked items are accepted too.
+        let bytes = [&bytes[..], &[10, 1, 4, 8, 6]].concat();
+        let loot = Loot::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(loot.coins, [1, -1, 300, 2, 3]);
+    }
+
+    #[test]
+    fn repeated_tags() {
+        let bytes = [0, 3, 8, 2, 0, 5, 8, 4];
+
+        let last = LastWins::deserialize(&mut Reader::new(&bytes[..])).unwrap();
+        assert_eq!((last.level, &*last.coins), (5, &[1, 2][..]));
+
+        let first = FirstWins::deserialize(&mut Reader::new(&bytes[..])).unwrap();
+        assert_eq!((first.level, &*first.coins), (3, &[1, 2][..]));
+
+        let error = Strict::deserialize(&mut Reader::new(&bytes[..])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Strict.level occurs more than once, with tag 0"
+        );
+
+        let strict = Strict::deserialize(&mut Reader::new(&[0, 3, 8, 2, 8, 4][..])).unwrap();
+        assert_eq!((strict.level, &*strict.coins), (3, &[1, 2][..]));
+    }
+
+    #[test]
+    fn default_variant() {
+        assert_eq!(
+            Stance::new(Runtime::new()),
+            Stance::new_guard(Runtime::new())
+        );
+
+        let guardian = Guardian::deserialize(&mut Reader::new(&[][..])).unwrap();
+        assert!(matches!(guardian.stance, Stance::Guard { .. }));
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut guardian = Guardian::new(runtime);
+        let mut mirror = Guardian::new(Runtime::new());
+
+        guardian.set_stance(Stance::new_idle(Runtime::new()));
+        guardian.set_stance(Stance::new_guard(Runtime::new()));
+        guardian.stance.set_guard_shield(3);
+
+        let bytes = logger.lock().unwrap().bytes();
+        mirror.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(mirror, guardian);
+
+        let schema = export_schema::<Stance>(true);
+        assert!(schema.contains("    #[steit(default)]\n    Guard = 2 {"));
+    }
+
+    #[test]
+    fn variant_helpers() {
+        let mut stance = Stance::new_aiming(Runtime::new());
+        stance.set_aiming_f0(30);
+
+        assert!(stance.is_aiming() && !stance.is_idle());
+        assert_eq!(stance.as_aiming(), Some(&30));
+        assert_eq!(stance.as_guard(), None);
+        assert!(Stance::new_idle(Runtime::new()).is_idle());
+
+        let stance = stance.try_into_guard().unwrap_err();
+        assert_eq!(stance.try_into_aiming(), Ok(30));
+
+        let mut stance = Stance::new_guard(Runtime::new());
+        stance.set_guard_shield(5).set_guard_facing(-1);
+        assert_eq!(stance.as_guard(), Some((&5, &-1)));
+        assert_eq!(stance.try_into_guard(), Ok((5, -1)));
+
+        let mut maybe = Maybe::new_some(Runtime::new());
+        maybe.set_some_f0(7u8);
+        assert!(maybe.is_some() && !maybe.is_none());
+        assert!(matches!(maybe.as_some(), Some(&7)));
+    }
+
+    #[test]
+    fn from_tag() {
+        let stance = Stance::new_aiming(Runtime::new());
+        assert_eq!(
+            Stance::from_tag(stance.variant_tag(), Runtime::new()),
+            Some(Stance::new_aiming(Runtime::new()))
+        );
+        assert_eq!(Stance::from_tag(3, Runtime::new()), None);
+    }
+
+    #[test]
+    fn non_exhaustive() {
+        let mut loadout = Loadout::new(Runtime::new());
+        loadout.set_weapon(Weapon::new_sword(Runtime::new()));
+        loadout.weapon.as_sword_mut().unwrap().set_damage(12);
+        loadout.set_arrows(3);
+
+        let bytes = loadout.to_bytes();
+        let accessor = LoadoutAccessor::new(&bytes);
+        assert_eq!(accessor.arrows().unwrap(), 3);
+        assert_eq!(accessor.weapon().unwrap(), loadout.weapon);
+        assert_eq!(
+            Weapon::new(Runtime::new()),
+            Weapon::new_sword(Runtime::new())
+        );
+    }
+
+    #[test]
+    fn fixtures() {
+        let mut loadout = Loadout::new_for_test();
+        loadout.set_arrows(5);
+        assert_eq!(loadout.arrows, 5);
+        assert!(loadout.weapon.as_sword_mut().is_some());
+        assert_eq!(Weapon::new_for_test(), Weapon::new_sword(Runtime::new()));
+    }
+
+    #[test]
+    fn paths() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut armory = Armory::new(runtime);
+        armory.loadouts.push_with(Loadout::new);
+        armory.loadouts.push_with(Loadout::new);
+        logger.lock().unwrap().clear();
+
+        armory.loadouts[1]
+            .weapon
+            .as_sword_mut()
+            .unwrap()
+            .set_damage(7);
+
+        let path = Armory::path().loadouts().item(1).weapon().sword_damage();
+        let entries = logger.lock().unwrap().pluck();
+        assert_eq!(entries[0].path(), Some(path.segments()));
+        assert_eq!(Armory::path().spare().segments(), [1]);
+        assert!(Armory::path().loadouts().contains(path.segments()));
+    }
+
+    #[test]
+    fn getters() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut armory = Armory::new(runtime);
+        armory.loadouts_mut().push_with(Loadout::new);
+        armory.loadouts[0].set_arrows(3);
+
+        assert_eq!(*armory.loadouts()[0].arrows(), 3);
+        assert_eq!(armory.spare().sword_damage(), Some(&0));
+
+        armory.spare_mut().set_sword_damage(5);
+        assert_eq!(armory.spare.sword_damage(), Some(&5));
+        assert_eq!(logger.lock().unwrap().pluck().len(), 3);
+
+        armory.set_spare(Weapon::new_bow(Runtime::new()));
+        assert_eq!(armory.spare().sword_damage(), None);
+    }
+
     #[test]
     fn test() {
         let base_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../steit-csharp");
@@ -263,6 +795,7 @@ mod tests {
         .skip_builtins(false);
 
         generator.generate::<Maybe<u8>>(&setting).unwrap();
+        generator.generate::<RngState>(&setting).unwrap();
 
         let setting = Setting::new(&base_dir.join("tests"), CSharpSetting::new("Just.To.Test"));
 
@@ -272,6 +805,15 @@ mod tests {
         generator.generate::<Outer>(&setting).unwrap();
         generator.generate::<Multicase>(&setting).unwrap();
         generator.generate::<Woof>(&setting).unwrap();
+        generator.generate::<Transform>(&setting).unwrap();
+
+        let component_setting =
+            Setting::new(&base_dir.join("tests"), CSharpSetting::new("Just.To.Test"))
+                .skip_components(true);
+
+        let mut generated_names = generator.generate::<Squad>(&component_setting).unwrap();
+        generated_names.sort();
+        assert_eq!(generated_names, &["Formation", "Squad"]);
 
         println!("\nHELLO!");
 
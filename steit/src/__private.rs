@@ -0,0 +1,13 @@
+//! Helpers called by generated code only.
+//!
+//! These aren't part of the public API and may change in any release,
+//! so derived types keep building as long as they are generated by the matching `steit-derive`.
+
+pub use crate::{
+    de::{check_range, merge_max_len, trace_field, validate_max_len},
+    rt::{expect_logged, expect_valid, expect_written},
+    types::{validate_unique_keys, KeyIndex},
+};
+
+#[cfg(feature = "runtime")]
+pub use crate::de::{handle_max_len, handle_range};
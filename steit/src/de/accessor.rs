@@ -0,0 +1,189 @@
+use std::{cell::OnceCell, collections::HashMap, io};
+
+use crate::wire_fmt::WireType;
+
+use super::{de::Deserialize, reader::Reader};
+
+/// Reads fields of an encoded message in place, without deserializing the rest of it.
+///
+/// Offsets of fields are indexed on first access, in one scan over the tags.
+/// Accessors for derived types are generated with `#[steit(accessor)]`.
+pub struct Accessor<'a> {
+    bytes: &'a [u8],
+    offsets: OnceCell<HashMap<u32, Vec<(WireType, usize)>>>,
+}
+
+impl<'a> Accessor<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offsets: OnceCell::new(),
+        }
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    // Each occurrence of a field is recorded by the offset of its value, right after its tag.
+    fn offsets(&self) -> io::Result<&HashMap<u32, Vec<(WireType, usize)>>> {
+        if let Some(offsets) = self.offsets.get() {
+            return Ok(offsets);
+        }
+
+        let mut offsets = HashMap::<_, Vec<_>>::new();
+        let mut rest = self.bytes;
+
+        // Readers peek a byte to check for EOF, so a new one is used per read to keep `rest` exact.
+        while !rest.is_empty() {
+            let (field_number, wire_type) = Reader::new(&mut rest).read_tag()?;
+            let offset = self.bytes.len() - rest.len();
+            offsets
+                .entry(field_number)
+                .or_default()
+                .push((wire_type, offset));
+            Reader::new(&mut rest).skip_field(wire_type)?;
+        }
+
+        Ok(self.offsets.get_or_init(|| offsets))
+    }
+
+    pub fn has(&self, field_number: u32) -> io::Result<bool> {
+        Ok(self.offsets()?.contains_key(&field_number))
+    }
+
+    /// Merges every occurrence of a field into `value` with `merge`, in order.
+    pub fn get_with<T>(
+        &self,
+        field_number: u32,
+        mut value: T,
+        mut merge: impl FnMut(&mut T, WireType, &mut Reader<&'a [u8]>) -> io::Result<()>,
+    ) -> io::Result<T> {
+        if let Some(occurrences) = self.offsets()?.get(&field_number) {
+            for &(wire_type, offset) in occurrences {
+                let bytes = self.bytes.get(offset..).unwrap_or_default();
+                merge(&mut value, wire_type, &mut Reader::new(bytes))?;
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Reads a field as `Deserialize::merge` would, or its default if it's absent.
+    pub fn get<T: Deserialize>(&self, field_number: u32) -> io::Result<T> {
+        self.get_with(field_number, T::default(), |value, wire_type, reader| {
+            value.merge_nested(wire_type, reader)
+        })
+    }
+
+    /// Accesses a nested message, which is empty if the field is absent.
+    ///
+    /// Only its last occurrence is accessed, since merging occurrences requires deserializing them.
+    pub fn nested(&self, field_number: u32) -> io::Result<Accessor<'a>> {
+        let last = self
+            .offsets()?
+            .get(&field_number)
+            .and_then(|occurrences| occurrences.last());
+
+        match last {
+            Some(&(WireType::Sized, offset)) => {
+                let mut rest = self.bytes.get(offset..).unwrap_or_default();
+                let size = u64::deserialize(&mut Reader::new(&mut rest))? as usize;
+
+                let bytes = rest.get(..size).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "nested message truncated")
+                })?;
+
+                Ok(Accessor::new(bytes))
+            }
+
+            Some(&(wire_type, _)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "field {} is not a nested message but {:?}",
+                    field_number, wire_type
+                ),
+            )),
+
+            None => Ok(Accessor::new(&[])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::Runtime,
+        ser::Serialize,
+        steit_derive,
+        test_util::{Foo, Point},
+        types::List,
+    };
+
+    use super::Accessor;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, accessor)]
+    struct Unit {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        position: Point,
+        #[steit(tag = 2)]
+        path: List<Point>,
+        #[steit(tag = 3, range = "0..=100")]
+        health: u8,
+    }
+
+    fn unit() -> Unit {
+        let mut unit = Unit::new(Runtime::new());
+        unit.set_name("scout".to_string()).set_health(80);
+        unit.position.set_x(-3).set_z(9);
+        unit.path.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        unit
+    }
+
+    #[test]
+    fn accessor_fields() {
+        let unit = unit();
+        let bytes = unit.to_bytes();
+        let accessor = UnitAccessor::new(&bytes);
+
+        assert_eq!(accessor.name().unwrap(), "scout");
+        assert_eq!(accessor.health().unwrap(), 80);
+        assert_eq!(accessor.position().unwrap(), unit.position);
+        assert_eq!(accessor.path().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn accessor_nested() {
+        let bytes = unit().to_bytes();
+        let accessor = UnitAccessor::new(&bytes);
+        let position = accessor.inner().nested(1).unwrap();
+
+        assert_eq!(position.get::<i32>(0).unwrap(), -3);
+        assert_eq!(position.get::<i32>(1).unwrap(), 0);
+        assert!(!position.has(1).unwrap());
+        assert!(accessor.inner().nested(3).is_err());
+        assert!(accessor.inner().nested(7).unwrap().bytes().is_empty());
+    }
+
+    #[test]
+    fn accessor_merges_occurrences() {
+        let foo = Foo::new(-1, 2);
+        let mut bytes = foo.to_bytes();
+        bytes.extend(Foo::new(3, 0).to_bytes());
+
+        let accessor = Accessor::new(&bytes);
+        assert_eq!(accessor.get::<i32>(0).unwrap(), 3);
+        assert_eq!(accessor.get::<i32>(1).unwrap(), 2);
+    }
+
+    #[test]
+    fn accessor_validates() {
+        let mut bytes = unit().to_bytes();
+        bytes.extend(&[3 << 3, 101]);
+        assert!(UnitAccessor::new(&bytes).health().is_err());
+        assert!(Accessor::new(&[2 << 3 | 2, 5]).nested(2).is_err());
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    fmt,
+    io::{self, Read},
+};
+
+use crate::{types::Bytes, wire_fmt::WireType};
+
+#[cfg(feature = "runtime")]
+use crate::{
+    log::LogEntryKind,
+    state::State,
+    types::{List, Map, MapKey},
+};
+
+use super::{de::Deserialize, reader::Reader};
+
+/// Types whose length can be bounded by `#[steit(max_len = …)]`.
+///
+/// The bound is checked when fields are set, decoded or replayed, but not when they grow
+/// in place, like by pushing to a nested `List`, which knows nothing of it.
+/// Servers growing bounded fields that way should check their lengths first.
+pub trait HasLen {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Upper bound of the encoded size of a value with at most `max_len` items, if known,
+    /// so oversized input can be rejected before it is read.
+    fn max_size(_max_len: usize) -> Option<u64> {
+        None
+    }
+
+    /// Whether an update of the item at `field_number` would add an item rather than replace one.
+    fn adds_item(&self, _field_number: u32) -> bool {
+        false
+    }
+}
+
+impl HasLen for String {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn max_size(max_len: usize) -> Option<u64> {
+        Some(max_len as u64)
+    }
+}
+
+impl HasLen for &str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+
+    fn max_size(max_len: usize) -> Option<u64> {
+        Some(max_len as u64)
+    }
+}
+
+impl HasLen for Bytes {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn max_size(max_len: usize) -> Option<u64> {
+        Some(max_len as u64)
+    }
+}
+
+impl<T> HasLen for Vec<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<T: State> HasLen for List<T> {
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<K: MapKey, V: State> HasLen for Map<K, V> {
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    fn adds_item(&self, field_number: u32) -> bool {
+        !self.contains_field_number(field_number)
+    }
+}
+
+pub fn validate_max_len(name: &str, len: usize, max_len: usize) -> io::Result<()> {
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "`{}` must not be longer than {}, got {}",
+                name, max_len, len,
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Like `merge_nested`, but fails once `value` would be longer than `max_len`.
+pub fn merge_max_len<T: Deserialize + HasLen>(
+    value: &mut T,
+    name: &str,
+    max_len: usize,
+    wire_type: WireType,
+    reader: &mut Reader<impl io::Read>,
+) -> io::Result<()> {
+    if wire_type != WireType::Sized || T::WIRE_TYPE != WireType::Sized {
+        return value.merge_nested(wire_type, reader);
+    }
+
+    let size = u64::deserialize(reader)?;
+
+    if let Some(max_size) = T::max_size(max_len) {
+        validate_max_len(name, size as usize, max_size as usize)?;
+    }
+
+    value.merge(&mut reader.by_ref().take(size).into())?;
+    validate_max_len(name, value.len(), max_len)
+}
+
+#[cfg(feature = "runtime")]
+/// Like `State::handle`, but rejects entries which would make `value` longer than `max_len`.
+/// Entries are checked before they are applied, so rejected ones leave `value` as it was.
+pub fn handle_max_len<T: State + HasLen>(
+    value: &mut T,
+    name: &str,
+    max_len: usize,
+    path: impl Iterator<Item = u32>,
+    kind: LogEntryKind,
+    key: Option<u32>,
+    reader: &mut Reader<impl io::Read>,
+) -> io::Result<()> {
+    let path: Vec<_> = path.collect();
+
+    if let [field_number] = *path {
+        // Updates of items only make `value` longer if they insert the item, like for maps.
+        if kind == LogEntryKind::Update && value.adds_item(field_number) {
+            validate_max_len(name, value.len() + 1, max_len)?;
+        }
+    } else if path.is_empty() {
+        match kind {
+            LogEntryKind::Update => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+
+                let new_value = T::deserialize(&mut Reader::new(&*bytes))?;
+                validate_max_len(name, new_value.len(), max_len)?;
+                return value.handle(path.into_iter(), kind, key, &mut Reader::new(&*bytes));
+            }
+
+            LogEntryKind::ListPush => validate_max_len(name, value.len() + 1, max_len)?,
+
+            LogEntryKind::ListExtend => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+
+                let items = T::deserialize(&mut Reader::new(&*bytes))?;
+                validate_max_len(name, value.len() + items.len(), max_len)?;
+                return value.handle(path.into_iter(), kind, key, &mut Reader::new(&*bytes));
+            }
+
+            _ => (),
+        }
+    }
+
+    value.handle(path.into_iter(), kind, key, reader)
+}
+
+fn format_range<T: fmt::Display>(min: Option<T>, max: Option<T>) -> String {
+    let format = |bound: Option<T>| bound.map_or(String::new(), |bound| bound.to_string());
+    format!("{}..={}", format(min), format(max))
+}
+
+/// Checks `value` against inclusive bounds, clamping it instead if `clamp` is set.
+pub fn check_range<T: PartialOrd + Copy + fmt::Display>(
+    name: &str,
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+    clamp: bool,
+) -> io::Result<T> {
+    let bound = match (min, max) {
+        (Some(min), _) if value < min => min,
+        (_, Some(max)) if value > max => max,
+        _ => return Ok(value),
+    };
+
+    if clamp {
+        return Ok(bound);
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "`{}` must be within {}, got {}",
+            name,
+            format_range(min, max),
+            value,
+        ),
+    ))
+}
+
+/// Like `State::handle`, but checks the new value with `check_range` before it is applied.
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "runtime")]
+pub fn handle_range<T: State + PartialOrd + Copy + fmt::Display>(
+    value: &mut T,
+    name: &str,
+    min: Option<T>,
+    max: Option<T>,
+    clamp: bool,
+    path: impl Iterator<Item = u32>,
+    kind: LogEntryKind,
+    key: Option<u32>,
+    reader: &mut Reader<impl io::Read>,
+) -> io::Result<()> {
+    let mut new_value = *value;
+    new_value.handle(path, kind, key, reader)?;
+    *value = check_range(name, new_value, min, max, clamp)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        de::{Deserialize, Reader},
+        log::loggers::BufferLogger,
+        meta::{HasMeta, MessageMeta},
+        rt::Runtime,
+        ser::Serialize,
+        state::State,
+        steit_derive,
+        types::{List, Map},
+    };
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Profile {
+        #[steit(tag = 0, max_len = 4)]
+        name: String,
+        #[steit(tag = 1, max_len = 2)]
+        badges: List<u8>,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unit {
+        #[steit(tag = 0, range = "0..=100", clamp)]
+        hp: u8,
+        #[steit(tag = 1, range = "-10..10")]
+        heat: i32,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unbounded {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        badges: List<u8>,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct UnboundedUnit {
+        #[steit(tag = 0)]
+        hp: u8,
+        #[steit(tag = 1)]
+        heat: i32,
+    }
+
+    fn unbounded(name: &str, badges: &[u8]) -> (Unbounded, Vec<u8>) {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut value = Unbounded::new(runtime);
+        value.set_name(name.to_string());
+
+        for &badge in badges {
+            value.badges.push(badge);
+        }
+
+        let bytes = logger.lock().unwrap().bytes();
+        (value, bytes)
+    }
+
+    #[test]
+    fn merge_within_max_len() {
+        let (value, _) = unbounded("mint", &[1, 2]);
+        let profile = Profile::deserialize(&mut Reader::new(&*value.to_bytes())).unwrap();
+        assert_eq!(profile.name, "mint");
+        assert_eq!(&*profile.badges, &[1, 2]);
+    }
+
+    #[test]
+    fn merge_max_len_string() {
+        let (value, _) = unbounded("minty", &[]);
+        let error = Profile::deserialize(&mut Reader::new(&*value.to_bytes())).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Profile.name: `name` must not be longer than 4, got 5"
+        );
+    }
+
+    #[test]
+    fn merge_max_len_list() {
+        let (value, _) = unbounded("", &[1, 2, 3]);
+        let error = Profile::deserialize(&mut Reader::new(&*value.to_bytes())).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Profile.badges: `badges` must not be longer than 2, got 3"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "no-panic"),
+        should_panic(expected = "`name` must not be longer than 4, got 5")
+    )]
+    fn setter_max_len() {
+        let mut profile = Profile::new(Runtime::new());
+        profile.set_name("minty".to_string());
+        assert_eq!(profile.name, "");
+    }
+
+    #[test]
+    fn setter_max_len_reported() {
+        let runtime = Runtime::new();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+
+        runtime.on_log_error({
+            let errors = errors.clone();
+            move |error| errors.lock().unwrap().push(error.to_string())
+        });
+
+        let mut profile = Profile::new(runtime);
+        profile.set_name("minty".to_string());
+        assert_eq!(profile.name, "");
+        assert_eq!(
+            *errors.lock().unwrap(),
+            ["`name` must not be longer than 4, got 5"]
+        );
+    }
+
+    #[test]
+    fn try_setter_max_len() {
+        let mut profile = Profile::new(Runtime::new());
+        let error = profile.try_set_name("minty".to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "`name` must not be longer than 4, got 5");
+        assert_eq!(profile.name, "");
+
+        profile.try_set_name("mint".to_string()).unwrap();
+        assert_eq!(profile.name, "mint");
+    }
+
+    #[test]
+    fn replay_max_len() {
+        let mut profile = Profile::new(Runtime::new());
+
+        let (_, bytes) = unbounded("minty", &[]);
+        assert!(profile.replay(&mut Reader::new(&*bytes)).is_err());
+        assert_eq!(profile.name, "");
+
+        let (_, bytes) = unbounded("mint", &[1, 2, 3]);
+        assert!(profile.replay(&mut Reader::new(&*bytes)).is_err());
+        assert_eq!(profile.name, "mint");
+        assert_eq!(&*profile.badges, &[1, 2]);
+    }
+
+    #[test]
+    fn replay_extend_max_len() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut value = Unbounded::new(runtime);
+        value.badges.extend(vec![1, 2, 3]);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut profile = Profile::new(Runtime::new());
+        let error = profile.replay(&mut Reader::new(&*bytes)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`badges` must not be longer than 2, got 3"
+        );
+        assert!(profile.badges.is_empty());
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Stash {
+        #[steit(tag = 0, max_len = 1)]
+        items: Map<u32, u8>,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct UnboundedStash {
+        #[steit(tag = 0)]
+        items: Map<u32, u8>,
+    }
+
+    #[test]
+    fn replay_insert_max_len() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut value = UnboundedStash::new(runtime);
+        value.items.insert(1, 10);
+        value.items.insert(1, 11);
+        value.items.insert(2, 20);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut stash = Stash::new(Runtime::new());
+        let error = stash.replay(&mut Reader::new(&*bytes)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`items` must not be longer than 1, got 2"
+        );
+        assert_eq!(stash.items.get(&1), Some(&11));
+        assert_eq!(stash.items.len(), 1);
+    }
+
+    fn unbounded_unit(hp: u8, heat: i32) -> Vec<u8> {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        UnboundedUnit::new(runtime).set_hp(hp).set_heat(heat);
+        let bytes = logger.lock().unwrap().bytes();
+        bytes
+    }
+
+    #[test]
+    fn merge_range() {
+        let unit = Unit::deserialize(&mut Reader::new(&[0, 200, 1, 8, 19][..])).unwrap();
+        assert_eq!((unit.hp, unit.heat), (100, -10));
+
+        let error = Unit::deserialize(&mut Reader::new(&[8, 20][..])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unit.heat: `heat` must be within -10..=9, got 10"
+        );
+    }
+
+    #[test]
+    fn setter_range_clamp() {
+        let mut unit = Unit::new(Runtime::new());
+        unit.set_hp(250).set_heat(-10);
+        assert_eq!((unit.hp, unit.heat), (100, -10));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "no-panic"),
+        should_panic(expected = "`heat` must be within -10..=9, got 10")
+    )]
+    fn setter_range_error() {
+        let mut unit = Unit::new(Runtime::new());
+        unit.set_heat(10);
+        assert_eq!(unit.heat, 0);
+    }
+
+    #[test]
+    fn try_setter_range() {
+        let mut unit = Unit::new(Runtime::new());
+        let error = unit.try_set_heat(10).unwrap_err();
+        assert_eq!(error.to_string(), "`heat` must be within -10..=9, got 10");
+
+        unit.try_set_heat(-3).unwrap().try_set_hp(250).unwrap();
+        assert_eq!((unit.hp, unit.heat), (100, -3));
+    }
+
+    #[test]
+    fn replay_range() {
+        let mut unit = Unit::new(Runtime::new());
+        unit.replay(&mut Reader::new(&*unbounded_unit(150, 9)))
+            .unwrap();
+        assert_eq!((unit.hp, unit.heat), (100, 9));
+
+        assert!(unit
+            .replay(&mut Reader::new(&*unbounded_unit(0, 11)))
+            .is_err());
+        assert_eq!(unit.heat, 9);
+    }
+
+    #[test]
+    fn range_meta() {
+        let range = match &<Unit as HasMeta>::LINK.msg {
+            Some(MessageMeta::Struct(r#struct)) => r#struct.fields[1].range.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            (range.min, range.max, range.clamp),
+            (Some(-10), Some(9), false)
+        );
+    }
+}
@@ -13,18 +13,20 @@ pub trait Deserialize: Default + HasWireType {
         Ok(value)
     }
 
+    /// Merges a field of `wire_type`, or skips it if values of `Self` are encoded differently,
+    /// like a field whose type has changed since the bytes were written.
     fn merge_nested(
         &mut self,
         wire_type: WireType,
         reader: &mut Reader<impl io::Read>,
     ) -> io::Result<()> {
         if wire_type != Self::WIRE_TYPE {
-            return reader.skip_field(wire_type);
+            return skip_field(wire_type, reader);
         }
 
         match wire_type {
-            WireType::Varint => self.merge(reader),
             WireType::Sized => self.merge(&mut reader.nested()?),
+            _ => self.merge(reader),
         }
     }
 
@@ -36,4 +38,125 @@ pub trait Deserialize: Default + HasWireType {
         value.merge_nested(wire_type, reader)?;
         Ok(value)
     }
+
+    /// Decodes hex in either case, as written by `Serialize::to_hex`.
+    fn from_hex(hex: &str) -> io::Result<Self> {
+        Self::deserialize(&mut Reader::new(&*decode_hex(hex)?))
+    }
+}
+
+fn decode_hex(hex: &str) -> io::Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid hex {:?}", hex),
+        ));
+    }
+
+    // Every digit is ASCII, so pairs of bytes are valid `str`s.
+    Ok(hex
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).unwrap_or_default();
+            u8::from_str_radix(pair, 16).unwrap_or_default()
+        })
+        .collect())
+}
+
+/// Skips the value of a field of `wire_type`, for custom `merge` implementations
+/// to drop fields with unknown tags or unexpected wire types.
+pub fn skip_field(wire_type: WireType, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+    reader.skip_field(wire_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        de::{Deserialize, Reader},
+        ser::Serialize,
+        steit_derive,
+    };
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    struct Before {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        speed: f32,
+        #[steit(tag = 2)]
+        level: u32,
+    }
+
+    // Tags 0 and 1 have changed types, so their old values are skipped.
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    struct After {
+        #[steit(tag = 0)]
+        name: u64,
+        #[steit(tag = 1)]
+        speed: u32,
+        #[steit(tag = 2)]
+        level: u32,
+    }
+
+    // `Before` with fields of every wire type added since.
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    struct Newer {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 7)]
+        count: u64,
+        #[steit(tag = 8)]
+        ratio: f64,
+        #[steit(tag = 9)]
+        title: String,
+        #[steit(tag = 1)]
+        speed: f32,
+        #[steit(tag = 10)]
+        weight: f32,
+        #[steit(tag = 2)]
+        level: u32,
+    }
+
+    #[test]
+    fn skip_unknown_fields() {
+        let newer = Newer {
+            name: "Lina".to_string(),
+            count: 300,
+            ratio: 0.5,
+            title: "Slayer".to_string(),
+            speed: 1.5,
+            weight: 2.0,
+            level: 7,
+            ..Default::default()
+        };
+
+        let bytes = newer.to_bytes();
+        let before = Before::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(
+            (&*before.name, before.speed, before.level),
+            ("Lina", 1.5, 7)
+        );
+
+        // An unknown field cut short fails rather than being read as the rest of the stream.
+        let bytes = [7 << 3 | 2, 10, 1, 2];
+        assert!(Before::deserialize(&mut Reader::new(&bytes[..])).is_err());
+    }
+
+    #[test]
+    fn skip_mismatched_wire_types() {
+        let before = Before {
+            name: "Lina".to_string(),
+            speed: 1.5,
+            level: 7,
+            ..Default::default()
+        };
+
+        let bytes = before.to_bytes();
+        let after = After::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!((after.name, after.speed, after.level), (0, 0, 7));
+    }
 }
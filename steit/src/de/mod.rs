@@ -1,6 +1,14 @@
+mod accessor;
+mod constraint;
 #[allow(clippy::module_inception)]
 mod de;
 mod reader;
+mod stream;
+mod trace;
 
+pub use accessor::*;
+pub use constraint::*;
 pub use de::*;
 pub use reader::*;
+pub use stream::*;
+pub use trace::*;
@@ -32,6 +32,22 @@ impl<R: io::Read> Reader<R> {
         wire_fmt::parse_tag(value)
     }
 
+    /// Reads 4 little-endian bytes, written by `put_fixed32`.
+    pub fn read_fixed32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0; 4];
+        self.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Reads 8 little-endian bytes, written by `put_fixed64`.
+    pub fn read_fixed64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        self.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Skips a value of `wire_type`, which needs no knowledge of its type,
+    /// so readers can drop fields added after they were built.
     pub fn skip_field(&mut self, wire_type: WireType) -> io::Result<()> {
         match wire_type {
             WireType::Varint => {
@@ -39,9 +55,24 @@ impl<R: io::Read> Reader<R> {
             }
 
             WireType::Sized => {
+                // Copied to a sink rather than buffered, as the size comes from the input.
                 let size = u64::deserialize(self)?;
-                let mut buf = Vec::with_capacity(size as usize);
-                self.by_ref().take(size).read_to_end(&mut buf)?;
+                let skipped = io::copy(&mut self.by_ref().take(size), &mut io::sink())?;
+
+                if skipped < size {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("field of {} bytes ends after {}", size, skipped),
+                    ));
+                }
+            }
+
+            WireType::Fixed32 => {
+                self.read_fixed32()?;
+            }
+
+            WireType::Fixed64 => {
+                self.read_fixed64()?;
             }
         }
 
@@ -0,0 +1,250 @@
+use std::{convert::TryFrom, io};
+
+use crate::wire_fmt::{self, WireType};
+
+use super::{de::Deserialize, reader::Reader};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Step {
+    // Size of the next message.
+    Size,
+    // Key of the next field.
+    Key,
+    // Value of a `Varint` field, or of the whole message if it is `Varint` itself.
+    Varint,
+    // Size of a `Sized` field.
+    FieldSize,
+    // Remaining bytes of a `Sized` or fixed field, or of the whole message if it is fixed itself.
+    FieldBody(u64),
+}
+
+/// Decodes a stream of nested messages fed in arbitrary chunks, like TCP segments.
+///
+/// Complete top-level fields are merged as soon as they arrive,
+/// so only the field being received is buffered, not the whole message.
+pub struct StreamDecoder<T: Deserialize> {
+    value: T,
+    step: Step,
+    // Bytes of the current message not received yet.
+    remaining: u64,
+    field: Vec<u8>,
+    varint: u64,
+    shift: u32,
+}
+
+impl<T: Deserialize> Default for StreamDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Deserialize> StreamDecoder<T> {
+    pub fn new() -> Self {
+        Self {
+            value: T::default(),
+            step: Self::first_step(),
+            remaining: 0,
+            field: Vec::new(),
+            varint: 0,
+            shift: 0,
+        }
+    }
+
+    fn first_step() -> Step {
+        match T::WIRE_TYPE {
+            WireType::Varint => Step::Varint,
+            WireType::Sized => Step::Size,
+            wire_type => Step::FieldBody(wire_type.fixed_size().unwrap_or_default() as u64),
+        }
+    }
+
+    /// Whether part of a message has been fed, but not all of it.
+    pub fn is_pending(&self) -> bool {
+        self.step != Self::first_step() || !self.field.is_empty() || self.shift > 0
+    }
+
+    /// Returns messages completed by `bytes`, in order.
+    ///
+    /// After an error the stream can't be resynced, so the decoder must be dropped.
+    pub fn feed(&mut self, mut bytes: &[u8]) -> io::Result<Vec<T>> {
+        let mut values = Vec::new();
+
+        while let Some((&byte, rest)) = bytes.split_first() {
+            if let Step::FieldBody(len) = self.step {
+                let n = (len as usize).min(bytes.len());
+                let (body, rest) = bytes.split_at(n);
+                self.take(n as u64)?;
+                self.field.extend_from_slice(body);
+                bytes = rest;
+
+                if n as u64 == len {
+                    self.end_field(&mut values)?;
+                } else {
+                    self.step = Step::FieldBody(len - n as u64);
+                }
+
+                continue;
+            }
+
+            bytes = rest;
+
+            if self.step != Step::Size {
+                self.take(1)?;
+                self.field.push(byte);
+            }
+
+            let value = match self.read_varint(byte)? {
+                Some(value) => value,
+                None => continue,
+            };
+
+            match self.step {
+                Step::Size if value == 0 => self.complete(&mut values),
+                Step::Size => {
+                    self.remaining = value;
+                    self.step = Step::Key;
+                }
+
+                Step::Key => {
+                    let tag = u32::try_from(value).map_err(|_| invalid("tag overflows u32"))?;
+
+                    self.step = match wire_fmt::parse_tag(tag)?.1 {
+                        WireType::Varint => Step::Varint,
+                        WireType::Sized => Step::FieldSize,
+                        wire_type => {
+                            Step::FieldBody(wire_type.fixed_size().unwrap_or_default() as u64)
+                        }
+                    };
+                }
+
+                Step::Varint => self.end_field(&mut values)?,
+                Step::FieldSize if value == 0 => self.end_field(&mut values)?,
+                Step::FieldSize => self.step = Step::FieldBody(value),
+                Step::FieldBody(_) => unreachable!(),
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn read_varint(&mut self, byte: u8) -> io::Result<Option<u64>> {
+        if self.shift >= 64 {
+            return Err(invalid("varint overflows u64"));
+        }
+
+        self.varint |= ((byte & 0x7f) as u64) << self.shift;
+        self.shift += 7;
+
+        if byte & 0x80 != 0 {
+            return Ok(None);
+        }
+
+        let value = self.varint;
+        self.varint = 0;
+        self.shift = 0;
+        Ok(Some(value))
+    }
+
+    // Messages of `Varint` type have no size to check against.
+    fn take(&mut self, n: u64) -> io::Result<()> {
+        if T::WIRE_TYPE == WireType::Sized {
+            if n > self.remaining {
+                return Err(invalid("field overruns its message"));
+            }
+
+            self.remaining -= n;
+        }
+
+        Ok(())
+    }
+
+    fn end_field(&mut self, values: &mut Vec<T>) -> io::Result<()> {
+        self.value.merge(&mut Reader::new(&*self.field))?;
+        self.field.clear();
+
+        if T::WIRE_TYPE != WireType::Sized || self.remaining == 0 {
+            self.complete(values);
+        } else {
+            self.step = Step::Key;
+        }
+
+        Ok(())
+    }
+
+    fn complete(&mut self, values: &mut Vec<T>) {
+        values.push(std::mem::take(&mut self.value));
+        self.step = Self::first_step();
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{
+        rt::Runtime,
+        test_util::{serialize_nested, Foo, Point},
+    };
+
+    use super::StreamDecoder;
+
+    fn stream() -> Vec<u8> {
+        let mut bytes = serialize_nested(Foo::new(-1, -2), None);
+        bytes.extend(serialize_nested(Foo::empty(), None));
+        bytes.extend(serialize_nested(Foo::new(1337, 0), None));
+        bytes
+    }
+
+    fn expected() -> Vec<Foo> {
+        vec![Foo::new(-1, -2), Foo::empty(), Foo::new(1337, 0)]
+    }
+
+    #[test]
+    fn byte_by_byte() {
+        let mut decoder = StreamDecoder::<Foo>::new();
+        let mut values = Vec::new();
+
+        for byte in stream() {
+            values.extend(decoder.feed(&[byte]).unwrap());
+        }
+
+        assert_eq!(values, expected());
+        assert!(!decoder.is_pending());
+    }
+
+    #[test]
+    fn chunks() {
+        let stream = stream();
+        let mut decoder = StreamDecoder::<Foo>::new();
+
+        let mut values = decoder.feed(&stream[..4]).unwrap();
+        assert!(decoder.is_pending());
+        values.extend(decoder.feed(&stream[4..]).unwrap());
+
+        assert_eq!(values, expected());
+    }
+
+    #[test]
+    fn nested_and_varint() {
+        let point = Point::new(Runtime::new(), 1, -300, 70000);
+        let mut decoder = StreamDecoder::<Point>::new();
+        let values = decoder.feed(&serialize_nested(point, None)).unwrap();
+        assert_eq!(values, [Point::new(Runtime::new(), 1, -300, 70000)]);
+
+        let mut decoder = StreamDecoder::<u32>::new();
+        let bytes = [serialize_nested(300u32, None), serialize_nested(7u32, None)].concat();
+        assert_eq!(decoder.feed(&bytes[..1]).unwrap(), []);
+        assert_eq!(decoder.feed(&bytes[1..]).unwrap(), [300, 7]);
+    }
+
+    #[test]
+    fn overrun() {
+        let mut decoder = StreamDecoder::<Foo>::new();
+        let error = decoder.feed(&[1, 2, 1, 0]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+enum Step {
+    Field(&'static str),
+    Index(u32),
+}
+
+/// Error of decoding a nested value, with the path to it from the outermost type it's in,
+/// like `Player.inventory[2].item_id`.
+///
+/// It's wrapped in an `io::Error` of the same kind as the error it was caused by.
+#[derive(Debug)]
+pub struct DecodeError {
+    type_name: Option<&'static str>,
+    // Innermost first, as they are added while unwinding.
+    steps: Vec<Step>,
+    source: io::Error,
+}
+
+impl DecodeError {
+    pub fn path(&self) -> String {
+        let mut path = self.type_name.unwrap_or_default().to_string();
+
+        for step in self.steps.iter().rev() {
+            match step {
+                Step::Field(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                }
+                Step::Index(index) => path.push_str(&format!("[{}]", index)),
+            }
+        }
+
+        path
+    }
+
+    pub fn cause(&self) -> &io::Error {
+        &self.source
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path(), self.source)
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn trace(mut error: io::Error, type_name: Option<&'static str>, step: Step) -> io::Error {
+    if let Some(traced) = error
+        .get_mut()
+        .and_then(|error| error.downcast_mut::<DecodeError>())
+    {
+        traced.steps.push(step);
+
+        if type_name.is_some() {
+            traced.type_name = type_name;
+        }
+
+        return error;
+    }
+
+    io::Error::new(
+        error.kind(),
+        DecodeError {
+            type_name,
+            steps: vec![step],
+            source: error,
+        },
+    )
+}
+
+/// Runs `merge` on field `field` of `type_name`, adding them to the path of its error.
+pub fn trace_field(
+    type_name: &'static str,
+    field: &'static str,
+    merge: impl FnOnce() -> io::Result<()>,
+) -> io::Result<()> {
+    merge().map_err(|error| trace(error, Some(type_name), Step::Field(field)))
+}
+
+/// Adds the index of an item to the path of its error, for collections with custom `merge`.
+pub fn trace_index<T>(result: io::Result<T>, index: u32) -> io::Result<T> {
+    result.map_err(|error| trace(error, None, Step::Index(index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{
+        de::{Deserialize, Reader},
+        steit_derive,
+        types::List,
+    };
+
+    use super::DecodeError;
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Item {
+        #[steit(tag = 0)]
+        item_id: u32,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Player {
+        #[steit(tag = 0)]
+        level: u8,
+        #[steit(tag = 1)]
+        inventory: List<Item>,
+    }
+
+    #[test]
+    fn nested_path() {
+        // The third item's id is cut off in the middle of its varint.
+        let bytes = [0, 3, 10, 9, 2, 0, 1, 2, 0, 2, 2, 0, 255];
+        let error = Player::deserialize(&mut Reader::new(&bytes[..])).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let traced = error.get_ref().unwrap().downcast_ref::<DecodeError>();
+        assert_eq!(traced.unwrap().path(), "Player.inventory[2].item_id");
+        assert!(error
+            .to_string()
+            .starts_with("Player.inventory[2].item_id: "));
+
+        let error = List::<u32>::deserialize(&mut Reader::new(&[1, 255][..])).unwrap_err();
+        assert!(error.to_string().starts_with("[1]: "));
+    }
+}
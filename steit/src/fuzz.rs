@@ -0,0 +1,495 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Read},
+    marker::PhantomData,
+};
+
+use crate::{
+    de::{Deserialize, Reader},
+    gen::gen_util,
+    log::{loggers::NoopLogger, LogEntryKind},
+    meta::{
+        decode_value, describe_path, FieldMeta, FieldTypeMeta, HasMeta, MessageMeta, TypeMeta,
+        Value,
+    },
+    rt::Runtime,
+    ser::Serialize,
+    state::State,
+    types::RngState,
+    wire_fmt::WireType,
+};
+
+/// Decodes the same bytes with the derived deserializer of `T`
+/// and with the meta-driven `decode_value`, which must agree.
+///
+/// The derived result is compared by re-serializing it and decoding that with `decode_value`.
+pub fn check<T: Deserialize + Serialize + HasMeta>(bytes: &[u8]) -> Result<(), Disagreement> {
+    let reference = decode_value::<T>(bytes);
+    let derived = T::deserialize(&mut Reader::new(bytes))
+        .and_then(|value| decode_value::<T>(&value.to_bytes()));
+
+    let agreed = match (&derived, &reference) {
+        (Ok(derived), Ok(reference)) => derived == reference,
+        (Err(_), Err(_)) => true,
+        _ => false,
+    };
+
+    if agreed {
+        Ok(())
+    } else {
+        Err(Disagreement {
+            bytes: bytes.to_vec(),
+            derived,
+            reference,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Disagreement {
+    pub bytes: Vec<u8>,
+    pub derived: io::Result<Value>,
+    pub reference: io::Result<Value>,
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "decoders disagree on {:?}: derived {:?}, reference {:?}",
+            self.bytes, self.derived, self.reference,
+        )
+    }
+}
+
+/// Runs `check` over randomly mutated copies of a corpus of valid encodings.
+pub struct Fuzz<T> {
+    corpus: Vec<Vec<u8>>,
+    seed: u64,
+    rounds: usize,
+    mutations: usize,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Deserialize + Serialize + HasMeta> Fuzz<T> {
+    pub fn new() -> Self {
+        Self {
+            corpus: vec![Vec::new()],
+            seed: 0,
+            rounds: 10_000,
+            mutations: 4,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn sample(mut self, value: &T) -> Self {
+        self.corpus.push(value.to_bytes());
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Each round applies up to this many mutations to a sample.
+    pub fn mutations(mut self, mutations: usize) -> Self {
+        self.mutations = mutations.max(1);
+        self
+    }
+
+    pub fn run(&self) -> Result<(), Disagreement> {
+        let mut rng = RngState::with_seed(Runtime::with_logger(NoopLogger::new()), self.seed);
+
+        for sample in &self.corpus {
+            check::<T>(sample)?;
+        }
+
+        for _ in 0..self.rounds {
+            let index = rng.next_below(self.corpus.len() as u64) as usize;
+
+            let mut bytes = match self.corpus.get(index) {
+                Some(sample) => sample.clone(),
+                None => break,
+            };
+
+            for _ in 0..=rng.next_below(self.mutations as u64) {
+                mutate(&mut rng, &mut bytes);
+            }
+
+            check::<T>(&bytes)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Deserialize + Serialize + HasMeta> Default for Fuzz<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A field of a `check_replay` sample which didn't replay into the value it was logged with.
+#[derive(Debug)]
+pub struct ReplayGap {
+    /// The field, described by schema names.
+    pub path: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ReplayGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} doesn't replay: {}", self.path, self.reason)
+    }
+}
+
+/// Logs each field set in `sample` as its own `Update`, going into nested states field by field,
+/// and checks it replays into a fresh `T` with the value it has in `sample`.
+///
+/// This catches fields the derived replay code misses, so `sample` should set every field.
+/// Unset fields are reported too, except in variants other than the one `sample` holds.
+/// Collections and generic types are updated whole.
+pub fn check_replay<T: State + HasMeta>(sample: &T) -> Result<(), ReplayGap> {
+    let bytes = sample.to_bytes();
+    let gap = |path: &[u32], reason: String| ReplayGap {
+        path: describe_path::<T>(path),
+        reason,
+    };
+
+    let mut leaves = Leaves {
+        msgs: gen_util::collect_meta::<T>(|msg| msg.rust_name().to_string()),
+        path: Vec::new(),
+        entries: Vec::new(),
+        unset: Vec::new(),
+    };
+
+    leaves
+        .collect(T::TYPE, bytes.clone())
+        .map_err(|error| gap(&[], error.to_string()))?;
+
+    let expected = decode_value::<T>(&bytes).map_err(|error| gap(&[], error.to_string()))?;
+    let mut replayed = T::with_runtime(Runtime::with_logger(NoopLogger::new()));
+
+    for (path, bytes, is_leaf) in leaves.entries {
+        let reader = &mut Reader::new(&*bytes);
+        replayed
+            .handle(path.iter().copied(), LogEntryKind::Update, None, reader)
+            .map_err(|error| gap(&path, error.to_string()))?;
+
+        let actual = decode_value::<T>(&replayed.to_bytes())
+            .map_err(|error| gap(&path, error.to_string()))?;
+
+        if is_leaf && value_at(&actual, &path) != value_at(&expected, &path) {
+            return Err(gap(&path, "replayed into a different value".to_string()));
+        }
+    }
+
+    match leaves.unset.first() {
+        Some(path) => Err(gap(path, "not set in the sample".to_string())),
+        None => Ok(()),
+    }
+}
+
+// Splits a sample into `Update` entries of its leaf fields.
+struct Leaves {
+    msgs: HashMap<String, &'static MessageMeta>,
+    path: Vec<u32>,
+    // Entries are flagged as leaves, the others only switching variants.
+    entries: Vec<(Vec<u32>, Vec<u8>, bool)>,
+    unset: Vec<Vec<u32>>,
+}
+
+impl Leaves {
+    fn collect(&mut self, ty: &'static TypeMeta, bytes: Vec<u8>) -> io::Result<()> {
+        let msg = match ty {
+            TypeMeta::Ref(name, []) => self.msgs.get(name.rust).copied(),
+            _ => None,
+        };
+
+        match msg {
+            Some(MessageMeta::Struct(r#struct)) if r#struct.type_params.is_empty() => {
+                self.collect_fields(r#struct.fields, &bytes)
+            }
+
+            Some(MessageMeta::Enum(r#enum)) if r#enum.type_params.is_empty() => {
+                let mut reader = Reader::new(&*bytes);
+                let tag = u32::deserialize(&mut reader)?;
+                let mut rest = Vec::new();
+                reader.read_to_end(&mut rest)?;
+
+                self.entries
+                    .push((self.path.clone(), tag.to_bytes(), false));
+
+                if let Some(variant) = r#enum.variants.iter().find(|variant| variant.tag == tag) {
+                    self.path.push(tag);
+                    self.collect_fields(variant.ty.fields, &rest)?;
+                    self.path.pop();
+                }
+
+                Ok(())
+            }
+
+            _ => {
+                self.entries.push((self.path.clone(), bytes, true));
+                Ok(())
+            }
+        }
+    }
+
+    fn collect_fields(&mut self, fields: &'static [FieldMeta], bytes: &[u8]) -> io::Result<()> {
+        let mut values = HashMap::new();
+        let reader = &mut Reader::new(bytes);
+
+        while !reader.eof()? {
+            let (tag, wire_type) = reader.read_tag()?;
+            let mut value = Vec::new();
+
+            match wire_type {
+                WireType::Varint => value = u64::deserialize(reader)?.to_bytes(),
+                WireType::Sized => {
+                    reader.nested()?.read_to_end(&mut value)?;
+                }
+                WireType::Fixed32 => value = reader.read_fixed32()?.to_le_bytes().to_vec(),
+                WireType::Fixed64 => value = reader.read_fixed64()?.to_le_bytes().to_vec(),
+            }
+
+            values.insert(tag, value);
+        }
+
+        for field in fields {
+            self.path.push(field.tag);
+
+            match (values.remove(&field.tag), field.ty) {
+                (None, _) => self.unset.push(self.path.clone()),
+                (Some(value), FieldTypeMeta::Type(ty)) => self.collect(ty, value)?,
+                (Some(value), FieldTypeMeta::TypeParam(_)) => {
+                    self.entries.push((self.path.clone(), value, true))
+                }
+            }
+
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+}
+
+fn value_at<'a>(value: &'a Value, path: &[u32]) -> Option<&'a Value> {
+    let (&tag, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some(value),
+    };
+
+    match value {
+        Value::Struct(fields) => value_at(fields.get(&tag)?, rest),
+        Value::Variant(variant, fields) if *variant == tag => match rest.split_first() {
+            Some((field, rest)) => value_at(fields.get(field)?, rest),
+            None => Some(value),
+        },
+        _ => None,
+    }
+}
+
+fn mutate(rng: &mut RngState, bytes: &mut Vec<u8>) {
+    let len = bytes.len() as u64;
+
+    if len == 0 {
+        bytes.push(rng.next_u32() as u8);
+        return;
+    }
+
+    let index = rng.next_below(len) as usize;
+
+    match rng.next_below(6) {
+        0 => {
+            let bit = 1 << rng.next_below(8);
+
+            if let Some(byte) = bytes.get_mut(index) {
+                *byte ^= bit;
+            }
+        }
+        1 => {
+            let value = rng.next_u32() as u8;
+
+            if let Some(byte) = bytes.get_mut(index) {
+                *byte = value;
+            }
+        }
+        2 => bytes.insert(index, rng.next_u32() as u8),
+        3 => {
+            bytes.remove(index);
+        }
+        4 => bytes.truncate(index),
+        _ => {
+            let end = index + rng.next_below(len - index as u64) as usize + 1;
+            let chunk = bytes.get(index..end).unwrap_or_default().to_vec();
+            bytes.splice(index..index, chunk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::{Runtime, SizeCache},
+        state::State,
+        steit_derive,
+        test_util::{Foo, Point},
+        types::{List, Map, Maybe},
+    };
+
+    use super::{check, check_replay, Fuzz};
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Camp {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        tents: List<Point>,
+        #[steit(tag = 2)]
+        supplies: Map<u16, u8>,
+        #[steit(tag = 4)]
+        watch: Maybe<Point>,
+        #[steit(tag = 5)]
+        lit: bool,
+    }
+
+    #[steit_derive(Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    enum Order {
+        #[steit(tag = 0)]
+        Hold,
+        #[steit(tag = 1)]
+        March {
+            #[steit(tag = 0)]
+            steps: Vec<i16>,
+            #[steit(tag = 3)]
+            guard: Option<Foo>,
+        },
+    }
+
+    fn camp() -> Camp {
+        let mut camp = Camp::new(Runtime::new());
+        let watch = Maybe::some(
+            camp.watch.runtime().clone(),
+            Point::new(Runtime::new(), 1, 2, 3),
+        );
+
+        camp.set_name("north".to_string())
+            .set_watch(watch)
+            .set_lit(true);
+        camp.tents
+            .push_with(|runtime| Point::new(runtime, -1, 0, 300));
+        camp.tents
+            .push_with(|runtime| Point::new(runtime, 7, -7, 0));
+        camp.supplies.insert(4, 9);
+        camp.supplies.insert(1000, 255);
+        camp
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Outpost {
+        #[steit(tag = 0)]
+        camp: Camp,
+        #[steit(tag = 1)]
+        stance: Stance,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    enum Stance {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1)]
+        Guard {
+            #[steit(tag = 0)]
+            post: Point,
+            #[steit(tag = 1)]
+            alert: bool,
+        },
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Beacon {
+        #[steit(tag = 0)]
+        lit: bool,
+        #[steit(tag = 1, no_state)]
+        range: u32,
+    }
+
+    #[test]
+    fn replay_every_field() {
+        let mut outpost = Outpost::new(Runtime::new());
+        outpost.set_camp(camp());
+
+        let mut stance = Stance::new_guard(outpost.stance.runtime().clone());
+        stance.set_guard_post(Point::new(Runtime::new(), 4, 5, 6));
+        stance.set_guard_alert(true);
+        outpost.set_stance(stance);
+
+        check_replay(&outpost).unwrap_or_else(|gap| panic!("{}", gap));
+        check_replay(&camp()).unwrap_or_else(|gap| panic!("{}", gap));
+    }
+
+    #[test]
+    fn replay_gaps() {
+        let mut beacon = Beacon::new(Runtime::new());
+        beacon.lit = true;
+        beacon.range = 30;
+
+        let gap = check_replay(&beacon).unwrap_err();
+        assert_eq!(gap.path, "Beacon.range");
+
+        beacon.range = 0;
+        let gap = check_replay(&beacon).unwrap_err();
+        assert_eq!(gap.path, "Beacon.range");
+        assert_eq!(gap.reason, "not set in the sample");
+
+        let outpost = Outpost::new(Runtime::new());
+        assert_eq!(
+            check_replay(&outpost).unwrap_err().path,
+            "Outpost.camp.name"
+        );
+    }
+
+    #[test]
+    fn check_samples() {
+        assert!(check::<Camp>(&[]).is_ok());
+        assert!(check::<Point>(&[8, 4, 8, 6]).is_ok());
+        assert!(check::<Camp>(&[255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1]).is_ok());
+    }
+
+    #[test]
+    fn fuzz_struct() {
+        Fuzz::<Camp>::new()
+            .sample(&camp())
+            .seed(1)
+            .rounds(5_000)
+            .run()
+            .unwrap_or_else(|disagreement| panic!("{}", disagreement));
+    }
+
+    #[test]
+    fn fuzz_enum() {
+        Fuzz::<Order>::new()
+            .sample(&Order::March {
+                steps: vec![1, -300, 0],
+                guard: Some(Foo::new(5, -6)),
+                size_cache: SizeCache::new(),
+            })
+            .seed(2)
+            .rounds(5_000)
+            .run()
+            .unwrap_or_else(|disagreement| panic!("{}", disagreement));
+    }
+}
@@ -3,7 +3,7 @@ use std::{
     io::{self, Write},
 };
 
-use crate::meta::{EnumMeta, HasMeta, MessageMeta, StructMeta};
+use crate::meta::{EnumMeta, FlagsMeta, HasMeta, MessageMeta, StructMeta};
 
 use super::{gen_util, setting::Setting, writer::Writer};
 
@@ -18,9 +18,21 @@ pub trait Generator {
         is_variant: bool,
         setting: &Self::Setting,
         writer: &mut Writer,
-    );
+    ) -> io::Result<()>;
 
-    fn gen_enum(&self, r#enum: &EnumMeta, setting: &Self::Setting, writer: &mut Writer);
+    fn gen_enum(
+        &self,
+        r#enum: &EnumMeta,
+        setting: &Self::Setting,
+        writer: &mut Writer,
+    ) -> io::Result<()>;
+
+    fn gen_flags(
+        &self,
+        flags: &FlagsMeta,
+        setting: &Self::Setting,
+        writer: &mut Writer,
+    ) -> io::Result<()>;
 
     fn generate<T: HasMeta>(&self, setting: &Setting<Self::Setting>) -> io::Result<Vec<String>> {
         let mut generated_names = Vec::new();
@@ -30,6 +42,10 @@ pub trait Generator {
                 continue;
             }
 
+            if meta.is_component() && setting.skip_components {
+                continue;
+            }
+
             if setting.skip_names.contains(&name) {
                 continue;
             }
@@ -38,11 +54,15 @@ pub trait Generator {
 
             match meta {
                 MessageMeta::Struct(r#struct) => {
-                    self.gen_struct(&r#struct, false, &*setting, &mut writer);
+                    self.gen_struct(r#struct, false, setting, &mut writer)?;
                 }
 
                 MessageMeta::Enum(r#enum) => {
-                    self.gen_enum(&r#enum, &*setting, &mut writer);
+                    self.gen_enum(r#enum, setting, &mut writer)?;
+                }
+
+                MessageMeta::Flags(flags) => {
+                    self.gen_flags(flags, setting, &mut writer)?;
                 }
             };
 
@@ -1,3 +1,5 @@
+use std::io;
+
 use crate::{
     gen::{str_util, Generator, Writer},
     meta::*,
@@ -73,7 +75,7 @@ impl Generator for CSharpGenerator {
         is_variant: bool,
         setting: &Self::Setting,
         writer: &mut Writer,
-    ) {
+    ) -> io::Result<()> {
         let name = r#struct.name.csharp(String::from);
         let var_name = str_util::uncap_first_char(&name);
 
@@ -84,7 +86,11 @@ impl Generator for CSharpGenerator {
         };
 
         let variant_accessibility = if is_variant { "internal" } else { "public" };
-        let fields: Vec<_> = r#struct.fields.iter().map(CSharpField::from_meta).collect();
+        let fields = r#struct
+            .fields
+            .iter()
+            .map(CSharpField::from_meta)
+            .collect::<io::Result<Vec<_>>>()?;
 
         if !is_variant {
             self.gen_file_opening(setting, writer);
@@ -111,6 +117,17 @@ impl Generator for CSharpGenerator {
             ));
         }
 
+        // Export constraints so that clients can validate before sending
+        let constraints: Vec<_> = fields.iter().flat_map(CSharpField::constraints).collect();
+
+        if !constraints.is_empty() {
+            writer.newline();
+        }
+
+        for constraint in constraints {
+            writer.writeln(constraint);
+        }
+
         writer
             .newline()
             .writeln(format!(
@@ -246,16 +263,24 @@ impl Generator for CSharpGenerator {
         // Return wire types
         for field in r#struct.fields {
             let wire_type = match field.ty {
-                FieldTypeMeta::Type(TypeMeta::Primitive(_, wire_type)) => match wire_type {
-                    WireType::Varint => "WireType.Varint".to_string(),
-                    WireType::Sized => "WireType.Sized".to_string(),
-                },
+                FieldTypeMeta::Type(TypeMeta::Primitive(_, wire_type)) => {
+                    format!("WireType.{}", wire_type.name())
+                }
+
+                // `Flags` is serialized as a varint of its bits
+                FieldTypeMeta::Type(TypeMeta::Ref(name, _)) if name.rust == "Flags" => {
+                    format!("WireType.{}", WireType::Varint.name())
+                }
 
-                FieldTypeMeta::Type(TypeMeta::Ref(_, _)) => "WireType.Sized".to_string(),
+                FieldTypeMeta::Type(TypeMeta::Ref(_, _)) => {
+                    format!("WireType.{}", WireType::Sized.name())
+                }
 
                 FieldTypeMeta::TypeParam(type_param) => format!(
-                    "StateFactory.IsStateType(typeof({})) ? WireType.Sized : WireType.Varint",
+                    "StateFactory.IsStateType(typeof({})) ? WireType.{} : WireType.{}",
                     type_param,
+                    WireType::Sized.name(),
+                    WireType::Varint.name(),
                 ),
             };
 
@@ -369,9 +394,16 @@ impl Generator for CSharpGenerator {
         if !is_variant {
             self.gen_file_closing(writer);
         }
+
+        Ok(())
     }
 
-    fn gen_enum(&self, r#enum: &EnumMeta, setting: &Self::Setting, writer: &mut Writer) {
+    fn gen_enum(
+        &self,
+        r#enum: &EnumMeta,
+        setting: &Self::Setting,
+        writer: &mut Writer,
+    ) -> io::Result<()> {
         let name = r#enum.name.csharp(String::from);
         let var_name = str_util::uncap_first_char(&name);
         let type_name = format!("{}{}", &name, type_params(r#enum.type_params));
@@ -385,7 +417,12 @@ impl Generator for CSharpGenerator {
         let default_variant = variants
             .iter()
             .find(|variant| variant.meta.default())
-            .unwrap_or_else(|| panic!("expected a default variant for enum {}", name));
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected a default variant for enum {}", name),
+                )
+            })?;
 
         self.gen_file_opening(setting, writer);
 
@@ -510,7 +547,11 @@ impl Generator for CSharpGenerator {
 
         // Return wire types
         for variant in r#enum.variants {
-            writer.writeln(format!("case {}: return WireType.Sized;", variant.tag));
+            writer.writeln(format!(
+                "case {}: return WireType.{};",
+                variant.tag,
+                WireType::Sized.name(),
+            ));
         }
 
         writer
@@ -567,12 +608,42 @@ impl Generator for CSharpGenerator {
                 ))
                 .newline();
 
-            self.gen_struct(&variant.ty, true, setting, writer);
+            self.gen_struct(&variant.ty, true, setting, writer)?;
         }
 
         writer.outdent_writeln("}");
 
         self.gen_file_closing(writer);
+        Ok(())
+    }
+
+    fn gen_flags(
+        &self,
+        flags: &FlagsMeta,
+        setting: &Self::Setting,
+        writer: &mut Writer,
+    ) -> io::Result<()> {
+        let name = flags.name.csharp(String::from);
+
+        self.gen_file_opening(setting, writer);
+
+        writer
+            .writeln("[Flags]")
+            .writeln(format!("public enum {} : UInt64 {{", name))
+            .indent();
+
+        for flag in flags.flags {
+            writer.writeln(format!(
+                "{} = 1UL << {},",
+                flag.name.csharp(|name| str_util::to_camel_case(name, true)),
+                flag.bit,
+            ));
+        }
+
+        writer.outdent_writeln("}");
+
+        self.gen_file_closing(writer);
+        Ok(())
     }
 }
 
@@ -584,14 +655,51 @@ struct CSharpField {
 }
 
 impl CSharpField {
-    pub fn from_meta(field: &'static FieldMeta) -> Self {
-        Self {
+    pub fn from_meta(field: &'static FieldMeta) -> io::Result<Self> {
+        Ok(Self {
             meta: field,
             upper_camel_case_name: field
                 .name
                 .csharp(|name| str_util::to_camel_case(name, true)),
-            type_name: field_type(field.ty),
+            type_name: field_type(field.ty)?,
+        })
+    }
+
+    fn constraints(&self) -> Vec<String> {
+        let name = &self.upper_camel_case_name;
+        let mut constraints = Vec::new();
+
+        if let Some(max_len) = self.meta.max_len {
+            constraints.push(format!(
+                "public const Int32 {}MaxLength = {};",
+                name, max_len
+            ));
+        }
+
+        if let Some(range) = &self.meta.range {
+            if let Some(min) = range.min {
+                constraints.push(format!(
+                    "public const {} {}Min = {};",
+                    self.type_name, name, min,
+                ));
+            }
+
+            if let Some(max) = range.max {
+                constraints.push(format!(
+                    "public const {} {}Max = {};",
+                    self.type_name, name, max,
+                ));
+            }
+        }
+
+        if self.meta.client_writable {
+            constraints.push(format!(
+                "public const Boolean {}ClientWritable = true;",
+                name
+            ));
         }
+
+        constraints
     }
 }
 
@@ -621,26 +729,33 @@ fn type_params(type_params: &'static [&'static str]) -> String {
     format!("<{}>", type_params.join(", "))
 }
 
-fn field_type(ty: &'static FieldTypeMeta) -> String {
+fn field_type(ty: &'static FieldTypeMeta) -> io::Result<String> {
     match *ty {
         FieldTypeMeta::Type(ty) => match ty {
-            TypeMeta::Primitive(name, _) => name
-                .csharp
-                .expect("expected a C# name for every primitive type")
-                .to_string(),
+            TypeMeta::Primitive(name, _) => name.csharp.map(str::to_string).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected a C# name for primitive type {}", name.rust),
+                )
+            }),
 
             TypeMeta::Ref(name, type_args) => {
                 let type_name = name.csharp(String::from);
 
                 if type_args.is_empty() {
-                    return type_name;
+                    return Ok(type_name);
                 }
 
-                let mut type_args: Vec<_> = type_args.iter().map(field_type).collect();
+                let mut type_args = type_args
+                    .iter()
+                    .map(field_type)
+                    .collect::<io::Result<Vec<_>>>()?;
 
                 // A hack to bypass `Box`
                 if &type_name == "Box" {
-                    return type_args[0].clone();
+                    if let Some(type_arg) = type_args.pop() {
+                        return Ok(type_arg);
+                    }
                 }
 
                 // A hack to shadow the first type argument of `Map`
@@ -648,10 +763,10 @@ fn field_type(ty: &'static FieldTypeMeta) -> String {
                     type_args.remove(0);
                 }
 
-                format!("{}<{}>", type_name, type_args.join(", "))
+                Ok(format!("{}<{}>", type_name, type_args.join(", ")))
             }
         },
 
-        FieldTypeMeta::TypeParam(type_param) => type_param.to_string(),
+        FieldTypeMeta::TypeParam(type_param) => Ok(type_param.to_string()),
     }
 }
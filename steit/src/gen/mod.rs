@@ -1,7 +1,7 @@
 pub mod generators;
 pub mod str_util;
 
-mod gen_util;
+pub(crate) mod gen_util;
 mod generator;
 mod setting;
 mod writer;
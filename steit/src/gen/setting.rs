@@ -6,6 +6,7 @@ pub struct Setting<T> {
     pub(in crate::gen) out_dir: PathBuf,
     pub(in crate::gen) get_name: fn(&'static MessageMeta) -> String,
     pub(in crate::gen) skip_builtins: bool,
+    pub(in crate::gen) skip_components: bool,
     pub(in crate::gen) skip_names: HashSet<String>,
     inner: T,
 }
@@ -27,6 +28,7 @@ impl<T> Setting<T> {
             out_dir: PathBuf::from(out_dir),
             get_name: GET_NAME_CSHARP,
             skip_builtins: true,
+            skip_components: false,
             skip_names: HashSet::new(),
             inner,
         }
@@ -47,6 +49,11 @@ impl<T> Setting<T> {
         self
     }
 
+    pub fn skip_components(mut self, skip_components: bool) -> Self {
+        self.skip_components = skip_components;
+        self
+    }
+
     pub fn skip_names(mut self, skip_names: impl IntoIterator<Item = impl ToString>) -> Self {
         self.skip_names = skip_names
             .into_iter()
@@ -0,0 +1,161 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    de::{Deserialize, Reader},
+    meta::{HasMeta, MetaLink, NameMeta, TypeMeta},
+    rt::SizeCache,
+    ser::Serialize,
+    wire_fmt::{HasWireType, WireType},
+};
+
+#[cfg(feature = "runtime")]
+use crate::{log::LogEntryKind, rt::Runtime, state::State};
+
+// Hot subtrees of a state can be put behind an `Arc`, making clones of the state cheap.
+// A clone is then a consistent snapshot, which can be serialized on another thread
+// while game logic keeps on mutating the original through `Arc::make_mut`,
+// copying only the subtrees it touches while the snapshot is alive.
+
+impl<T: HasWireType> HasWireType for Arc<T> {
+    const WIRE_TYPE: WireType = T::WIRE_TYPE;
+}
+
+impl<T: Serialize> Serialize for Arc<T> {
+    fn compute_size(&self) -> u32 {
+        self.as_ref().compute_size()
+    }
+
+    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.as_ref().serialize_cached(writer)
+    }
+
+    fn size_cache(&self) -> Option<&SizeCache> {
+        self.as_ref().size_cache()
+    }
+}
+
+impl<T: Deserialize + Clone> Deserialize for Arc<T> {
+    fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        Arc::make_mut(self).merge(reader)
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<T: State + Clone> State for Arc<T> {
+    fn with_runtime(runtime: Runtime) -> Self {
+        Self::new(T::with_runtime(runtime))
+    }
+
+    fn runtime(&self) -> &Runtime {
+        self.as_ref().runtime()
+    }
+
+    fn set_runtime(&mut self, runtime: Runtime) {
+        Arc::make_mut(self).set_runtime(runtime)
+    }
+
+    fn refresh_aggregates(&mut self) {
+        Arc::make_mut(self).refresh_aggregates()
+    }
+
+    fn prepare_serialize(&mut self) {
+        Arc::make_mut(self).prepare_serialize()
+    }
+
+    fn finish_deserialize(&mut self) {
+        Arc::make_mut(self).finish_deserialize()
+    }
+
+    fn handle(
+        &mut self,
+        path: impl Iterator<Item = u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        Arc::make_mut(self).handle(path, kind, key, reader)
+    }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        self.as_ref().serialize_at(path, writer)
+    }
+}
+
+impl<T: HasMeta> HasMeta for Arc<T> {
+    // Transparent on the wire, so described like a box.
+    const NAME: &'static NameMeta = <Box<T>>::NAME;
+
+    const TYPE: &'static TypeMeta = T::TYPE;
+
+    const LINK: &'static MetaLink = &MetaLink {
+        r#type: Self::TYPE,
+        msg: None,
+        links: || &[T::LINK],
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use crate::{
+        de::{Deserialize, Reader},
+        rt::Runtime,
+        ser::Serialize,
+        steit_derive,
+        types::List,
+    };
+
+    #[steit_derive(Clone, PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unit {
+        #[steit(tag = 0)]
+        hp: i32,
+    }
+
+    #[steit_derive(Clone, PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct World {
+        #[steit(tag = 0)]
+        tick: u32,
+        #[steit(tag = 1)]
+        units: Arc<List<Unit>>,
+    }
+
+    #[test]
+    fn snapshot_while_mutating() {
+        let mut world = World::new(Runtime::new());
+        Arc::make_mut(&mut world.units).push(Unit::new(Runtime::new()));
+
+        let snapshot = world.clone();
+        let expected = snapshot.clone();
+
+        let serialized = thread::spawn(move || {
+            let mut bytes = Vec::new();
+            snapshot.steit_serialize(&mut bytes).unwrap();
+            bytes
+        });
+
+        world.set_tick(1);
+        Arc::make_mut(&mut world.units)[0].set_hp(5);
+
+        let serialized = serialized.join().unwrap();
+        let deserialized = World::deserialize(&mut Reader::new(&*serialized)).unwrap();
+        assert_eq!(deserialized, expected);
+        assert_eq!(world.units[0].hp, 5);
+        assert_eq!(expected.units[0].hp, 0);
+    }
+
+    #[test]
+    fn shared_until_mutated() {
+        let mut world = World::new(Runtime::new());
+        let snapshot = world.clone();
+
+        world.set_tick(1);
+        assert!(Arc::ptr_eq(&world.units, &snapshot.units));
+
+        Arc::make_mut(&mut world.units).push(Unit::new(Runtime::new()));
+        assert!(!Arc::ptr_eq(&world.units, &snapshot.units));
+        assert!(snapshot.units.is_empty());
+    }
+}
@@ -2,14 +2,16 @@ use std::io::{self, Read};
 
 use crate::{
     de::{Deserialize, Reader},
+    impl_state_primitive,
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::SizeCache,
+    rt::{expect_written, SizeCache},
     ser::Serialize,
     wire_fmt::{HasWireType, WireType},
 };
 
+// Encoded like a `Vec` of the same items, missing ones being left as they are when merged.
 macro_rules! impl_array {
-    ($len:literal) => {
+    ($($len:literal)+) => {$(
         impl<T> HasWireType for [T; $len] {
             const WIRE_TYPE: WireType = WireType::Sized;
         }
@@ -19,7 +21,7 @@ macro_rules! impl_array {
                 let mut size = 0;
 
                 for item in self {
-                    size += item.compute_size_nested(None, false).unwrap();
+                    size += expect_written(item.compute_size_nested(None, false));
                 }
 
                 size
@@ -40,12 +42,12 @@ macro_rules! impl_array {
 
         impl<T: Deserialize> Deserialize for [T; $len] {
             fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
-                let mut index = 0;
+                for slot in self.iter_mut() {
+                    if reader.eof()? {
+                        break;
+                    }
 
-                while index < $len && !reader.eof()? {
-                    let item = T::deserialize_nested(T::WIRE_TYPE, reader)?;
-                    self[index] = item;
-                    index += 1;
+                    *slot = T::deserialize_nested(T::WIRE_TYPE, reader)?;
                 }
 
                 let mut bytes = Vec::new();
@@ -55,6 +57,8 @@ macro_rules! impl_array {
             }
         }
 
+        impl_state_primitive!(impl<T> [T; $len]);
+
         impl<T: HasMeta> HasMeta for [T; $len] {
             const NAME: &'static NameMeta = &NameMeta {
                 rust: "Vec",
@@ -70,16 +74,13 @@ macro_rules! impl_array {
                 links: || &[T::LINK],
             };
         }
-    };
+    )+};
 }
 
-impl_array!(1);
-impl_array!(2);
-impl_array!(3);
-impl_array!(4);
-impl_array!(5);
-impl_array!(6);
-impl_array!(7);
+impl_array! {
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+}
 
 #[cfg(test)]
 mod tests {
@@ -99,4 +100,5 @@ mod tests {
 
     test_case!(merge_01: assert_merge; [1, 2, 3], &[1, 2, 3] => [-1, 1, -2]);
     test_case!(merge_02: assert_merge; [1, 2, 3], &[1] => [-1, 2, 3]);
+    test_case!(merge_03: assert_merge; [0u8; 32], &[7; 32] => [7; 32]);
 }
@@ -2,14 +2,15 @@ use std::io;
 
 use crate::{
     de::{Deserialize, Reader},
-    log::LogEntryKind,
     meta::{HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::{Runtime, SizeCache},
+    rt::SizeCache,
     ser::Serialize,
-    state::State,
     wire_fmt::{HasWireType, WireType},
 };
 
+#[cfg(feature = "runtime")]
+use crate::{log::LogEntryKind, rt::Runtime, state::State};
+
 impl<T: HasWireType> HasWireType for Box<T> {
     const WIRE_TYPE: WireType = T::WIRE_TYPE;
 }
@@ -34,6 +35,7 @@ impl<T: Deserialize> Deserialize for Box<T> {
     }
 }
 
+#[cfg(feature = "runtime")]
 impl<T: State> State for Box<T> {
     fn with_runtime(runtime: Runtime) -> Self {
         Self::new(T::with_runtime(runtime))
@@ -47,6 +49,18 @@ impl<T: State> State for Box<T> {
         self.as_mut().set_runtime(runtime)
     }
 
+    fn refresh_aggregates(&mut self) {
+        self.as_mut().refresh_aggregates()
+    }
+
+    fn prepare_serialize(&mut self) {
+        self.as_mut().prepare_serialize()
+    }
+
+    fn finish_deserialize(&mut self) {
+        self.as_mut().finish_deserialize()
+    }
+
     fn handle(
         &mut self,
         path: impl Iterator<Item = u32>,
@@ -56,6 +70,10 @@ impl<T: State> State for Box<T> {
     ) -> io::Result<()> {
         self.as_mut().handle(path, kind, key, reader)
     }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        self.as_ref().serialize_at(path, writer)
+    }
 }
 
 impl<T: HasMeta> HasMeta for Box<T> {
@@ -86,7 +104,7 @@ mod tests {
         let value = Some(Foo::new(-1, 0));
         assert_eq!(value.as_ref().unwrap().cached_size(), 0);
         assert_eq!(value.cache_size(), 3);
-        assert_eq!(value.unwrap().cached_size(), 2);
+        assert_eq!(value.as_ref().unwrap().cached_size(), 2);
     }
 
     test_case!(size_01: assert_size; Box::new(0) => 1);
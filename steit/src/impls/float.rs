@@ -0,0 +1,81 @@
+use std::io;
+
+use crate::{
+    de::{Deserialize, Reader},
+    impl_meta_primitive, impl_serialize_primitive, impl_state_primitive,
+    ser::{put_fixed32, put_fixed64, FloatMode},
+    wire_fmt::{HasWireType, WireType},
+};
+
+// Floats are written in `FloatMode::Canonical`, so equal states always encode to the same bytes.
+
+impl HasWireType for f32 {
+    const WIRE_TYPE: WireType = WireType::Fixed32;
+}
+
+fn compute_size_f32(_value: &f32) -> u32 {
+    4
+}
+
+fn serialize_f32(value: &f32, writer: &mut impl io::Write) -> io::Result<()> {
+    put_fixed32(FloatMode::Canonical.f32_bits(*value), writer)
+}
+
+impl_serialize_primitive!(f32, compute_size_f32, serialize_f32);
+
+impl Deserialize for f32 {
+    fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        *self = f32::from_bits(reader.read_fixed32()?);
+        Ok(())
+    }
+}
+
+impl_state_primitive!(f32);
+impl_meta_primitive!(f32, "Single", WireType::Fixed32);
+
+impl HasWireType for f64 {
+    const WIRE_TYPE: WireType = WireType::Fixed64;
+}
+
+fn compute_size_f64(_value: &f64) -> u32 {
+    8
+}
+
+fn serialize_f64(value: &f64, writer: &mut impl io::Write) -> io::Result<()> {
+    put_fixed64(FloatMode::Canonical.f64_bits(*value), writer)
+}
+
+impl_serialize_primitive!(f64, compute_size_f64, serialize_f64);
+
+impl Deserialize for f64 {
+    fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        *self = f64::from_bits(reader.read_fixed64()?);
+        Ok(())
+    }
+}
+
+impl_state_primitive!(f64);
+impl_meta_primitive!(f64, "Double", WireType::Fixed64);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_case,
+        test_util::{assert_merge, assert_serialize, assert_serialize_nested, assert_size},
+    };
+
+    test_case!(size_01: assert_size; 0f32 => 4);
+    test_case!(size_02: assert_size; 1.5f64 => 8);
+
+    test_case!(serialize_01: assert_serialize; 1.5f32 => &[0, 0, 192, 63]);
+    test_case!(serialize_02: assert_serialize; -0f32 => &[0, 0, 0, 0]);
+    test_case!(serialize_03: assert_serialize; 1.5f64 => &[0, 0, 0, 0, 0, 0, 248, 63]);
+
+    test_case!(serialize_nested_01: assert_serialize_nested; 1.5f32, None => &[0, 0, 192, 63]);
+    test_case!(serialize_nested_02: assert_serialize_nested; 1.5f32, Some(10) => &[85, 0, 0, 192, 63]);
+    test_case!(serialize_nested_03: assert_serialize_nested; 0f32, Some(10) => &[]);
+    test_case!(serialize_nested_04: assert_serialize_nested; -2f64, Some(1) => &[9, 0, 0, 0, 0, 0, 0, 0, 192]);
+
+    test_case!(merge_01: assert_merge; 0f32, &[0, 0, 192, 63] => 1.5f32);
+    test_case!(merge_02: assert_merge; 1f64, &[0, 0, 0, 0, 0, 0, 0, 192] => -2f64);
+}
@@ -2,7 +2,7 @@ use std::{collections::HashMap, hash::Hash, io};
 
 use crate::{
     de::{Deserialize, Reader},
-    rt::SizeCache,
+    rt::{expect_written, SizeCache},
     ser::Serialize,
     wire_fmt::{HasWireType, WireType},
 };
@@ -11,13 +11,15 @@ impl<K, V> HasWireType for HashMap<K, V> {
     const WIRE_TYPE: WireType = WireType::Sized;
 }
 
+// Entries are written in the iteration order of `HashMap`, which is arbitrary,
+// so the output is not reproducible. Use `Map` where that matters.
 impl<K: Serialize, V: Serialize> Serialize for HashMap<K, V> {
     fn compute_size(&self) -> u32 {
         let mut size = 0;
 
         for (key, value) in self {
-            size += key.compute_size_nested(None, false).unwrap();
-            size += value.compute_size_nested(None, false).unwrap();
+            size += expect_written(key.compute_size_nested(None, false));
+            size += expect_written(value.compute_size_nested(None, false));
         }
 
         size
@@ -36,10 +36,15 @@ macro_rules! impl_serialize_primitive {
     };
 }
 
+/// Implements `State` for a type which is replaced as a whole by updates, like integers.
+/// Type parameters, if any, are bound by `Serialize + Deserialize`.
+#[cfg(feature = "runtime")]
 #[macro_export]
 macro_rules! impl_state_primitive {
-    ($type:ty) => {
-        impl $crate::state::State for $type {
+    (impl<$($param:ident),*> $type:ty) => {
+        impl<$($param: $crate::ser::Serialize + $crate::de::Deserialize),*> $crate::state::State
+            for $type
+        {
             fn with_runtime(_runtime: $crate::rt::Runtime) -> Self {
                 Self::default()
             }
@@ -73,7 +78,7 @@ macro_rules! impl_state_primitive {
 
                         _ => Err(::std::io::Error::new(
                             ::std::io::ErrorKind::InvalidData,
-                            format!("{:?} is not supported on `{}`", kind, stringify!($name)),
+                            format!("{:?} is not supported on `{}`", kind, stringify!($type)),
                         )),
                     }
                 } else {
@@ -81,7 +86,7 @@ macro_rules! impl_state_primitive {
                         ::std::io::ErrorKind::InvalidData,
                         format!(
                             "`{}` expected end-of-path but still got {:?} remaining",
-                            stringify!($name),
+                            stringify!($type),
                             path,
                         ),
                     ))
@@ -89,6 +94,17 @@ macro_rules! impl_state_primitive {
             }
         }
     };
+
+    ($type:ty) => {
+        $crate::impl_state_primitive!(impl<> $type);
+    };
+}
+
+/// Without the `runtime` feature, there is no `State` to implement.
+#[cfg(not(feature = "runtime"))]
+#[macro_export]
+macro_rules! impl_state_primitive {
+    ($($tokens:tt)*) => {};
 }
 
 #[macro_export]
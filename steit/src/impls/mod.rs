@@ -1,6 +1,8 @@
+mod arc;
 mod array;
 mod bool;
 mod r#box;
+mod float;
 mod hash_map;
 mod macros;
 mod option;
@@ -3,19 +3,23 @@ use std::io;
 use crate::{
     de::{Deserialize, Reader},
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::SizeCache,
+    rt::{expect_written, SizeCache},
     ser::Serialize,
     wire_fmt::{HasWireType, WireType},
 };
 
+#[cfg(feature = "runtime")]
+use crate::{log::LogEntryKind, rt::Runtime, state::State};
+
 impl<T> HasWireType for Option<T> {
     const WIRE_TYPE: WireType = WireType::Sized;
 }
 
+/// `Some` is its value nested without a tag, `None` is empty.
 impl<T: Serialize> Serialize for Option<T> {
     fn compute_size(&self) -> u32 {
         match self {
-            Some(value) => value.compute_size_nested(None, false).unwrap(),
+            Some(value) => expect_written(value.compute_size_nested(None, false)),
             None => 0,
         }
     }
@@ -48,6 +52,90 @@ impl<T: Deserialize> Deserialize for Option<T> {
     }
 }
 
+/// Updates at the path of an option replace it as a whole, so setting it to `None` clears it.
+/// Other entries are handled by the value inside, which shares the path of the option.
+///
+/// Enums inside log their variant switches as updates at that path, which would be read
+/// as updates of the option, so they should be switched by setting the option instead.
+#[cfg(feature = "runtime")]
+impl<T: State> State for Option<T> {
+    fn with_runtime(_runtime: Runtime) -> Self {
+        None
+    }
+
+    // Like primitives, `None` has no runtime to return.
+    #[allow(clippy::panic)]
+    fn runtime(&self) -> &Runtime {
+        match self {
+            Some(value) => value.runtime(),
+            None => panic!("cannot get `Runtime` from `None`"),
+        }
+    }
+
+    fn set_runtime(&mut self, runtime: Runtime) {
+        if let Some(value) = self {
+            value.set_runtime(runtime);
+        }
+    }
+
+    fn refresh_aggregates(&mut self) {
+        if let Some(value) = self {
+            value.refresh_aggregates();
+        }
+    }
+
+    fn prepare_serialize(&mut self) {
+        if let Some(value) = self {
+            value.prepare_serialize();
+        }
+    }
+
+    fn finish_deserialize(&mut self) {
+        if let Some(value) = self {
+            value.finish_deserialize();
+        }
+    }
+
+    fn handle_update(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        *self = Self::deserialize(reader)?;
+        Ok(())
+    }
+
+    fn handle(
+        &mut self,
+        path: impl Iterator<Item = u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        let mut path = path.peekable();
+
+        if path.peek().is_none() && kind == LogEntryKind::Update {
+            return self.handle_update(reader);
+        }
+
+        match self {
+            Some(value) => value.handle(path, kind, key, reader),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{:?} is not supported on `None`", kind),
+            )),
+        }
+    }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        if path.is_empty() {
+            self.steit_serialize(writer)?;
+            return Ok(true);
+        }
+
+        match self {
+            Some(value) => value.serialize_at(path, writer),
+            None => Ok(false),
+        }
+    }
+}
+
 impl<T: HasMeta> HasMeta for Option<T> {
     const NAME: &'static NameMeta = &NameMeta {
         rust: "Option",
@@ -76,7 +164,7 @@ mod tests {
         let value = Some(Foo::new(-1, 0));
         assert_eq!(value.as_ref().unwrap().cached_size(), 0);
         assert_eq!(value.cache_size(), 3);
-        assert_eq!(value.unwrap().cached_size(), 2);
+        assert_eq!(value.as_ref().unwrap().cached_size(), 2);
     }
 
     test_case!(size_01: assert_size; None::<u8> => 0);
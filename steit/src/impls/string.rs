@@ -2,7 +2,7 @@ use std::io::{self, Read};
 
 use crate::{
     de::{Deserialize, Reader},
-    impl_meta_primitive, impl_serialize_primitive,
+    impl_meta_primitive, impl_serialize_primitive, impl_state_primitive,
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -29,6 +29,7 @@ impl Deserialize for String {
     }
 }
 
+impl_state_primitive!(String);
 impl_meta_primitive!(String, "String", WireType::Sized);
 
 fn from_utf8(bytes: Vec<u8>) -> io::Result<String> {
@@ -4,11 +4,13 @@ use std::io::{self, Read};
 
 use crate::{
     de::{Deserialize, Reader},
-    rt::SizeCache,
+    impl_state_primitive,
+    rt::{expect_written, SizeCache},
     ser::Serialize,
     wire_fmt::{HasWireType, WireType},
 };
 
+// Elements are nested in order without tags, like the items of a `Vec`.
 macro_rules! impl_tuple {
     ( $( $name:ident )+ ) => {
         impl<$($name),+> HasWireType for ($($name),+) {
@@ -19,7 +21,7 @@ macro_rules! impl_tuple {
             fn compute_size(&self) -> u32 {
                 let ($($name),+) = self;
                 let mut size = 0;
-                $(size += $name.compute_size_nested(None, false).unwrap();)+
+                $(size += expect_written($name.compute_size_nested(None, false));)+
                 size
             }
 
@@ -45,6 +47,8 @@ macro_rules! impl_tuple {
                 Ok(())
             }
         }
+
+        impl_state_primitive!(impl<$($name),+> ($($name),+));
     };
 }
 
@@ -54,3 +58,23 @@ impl_tuple! { A B C D }
 impl_tuple! { A B C D E }
 impl_tuple! { A B C D E F }
 impl_tuple! { A B C D E F G }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_case,
+        test_util::{assert_merge, assert_serialize, assert_serialize_nested, assert_size, Foo},
+    };
+
+    test_case!(size_01: assert_size; (0, false) => 2);
+    test_case!(size_02: assert_size; (1337, Foo::new(-1, -2), "π".to_string()) => 10);
+
+    test_case!(serialize_01: assert_serialize; (1337, true) => &[242, 20, 1]);
+    test_case!(serialize_02: assert_serialize; (Foo::new(-1, -2), "33".to_string()) => &[4, 0, 1, 8, 3, 2, 51, 51]);
+
+    test_case!(serialize_nested_01: assert_serialize_nested; (1, 2), None => &[2, 2, 4]);
+    test_case!(serialize_nested_02: assert_serialize_nested; (1, 2), Some(10) => &[82, 2, 2, 4]);
+
+    test_case!(merge_01: assert_merge; (1, 2), &[4, 6] => (2, 3));
+    test_case!(merge_02: assert_merge; (1, Foo::new(-1, -2)), &[0, 2, 8, 4] => (0, Foo::new(0, 2)));
+}
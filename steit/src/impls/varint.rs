@@ -1,14 +1,14 @@
 macro_rules! impl_unsigned_varint {
-    (u64, $dummy:ident, "UInt64") => {
-        impl_unsigned_varint!(@impl u64, $dummy, size_64, i64, "UInt64");
+    (u64, "UInt64") => {
+        impl_unsigned_varint!(@impl u64, size_64, i64, "UInt64");
     };
 
-    ($type:ty, $dummy:ident, $csharp_name:literal) => {
-        impl_unsigned_varint!(@impl $type, $dummy, size_32, i32, $csharp_name);
+    ($type:ty, $csharp_name:literal) => {
+        impl_unsigned_varint!(@impl $type, size_32, i32, $csharp_name);
     };
 
-    (@impl $type:ty, $dummy:ident, $size_fn:ident, $size_type:ty, $csharp_name:literal) => {
-        const $dummy: () = {
+    (@impl $type:ty, $size_fn:ident, $size_type:ty, $csharp_name:literal) => {
+        const _: () = {
             impl $crate::wire_fmt::HasWireType for $type {
                 const WIRE_TYPE: $crate::wire_fmt::WireType =
                     $crate::wire_fmt::WireType::Varint;
@@ -50,7 +50,11 @@ macro_rules! impl_unsigned_varint {
 
                     loop {
                         reader.read_exact(&mut buf)?;
-                        value |= (buf[0] & 0x7f) as $type << offset;
+
+                        // Bits beyond the width of the type are dropped.
+                        if offset < <$type>::BITS {
+                            value |= ((buf[0] & 0x7f) as $type) << offset;
+                        }
 
                         if buf[0] & 0x80 == 0 {
                             *self = value;
@@ -68,18 +72,20 @@ macro_rules! impl_unsigned_varint {
     };
 }
 
-impl_unsigned_varint!(u8, _U8_IMPLS, "Byte");
-impl_unsigned_varint!(u16, _U16_IMPLS, "UInt16");
-impl_unsigned_varint!(u32, _U32_IMPLS, "UInt32");
-impl_unsigned_varint!(u64, _U64_IMPLS, "UInt64");
+impl_unsigned_varint!(u8, "Byte");
+impl_unsigned_varint!(u16, "UInt16");
+impl_unsigned_varint!(u32, "UInt32");
+impl_unsigned_varint!(u64, "UInt64");
 
 macro_rules! impl_signed_varint {
-    ($type:ty, $dummy:ident, $unsigned_type:ty, $csharp_name:literal) => {
-        const $dummy: () = {
+    ($type:ty, $unsigned_type:ty, $csharp_name:literal) => {
+        const _: () = {
             impl $crate::wire_fmt::HasWireType for $type {
                 const WIRE_TYPE: $crate::wire_fmt::WireType = $crate::wire_fmt::WireType::Varint;
             }
 
+            // Signed integers are always zigzag encoded, like `sint32` and `sint64` of protobuf,
+            // so small negative values take as few bytes as small positive ones.
             // More about Zigzag encoding can be found at:
             // https://en.wikipedia.org/wiki/Variable-length_quantity#Zigzag_encoding
 
@@ -87,8 +93,9 @@ macro_rules! impl_signed_varint {
                 (value << 1) ^ (value >> ((std::mem::size_of::<$type>() << 3) - 1))
             }
 
+            // The shift must be logical, or large values get their sign bit smeared.
             fn decode(value: $type) -> $type {
-                (value >> 1) ^ -(value & 1)
+                ((value as $unsigned_type) >> 1) as $type ^ -(value & 1)
             }
 
             fn compute_size(value: &$type) -> u32 {
@@ -123,10 +130,10 @@ macro_rules! impl_signed_varint {
     };
 }
 
-impl_signed_varint!(i8, _I8_IMPLS, u8, "SByte");
-impl_signed_varint!(i16, _I16_IMPLS, u16, "Int16");
-impl_signed_varint!(i32, _I32_IMPLS, u32, "Int32");
-impl_signed_varint!(i64, _I64_IMPLS, u64, "Int64");
+impl_signed_varint!(i8, u8, "SByte");
+impl_signed_varint!(i16, u16, "Int16");
+impl_signed_varint!(i32, u32, "Int32");
+impl_signed_varint!(i64, u64, "Int64");
 
 /// Gets varint size in bytes of a 32-bit integer.
 ///
@@ -208,18 +215,29 @@ mod tests {
     test_case!(encode_zig_zag_03: assert_serialize;  1 => &[2]);
     test_case!(encode_zig_zag_04: assert_serialize; -2 => &[3]);
     test_case!(encode_zig_zag_05: assert_serialize;  2 => &[4]);
+    test_case!(encode_zig_zag_06: assert_serialize; -65i8 => &[129, 1]);
+    test_case!(encode_zig_zag_07: assert_serialize; i32::MIN => &[255, 255, 255, 255, 15]);
+    test_case!(encode_zig_zag_08: assert_serialize; i64::MIN => &[255, 255, 255, 255, 255, 255, 255, 255, 255, 1]);
 
     test_case!(decode_zig_zag_01: assert_deserialize; &[0] =>  0);
     test_case!(decode_zig_zag_02: assert_deserialize; &[1] => -1);
     test_case!(decode_zig_zag_03: assert_deserialize; &[2] =>  1);
     test_case!(decode_zig_zag_04: assert_deserialize; &[3] => -2);
     test_case!(decode_zig_zag_05: assert_deserialize; &[4] =>  2);
+    test_case!(decode_zig_zag_06: assert_deserialize; &[255, 255, 255, 255, 15] => i32::MIN);
+    test_case!(decode_zig_zag_07: assert_deserialize; &[254, 255, 255, 255, 255, 255, 255, 255, 255, 1] => i64::MAX);
+
+    test_case!(decode_overlong_01: assert_deserialize; &[255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1] => !0u64);
+    test_case!(decode_overlong_02: assert_deserialize; &[129, 128, 128, 0] => 1u8);
 
     test_case!(back_and_forth_01: assert_ser_de; -1i8 as u64);
     test_case!(back_and_forth_02: assert_ser_de; !0u64);
     test_case!(back_and_forth_03: assert_ser_de; -1i8 as u32);
     test_case!(back_and_forth_04: assert_ser_de; 1_000_000);
     test_case!(back_and_forth_05: assert_ser_de; 42);
+    test_case!(back_and_forth_06: assert_ser_de; 1_274_382_577i32);
+    test_case!(back_and_forth_07: assert_ser_de; i32::MIN);
+    test_case!(back_and_forth_08: assert_ser_de; i64::MAX);
 
     test_case!(serialize_nested_01: assert_serialize_nested; 0, None => &[0]);
     test_case!(serialize_nested_02: assert_serialize_nested; 1, None => &[2]);
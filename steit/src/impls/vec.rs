@@ -1,9 +1,10 @@
 use std::io;
 
 use crate::{
-    de::{Deserialize, Reader},
+    de::{skip_field, trace_index, Deserialize, Reader},
+    impl_state_primitive,
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::SizeCache,
+    rt::{expect_written, SizeCache},
     ser::Serialize,
     wire_fmt::{HasWireType, WireType},
 };
@@ -12,12 +13,13 @@ impl<T> HasWireType for Vec<T> {
     const WIRE_TYPE: WireType = WireType::Sized;
 }
 
+/// Items are nested one after another without tags, so varints are packed.
 impl<T: Serialize> Serialize for Vec<T> {
     fn compute_size(&self) -> u32 {
         let mut size = 0;
 
         for item in self {
-            size += item.compute_size_nested(None, false).unwrap();
+            size += expect_written(item.compute_size_nested(None, false));
         }
 
         size
@@ -36,17 +38,40 @@ impl<T: Serialize> Serialize for Vec<T> {
     }
 }
 
+/// Occurrences of a `Vec` field are appended, like protobuf repeated fields.
 impl<T: Deserialize> Deserialize for Vec<T> {
     fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
         while !reader.eof()? {
-            let item = T::deserialize_nested(T::WIRE_TYPE, reader)?;
+            let item = trace_index(
+                T::deserialize_nested(T::WIRE_TYPE, reader),
+                self.len() as u32,
+            )?;
             self.push(item);
         }
 
         Ok(())
     }
+
+    // Numbers can also be written unpacked, one item per occurrence, as protobuf allows.
+    fn merge_nested(
+        &mut self,
+        wire_type: WireType,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        match wire_type {
+            WireType::Sized => self.merge(&mut reader.nested()?),
+            _ if wire_type == T::WIRE_TYPE => {
+                let item = trace_index(T::deserialize(reader), self.len() as u32)?;
+                self.push(item);
+                Ok(())
+            }
+            _ => skip_field(wire_type, reader),
+        }
+    }
 }
 
+impl_state_primitive!(impl<T> Vec<T>);
+
 impl<T: HasMeta> HasMeta for Vec<T> {
     const NAME: &'static NameMeta = &NameMeta {
         rust: "Vec",
@@ -65,8 +90,10 @@ impl<T: HasMeta> HasMeta for Vec<T> {
 #[cfg(test)]
 mod tests {
     use crate::{
+        de::{Deserialize, Reader},
         test_case,
         test_util::{assert_merge, assert_serialize, assert_serialize_nested, assert_size},
+        wire_fmt::WireType,
     };
 
     test_case!(size_01: assert_size; Vec::<u8>::new() => 0);
@@ -87,4 +114,24 @@ mod tests {
     test_case!(merge_02: assert_merge; vec![], &[1] => vec![-1]);
     test_case!(merge_03: assert_merge; vec![-1], &[] => vec![-1]);
     test_case!(merge_04: assert_merge; vec![-1], &[0, 242, 20, 0, 3, 0] => vec![-1, 0, 1337, 0, -2, 0]);
+
+    #[test]
+    fn merge_unpacked() {
+        let mut value = vec![true];
+        let reader = &mut Reader::new(&[1, 0, 1][..]);
+
+        while !reader.eof().unwrap() {
+            value.merge_nested(WireType::Varint, reader).unwrap();
+        }
+
+        value
+            .merge_nested(WireType::Sized, &mut Reader::new(&[2, 0, 1][..]))
+            .unwrap();
+        assert_eq!(value, [true, true, false, true, false, true]);
+
+        value
+            .merge_nested(WireType::Fixed32, &mut Reader::new(&[0; 4][..]))
+            .unwrap();
+        assert_eq!(value.len(), 6);
+    }
 }
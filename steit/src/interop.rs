@@ -0,0 +1,129 @@
+use std::io;
+
+use crate::{
+    de::{Deserialize, Reader},
+    meta::{check_protobuf_layout, schema_hash, HasMeta},
+    ser::Serialize,
+};
+
+/// Pairs a steit type with the prost type sharing its tags, so either can be converted to the other
+/// by going through their common encoding.
+///
+/// Call `check_interop` in a test to make sure both sides still agree.
+pub trait ProstInterop: Serialize + Deserialize + HasMeta {
+    type Prost: prost::Message + Default;
+
+    /// The `schema_hash` of `Self` when `Prost` was generated.
+    const SCHEMA_HASH: u64;
+
+    fn to_prost(&self) -> io::Result<Self::Prost> {
+        prost::Message::decode(&*self.to_bytes())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+    }
+
+    fn from_prost(message: &Self::Prost) -> io::Result<Self> {
+        Self::deserialize(&mut Reader::new(&*prost::Message::encode_to_vec(message)))
+    }
+}
+
+/// Checks that `T` is laid out like protobuf and its schema hasn't changed since `SCHEMA_HASH` was pinned.
+pub fn check_interop<T: ProstInterop>() -> io::Result<()> {
+    check_protobuf_layout::<T>()?;
+
+    let hash = schema_hash::<T>();
+
+    if hash != T::SCHEMA_HASH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "schema of `{}` changed, its hash is now {:#x}, regenerate `{}` and update `SCHEMA_HASH`",
+                T::NAME.rust,
+                hash,
+                std::any::type_name::<T::Prost>(),
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{meta::schema_hash, rt::Runtime, steit_derive, types::List};
+
+    use super::{check_interop, ProstInterop};
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Position {
+        #[steit(tag = 1)]
+        x: i32,
+        #[steit(tag = 2)]
+        y: i32,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Hero {
+        #[steit(tag = 1)]
+        name: String,
+        #[steit(tag = 2)]
+        position: Position,
+        #[steit(tag = 3)]
+        items: List<u32>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct ProtoPosition {
+        #[prost(sint32, tag = "1")]
+        x: i32,
+        #[prost(sint32, tag = "2")]
+        y: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct ProtoHero {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(message, optional, tag = "2")]
+        position: Option<ProtoPosition>,
+        #[prost(uint32, repeated, tag = "3")]
+        items: Vec<u32>,
+    }
+
+    impl ProstInterop for Hero {
+        type Prost = ProtoHero;
+        const SCHEMA_HASH: u64 = 0xce19_ef67_ccb6_3645;
+    }
+
+    // Pinned before `y` was added.
+    impl ProstInterop for Position {
+        type Prost = ProtoPosition;
+        const SCHEMA_HASH: u64 = 0x1234;
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut hero = Hero::new(Runtime::new());
+        hero.set_name("Lina".to_string());
+        hero.position.set_x(1).set_y(-2);
+        hero.items.push(7);
+        hero.items.push(300);
+
+        let proto = hero.to_prost().unwrap();
+        assert_eq!(proto.name, "Lina");
+        assert_eq!(proto.position.as_ref().map(|point| point.y), Some(-2));
+        assert_eq!(proto.items, &[7, 300]);
+
+        assert_eq!(Hero::from_prost(&proto).unwrap(), hero);
+    }
+
+    #[test]
+    fn check_hash() {
+        check_interop::<Hero>().unwrap();
+
+        let error = check_interop::<Position>().unwrap_err();
+        let hash = format!("{:#x}", schema_hash::<Position>());
+        assert!(error.to_string().contains(&hash));
+    }
+}
@@ -1,14 +1,40 @@
+// With `no-panic`, the crate is checked not to panic, except for indexing collections,
+// which panics out of bounds like indexing slices does.
+#![cfg_attr(
+    all(feature = "no-panic", not(test)),
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing
+    )
+)]
+
+#[doc(hidden)]
+pub mod __private;
 pub mod de;
+#[cfg(all(feature = "runtime", any(test, feature = "fuzz")))]
+pub mod fuzz;
 pub mod gen;
+#[cfg(feature = "prost-interop")]
+pub mod interop;
+#[cfg(feature = "runtime")]
 pub mod log;
 pub mod meta;
+#[cfg(all(feature = "runtime", any(test, feature = "mock")))]
+pub mod net;
 pub mod rt;
+#[cfg(feature = "runtime")]
+pub mod runtime;
 pub mod ser;
+#[cfg(feature = "runtime")]
 pub mod state;
 pub mod types;
+pub mod wire;
 pub mod wire_fmt;
 
 mod impls;
+mod macros;
 
 pub use steit_derive::*;
 
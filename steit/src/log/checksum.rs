@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, hash::Hasher, io};
+
+use crate::{
+    de::Reader,
+    ser::{MapOrder, Serialize},
+    steit_derive,
+};
+
+// FNV-1a, which unlike `DefaultHasher` is the same everywhere, so clients in other languages
+// can compute checksums too.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Hash of the encoding of `value` with maps written in `MapOrder::Canonical`,
+/// so equal states hash the same however they were built.
+pub fn state_hash(value: &impl Serialize) -> u64 {
+    let mut hasher = Fnv1a::default();
+    hasher.write(&value.to_bytes_in(MapOrder::Canonical));
+    hasher.finish()
+}
+
+/// Hashes of a state, sent along frames so clients can detect they have diverged
+/// from the server and resync before it shows.
+///
+/// Top-level fields, like those of structs and entries of maps, are also hashed one by one,
+/// so a client can tell which subtree diverged first.
+#[steit_derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[steit(steit_owned, no_size_cache)]
+pub struct Checksum {
+    #[steit(tag = 0)]
+    pub hash: u64,
+    /// Sorted, with the hash of each field in `field_hashes` at the same index.
+    #[steit(tag = 1)]
+    pub field_numbers: Vec<u32>,
+    #[steit(tag = 2)]
+    pub field_hashes: Vec<u64>,
+}
+
+impl Checksum {
+    pub fn of(state: &impl Serialize) -> Self {
+        let bytes = state.to_bytes_in(MapOrder::Canonical);
+        let mut hasher = Fnv1a::default();
+        hasher.write(&bytes);
+
+        // Values which aren't messages have no fields to tell apart.
+        let fields = hash_fields(&bytes).unwrap_or_default();
+
+        Self {
+            hash: hasher.finish(),
+            field_numbers: fields.keys().copied().collect(),
+            field_hashes: fields.values().copied().collect(),
+        }
+    }
+
+    /// Returns `None` if `state` matches the state this was computed from.
+    ///
+    /// Otherwise, returns the path of the first top-level field which differs,
+    /// or an empty path if the difference can't be narrowed down.
+    pub fn mismatch(&self, state: &impl Serialize) -> Option<Vec<u32>> {
+        let other = Self::of(state);
+
+        if other.hash == self.hash {
+            return None;
+        }
+
+        let ours: BTreeMap<_, _> = self.fields().collect();
+        let theirs: BTreeMap<_, _> = other.fields().collect();
+
+        let mismatch = ours
+            .keys()
+            .chain(theirs.keys())
+            .filter(|field_number| ours.get(field_number) != theirs.get(field_number))
+            .min();
+
+        Some(mismatch.map_or_else(Vec::new, |&field_number| vec![field_number]))
+    }
+
+    fn fields(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        self.field_numbers
+            .iter()
+            .copied()
+            .zip(self.field_hashes.iter().copied())
+    }
+}
+
+// Fields occurring more than once, like items of unpacked lists, are hashed together in order.
+fn hash_fields(bytes: &[u8]) -> io::Result<BTreeMap<u32, u64>> {
+    let mut hashers: BTreeMap<u32, Fnv1a> = BTreeMap::new();
+    let mut rest = bytes;
+
+    // Readers peek a byte to check for EOF, so a new one is used per read to keep `rest` exact.
+    while !rest.is_empty() {
+        let start = bytes.len() - rest.len();
+        let (field_number, wire_type) = Reader::new(&mut rest).read_tag()?;
+        Reader::new(&mut rest).skip_field(wire_type)?;
+        let end = bytes.len() - rest.len();
+
+        hashers
+            .entry(field_number)
+            .or_default()
+            .write(bytes.get(start..end).unwrap_or_default());
+    }
+
+    Ok(hashers
+        .into_iter()
+        .map(|(field_number, hasher)| (field_number, hasher.finish()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::Runtime,
+        test_util::Point,
+        types::{List, Map},
+    };
+
+    use super::{state_hash, Checksum};
+
+    #[test]
+    fn mismatched_fields() {
+        let server = Point::new(Runtime::new(), 1, 2, 3);
+        let checksum = Checksum::of(&server);
+        assert_eq!(checksum.field_numbers, [0, 1, 2]);
+
+        assert_eq!(
+            checksum.mismatch(&Point::new(Runtime::new(), 1, 2, 3)),
+            None
+        );
+        assert_eq!(
+            checksum.mismatch(&Point::new(Runtime::new(), 1, 5, 6)),
+            Some(vec![1])
+        );
+        assert_eq!(
+            checksum.mismatch(&Point::new(Runtime::new(), 1, 2, 0)),
+            Some(vec![2])
+        );
+
+        let list = List::<u8>::new(Runtime::new());
+        assert_eq!(Checksum::of(&list).mismatch(&list), None);
+    }
+
+    #[test]
+    fn canonical_map_hash() {
+        let mut map = Map::new(Runtime::new());
+        map.insert(3u8, 30u8);
+        map.insert(1, 10);
+
+        let mut sorted = Map::new(Runtime::new());
+        sorted.insert(1u8, 10u8);
+        sorted.insert(3, 30);
+
+        assert_eq!(state_hash(&map), state_hash(&sorted));
+        assert_eq!(Checksum::of(&map).mismatch(&sorted), None);
+
+        sorted.insert(3, 31);
+        assert_eq!(Checksum::of(&map).mismatch(&sorted), Some(vec![3]));
+    }
+}
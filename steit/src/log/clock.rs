@@ -0,0 +1,54 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Source of timestamps, so that tests can control time.
+pub trait Clock: Send {
+    /// Time since an epoch, which clocks must share for their timestamps to be compared.
+    fn now(&self) -> Duration;
+}
+
+/// Wall-clock time since the UNIX epoch, comparable across machines as far as they're synced.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// A clock which only moves when told to, with microsecond precision.
+/// Clones share the same time, so one can be kept to drive a clock moved into a logger.
+#[derive(Clone, Default, Debug)]
+pub struct ManualClock {
+    micros: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, time: Duration) {
+        self.micros
+            .store(time.as_micros() as u64, Ordering::Release);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.micros
+            .fetch_add(duration.as_micros() as u64, Ordering::AcqRel);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_micros(self.micros.load(Ordering::Acquire))
+    }
+}
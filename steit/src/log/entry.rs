@@ -1,7 +1,11 @@
+use std::io;
+
 use serde::Serialize as JsonSerialize;
 
 use crate::{
-    rt::{Node, SizeCache},
+    de::{Deserialize, Reader},
+    log::StringTable,
+    rt::{expect_written, Node, SizeCache},
     ser::Serialize,
     steit_derive,
     types::Bytes,
@@ -10,8 +14,13 @@ use crate::{
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum LogEntryKind {
     Update = 0,
+    Intern = 1,
+    UpdateInterned = 2,
+    Replace = 3,
     ListPush = 8,
     ListPop = 9,
+    ListExtend = 10,
+    ListMove = 11,
     MapRemove = 12,
 }
 
@@ -26,6 +35,27 @@ pub enum LogEntry {
         #[steit(tag = 1)]
         value: Bytes,
     },
+    #[steit(tag = 1)]
+    Intern {
+        #[steit(tag = 0)]
+        index: u32,
+        #[steit(tag = 1)]
+        value: String,
+    },
+    #[steit(tag = 2)]
+    UpdateInterned {
+        #[steit(tag = 0, csharp_name = "flatten_path")]
+        path: Vec<u32>,
+        #[steit(tag = 1)]
+        index: u32,
+    },
+    #[steit(tag = 3)]
+    Replace {
+        #[steit(tag = 0, csharp_name = "flatten_path")]
+        path: Vec<u32>,
+        #[steit(tag = 1)]
+        value: Bytes,
+    },
     #[steit(tag = 8)]
     ListPush {
         #[steit(tag = 0, csharp_name = "flatten_path")]
@@ -38,6 +68,22 @@ pub enum LogEntry {
         #[steit(tag = 0, csharp_name = "flatten_path")]
         path: Vec<u32>,
     },
+    #[steit(tag = 10)]
+    ListExtend {
+        #[steit(tag = 0, csharp_name = "flatten_path")]
+        path: Vec<u32>,
+        #[steit(tag = 1)]
+        items: Bytes,
+    },
+    #[steit(tag = 11)]
+    ListMove {
+        #[steit(tag = 0, csharp_name = "flatten_path")]
+        path: Vec<u32>,
+        #[steit(tag = 1)]
+        from: u32,
+        #[steit(tag = 2)]
+        to: u32,
+    },
     #[steit(tag = 12)]
     MapRemove {
         #[steit(tag = 0, csharp_name = "flatten_path")]
@@ -47,6 +93,8 @@ pub enum LogEntry {
     },
 }
 
+type UnpackedLogEntry = (LogEntryKind, Vec<u32>, Option<u32>, Option<Vec<u8>>);
+
 impl LogEntry {
     pub fn new_update(path: &Node<u32>, value: &impl Serialize) -> Self {
         LogEntry::Update {
@@ -56,6 +104,30 @@ impl LogEntry {
         }
     }
 
+    pub fn new_intern(index: u32, value: impl Into<String>) -> Self {
+        LogEntry::Intern {
+            index,
+            value: value.into(),
+            size_cache: SizeCache::new(),
+        }
+    }
+
+    pub fn new_update_interned(path: &Node<u32>, index: u32) -> Self {
+        LogEntry::UpdateInterned {
+            path: path.collect_values(),
+            index,
+            size_cache: SizeCache::new(),
+        }
+    }
+
+    pub fn new_replace(path: &Node<u32>, value: &impl Serialize) -> Self {
+        LogEntry::Replace {
+            path: path.collect_values(),
+            value: Bytes::from_value(value),
+            size_cache: SizeCache::new(),
+        }
+    }
+
     pub fn new_list_push(path: &Node<u32>, item: &impl Serialize) -> Self {
         LogEntry::ListPush {
             path: path.collect_values(),
@@ -71,6 +143,23 @@ impl LogEntry {
         }
     }
 
+    pub fn new_list_extend<T: Serialize>(path: &Node<u32>, items: &[T]) -> Self {
+        LogEntry::ListExtend {
+            path: path.collect_values(),
+            items: serialize_items(items),
+            size_cache: SizeCache::new(),
+        }
+    }
+
+    pub fn new_list_move(path: &Node<u32>, from: u32, to: u32) -> Self {
+        LogEntry::ListMove {
+            path: path.collect_values(),
+            from,
+            to,
+            size_cache: SizeCache::new(),
+        }
+    }
+
     pub fn new_map_remove(path: &Node<u32>, key: u32) -> Self {
         LogEntry::MapRemove {
             path: path.collect_values(),
@@ -79,12 +168,134 @@ impl LogEntry {
         }
     }
 
+    pub fn path(&self) -> Option<&[u32]> {
+        match self {
+            LogEntry::Update { path, .. }
+            | LogEntry::UpdateInterned { path, .. }
+            | LogEntry::Replace { path, .. }
+            | LogEntry::ListPush { path, .. }
+            | LogEntry::ListPop { path, .. }
+            | LogEntry::ListExtend { path, .. }
+            | LogEntry::ListMove { path, .. }
+            | LogEntry::MapRemove { path, .. } => Some(path),
+            LogEntry::Intern { .. } => None,
+        }
+    }
+
+    pub fn path_mut(&mut self) -> Option<&mut Vec<u32>> {
+        match self {
+            LogEntry::Update { path, .. }
+            | LogEntry::UpdateInterned { path, .. }
+            | LogEntry::Replace { path, .. }
+            | LogEntry::ListPush { path, .. }
+            | LogEntry::ListPop { path, .. }
+            | LogEntry::ListExtend { path, .. }
+            | LogEntry::ListMove { path, .. }
+            | LogEntry::MapRemove { path, .. } => Some(path),
+            LogEntry::Intern { .. } => None,
+        }
+    }
+
     pub fn kind(&self) -> LogEntryKind {
         match self {
             LogEntry::Update { .. } => LogEntryKind::Update,
+            LogEntry::Intern { .. } => LogEntryKind::Intern,
+            LogEntry::UpdateInterned { .. } => LogEntryKind::UpdateInterned,
+            LogEntry::Replace { .. } => LogEntryKind::Replace,
             LogEntry::ListPush { .. } => LogEntryKind::ListPush,
             LogEntry::ListPop { .. } => LogEntryKind::ListPop,
+            LogEntry::ListExtend { .. } => LogEntryKind::ListExtend,
+            LogEntry::ListMove { .. } => LogEntryKind::ListMove,
             LogEntry::MapRemove { .. } => LogEntryKind::MapRemove,
         }
     }
+
+    /// Encoded value the entry carries, which is the new value of `Update` or `Replace`,
+    /// the item of `ListPush` or the items of `ListExtend`, back to back.
+    pub fn payload(&self) -> Option<&[u8]> {
+        match self {
+            LogEntry::Update { value, .. } | LogEntry::Replace { value, .. } => {
+                Some(value.as_raw())
+            }
+            LogEntry::ListPush { item, .. } => Some(item.as_raw()),
+            LogEntry::ListExtend { items, .. } => Some(items.as_raw()),
+            _ => None,
+        }
+    }
+
+    /// Decodes the payload, as a `Vec` of items for `ListExtend`.
+    pub fn decode_payload<T: Deserialize>(&self) -> io::Result<T> {
+        let payload = self.payload().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{:?}` entries carry no payload", self.kind()),
+            )
+        })?;
+
+        T::deserialize(&mut Reader::new(payload))
+    }
+
+    /// Takes the entry apart for `State::handle`, resolving interned strings through `strings`.
+    /// `Intern` entries only go into `strings`, so there is nothing to handle.
+    pub(crate) fn unpack(self, strings: &mut StringTable) -> io::Result<Option<UnpackedLogEntry>> {
+        Ok(Some(match self {
+            LogEntry::Update { path, value, .. } => {
+                (LogEntryKind::Update, path, None, Some(value.into_raw()))
+            }
+
+            LogEntry::Intern { index, value, .. } => {
+                strings.insert(index, value)?;
+                return Ok(None);
+            }
+
+            LogEntry::UpdateInterned { path, index, .. } => {
+                let value = strings.get(index).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("interned string {} not found", index),
+                    )
+                })?;
+
+                (LogEntryKind::Update, path, None, Some(value.to_bytes()))
+            }
+
+            // Updates of states already swap them whole, so only clients tell both apart.
+            LogEntry::Replace { path, value, .. } => {
+                (LogEntryKind::Update, path, None, Some(value.into_raw()))
+            }
+
+            LogEntry::ListPush { path, item, .. } => {
+                (LogEntryKind::ListPush, path, None, Some(item.into_raw()))
+            }
+
+            LogEntry::ListPop { path, .. } => (LogEntryKind::ListPop, path, None, None),
+
+            LogEntry::ListExtend { path, items, .. } => {
+                (LogEntryKind::ListExtend, path, None, Some(items.into_raw()))
+            }
+
+            // Both indices are passed to handlers as varints, `from` first.
+            LogEntry::ListMove { path, from, to, .. } => {
+                let mut bytes = from.to_bytes();
+                bytes.extend(to.to_bytes());
+                (LogEntryKind::ListMove, path, None, Some(bytes))
+            }
+
+            LogEntry::MapRemove { path, key, .. } => {
+                (LogEntryKind::MapRemove, path, Some(key), None)
+            }
+        }))
+    }
+}
+
+/// Writes `items` back to back, the same way as in a serialized `List`.
+pub(crate) fn serialize_items<T: Serialize>(items: &[T]) -> Bytes {
+    let mut bytes = Vec::new();
+
+    for item in items {
+        expect_written(item.compute_size_nested(None, false));
+        expect_written(item.serialize_nested(None, false, &mut bytes));
+    }
+
+    Bytes::from_raw(bytes)
 }
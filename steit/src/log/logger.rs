@@ -1,4 +1,6 @@
-use std::io;
+use std::{io, net::TcpStream, sync::Arc};
+
+use crate::ser::Serialize;
 
 use super::entry::LogEntry;
 
@@ -19,3 +21,77 @@ impl<T: ?Sized + Logger> Logger for Box<T> {
         self.as_mut().log(entry)
     }
 }
+
+/// A logger which needs no exclusive access, like a socket or a collector shared with tests.
+/// As an `Arc`, it can be given to a runtime and still be used from elsewhere.
+pub trait LogSink: Send + Sync {
+    fn log(&self, entry: &LogEntry) -> io::Result<()>;
+}
+
+impl<S: ?Sized + LogSink> Logger for Arc<S> {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        LogSink::log(&**self, &entry)
+    }
+}
+
+/// Writes entries as they are logged, in the format `State::replay` reads.
+impl LogSink for TcpStream {
+    fn log(&self, entry: &LogEntry) -> io::Result<()> {
+        entry.cache_size();
+        entry.serialize_nested(None, false, &mut &*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Read,
+        net::{TcpListener, TcpStream},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        log::LogEntryKind,
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    use super::{LogEntry, LogSink};
+
+    #[derive(Default)]
+    struct Collector(Mutex<Vec<LogEntryKind>>);
+
+    impl LogSink for Collector {
+        fn log(&self, entry: &LogEntry) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(entry.kind());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn shared_sink() {
+        let collector = Arc::new(Collector::default());
+        let mut point = Point::empty(Runtime::with_logger(collector.clone()));
+        point.set_x(1).set_y(2);
+
+        assert_eq!(*collector.0.lock().unwrap(), [LogEntryKind::Update; 2]);
+    }
+
+    #[test]
+    fn tcp_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut receiver, _) = listener.accept().unwrap();
+
+        let mut point = Point::empty(Runtime::with_logger(Arc::new(stream)));
+        point.set_x(1).set_z(-3);
+        drop(point);
+
+        let mut bytes = Vec::new();
+        receiver.read_to_end(&mut bytes).unwrap();
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &bytes);
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, 0, -3));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    log::{LogEntry, Logger},
+    rt::lock,
+};
+
+use super::BufferLogger;
+
+#[derive(Default)]
+struct Frames {
+    // Epoch of the first frame in `frames`.
+    first_epoch: u64,
+    frames: VecDeque<Arc<[u8]>>,
+    // Epoch of the next frame to be polled, by subscriber.
+    cursors: HashMap<u64, u64>,
+    next_id: u64,
+}
+
+impl Frames {
+    fn next_epoch(&self) -> u64 {
+        self.first_epoch + self.frames.len() as u64
+    }
+
+    // Retires frames every subscriber has polled.
+    // Their memory is released once the last subscriber drops its reference.
+    fn reclaim(&mut self) {
+        let min_epoch = self
+            .cursors
+            .values()
+            .copied()
+            .min()
+            .unwrap_or_else(|| self.next_epoch());
+
+        while self.first_epoch < min_epoch {
+            self.frames.pop_front();
+            self.first_epoch += 1;
+        }
+    }
+}
+
+/// Serializes entries once per frame and shares the bytes with all subscribers,
+/// so fanning out to many clients doesn't copy them per client.
+///
+/// Each flush starts a new epoch. Frames are retired as soon as every subscriber has polled them.
+#[derive(Default)]
+pub struct BroadcastLogger {
+    buffer: BufferLogger,
+    frames: Arc<Mutex<Frames>>,
+}
+
+impl BroadcastLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to frames flushed from now on.
+    pub fn subscribe(&self) -> Subscriber {
+        let mut frames = lock(&self.frames);
+        let id = frames.next_id;
+        let epoch = frames.next_epoch();
+
+        frames.next_id += 1;
+        frames.cursors.insert(id, epoch);
+
+        Subscriber {
+            id,
+            frames: self.frames.clone(),
+        }
+    }
+
+    /// Turns entries logged since the last flush into a frame and returns its epoch.
+    /// Nothing is retained if there are no subscribers.
+    pub fn flush(&mut self) -> u64 {
+        let bytes = self.buffer.pluck_bytes();
+        let mut frames = lock(&self.frames);
+        let epoch = frames.next_epoch();
+
+        frames.frames.push_back(bytes.into());
+        frames.reclaim();
+        epoch
+    }
+
+    /// Number of frames not yet polled by every subscriber.
+    pub fn retained(&self) -> usize {
+        lock(&self.frames).frames.len()
+    }
+}
+
+impl Logger for BroadcastLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.buffer.log(entry)
+    }
+}
+
+/// Receives frames of a `BroadcastLogger`, unsubscribing when dropped.
+pub struct Subscriber {
+    id: u64,
+    frames: Arc<Mutex<Frames>>,
+}
+
+impl Subscriber {
+    /// Takes frames flushed since the last poll, oldest first.
+    pub fn poll(&mut self) -> Vec<Arc<[u8]>> {
+        let mut frames = lock(&self.frames);
+        let next_epoch = frames.next_epoch();
+        let epoch = frames
+            .cursors
+            .insert(self.id, next_epoch)
+            .unwrap_or(next_epoch);
+        let start = epoch.saturating_sub(frames.first_epoch) as usize;
+        let polled = frames.frames.range(start..).cloned().collect();
+
+        frames.reclaim();
+        polled
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        let mut frames = lock(&self.frames);
+        frames.cursors.remove(&self.id);
+        frames.reclaim();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    use super::BroadcastLogger;
+
+    #[test]
+    fn shared_frames() {
+        let (runtime, logger) = Runtime::with_logger_returned(BroadcastLogger::new());
+        let mut first = logger.lock().unwrap().subscribe();
+        let mut second = logger.lock().unwrap().subscribe();
+
+        let mut point = Point::empty(runtime);
+        point.set_x(1);
+        logger.lock().unwrap().flush();
+        point.set_y(-2);
+        logger.lock().unwrap().flush();
+
+        let frames = first.poll();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(logger.lock().unwrap().retained(), 2);
+
+        let second_frames = second.poll();
+        assert!(Arc::ptr_eq(&frames[0], &second_frames[0]));
+        assert_eq!(logger.lock().unwrap().retained(), 0);
+
+        let mut mirror = Point::empty(Runtime::new());
+
+        for frame in &frames {
+            replay(&mut mirror, frame);
+        }
+
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, -2, 0));
+        assert!(first.poll().is_empty());
+    }
+
+    #[test]
+    fn reclaim_on_unsubscribe() {
+        let mut logger = BroadcastLogger::new();
+        logger.flush();
+        assert_eq!(logger.retained(), 0);
+
+        let mut first = logger.subscribe();
+        let second = logger.subscribe();
+        logger.flush();
+        logger.flush();
+
+        first.poll();
+        assert_eq!(logger.retained(), 2);
+
+        drop(second);
+        assert_eq!(logger.retained(), 0);
+
+        let third = logger.subscribe();
+        assert_eq!(logger.flush(), 3);
+        assert_eq!(logger.retained(), 1);
+
+        drop(third);
+        first.poll();
+        assert_eq!(logger.retained(), 0);
+    }
+}
@@ -1,13 +1,18 @@
-use std::io;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 use crate::{
     log::{LogEntry, Logger},
+    rt::expect_written,
     ser::Serialize,
 };
 
 #[derive(Default)]
 pub struct BufferLogger {
     entries: Vec<LogEntry>,
+    capacity: usize,
 }
 
 impl BufferLogger {
@@ -15,12 +20,26 @@ impl BufferLogger {
         Self::default()
     }
 
+    /// Preallocates room for `capacity` entries, which is kept across `pluck` calls,
+    /// so it's best set to the number of entries expected per tick.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
     pub fn bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let size = self
+            .entries
+            .iter()
+            .map(|entry| expect_written(entry.compute_size_nested(None, false)))
+            .sum::<u32>();
+
+        let mut bytes = Vec::with_capacity(size as usize);
 
         for entry in &self.entries {
-            entry.cache_size();
-            entry.serialize_nested(None, false, &mut bytes).unwrap();
+            expect_written(entry.serialize_nested(None, false, &mut bytes));
         }
 
         bytes
@@ -31,7 +50,7 @@ impl BufferLogger {
     }
 
     pub fn pluck(&mut self) -> Vec<LogEntry> {
-        std::mem::replace(&mut self.entries, Vec::new())
+        std::mem::replace(&mut self.entries, Vec::with_capacity(self.capacity))
     }
 
     pub fn pluck_bytes(&mut self) -> Vec<u8> {
@@ -39,6 +58,36 @@ impl BufferLogger {
         self.clear();
         bytes
     }
+
+    /// Writes entries oldest first until `budget` runs out, keeping the rest for the next call,
+    /// so servers can bound the time spent replicating per tick.
+    ///
+    /// At least one entry is written per call, so the backlog drains even if the budget is tiny.
+    /// Returns whether every entry has been written.
+    pub fn flush_for(&mut self, budget: Duration, writer: &mut impl io::Write) -> io::Result<bool> {
+        let start = Instant::now();
+        let mut written = 0;
+
+        for entry in &self.entries {
+            let result = entry
+                .compute_size_nested(None, false)
+                .and_then(|_| entry.serialize_nested(None, false, &mut *writer));
+
+            if let Err(error) = result {
+                self.entries.drain(..written);
+                return Err(error);
+            }
+
+            written += 1;
+
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+
+        self.entries.drain(..written);
+        Ok(self.entries.is_empty())
+    }
 }
 
 impl Logger for BufferLogger {
@@ -47,3 +96,53 @@ impl Logger for BufferLogger {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        log::{LogEntry, Logger},
+        rt::Node,
+    };
+
+    use super::BufferLogger;
+
+    #[test]
+    fn pluck_with_capacity() {
+        let mut logger = BufferLogger::with_capacity(16);
+        logger.log(LogEntry::new_list_pop(&Node::Root)).unwrap();
+        assert_eq!(logger.pluck().len(), 1);
+        assert!(logger.entries.capacity() >= 16);
+    }
+
+    #[test]
+    fn bytes() {
+        let mut logger = BufferLogger::new();
+        logger.log(LogEntry::new_list_pop(&Node::Root)).unwrap();
+        logger
+            .log(LogEntry::new_map_remove(&Node::Root, 1))
+            .unwrap();
+        assert_eq!(logger.bytes(), &[1, 9, 3, 12, 8, 1]);
+    }
+
+    #[test]
+    fn flush_for() {
+        let mut logger = BufferLogger::new();
+        logger.log(LogEntry::new_list_pop(&Node::Root)).unwrap();
+        logger
+            .log(LogEntry::new_map_remove(&Node::Root, 1))
+            .unwrap();
+
+        let mut bytes = Vec::new();
+        assert!(!logger.flush_for(Duration::ZERO, &mut bytes).unwrap());
+        assert_eq!(bytes, &[1, 9]);
+
+        logger.log(LogEntry::new_list_pop(&Node::Root)).unwrap();
+        assert!(logger
+            .flush_for(Duration::from_secs(1), &mut bytes)
+            .unwrap());
+        assert_eq!(bytes, &[1, 9, 3, 12, 8, 1, 1, 9]);
+        assert!(logger.flush_for(Duration::ZERO, &mut bytes).unwrap());
+    }
+}
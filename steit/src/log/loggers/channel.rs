@@ -0,0 +1,137 @@
+use std::{io, sync::mpsc};
+
+use crate::log::{LogEntry, Logger};
+
+/// What a bounded `ChannelLogger` does with an entry when the channel is full.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FullPolicy {
+    /// Waits for the receiver to make room, stalling the logging thread.
+    Block,
+    /// Drops the entry and counts it in `dropped`.
+    /// Clients miss the change, so they must be resynced with a full snapshot.
+    Drop,
+    /// Fails with `io::ErrorKind::WouldBlock`, leaving the entry to the caller.
+    Fail,
+}
+
+enum Sender {
+    Unbounded(mpsc::Sender<LogEntry>),
+    Bounded(mpsc::SyncSender<LogEntry>, FullPolicy),
+}
+
+/// Sends entries to another thread, so they can be serialized and sent off the game loop.
+///
+/// Logging fails with `io::ErrorKind::BrokenPipe` once the receiver is gone.
+pub struct ChannelLogger {
+    sender: Sender,
+    dropped: u64,
+}
+
+impl ChannelLogger {
+    pub fn unbounded() -> (Self, mpsc::Receiver<LogEntry>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self::with_sender(Sender::Unbounded(sender)), receiver)
+    }
+
+    /// Holds up to `capacity` entries not received yet, past which `policy` applies.
+    pub fn bounded(capacity: usize, policy: FullPolicy) -> (Self, mpsc::Receiver<LogEntry>) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        (Self::with_sender(Sender::Bounded(sender, policy)), receiver)
+    }
+
+    fn with_sender(sender: Sender) -> Self {
+        Self { sender, dropped: 0 }
+    }
+
+    /// Number of entries dropped under `FullPolicy::Drop`.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+fn disconnected() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "log receiver disconnected")
+}
+
+impl Logger for ChannelLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        match &self.sender {
+            Sender::Unbounded(sender) => sender.send(entry).map_err(|_| disconnected()),
+
+            Sender::Bounded(sender, FullPolicy::Block) => {
+                sender.send(entry).map_err(|_| disconnected())
+            }
+
+            Sender::Bounded(sender, policy) => match sender.try_send(entry) {
+                Ok(()) => Ok(()),
+                Err(mpsc::TrySendError::Disconnected(_)) => Err(disconnected()),
+
+                Err(mpsc::TrySendError::Full(_)) if *policy == FullPolicy::Drop => {
+                    self.dropped += 1;
+                    Ok(())
+                }
+
+                Err(mpsc::TrySendError::Full(_)) => Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "log channel is full",
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, thread};
+
+    use crate::{
+        log::{loggers::BufferLogger, LogEntry, Logger},
+        rt::{Node, Runtime},
+        test_util::{replay, Point},
+    };
+
+    use super::{ChannelLogger, FullPolicy};
+
+    #[test]
+    fn unbounded_across_threads() {
+        let (logger, receiver) = ChannelLogger::unbounded();
+        let runtime = Runtime::with_logger(logger);
+
+        let received = thread::spawn(move || {
+            let mut buffer = BufferLogger::new();
+
+            for entry in receiver {
+                buffer.log(entry).unwrap();
+            }
+
+            buffer.bytes()
+        });
+
+        let mut point = Point::empty(runtime);
+        point.set_x(1).set_y(-2);
+        drop(point);
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &received.join().unwrap());
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, -2, 0));
+    }
+
+    #[test]
+    fn bounded_policies() {
+        let entry = || LogEntry::new_list_pop(&Node::Root);
+
+        let (mut logger, _receiver) = ChannelLogger::bounded(1, FullPolicy::Drop);
+        logger.log(entry()).unwrap();
+        logger.log(entry()).unwrap();
+        assert_eq!(logger.dropped(), 1);
+
+        let (mut logger, receiver) = ChannelLogger::bounded(1, FullPolicy::Fail);
+        logger.log(entry()).unwrap();
+        let error = logger.log(entry()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+
+        drop(receiver);
+        let error = logger.log(entry()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
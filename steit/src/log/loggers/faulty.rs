@@ -0,0 +1,130 @@
+use std::io;
+
+use crate::log::{LogEntry, Logger};
+
+/// Which entries a `FaultyLogger` fails, by their index among all entries it got, from 0.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FaultPattern {
+    Never,
+    Always,
+    /// Fails every `n`th entry, i.e. entries `n - 1`, `2n - 1` and so on.
+    Every(u64),
+    /// Fails entries from index `n` on, like a disk filling up.
+    After(u64),
+    /// Fails the entries at these indices.
+    At(Vec<u64>),
+}
+
+impl FaultPattern {
+    pub fn fails(&self, index: u64) -> bool {
+        match self {
+            FaultPattern::Never => false,
+            FaultPattern::Always => true,
+            FaultPattern::Every(n) => (index + 1).is_multiple_of(*n),
+            FaultPattern::After(n) => index >= *n,
+            FaultPattern::At(indices) => indices.contains(&index),
+        }
+    }
+}
+
+/// Fails entries matching a `FaultPattern` instead of passing them to the inner logger,
+/// to test how code handles logging failures.
+pub struct FaultyLogger<L: Logger> {
+    inner: L,
+    pattern: FaultPattern,
+    count: u64,
+    failures: u64,
+}
+
+impl<L: Logger> FaultyLogger<L> {
+    pub fn new(inner: L, pattern: FaultPattern) -> Self {
+        Self {
+            inner,
+            pattern,
+            count: 0,
+            failures: 0,
+        }
+    }
+
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    /// Changes the pattern, which keeps counting entries from where the previous one was.
+    pub fn set_pattern(&mut self, pattern: FaultPattern) {
+        self.pattern = pattern;
+    }
+
+    /// Number of entries this logger got, failed or not.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+}
+
+impl<L: Logger> Logger for FaultyLogger<L> {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        let index = self.count;
+        self.count += 1;
+
+        if self.pattern.fails(index) {
+            self.failures += 1;
+
+            return Err(io::Error::other(format!(
+                "injected failure of entry {}",
+                index
+            )));
+        }
+
+        self.inner.log(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::BufferLogger,
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    use super::{FaultPattern, FaultyLogger};
+
+    #[test]
+    fn patterns() {
+        let fails = |pattern: FaultPattern| -> Vec<u64> {
+            (0..6).filter(|&index| pattern.fails(index)).collect()
+        };
+
+        assert_eq!(fails(FaultPattern::Never), []);
+        assert_eq!(fails(FaultPattern::Always), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(fails(FaultPattern::Every(2)), [1, 3, 5]);
+        assert_eq!(fails(FaultPattern::Every(0)), []);
+        assert_eq!(fails(FaultPattern::After(4)), [4, 5]);
+        assert_eq!(fails(FaultPattern::At(vec![0, 3])), [0, 3]);
+    }
+
+    #[test]
+    fn failed_entries_dropped() {
+        let logger = FaultyLogger::new(BufferLogger::new(), FaultPattern::At(vec![1]));
+        let (runtime, logger) = Runtime::with_logger_returned(logger);
+
+        assert!(runtime.nested(0).log_update(&1).is_ok());
+        assert!(runtime.nested(1).log_update(&2).is_err());
+        assert!(runtime.nested(2).log_update(&3).is_ok());
+
+        let mut logger = logger.lock().unwrap();
+        assert_eq!((logger.count(), logger.failures()), (3, 1));
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &logger.inner_mut().pluck_bytes());
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, 0, 3));
+    }
+}
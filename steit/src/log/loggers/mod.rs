@@ -1,9 +1,31 @@
+mod broadcast;
 mod buffer;
+mod channel;
+mod faulty;
 mod noop;
 mod panic;
+mod profile;
+mod recorder;
+mod recording;
+mod replicate;
+mod resume;
+mod snapshot_cache;
+mod split;
+mod window;
 mod writer;
 
+pub use broadcast::*;
 pub use buffer::*;
+pub use channel::*;
+pub use faulty::*;
 pub use noop::*;
 pub use panic::*;
+pub use profile::*;
+pub use recorder::*;
+pub use recording::*;
+pub use replicate::*;
+pub use resume::*;
+pub use snapshot_cache::*;
+pub use split::*;
+pub use window::*;
 pub use writer::*;
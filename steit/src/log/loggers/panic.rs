@@ -1,18 +1,27 @@
 use std::io;
 
-use crate::log::{LogEntry, Logger};
+use crate::{
+    log::{LogEntry, Logger},
+    rt::fail,
+};
 
+/// Panics on every entry, or rejects it with `no-panic`.
 #[derive(Default)]
 pub struct PanicLogger;
 
 impl PanicLogger {
     pub fn new() -> Self {
-        Self::default()
+        Self
     }
 }
 
 impl Logger for PanicLogger {
     fn log(&mut self, entry: LogEntry) -> io::Result<()> {
-        panic!("got an entry but prefer to panic! {:#?}", entry);
+        fail(format_args!(
+            "got an entry but prefer to panic! {:#?}",
+            entry
+        ));
+
+        Err(io::Error::other("got an entry but prefer to reject it"))
     }
 }
@@ -0,0 +1,104 @@
+use std::{collections::HashMap, io};
+
+use crate::{
+    log::{LogEntry, Logger},
+    meta::{self, HasMeta},
+};
+
+/// Counts logged entries per path before passing them to the inner logger,
+/// which helps finding fields that get set too often.
+pub struct ProfileLogger<T: Logger> {
+    inner: T,
+    counts: HashMap<Vec<u32>, u64>,
+}
+
+impl<T: Logger> ProfileLogger<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn count(&self, path: &[u32]) -> u64 {
+        self.counts.get(path).copied().unwrap_or_default()
+    }
+
+    /// Returns the `n` most logged paths, most logged first.
+    pub fn top(&self, n: usize) -> Vec<(&[u32], u64)> {
+        let mut counts: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(path, &count)| (path.as_slice(), count))
+            .collect();
+
+        counts.sort_by(|(path, count), (other_path, other_count)| {
+            other_count.cmp(count).then_with(|| path.cmp(other_path))
+        });
+
+        counts.truncate(n);
+        counts
+    }
+
+    /// Same as `top` but with paths described by the schema of the root state `S`.
+    pub fn report<S: HasMeta>(&self, n: usize) -> Vec<(String, u64)> {
+        self.top(n)
+            .into_iter()
+            .map(|(path, count)| (meta::describe_path::<S>(path), count))
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        self.counts.clear();
+    }
+}
+
+impl<T: Logger> Logger for ProfileLogger<T> {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        if let Some(path) = entry.path() {
+            *self.counts.entry(path.to_vec()).or_default() += 1;
+        }
+
+        self.inner.log(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{log::loggers::NoopLogger, rt::Runtime, test_util::Point};
+
+    use super::ProfileLogger;
+
+    #[test]
+    fn report() {
+        let (runtime, logger) = Runtime::with_logger_returned(ProfileLogger::new(NoopLogger));
+        let mut point = Point::new(runtime, 0, 0, 0);
+
+        for x in 0..3 {
+            point.set_x(x);
+        }
+
+        point.set_z(1).set_y(1).set_z(2);
+
+        let logger = logger.lock().unwrap();
+        assert_eq!(logger.count(&[0]), 3);
+        assert_eq!(logger.top(1), &[(&[0][..], 3)]);
+
+        assert_eq!(
+            logger.report::<Point>(3),
+            &[
+                ("Point.x".to_string(), 3),
+                ("Point.z".to_string(), 2),
+                ("Point.y".to_string(), 1),
+            ],
+        );
+    }
+}
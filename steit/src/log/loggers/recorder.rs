@@ -0,0 +1,200 @@
+use std::{io, time::Duration};
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{Checksum, Clock, LogEntry, Logger, SystemClock},
+    ser::Serialize,
+    steit_derive,
+    types::Bytes,
+};
+
+use super::BufferLogger;
+
+/// Entries logged between two flushes, stamped with the time of the later one.
+#[steit_derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[steit(steit_owned, no_size_cache)]
+pub struct Frame {
+    /// Microseconds since the epoch of the recording clock.
+    #[steit(tag = 0)]
+    pub timestamp: u64,
+    #[steit(tag = 1)]
+    pub entries: Bytes,
+    /// Checksum of the state after the entries, attached by `Recorder::flush_checked`.
+    #[steit(tag = 2)]
+    pub checksum: Option<Checksum>,
+}
+
+impl Frame {
+    pub fn time(&self) -> Duration {
+        Duration::from_micros(self.timestamp)
+    }
+
+    /// Time since the frame was recorded, as told by `clock`, which must share
+    /// the epoch of the recording clock. Measured on receipt, this is the end-to-end latency.
+    pub fn age(&self, clock: &impl Clock) -> Duration {
+        clock.now().saturating_sub(self.time())
+    }
+}
+
+/// Records entries as timestamped frames, one per `flush`, so sessions can be saved
+/// with `write` and replayed later at the pace they were recorded.
+pub struct Recorder<C: Clock = SystemClock> {
+    buffer: BufferLogger,
+    clock: C,
+    frames: Vec<Frame>,
+    checksum_every: u32,
+    checked_flushes: u32,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> Recorder<C> {
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            buffer: BufferLogger::new(),
+            clock,
+            frames: Vec::new(),
+            checksum_every: 1,
+            checked_flushes: 0,
+        }
+    }
+
+    /// Has `flush_checked` attach a checksum to one in every `frames` frames, 1 by default.
+    pub fn checksum_every(mut self, frames: u32) -> Self {
+        self.checksum_every = frames.max(1);
+        self
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Stamps entries logged since the last flush as a frame, usually once per tick.
+    /// Nothing is recorded if there are none.
+    pub fn flush(&mut self) -> Option<&Frame> {
+        let entries = self.buffer.pluck_bytes();
+
+        if entries.is_empty() {
+            return None;
+        }
+
+        self.frames.push(Frame {
+            timestamp: self.clock.now().as_micros() as u64,
+            entries: Bytes::from_raw(entries),
+            checksum: None,
+        });
+
+        self.frames.last()
+    }
+
+    /// Like `flush`, with a checksum of `state`, which the entries must have been logged from,
+    /// attached every `checksum_every` frames for clients to check theirs against.
+    pub fn flush_checked(&mut self, state: &impl Serialize) -> Option<&Frame> {
+        self.flush()?;
+        self.checked_flushes += 1;
+
+        let frame = self.frames.last_mut()?;
+
+        if self.checked_flushes.is_multiple_of(self.checksum_every) {
+            frame.checksum = Some(Checksum::of(state));
+        }
+
+        Some(frame)
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Takes the frames recorded so far, which `write_frames` can then save.
+    pub fn take_frames(&mut self) -> Vec<Frame> {
+        std::mem::take(&mut self.frames)
+    }
+}
+
+impl<C: Clock> Logger for Recorder<C> {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.buffer.log(entry)
+    }
+}
+
+/// Writes `frames` one after another, each prefixed by its size, to be read by `read_frames`.
+pub fn write_frames(frames: &[Frame], writer: &mut impl io::Write) -> io::Result<()> {
+    for frame in frames {
+        frame.serialize_nested(None, false, &mut *writer)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_frames(bytes: &[u8]) -> io::Result<Vec<Frame>> {
+    Vec::deserialize(&mut Reader::new(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        log::{Clock, ManualClock},
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    use super::{read_frames, write_frames, Recorder};
+
+    #[test]
+    fn stamp_and_replay() {
+        let clock = ManualClock::new();
+        clock.set(Duration::from_secs(10));
+
+        let (runtime, recorder) =
+            Runtime::with_logger_returned(Recorder::with_clock(clock.clone()));
+        let mut point = Point::empty(runtime);
+
+        point.set_x(1);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(
+            recorder.lock().unwrap().flush().unwrap().timestamp,
+            10_050_000
+        );
+        assert!(recorder.lock().unwrap().flush().is_none());
+
+        point.set_y(2).set_z(3);
+        clock.advance(Duration::from_millis(50));
+        recorder.lock().unwrap().flush();
+
+        let mut bytes = Vec::new();
+        let frames = recorder.lock().unwrap().take_frames();
+        write_frames(&frames, &mut bytes).unwrap();
+
+        let frames = read_frames(&bytes).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            frames[1].time() - frames[0].time(),
+            Duration::from_millis(50)
+        );
+
+        clock.advance(Duration::from_millis(20));
+        assert_eq!(frames[1].age(&clock), Duration::from_millis(20));
+        assert_eq!(clock.now(), Duration::from_millis(10_120));
+
+        let mut mirror = Point::empty(Runtime::new());
+
+        for frame in &frames {
+            replay(&mut mirror, frame.entries.as_raw());
+        }
+
+        assert_eq!(mirror, point);
+    }
+}
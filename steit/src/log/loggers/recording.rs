@@ -0,0 +1,83 @@
+use std::{io, sync::Mutex};
+
+use crate::{
+    log::{LogEntry, LogSink},
+    rt::lock,
+};
+
+/// Keeps entries in memory for tests to inspect what has been logged.
+///
+/// As a `LogSink`, it's given to a runtime as an `Arc` which the test keeps a clone of.
+#[derive(Default)]
+pub struct RecordingLogger {
+    entries: Mutex<Vec<LogEntry>>,
+}
+
+impl RecordingLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entries logged so far, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        lock(&self.entries).clone()
+    }
+
+    /// Takes the entries logged so far, so later assertions only see what comes next.
+    pub fn drain(&self) -> Vec<LogEntry> {
+        std::mem::take(&mut *lock(&self.entries))
+    }
+
+    pub fn len(&self) -> usize {
+        lock(&self.entries).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        lock(&self.entries).is_empty()
+    }
+}
+
+impl LogSink for RecordingLogger {
+    fn log(&self, entry: &LogEntry) -> io::Result<()> {
+        lock(&self.entries).push(entry.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{log::LogEntryKind, rt::Runtime, test_util::Point, types::List};
+
+    use super::RecordingLogger;
+
+    #[test]
+    fn inspect_entries() {
+        let recording = Arc::new(RecordingLogger::new());
+        let mut list = List::new(Runtime::with_logger(recording.clone()));
+
+        list.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        list[0].set_y(-4);
+        assert_eq!(recording.len(), 2);
+
+        let entries = recording.drain();
+        assert!(recording.is_empty());
+
+        assert_eq!(entries[0].kind(), LogEntryKind::ListPush);
+        assert_eq!(entries[0].path(), Some(&[][..]));
+        assert_eq!(
+            entries[0].decode_payload::<Point>().unwrap(),
+            Point::new(Runtime::new(), 1, 2, 3)
+        );
+
+        assert_eq!(entries[1].kind(), LogEntryKind::Update);
+        assert_eq!(entries[1].path(), Some(&[0, 1][..]));
+        assert_eq!(entries[1].payload(), Some(&[7][..]));
+        assert_eq!(entries[1].decode_payload::<i32>().unwrap(), -4);
+
+        list.pop();
+        assert!(recording.entries()[0].decode_payload::<i32>().is_err());
+        assert_eq!(recording.len(), 1);
+    }
+}
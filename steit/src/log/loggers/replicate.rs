@@ -0,0 +1,93 @@
+use std::io;
+
+use crate::{
+    log::{LogEntry, Logger, StringTable},
+    ser::Serialize,
+    steit_derive,
+    types::Bytes,
+};
+
+/// The whole state, sent to clients joining, which then apply the deltas following it.
+#[steit_derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[steit(steit_owned, no_size_cache)]
+pub struct Snapshot {
+    /// Sequence of the last entry the state includes, 0 if none was logged before.
+    #[steit(tag = 0)]
+    pub seq: u64,
+    #[steit(tag = 1)]
+    pub state: Bytes,
+    /// Strings interned so far, by index, which deltas after the snapshot refer to.
+    #[steit(tag = 2)]
+    pub strings: Vec<String>,
+}
+
+/// A log entry, tagged with its sequence, which starts from 1 and has no gaps.
+#[steit_derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[steit(steit_owned, no_size_cache)]
+pub struct Delta {
+    #[steit(tag = 0)]
+    pub seq: u64,
+    #[steit(tag = 1)]
+    pub entry: Bytes,
+}
+
+/// Numbers entries as they are logged and keeps them as `Delta`s until drained,
+/// so clients can be sent a `Snapshot` at any time and catch up with the deltas after it.
+///
+/// Deltas already included in a snapshot may still be sent after it, as `Replica` skips them.
+#[derive(Default)]
+pub struct ReplicateLogger {
+    deltas: Vec<Delta>,
+    last_seq: u64,
+    strings: StringTable,
+}
+
+impl ReplicateLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence of the last entry logged, 0 if none was.
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// Strings interned by the entries logged so far.
+    pub fn strings(&self) -> &StringTable {
+        &self.strings
+    }
+
+    /// Snapshot of `root`, which must be the state this logger is attached to.
+    pub fn snapshot(&self, root: &impl Serialize) -> Snapshot {
+        Snapshot {
+            seq: self.last_seq,
+            state: Bytes::from_value(root),
+            strings: self.strings.values().to_vec(),
+        }
+    }
+
+    /// Deltas logged since the last call, oldest first.
+    pub fn drain(&mut self) -> Vec<Delta> {
+        std::mem::take(&mut self.deltas)
+    }
+}
+
+impl Logger for ReplicateLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        entry.compute_size_nested(None, false)?;
+        entry.serialize_nested(None, false, &mut bytes)?;
+
+        if let LogEntry::Intern { index, value, .. } = entry {
+            self.strings.insert(index, value)?;
+        }
+
+        self.last_seq += 1;
+        self.deltas.push(Delta {
+            seq: self.last_seq,
+            entry: Bytes::from_raw(bytes),
+        });
+
+        Ok(())
+    }
+}
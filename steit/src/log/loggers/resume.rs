@@ -0,0 +1,140 @@
+use std::{collections::VecDeque, io, sync::Arc};
+
+use crate::log::{LogEntry, Logger};
+
+use super::BufferLogger;
+
+/// Keeps the last few frames flushed, so clients reconnecting after a brief disconnect
+/// catch up from the last frame they acknowledged instead of receiving a full snapshot.
+///
+/// Frames are numbered from 1, and a snapshot taken right after frame `n` is flushed
+/// stands for sequence `n`, so a client acknowledges it as such.
+pub struct ResumeLogger {
+    buffer: BufferLogger,
+    // Number of frames kept, i.e. how far behind a client can be to resume.
+    grace: usize,
+    frames: VecDeque<Arc<[u8]>>,
+    last_seq: u64,
+}
+
+impl ResumeLogger {
+    pub fn new(grace: usize) -> Self {
+        Self {
+            buffer: BufferLogger::new(),
+            grace,
+            frames: VecDeque::with_capacity(grace),
+            last_seq: 0,
+        }
+    }
+
+    /// Sequence of the last frame flushed, 0 if none was.
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// Sequence of the oldest frame still kept.
+    pub fn first_seq(&self) -> u64 {
+        self.last_seq + 1 - self.frames.len() as u64
+    }
+
+    /// Turns entries logged since the last flush into a frame and returns it with its sequence.
+    /// The oldest frame is dropped once more than `grace` are kept.
+    pub fn flush(&mut self) -> (u64, Arc<[u8]>) {
+        let frame: Arc<[u8]> = self.buffer.pluck_bytes().into();
+        self.last_seq += 1;
+
+        if self.grace > 0 {
+            if self.frames.len() == self.grace {
+                self.frames.pop_front();
+            }
+
+            self.frames.push_back(frame.clone());
+        }
+
+        (self.last_seq, frame)
+    }
+
+    /// Returns frames after `acked`, oldest first, for a client resuming from it.
+    /// `None` means some of them have been dropped, so the client needs a full snapshot.
+    pub fn resume(&self, acked: u64) -> Option<Vec<Arc<[u8]>>> {
+        if acked > self.last_seq || acked + 1 < self.first_seq() {
+            return None;
+        }
+
+        let start = (acked + 1 - self.first_seq()) as usize;
+        Some(self.frames.range(start..).cloned().collect())
+    }
+}
+
+impl Logger for ResumeLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.buffer.log(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        meta::{read_snapshot, write_snapshot, SnapshotLayout},
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    use super::ResumeLogger;
+
+    #[test]
+    fn resume_from_acked() {
+        let (runtime, logger) = Runtime::with_logger_returned(ResumeLogger::new(2));
+        let mut point = Point::empty(runtime);
+
+        point.set_x(1);
+        let (seq, frame) = logger.lock().unwrap().flush();
+        assert_eq!(seq, 1);
+
+        let mut client = Point::empty(Runtime::new());
+        replay(&mut client, &frame);
+
+        point.set_y(2);
+        logger.lock().unwrap().flush();
+        point.set_z(3);
+        logger.lock().unwrap().flush();
+
+        let frames = logger.lock().unwrap().resume(1).unwrap();
+        assert_eq!(frames.len(), 2);
+
+        for frame in &frames {
+            replay(&mut client, frame);
+        }
+
+        assert_eq!(client, Point::new(Runtime::new(), 1, 2, 3));
+        assert_eq!(logger.lock().unwrap().resume(3).unwrap().len(), 0);
+        assert!(logger.lock().unwrap().resume(4).is_none());
+    }
+
+    #[test]
+    fn snapshot_past_grace() {
+        let (runtime, logger) = Runtime::with_logger_returned(ResumeLogger::new(1));
+        let mut point = Point::empty(runtime);
+
+        for x in 1..=3 {
+            point.set_x(x);
+            logger.lock().unwrap().flush();
+        }
+
+        assert_eq!(logger.lock().unwrap().first_seq(), 3);
+        assert!(logger.lock().unwrap().resume(1).is_none());
+
+        let snapshot = write_snapshot(&point, SnapshotLayout::Tagged).unwrap();
+        let acked = logger.lock().unwrap().last_seq();
+        let mut client: Point = read_snapshot(&snapshot).unwrap();
+
+        point.set_y(4);
+        logger.lock().unwrap().flush();
+
+        for frame in logger.lock().unwrap().resume(acked).unwrap() {
+            replay(&mut client, &frame);
+        }
+
+        assert_eq!(client, Point::new(Runtime::new(), 3, 4, 0));
+    }
+}
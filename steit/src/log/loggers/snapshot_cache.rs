@@ -0,0 +1,107 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    log::{LogEntry, Logger},
+    meta::{write_snapshot, HasMeta, SnapshotLayout},
+    ser::Serialize,
+};
+
+/// Keeps the last snapshot of its root state until the next entry is logged,
+/// so clients joining around the same time share one serialization of the world.
+///
+/// Changes made while the logger is paused aren't seen, so call `invalidate` after them.
+pub struct SnapshotCacheLogger<L: Logger> {
+    inner: L,
+    snapshot: Option<(SnapshotLayout, Arc<[u8]>)>,
+}
+
+impl<L: Logger> SnapshotCacheLogger<L> {
+    pub fn new(inner: L) -> Self {
+        Self {
+            inner,
+            snapshot: None,
+        }
+    }
+
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    pub fn is_cached(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    pub fn invalidate(&mut self) {
+        self.snapshot = None;
+    }
+
+    /// Returns the cached snapshot of `root`, or writes a new one if it has changed since.
+    /// `root` must be the state this logger is attached to.
+    pub fn snapshot<T: Serialize + HasMeta>(
+        &mut self,
+        root: &T,
+        layout: SnapshotLayout,
+    ) -> io::Result<Arc<[u8]>> {
+        match &self.snapshot {
+            Some((cached_layout, bytes)) if *cached_layout == layout => Ok(bytes.clone()),
+            _ => {
+                let bytes: Arc<[u8]> = write_snapshot(root, layout)?.into();
+                self.snapshot = Some((layout, bytes.clone()));
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+impl<L: Logger> Logger for SnapshotCacheLogger<L> {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.snapshot = None;
+        self.inner.log(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        log::loggers::NoopLogger,
+        meta::{read_snapshot, SnapshotLayout},
+        rt::{LoggerHandle, Runtime},
+        test_util::Point,
+    };
+
+    use super::SnapshotCacheLogger;
+
+    type Handle = LoggerHandle<SnapshotCacheLogger<NoopLogger>>;
+
+    fn snapshot(logger: &Handle, point: &Point, layout: SnapshotLayout) -> Arc<[u8]> {
+        logger.lock().unwrap().snapshot(point, layout).unwrap()
+    }
+
+    #[test]
+    fn invalidated_by_entries() {
+        let (runtime, logger) =
+            Runtime::with_logger_returned(SnapshotCacheLogger::new(NoopLogger::new()));
+        let mut point = Point::empty(runtime);
+        point.set_x(1);
+
+        let first = snapshot(&logger, &point, SnapshotLayout::Tagged);
+        let second = snapshot(&logger, &point, SnapshotLayout::Tagged);
+        assert!(Arc::ptr_eq(&first, &second));
+
+        point.set_y(2);
+        assert!(!logger.lock().unwrap().is_cached());
+
+        let third = snapshot(&logger, &point, SnapshotLayout::Tagged);
+        let decoded: Point = read_snapshot(&third).unwrap();
+        assert_eq!(decoded, Point::new(Runtime::new(), 1, 2, 0));
+
+        let compact = snapshot(&logger, &point, SnapshotLayout::Compact);
+        assert!(!Arc::ptr_eq(&third, &compact));
+    }
+}
@@ -0,0 +1,84 @@
+use std::io;
+
+use crate::{
+    log::{LogEntry, Logger},
+    ser::Serialize,
+    steit_derive,
+    types::Bytes,
+};
+
+/// Part of an encoded log entry, of which a `Reassembler` puts entries back together.
+#[steit_derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[steit(steit_owned, no_size_cache)]
+pub struct Fragment {
+    /// Number of the entry this is part of, which starts from 1 and has no gaps.
+    #[steit(tag = 0)]
+    pub entry: u64,
+    /// Number of fragments of the same entry following this one.
+    #[steit(tag = 1)]
+    pub remaining: u32,
+    #[steit(tag = 2)]
+    pub bytes: Bytes,
+    /// Index of this fragment in its entry, 0 for the first.
+    #[steit(tag = 3)]
+    pub index: u32,
+}
+
+/// Encodes entries as they are logged and splits those larger than `max_size` bytes
+/// into several `Fragment`s, so transports capping the size of their frames
+/// can still carry large updates, like whole subtrees being replaced.
+///
+/// Each fragment carries up to `max_size` bytes of its entry, plus a header of a few bytes.
+pub struct SplitLogger {
+    max_size: usize,
+    fragments: Vec<Fragment>,
+    last_entry: u64,
+}
+
+impl SplitLogger {
+    /// Panics if `max_size` is 0.
+    pub fn new(max_size: usize) -> Self {
+        assert!(
+            max_size > 0,
+            "fragments must be able to carry at least a byte"
+        );
+
+        Self {
+            max_size,
+            fragments: Vec::new(),
+            last_entry: 0,
+        }
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Fragments logged since the last call, oldest first.
+    pub fn drain(&mut self) -> Vec<Fragment> {
+        std::mem::take(&mut self.fragments)
+    }
+}
+
+impl Logger for SplitLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        entry.compute_size_nested(None, false)?;
+        entry.serialize_nested(None, false, &mut bytes)?;
+
+        self.last_entry += 1;
+        let chunks = bytes.chunks(self.max_size);
+        let count = chunks.len() as u32;
+
+        for (index, chunk) in chunks.enumerate() {
+            self.fragments.push(Fragment {
+                entry: self.last_entry,
+                remaining: count - 1 - index as u32,
+                bytes: Bytes::from_raw(chunk.to_vec()),
+                index: index as u32,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+use std::{io, marker::PhantomData, ops::Range};
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{serialize_items, LogEntry, LogEntryKind, Logger},
+    rt::SizeCache,
+    state::State,
+    types::List,
+};
+
+/// Replicates only a window of the `List<T>` at `path`, for lists too large to be sent whole,
+/// like leaderboards or market listings. Entries elsewhere are passed through as usual.
+///
+/// Clients see a list holding just the items in the window, so indices under `path` are
+/// shifted by the start of the window. Moving the window with `set_window` resyncs the client.
+///
+/// Replacing an ancestor of the list sends it whole, so call `set_window` again after that.
+/// Moving items into or out of the window can't be replicated either, see `is_stale`.
+pub struct WindowLogger<L: Logger, T: State> {
+    inner: L,
+    path: Vec<u32>,
+    window: Range<u32>,
+    len: u32,
+    stale: bool,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<L: Logger, T: State> WindowLogger<L, T> {
+    /// Starts with an empty window until `set_window` is called.
+    pub fn new(inner: L, path: Vec<u32>) -> Self {
+        Self {
+            inner,
+            path,
+            window: 0..0,
+            len: 0,
+            stale: false,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.inner
+    }
+
+    pub fn window(&self) -> Range<u32> {
+        self.window.clone()
+    }
+
+    /// Whether items were moved into or out of the window since it was last set,
+    /// so the client is out of sync until `set_window` is called again.
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Moves the window and replaces the client list with the items in it.
+    /// `list` must be the current state of the list at `path`.
+    pub fn set_window(&mut self, window: Range<u32>, list: &List<T>) -> io::Result<()> {
+        self.window = window;
+        self.len = list.len() as u32;
+        self.stale = false;
+
+        let entry = LogEntry::Update {
+            path: self.path.clone(),
+            value: serialize_items(slice(list, self.window.clone())),
+            size_cache: SizeCache::new(),
+        };
+
+        self.inner.log(entry)
+    }
+
+    /// Same as `set_window` with the window set to page `page` of `page_size` items.
+    pub fn set_page(&mut self, page: u32, page_size: u32, list: &List<T>) -> io::Result<()> {
+        self.set_window(page * page_size..(page + 1) * page_size, list)
+    }
+
+    fn log_list(&mut self, mut entry: LogEntry) -> io::Result<()> {
+        match entry.kind() {
+            LogEntryKind::ListPush => {
+                let index = self.len;
+                self.len += 1;
+
+                if !self.window.contains(&index) {
+                    return Ok(());
+                }
+            }
+
+            LogEntryKind::ListPop => {
+                self.len = self.len.saturating_sub(1);
+
+                if !self.window.contains(&self.len) {
+                    return Ok(());
+                }
+            }
+
+            LogEntryKind::ListMove => {
+                let window = self.window.clone();
+
+                if let LogEntry::ListMove { from, to, .. } = &mut entry {
+                    if (*from).max(*to) < window.start || (*from).min(*to) >= window.end {
+                        return Ok(());
+                    }
+
+                    if !window.contains(from) || !window.contains(to) {
+                        self.stale = true;
+                        return Ok(());
+                    }
+
+                    *from -= window.start;
+                    *to -= window.start;
+                }
+            }
+
+            LogEntryKind::Update | LogEntryKind::Replace | LogEntryKind::ListExtend => {
+                let (path, bytes, from) = match entry {
+                    LogEntry::Update { path, value, .. }
+                    | LogEntry::Replace { path, value, .. } => (path, value, 0),
+                    LogEntry::ListExtend { path, items, .. } => (path, items, self.len),
+                    _ => unreachable!(),
+                };
+
+                let items = List::<T>::deserialize(&mut Reader::new(&*bytes.into_raw()))?;
+                self.len = from + items.len() as u32;
+
+                // Extended items start at index `from` of the list.
+                let window =
+                    self.window.start.saturating_sub(from)..self.window.end.saturating_sub(from);
+                let items = slice(&items, window);
+
+                entry = if from == 0 {
+                    LogEntry::Update {
+                        path,
+                        value: serialize_items(items),
+                        size_cache: SizeCache::new(),
+                    }
+                } else if items.is_empty() {
+                    return Ok(());
+                } else {
+                    LogEntry::ListExtend {
+                        path,
+                        items: serialize_items(items),
+                        size_cache: SizeCache::new(),
+                    }
+                };
+            }
+
+            _ => (),
+        }
+
+        self.inner.log(entry)
+    }
+}
+
+impl<L: Logger, T: State> Logger for WindowLogger<L, T> {
+    fn log(&mut self, mut entry: LogEntry) -> io::Result<()> {
+        let depth = self.path.len();
+
+        match entry.path() {
+            Some(path) if path.starts_with(&self.path) => {
+                if path.len() == depth {
+                    return self.log_list(entry);
+                }
+            }
+
+            _ => return self.inner.log(entry),
+        }
+
+        let start = self.window.start;
+
+        match entry.path_mut().and_then(|path| path.get_mut(depth)) {
+            Some(index) if self.window.contains(index) => *index -= start,
+            _ => return Ok(()),
+        }
+
+        self.inner.log(entry)
+    }
+}
+
+fn slice<T>(items: &[T], window: Range<u32>) -> &[T] {
+    let len = items.len() as u32;
+    let start = window.start.min(len);
+    let end = window.end.clamp(start, len);
+    items.get(start as usize..end as usize).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::BufferLogger,
+        rt::{LoggerHandle, Runtime},
+        steit_derive,
+        test_util::{replay, Point},
+        types::List,
+    };
+
+    use super::WindowLogger;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Board {
+        #[steit(tag = 0)]
+        season: u32,
+        #[steit(tag = 1)]
+        scores: List<Point>,
+    }
+
+    type Handle = LoggerHandle<WindowLogger<BufferLogger, Point>>;
+
+    fn board() -> (Board, Handle) {
+        let logger = WindowLogger::new(BufferLogger::new(), vec![1]);
+        let (runtime, logger) = Runtime::with_logger_returned(logger);
+        (Board::new(runtime), logger)
+    }
+
+    fn push_points(board: &mut Board, xs: impl IntoIterator<Item = i32>) {
+        for x in xs {
+            board
+                .scores
+                .push_with(|runtime| Point::new(runtime, x, 0, 0));
+        }
+    }
+
+    fn sync(client: &mut Board, logger: &Handle) {
+        let bytes = logger.lock().unwrap().inner_mut().pluck_bytes();
+        replay(client, &bytes);
+    }
+
+    fn xs(board: &Board) -> Vec<i32> {
+        board.scores.iter().map(|point| point.x).collect()
+    }
+
+    #[test]
+    fn window_only() {
+        let (mut board, logger) = board();
+        let mut client = Board::new(Runtime::new());
+
+        board.set_season(3);
+        push_points(&mut board, 0..5);
+        sync(&mut client, &logger);
+        assert_eq!(client.season, 3);
+        assert!(client.scores.is_empty());
+
+        logger
+            .lock()
+            .unwrap()
+            .set_window(2..4, &board.scores)
+            .unwrap();
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[2, 3]);
+
+        board.scores[0].set_y(1);
+        board.scores[3].set_y(2);
+        sync(&mut client, &logger);
+        assert_eq!(client.scores[0].y, 0);
+        assert_eq!(client.scores[1].y, 2);
+    }
+
+    #[test]
+    fn push_and_pop_into_window() {
+        let (mut board, logger) = board();
+        let mut client = Board::new(Runtime::new());
+
+        push_points(&mut board, 0..2);
+        logger
+            .lock()
+            .unwrap()
+            .set_window(1..4, &board.scores)
+            .unwrap();
+        push_points(&mut board, 2..6);
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[1, 2, 3]);
+
+        board.scores.pop();
+        board.scores.pop();
+        board.scores.pop();
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[1, 2]);
+    }
+
+    #[test]
+    fn extend_and_replace() {
+        let (mut board, logger) = board();
+        let mut client = Board::new(Runtime::new());
+
+        logger
+            .lock()
+            .unwrap()
+            .set_page(1, 2, &board.scores)
+            .unwrap();
+        board
+            .scores
+            .extend((0..5).map(|x| Point::new(Runtime::new(), x, 0, 0)));
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[2, 3]);
+
+        let scores = List::from_iter(
+            Runtime::new(),
+            (10..13).map(|x| Point::new(Runtime::new(), x, 0, 0)),
+        );
+
+        board.set_scores(scores);
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[12]);
+    }
+
+    #[test]
+    fn move_window() {
+        let (mut board, logger) = board();
+        let mut client = Board::new(Runtime::new());
+
+        push_points(&mut board, 0..10);
+        logger
+            .lock()
+            .unwrap()
+            .set_page(0, 3, &board.scores)
+            .unwrap();
+        logger
+            .lock()
+            .unwrap()
+            .set_page(2, 3, &board.scores)
+            .unwrap();
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[6, 7, 8]);
+        assert_eq!(logger.lock().unwrap().window(), 6..9);
+    }
+
+    #[test]
+    fn move_items() {
+        let (mut board, logger) = board();
+        let mut client = Board::new(Runtime::new());
+
+        push_points(&mut board, 0..6);
+        logger
+            .lock()
+            .unwrap()
+            .set_window(2..5, &board.scores)
+            .unwrap();
+        board.scores.move_item(4, 2);
+        board.scores.move_item(0, 1);
+        sync(&mut client, &logger);
+        assert_eq!(xs(&client), &[4, 2, 3]);
+        assert!(!logger.lock().unwrap().is_stale());
+
+        board.scores.move_item(5, 3);
+        assert!(logger.lock().unwrap().is_stale());
+    }
+}
@@ -1,7 +1,21 @@
 pub mod loggers;
 
+mod checksum;
+mod clock;
 mod entry;
 mod logger;
+mod playback;
+mod reassembler;
+mod replica;
+mod scheduler;
+mod strings;
 
+pub use checksum::*;
+pub use clock::*;
 pub use entry::*;
 pub use logger::*;
+pub use playback::*;
+pub use reassembler::*;
+pub use replica::*;
+pub use scheduler::*;
+pub use strings::*;
@@ -0,0 +1,248 @@
+use std::{io, time::Duration};
+
+use crate::{de::Reader, rt::Runtime, state::State};
+
+use super::{loggers::Frame, StringTable};
+
+type FrameCallback<T> = Box<dyn FnMut(&T, &Frame) + Send>;
+type DesyncCallback<T> = Box<dyn FnMut(&T, &[u32]) + Send>;
+
+/// Plays frames recorded by a `Recorder` back onto a state, at the pace they were recorded
+/// or faster or slower, for replays and killcams.
+///
+/// Times are relative to the first frame, which is applied by the first `advance`.
+pub struct Playback<T: State> {
+    initial: Vec<u8>,
+    frames: Vec<Frame>,
+    state: T,
+    strings: StringTable,
+    next: usize,
+    position: Duration,
+    speed: f64,
+    paused: bool,
+    on_frame: Option<FrameCallback<T>>,
+    on_desync: Option<DesyncCallback<T>>,
+}
+
+impl<T: State> Playback<T> {
+    /// Starts from `initial`, the state when recording started, with frames oldest first.
+    pub fn new(initial: &T, frames: Vec<Frame>) -> io::Result<Self> {
+        let initial = initial.to_bytes();
+
+        Ok(Self {
+            state: decode(&initial)?,
+            initial,
+            frames,
+            strings: StringTable::new(),
+            next: 0,
+            position: Duration::ZERO,
+            speed: 1.0,
+            paused: false,
+            on_frame: None,
+            on_desync: None,
+        })
+    }
+
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+
+    /// Calls `on_frame` with the state after each frame applied by `advance`, but not by `seek`.
+    pub fn on_frame(&mut self, on_frame: impl FnMut(&T, &Frame) + Send + 'static) -> &mut Self {
+        self.on_frame = Some(Box::new(on_frame));
+        self
+    }
+
+    /// Calls `on_desync` when the state after a frame doesn't match the checksum it carries,
+    /// with the path of the first mismatching subtree, as found by `Checksum::mismatch`.
+    /// Like `on_frame`, it's only called by `advance`.
+    pub fn on_desync(&mut self, on_desync: impl FnMut(&T, &[u32]) + Send + 'static) -> &mut Self {
+        self.on_desync = Some(Box::new(on_desync));
+        self
+    }
+
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Time of the last frame.
+    pub fn duration(&self) -> Duration {
+        self.frames
+            .last()
+            .map_or(Duration::ZERO, |frame| self.time(frame))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next == self.frames.len()
+    }
+
+    pub fn play(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Sets how many times faster than recorded frames are played. Negative speeds count as 0.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Moves playback forward by `elapsed` real time, scaled by the speed,
+    /// applying frames reached on the way. Returns how many were applied.
+    pub fn advance(&mut self, elapsed: Duration) -> io::Result<usize> {
+        if self.paused {
+            return Ok(0);
+        }
+
+        self.position += elapsed.mul_f64(self.speed);
+        self.apply_until(self.position, true)
+    }
+
+    /// Jumps to `position`, replaying from the initial state when going backwards.
+    pub fn seek(&mut self, position: Duration) -> io::Result<()> {
+        if position < self.position {
+            self.state = decode(&self.initial)?;
+            self.strings = StringTable::new();
+            self.next = 0;
+        }
+
+        self.position = position;
+        self.apply_until(position, false)?;
+        Ok(())
+    }
+
+    fn time(&self, frame: &Frame) -> Duration {
+        let start = self.frames.first().map_or(0, |first| first.timestamp);
+        Duration::from_micros(frame.timestamp.saturating_sub(start))
+    }
+
+    fn apply_until(&mut self, position: Duration, notify: bool) -> io::Result<usize> {
+        let from = self.next;
+
+        while let Some(frame) = self.frames.get(self.next) {
+            if self.time(frame) > position {
+                break;
+            }
+
+            let reader = &mut Reader::new(frame.entries.as_raw());
+            self.state.replay_with_strings(reader, &mut self.strings)?;
+            self.next += 1;
+
+            if !notify {
+                continue;
+            }
+
+            if let Some(on_frame) = &mut self.on_frame {
+                on_frame(&self.state, frame);
+            }
+
+            if let (Some(on_desync), Some(checksum)) = (&mut self.on_desync, &frame.checksum) {
+                if let Some(path) = checksum.mismatch(&self.state) {
+                    on_desync(&self.state, &path);
+                }
+            }
+        }
+
+        Ok(self.next - from)
+    }
+}
+
+fn decode<T: State>(bytes: &[u8]) -> io::Result<T> {
+    T::from_bytes(Runtime::new(), bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use crate::{
+        log::{loggers::Recorder, ManualClock},
+        rt::Runtime,
+        test_util::Point,
+    };
+
+    use super::Playback;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn play_pause_seek() {
+        let clock = ManualClock::new();
+        let (runtime, recorder) =
+            Runtime::with_logger_returned(Recorder::with_clock(clock.clone()));
+        let mut point = Point::new(runtime, 0, 7, 0);
+        let initial = Point::new(Runtime::new(), 0, 7, 0);
+
+        for x in 1..=3 {
+            point.set_x(x);
+            recorder.lock().unwrap().flush();
+            clock.advance(ms(100));
+        }
+
+        let frames = recorder.lock().unwrap().take_frames();
+        let mut playback = Playback::new(&initial, frames).unwrap();
+        assert_eq!(playback.duration(), ms(200));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+        playback.on_frame(move |point, _| seen_clone.lock().unwrap().push(point.x));
+
+        assert_eq!(playback.advance(ms(0)).unwrap(), 1);
+        playback.set_speed(2.0);
+        assert_eq!(playback.advance(ms(50)).unwrap(), 1);
+        assert_eq!(playback.state().x, 2);
+
+        playback.pause();
+        assert_eq!(playback.advance(ms(1000)).unwrap(), 0);
+
+        playback.seek(ms(0)).unwrap();
+        assert_eq!((playback.state().x, playback.state().y), (1, 7));
+
+        playback.seek(ms(250)).unwrap();
+        assert!(playback.is_finished());
+        assert_eq!(playback.state().x, 3);
+        assert_eq!(*seen.lock().unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn desync() {
+        let (runtime, recorder) = Runtime::with_logger_returned(Recorder::new().checksum_every(2));
+        let mut point = Point::empty(runtime);
+
+        for x in 1..=4 {
+            point.set_x(x);
+            recorder.lock().unwrap().flush_checked(&point);
+        }
+
+        let frames = recorder.lock().unwrap().take_frames();
+        let checked: Vec<_> = frames
+            .iter()
+            .map(|frame| frame.checksum.is_some())
+            .collect();
+        assert_eq!(checked, [false, true, false, true]);
+
+        // The client started from a state the server never had.
+        let mut playback = Playback::new(&Point::new(Runtime::new(), 0, 0, 5), frames).unwrap();
+        let desyncs = Arc::new(Mutex::new(Vec::new()));
+        let desyncs_clone = desyncs.clone();
+        playback.on_desync(move |_, path| desyncs_clone.lock().unwrap().push(path.to_vec()));
+
+        playback.advance(Duration::from_secs(1)).unwrap();
+        assert_eq!(*desyncs.lock().unwrap(), [vec![2], vec![2]]);
+    }
+}
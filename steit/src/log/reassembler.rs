@@ -0,0 +1,178 @@
+use std::io;
+
+use crate::{
+    de::{Deserialize, Reader},
+    wire_fmt::HasWireType,
+};
+
+use super::{loggers::Fragment, LogEntry};
+
+/// Puts entries split by a `SplitLogger` back together, for `State::replay_entry`.
+///
+/// Fragments must arrive in order. One missing fails the entry it's part of,
+/// and reassembly starts over from the next fragment beginning an entry.
+#[derive(Default)]
+pub struct Reassembler {
+    entry: u64,
+    next_index: u32,
+    bytes: Vec<u8>,
+    ready: Option<LogEntry>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether fragments of an entry have been received but not all of them yet.
+    pub fn is_pending(&self) -> bool {
+        !self.bytes.is_empty()
+    }
+
+    /// Adds `fragment`, returning its entry if it was the last fragment of it.
+    ///
+    /// Fails if fragments are missing before `fragment`. If it begins an entry, reassembly
+    /// restarts from it, and if it's the whole entry, the entry is left to `take_ready`.
+    pub fn push(&mut self, fragment: &Fragment) -> io::Result<Option<LogEntry>> {
+        let expected = if self.is_pending() {
+            fragment.entry == self.entry && fragment.index == self.next_index
+        } else {
+            fragment.index == 0
+        };
+
+        if !expected {
+            let entry = if self.is_pending() {
+                self.entry
+            } else {
+                fragment.entry
+            };
+
+            self.bytes.clear();
+
+            if fragment.index == 0 {
+                self.ready = self.append(fragment)?;
+            }
+
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("missing fragments of entry {}", entry),
+            ));
+        }
+
+        self.append(fragment)
+    }
+
+    /// Takes the entry made of a single fragment which `push` failed on,
+    /// as fragments of the entry before it were missing.
+    pub fn take_ready(&mut self) -> Option<LogEntry> {
+        self.ready.take()
+    }
+
+    fn append(&mut self, fragment: &Fragment) -> io::Result<Option<LogEntry>> {
+        self.entry = fragment.entry;
+        self.bytes.extend_from_slice(fragment.bytes.as_raw());
+
+        if fragment.remaining > 0 {
+            self.next_index = fragment.index + 1;
+            return Ok(None);
+        }
+
+        let bytes = std::mem::take(&mut self.bytes);
+        LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, &mut Reader::new(&*bytes)).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::{loggers::SplitLogger, StringTable},
+        rt::Runtime,
+        state::State,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::Reassembler;
+
+    #[test]
+    fn split_and_reassemble() {
+        let (runtime, logger) = Runtime::with_logger_returned(SplitLogger::new(4));
+        let mut list = List::new(runtime);
+        list.push(Point::new(Runtime::new(), 1000, 2000, 3000));
+        list.pop();
+
+        let fragments = logger.lock().unwrap().drain();
+        assert!(fragments.len() > 3);
+        assert!(fragments.iter().all(|fragment| fragment.bytes.len() <= 4));
+
+        let mut mirror = List::<Point>::new(Runtime::new());
+        let mut strings = StringTable::new();
+        let mut reassembler = Reassembler::new();
+
+        for fragment in &fragments {
+            if let Some(entry) = reassembler.push(fragment).unwrap() {
+                mirror.replay_entry(entry, &mut strings).unwrap();
+
+                if mirror.len() == 1 {
+                    assert_eq!(mirror[0], Point::new(Runtime::new(), 1000, 2000, 3000));
+                }
+            }
+        }
+
+        assert!(mirror.is_empty());
+        assert!(!reassembler.is_pending());
+
+        // The second fragment of the push is lost.
+        assert!(reassembler.push(&fragments[0]).unwrap().is_none());
+        assert!(reassembler.push(&fragments[2]).is_err());
+        assert!(reassembler
+            .push(fragments.last().unwrap())
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn restart_after_lost_last_fragment() {
+        let (runtime, logger) = Runtime::with_logger_returned(SplitLogger::new(4));
+        let mut list = List::new(runtime);
+        list.push(Point::new(Runtime::new(), 1000, 2000, 3000));
+        let first = logger.lock().unwrap().drain();
+        list.push(Point::new(Runtime::new(), 4000, 5000, 6000));
+        let second = logger.lock().unwrap().drain();
+        list.pop();
+        let pop = logger.lock().unwrap().drain();
+        assert_eq!(pop.len(), 1);
+
+        let mut reassembler = Reassembler::new();
+
+        // The last fragment of the first push is lost.
+        for fragment in &first[..first.len() - 1] {
+            assert!(reassembler.push(fragment).unwrap().is_none());
+        }
+
+        assert!(reassembler.push(&second[0]).is_err());
+        assert!(reassembler.take_ready().is_none());
+
+        let mut mirror = List::<Point>::new(Runtime::new());
+        let mut strings = StringTable::new();
+
+        for fragment in &second[1..] {
+            if let Some(entry) = reassembler.push(fragment).unwrap() {
+                mirror.replay_entry(entry, &mut strings).unwrap();
+            }
+        }
+
+        assert_eq!(mirror.len(), 1);
+        assert_eq!(mirror[0], Point::new(Runtime::new(), 4000, 5000, 6000));
+
+        // Fragments not beginning an entry are rejected when nothing is pending,
+        // and an entry of a single fragment following missing ones is kept.
+        assert!(reassembler.push(&second[1]).is_err());
+        assert!(reassembler.push(&first[0]).unwrap().is_none());
+        assert!(reassembler.push(&pop[0]).is_err());
+        let entry = reassembler.take_ready().unwrap();
+        mirror.replay_entry(entry, &mut strings).unwrap();
+        assert!(mirror.is_empty());
+        assert!(!reassembler.is_pending());
+    }
+}
@@ -0,0 +1,126 @@
+use std::io;
+
+use crate::{de::Reader, rt::Runtime, state::State};
+
+use super::{
+    loggers::{Delta, Snapshot},
+    StringTable,
+};
+
+/// Mirror of a state kept in sync by a `ReplicateLogger`, starting from a `Snapshot`
+/// and applying the `Delta`s after it in order.
+pub struct Replica<T: State> {
+    state: T,
+    seq: u64,
+    strings: StringTable,
+}
+
+impl<T: State> Replica<T> {
+    pub fn new(snapshot: &Snapshot, runtime: Runtime) -> io::Result<Self> {
+        Ok(Self {
+            state: T::from_bytes(runtime, snapshot.state.as_raw())?,
+            seq: snapshot.seq,
+            strings: StringTable::from(snapshot.strings.clone()),
+        })
+    }
+
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+
+    pub fn into_state(self) -> T {
+        self.state
+    }
+
+    /// Sequence of the last entry applied, or included in the last snapshot.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Replaces the state with `snapshot`, as when deltas have been missed.
+    /// Snapshots older than the state are ignored.
+    pub fn apply_snapshot(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if snapshot.seq < self.seq {
+            return Ok(());
+        }
+
+        let runtime = self.state.runtime().clone();
+        *self = Self::new(snapshot, runtime)?;
+        Ok(())
+    }
+
+    /// Applies `delta` if it's the next one, returning whether it was.
+    /// Deltas included already are skipped, but a gap fails, as a snapshot is needed to fill it.
+    pub fn apply_delta(&mut self, delta: &Delta) -> io::Result<bool> {
+        if delta.seq <= self.seq {
+            return Ok(false);
+        }
+
+        if delta.seq != self.seq + 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "missing deltas {} to {}, resync with a snapshot",
+                    self.seq + 1,
+                    delta.seq - 1,
+                ),
+            ));
+        }
+
+        self.state
+            .replay_with_strings(&mut Reader::new(delta.entry.as_raw()), &mut self.strings)?;
+        self.seq = delta.seq;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{log::loggers::ReplicateLogger, rt::Runtime, steit_derive};
+
+    use super::Replica;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Player {
+        #[steit(tag = 0)]
+        x: i32,
+        #[steit(tag = 1, string_intern)]
+        team: String,
+    }
+
+    #[test]
+    fn snapshot_then_deltas() {
+        let (runtime, logger) = Runtime::with_logger_returned(ReplicateLogger::new());
+        let mut player = Player::new(runtime);
+
+        player.set_team("red".to_string()).set_x(1);
+        let snapshot = logger.lock().unwrap().snapshot(&player);
+        assert_eq!(snapshot.seq, 3);
+        assert_eq!(snapshot.strings, ["red"]);
+
+        let mut replica = Replica::<Player>::new(&snapshot, Runtime::new()).unwrap();
+        player.set_team("blue".to_string());
+        player.set_team("red".to_string());
+
+        // The deltas before the snapshot are skipped,
+        // and the last one refers to a string interned before it.
+        for delta in logger.lock().unwrap().drain() {
+            assert_eq!(replica.apply_delta(&delta).unwrap(), delta.seq > 3);
+        }
+
+        assert_eq!(replica.seq(), 6);
+        assert_eq!(replica.state(), &player);
+
+        player.set_x(4);
+        player.set_x(5);
+        let deltas = logger.lock().unwrap().drain();
+        assert!(replica.apply_delta(&deltas[1]).is_err());
+
+        let snapshot = logger.lock().unwrap().snapshot(&player);
+        assert_eq!(snapshot.strings, ["red", "blue"]);
+        replica.apply_snapshot(&snapshot).unwrap();
+        assert!(!replica.apply_delta(&deltas[1]).unwrap());
+        assert_eq!(replica.state(), &player);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Arc,
+};
+
+struct Connection {
+    // Bytes which can be sent per tick.
+    budget: i64,
+    // Bytes left to send this tick, negative after a frame larger than what was left.
+    allowance: i64,
+    queue: VecDeque<Arc<[u8]>>,
+    backlog: usize,
+}
+
+/// Paces frames sent to each connection to its bandwidth limit,
+/// so a slow client only delays its own frames, not those of others.
+///
+/// Frames are queued with `enqueue` and released by `tick`, which must be called
+/// `tick_rate` times per second. Idle ticks don't save up bandwidth for later bursts.
+pub struct FrameScheduler<K> {
+    tick_rate: u32,
+    connections: HashMap<K, Connection>,
+}
+
+impl<K: Clone + Eq + Hash> FrameScheduler<K> {
+    pub fn new(tick_rate: u32) -> Self {
+        Self {
+            tick_rate: tick_rate.max(1),
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Adds a connection limited to `kbps` kilobits per second, or changes its limit.
+    pub fn connect(&mut self, client: K, kbps: u32) -> &mut Self {
+        let budget = (kbps as i64 * 1000 / 8 / self.tick_rate as i64).max(1);
+        let connection = self.connections.entry(client).or_insert(Connection {
+            budget,
+            allowance: budget,
+            queue: VecDeque::new(),
+            backlog: 0,
+        });
+
+        connection.budget = budget;
+        self
+    }
+
+    /// Drops the connection with its queued frames.
+    pub fn disconnect(&mut self, client: &K) {
+        self.connections.remove(client);
+    }
+
+    /// Queues `frame` to be sent to `client`. Does nothing if it isn't connected.
+    pub fn enqueue(&mut self, client: &K, frame: Arc<[u8]>) {
+        if let Some(connection) = self.connections.get_mut(client) {
+            connection.backlog += frame.len();
+            connection.queue.push_back(frame);
+        }
+    }
+
+    /// Queues `frame` for every connection, sharing its bytes.
+    pub fn broadcast(&mut self, frame: Arc<[u8]>) {
+        for connection in self.connections.values_mut() {
+            connection.backlog += frame.len();
+            connection.queue.push_back(frame.clone());
+        }
+    }
+
+    /// Replaces frames queued for `client` with `frame`, usually a snapshot
+    /// for a client whose backlog has grown past what it could catch up on.
+    pub fn reset(&mut self, client: &K, frame: Arc<[u8]>) {
+        if let Some(connection) = self.connections.get_mut(client) {
+            connection.queue.clear();
+            connection.backlog = frame.len();
+            connection.queue.push_back(frame);
+        }
+    }
+
+    /// Bytes queued for `client` and not sent yet.
+    pub fn backlog(&self, client: &K) -> usize {
+        self.connections
+            .get(client)
+            .map_or(0, |connection| connection.backlog)
+    }
+
+    /// Returns frames to send this tick, by connection, oldest first.
+    ///
+    /// A frame is sent as long as some of the tick's budget is left,
+    /// with the overrun taken from the next ticks, so frames larger than the budget still go out.
+    pub fn tick(&mut self) -> HashMap<K, Vec<Arc<[u8]>>> {
+        let mut sent = HashMap::new();
+
+        for (client, connection) in &mut self.connections {
+            connection.allowance =
+                (connection.allowance + connection.budget).min(connection.budget);
+            let mut frames = Vec::new();
+
+            while connection.allowance > 0 {
+                let frame = match connection.queue.pop_front() {
+                    Some(frame) => frame,
+                    None => break,
+                };
+
+                connection.allowance -= frame.len() as i64;
+                connection.backlog -= frame.len();
+                frames.push(frame);
+            }
+
+            if !frames.is_empty() {
+                sent.insert(client.clone(), frames);
+            }
+        }
+
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::FrameScheduler;
+
+    fn frame(len: usize) -> Arc<[u8]> {
+        vec![0; len].into()
+    }
+
+    #[test]
+    fn paced_per_connection() {
+        // 8 kbps at 10 ticks per second is 100 bytes per tick.
+        let mut scheduler = FrameScheduler::new(10);
+        scheduler.connect("slow", 8).connect("fast", 800);
+
+        for _ in 0..4 {
+            scheduler.broadcast(frame(60));
+        }
+
+        assert_eq!(scheduler.backlog(&"slow"), 240);
+
+        let sent = scheduler.tick();
+        assert_eq!(sent["slow"].len(), 2);
+        assert_eq!(sent["fast"].len(), 4);
+        assert_eq!(scheduler.backlog(&"slow"), 120);
+
+        // 20 bytes were overrun, leaving 80 for this tick.
+        let sent = scheduler.tick();
+        assert_eq!(sent["slow"].len(), 2);
+        assert!(!sent.contains_key("fast"));
+        assert_eq!(scheduler.backlog(&"slow"), 0);
+
+        scheduler.enqueue(&"slow", frame(60));
+        assert_eq!(scheduler.tick()["slow"].len(), 1);
+    }
+
+    #[test]
+    fn no_bursts_after_idle() {
+        let mut scheduler = FrameScheduler::new(10);
+        scheduler.connect(1, 8);
+
+        for _ in 0..5 {
+            scheduler.tick();
+        }
+
+        for _ in 0..3 {
+            scheduler.enqueue(&1, frame(50));
+        }
+
+        assert_eq!(scheduler.tick()[&1].len(), 2);
+    }
+
+    #[test]
+    fn reset_backlog() {
+        let mut scheduler = FrameScheduler::new(10);
+        scheduler.connect(1, 8);
+
+        for _ in 0..10 {
+            scheduler.enqueue(&1, frame(100));
+        }
+
+        scheduler.reset(&1, frame(30));
+        assert_eq!(scheduler.backlog(&1), 30);
+        assert_eq!(&*scheduler.tick()[&1], &[frame(30)]);
+
+        scheduler.disconnect(&1);
+        scheduler.enqueue(&1, frame(1));
+        assert!(scheduler.tick().is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+use std::{collections::HashMap, io};
+
+/// Per-stream table of interned strings.
+///
+/// Loggers intern repeated strings so that log entries can reference them by index.
+/// Replayers mirror the table by feeding it the `LogEntry::Intern` entries they receive.
+#[derive(Clone, Default, Debug)]
+pub struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u32>,
+}
+
+impl StringTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn get(&self, index: u32) -> Option<&str> {
+        self.strings.get(index as usize).map(String::as_str)
+    }
+
+    pub fn index_of(&self, value: &str) -> Option<u32> {
+        self.indices.get(value).copied()
+    }
+
+    /// Returns the index of `value` and whether it has just been added to the table.
+    pub fn intern(&mut self, value: &str) -> (u32, bool) {
+        if let Some(index) = self.index_of(value) {
+            return (index, false);
+        }
+
+        let index = self.strings.len() as u32;
+        self.strings.push(value.to_string());
+        self.indices.insert(value.to_string(), index);
+        (index, true)
+    }
+
+    /// Adds `value` at `index`, which must be the next free index of the table.
+    pub fn insert(&mut self, index: u32, value: String) -> io::Result<()> {
+        if index as usize != self.strings.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected interned string index {}, got {}",
+                    self.strings.len(),
+                    index,
+                ),
+            ));
+        }
+
+        self.indices.insert(value.clone(), index);
+        self.strings.push(value);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.strings.clear();
+        self.indices.clear();
+    }
+
+    /// Interned strings, by index.
+    pub fn values(&self) -> &[String] {
+        &self.strings
+    }
+}
+
+impl From<Vec<String>> for StringTable {
+    fn from(strings: Vec<String>) -> Self {
+        let indices = strings
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (value.clone(), index as u32))
+            .collect();
+
+        Self { strings, indices }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StringTable;
+
+    #[test]
+    fn intern_once() {
+        let mut strings = StringTable::new();
+        assert_eq!(strings.intern("sword"), (0, true));
+        assert_eq!(strings.intern("shield"), (1, true));
+        assert_eq!(strings.intern("sword"), (0, false));
+        assert_eq!(strings.get(1), Some("shield"));
+        assert_eq!(strings.len(), 2);
+    }
+
+    #[test]
+    fn insert_in_order() {
+        let mut strings = StringTable::new();
+        strings.insert(0, "sword".to_string()).unwrap();
+        strings.insert(1, "shield".to_string()).unwrap();
+        assert_eq!(strings.get(0), Some("sword"));
+        assert_eq!(strings.index_of("shield"), Some(1));
+    }
+
+    #[test]
+    fn insert_out_of_order() {
+        let mut strings = StringTable::new();
+        assert!(strings.insert(1, "sword".to_string()).is_err());
+        assert!(strings.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+/// Matches on the variants of a steit enum by name, binding only the fields listed.
+///
+/// ```ignore
+/// let damage = match_variant!(&action, Action {
+///     Attack { power } => *power,
+///     Heal => 0,
+/// });
+/// ```
+///
+/// A last `_` arm covers the rest, which `#[non_exhaustive]` enums of other crates require.
+#[macro_export]
+macro_rules! match_variant {
+    ($value:expr, $enum:ident { $($variant:ident $({ $($fields:tt)* })? => $body:expr,)* _ => $fallback:expr $(,)? }) => {
+        match $value {
+            $($enum::$variant { $($($fields)*,)? .. } => $body,)*
+            _ => $fallback,
+        }
+    };
+
+    ($value:expr, $enum:ident { $($variant:ident $({ $($fields:tt)* })? => $body:expr),+ $(,)? }) => {
+        match $value {
+            $($enum::$variant { $($($fields)*,)? .. } => $body,)+
+        }
+    };
+}
+
+/// Declares a fieldless enum whose variants name the bits of `Flags`,
+/// along with the meta needed to generate a flags enum for clients.
+///
+/// ```ignore
+/// flags! {
+///     pub enum Status {
+///         Stunned = 0,
+///         Silenced = 1,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! flags {
+    ($(#[$attr:meta])* $vis:vis enum $name:ident { $($flag:ident = $bit:literal),+ $(,)? }) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        $vis enum $name {
+            $($flag = $bit,)+
+        }
+
+        const _: () = {
+            $(assert!($bit < 64, "expected flag bits to be less than 64");)+
+
+            impl $crate::types::Flag for $name {
+                fn bit(self) -> u32 {
+                    self as u32
+                }
+            }
+
+            impl $crate::meta::HasMeta for $name {
+                const NAME: &'static $crate::meta::NameMeta =
+                    &$crate::meta::NameMeta::new(stringify!($name));
+
+                const TYPE: &'static $crate::meta::TypeMeta =
+                    &$crate::meta::TypeMeta::Ref(Self::NAME, &[]);
+
+                const LINK: &'static $crate::meta::MetaLink = &$crate::meta::MetaLink {
+                    r#type: Self::TYPE,
+                    msg: Some($crate::meta::MessageMeta::Flags($crate::meta::FlagsMeta {
+                        name: Self::NAME,
+                        flags: &[$($crate::meta::FlagMeta {
+                            name: &$crate::meta::NameMeta::new(stringify!($flag)),
+                            bit: $bit,
+                        },)+],
+                        builtin: false,
+                    })),
+                    links: || &[],
+                };
+            }
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{log::LogEntry, rt::Node};
+
+    fn describe(entry: &LogEntry) -> String {
+        match_variant!(entry, LogEntry {
+            Update => "update".to_string(),
+            Replace => "replace".to_string(),
+            Intern { index, value } => format!("intern {} {}", index, value),
+            UpdateInterned { index } => format!("update interned {}", index),
+            ListPush => "list push".to_string(),
+            ListPop => "list pop".to_string(),
+            ListExtend => "list extend".to_string(),
+            ListMove { from, to } => format!("list move {} {}", from, to),
+            MapRemove { key } => format!("map remove {}", key),
+        })
+    }
+
+    #[test]
+    fn match_variant() {
+        assert_eq!(
+            describe(&LogEntry::new_intern(3, "sword")),
+            "intern 3 sword"
+        );
+        assert_eq!(
+            describe(&LogEntry::new_map_remove(&Node::Root, 7)),
+            "map remove 7"
+        );
+        assert_eq!(describe(&LogEntry::new_list_pop(&Node::Root)), "list pop");
+    }
+
+    #[test]
+    fn match_variant_fallback() {
+        let index = |entry: &LogEntry| {
+            match_variant!(entry, LogEntry {
+                Intern { index } => Some(*index),
+                UpdateInterned { index } => Some(*index),
+                _ => None,
+            })
+        };
+
+        assert_eq!(index(&LogEntry::new_intern(3, "sword")), Some(3));
+        assert_eq!(index(&LogEntry::new_list_pop(&Node::Root)), None);
+    }
+
+    #[test]
+    fn variant_info() {
+        let entry = LogEntry::new_map_remove(&Node::Root, 7);
+        assert_eq!(entry.variant_tag(), 12);
+        assert_eq!(entry.variant_name(), "MapRemove");
+        assert_eq!(LogEntry::VARIANT_TAGS, &[0, 1, 2, 3, 8, 9, 10, 11, 12]);
+
+        let entry = LogEntry::from_tag(12).unwrap();
+        assert_eq!(entry.variant_name(), "MapRemove");
+        assert!(LogEntry::from_tag(4).is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fs, io, marker::PhantomData, path::Path};
+
+use crate::{
+    de::Reader,
+    log::loggers::BufferLogger,
+    rt::{lock, Runtime},
+    state::State,
+};
+
+use super::{
+    export::export_schema,
+    log_table::{json_string, write_json},
+    meta::HasMeta,
+    schema::schema_hash,
+    value::decode_value,
+};
+
+struct Case {
+    name: String,
+    state: Vec<u8>,
+    log: Vec<u8>,
+    expected: Vec<u8>,
+}
+
+/// Test vectors of `T` for runtimes in other languages, to keep them wire-compatible with this one.
+///
+/// `write` lays them out in a directory as:
+///
+/// - `manifest.json`, with the root type, its `schema_hash` and the case names,
+/// - `schema.steit`, the schema exported by `export_schema`,
+/// - a directory per case, holding the initial state in `state.bin`, the entries logged
+///   while changing it in `log.bin` and the final state in `final.bin`, with the states
+///   decoded in `state.json` and `final.json`, fields keyed by their tags.
+///
+/// A runtime conforms if it decodes both states to the JSON given
+/// and ends up with `final.bin` after replaying `log.bin` on `state.bin`.
+pub struct Conformance<T> {
+    cases: Vec<Case>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Default for Conformance<T> {
+    fn default() -> Self {
+        Self {
+            cases: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: State + HasMeta> Conformance<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a case starting from `initial`, with the entries logged by `change`.
+    ///
+    /// Names are used as directory names, so only ASCII letters, digits, `-` and `_` are allowed.
+    pub fn case(
+        &mut self,
+        name: &str,
+        initial: &T,
+        change: impl FnOnce(&mut T),
+    ) -> io::Result<&mut Self> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if !valid || self.cases.iter().any(|case| case.name == name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid or duplicate case name {:?}", name),
+            ));
+        }
+
+        let state = initial.to_bytes();
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut value = T::from_bytes(runtime, &state)?;
+        change(&mut value);
+
+        let log = lock(&logger).bytes();
+        let expected = value.to_bytes();
+
+        // Replaying here keeps vectors from asserting what this runtime doesn't do itself.
+        let mut replayed = T::deserialize(&mut Reader::new(&*state))?;
+        replayed.replay(&mut Reader::new(&*log))?;
+
+        if replayed.to_bytes() != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("case {:?} doesn't replay to its final state", name),
+            ));
+        }
+
+        self.cases.push(Case {
+            name: name.to_string(),
+            state,
+            log,
+            expected,
+        });
+
+        Ok(self)
+    }
+
+    /// Writes the vectors into `out_dir`, creating it if needed.
+    pub fn write(&self, out_dir: impl AsRef<Path>) -> io::Result<()> {
+        let out_dir = out_dir.as_ref();
+        fs::create_dir_all(out_dir)?;
+
+        let names: Vec<_> = self
+            .cases
+            .iter()
+            .map(|case| json_string(&case.name))
+            .collect();
+        let manifest = format!(
+            "{{\"root\":{},\"schema_hash\":\"{:016x}\",\"cases\":[{}]}}\n",
+            json_string(T::NAME.rust),
+            schema_hash::<T>(),
+            names.join(","),
+        );
+
+        fs::write(out_dir.join("manifest.json"), manifest)?;
+        fs::write(out_dir.join("schema.steit"), export_schema::<T>(true))?;
+
+        for case in &self.cases {
+            let dir = out_dir.join(&case.name);
+            fs::create_dir_all(&dir)?;
+
+            fs::write(dir.join("state.bin"), &case.state)?;
+            fs::write(dir.join("state.json"), json::<T>(&case.state)?)?;
+            fs::write(dir.join("log.bin"), &case.log)?;
+            fs::write(dir.join("final.bin"), &case.expected)?;
+            fs::write(dir.join("final.json"), json::<T>(&case.expected)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn json<T: HasMeta>(bytes: &[u8]) -> io::Result<String> {
+    let mut json = String::new();
+    write_json(&decode_value::<T>(bytes)?, &mut json);
+    json.push('\n');
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{rt::Runtime, test_util::Point};
+
+    use super::Conformance;
+
+    #[test]
+    fn write_vectors() {
+        let mut conformance = Conformance::new();
+        let origin = Point::empty(Runtime::new());
+
+        conformance
+            .case("move", &origin, |point| {
+                point.set_x(1).set_y(-2);
+            })
+            .unwrap()
+            .case("noop", &Point::new(Runtime::new(), 3, 0, 0), |_| {})
+            .unwrap();
+
+        assert!(conformance.case("move", &origin, |_| {}).is_err());
+        assert!(conformance.case("a/b", &origin, |_| {}).is_err());
+
+        let dir = std::env::temp_dir().join(format!("steit-conformance-{}", std::process::id()));
+        conformance.write(&dir).unwrap();
+
+        let manifest = fs::read_to_string(dir.join("manifest.json")).unwrap();
+        assert!(manifest.starts_with("{\"root\":\"Point\""));
+        assert!(manifest.ends_with("\"cases\":[\"move\",\"noop\"]}\n"));
+
+        let read = |path: &str| fs::read(dir.join(path)).unwrap();
+        assert_eq!(read("move/state.bin"), []);
+        assert_eq!(read("move/final.bin"), [0, 2, 8, 3]);
+        assert_eq!(read("move/final.json"), b"{\"0\":1,\"1\":-2,\"2\":0}\n");
+        assert!(!read("move/log.bin").is_empty());
+        assert_eq!(read("noop/log.bin"), []);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+use super::{
+    export::field_type_name,
+    meta::HasMeta,
+    msg::{FieldMeta, MessageMeta, StructMeta},
+    r#type::FieldTypeMeta,
+    value::Decoder,
+};
+
+/// Renders a Markdown reference of the protocol rooted at `T`: every type it refers to,
+/// sorted by name, with the tag, type, wire type, constraints and writers of each field.
+///
+/// Built from the meta of derived types, so it can be regenerated on every build
+/// and never drifts from what is actually sent.
+pub fn protocol_doc<T: HasMeta>(skip_builtins: bool) -> String {
+    let decoder = Decoder::new::<T>(false);
+    let mut msgs: Vec<_> = decoder
+        .msgs()
+        .values()
+        .filter(|msg| !skip_builtins || !msg.is_builtin())
+        .collect();
+    msgs.sort_by_key(|msg| msg.rust_name());
+
+    let mut doc = String::new();
+    doc.push_str(&format!("# Protocol of `{}`\n", T::NAME.rust));
+
+    for msg in msgs {
+        doc.push('\n');
+
+        match msg {
+            MessageMeta::Struct(r#struct) => {
+                write_heading("struct", msg, r#struct.type_params, &mut doc);
+                write_fields(&decoder, r#struct, &mut doc);
+            }
+
+            MessageMeta::Enum(r#enum) => {
+                write_heading("enum", msg, r#enum.type_params, &mut doc);
+                doc.push_str("| Tag | Variant | Default |\n|---|---|---|\n");
+
+                for variant in r#enum.variants {
+                    doc.push_str(&format!(
+                        "| {} | `{}` | {} |\n",
+                        variant.tag,
+                        variant.ty.name.rust,
+                        if variant.is_default { "yes" } else { "" }
+                    ));
+                }
+
+                for variant in r#enum.variants {
+                    if !variant.ty.fields.is_empty() {
+                        doc.push_str(&format!(
+                            "\n### `{}::{}`\n\n",
+                            r#enum.name.rust, variant.ty.name.rust
+                        ));
+                        write_fields(&decoder, &variant.ty, &mut doc);
+                    }
+                }
+            }
+
+            MessageMeta::Flags(flags) => {
+                write_heading("flags", msg, &[], &mut doc);
+                doc.push_str("| Bit | Flag |\n|---|---|\n");
+
+                for flag in flags.flags {
+                    doc.push_str(&format!("| {} | `{}` |\n", flag.bit, flag.name.rust));
+                }
+            }
+        }
+    }
+
+    doc
+}
+
+fn write_heading(kind: &str, msg: &MessageMeta, type_params: &[&str], doc: &mut String) {
+    let name = match type_params {
+        [] => msg.rust_name().to_string(),
+        _ => format!("{}<{}>", msg.rust_name(), type_params.join(", ")),
+    };
+
+    doc.push_str(&format!("## {} `{}`\n\n", kind, name));
+
+    if msg.csharp_name() != msg.rust_name() {
+        doc.push_str(&format!("Named `{}` in C#.\n\n", msg.csharp_name()));
+    }
+}
+
+fn write_fields(decoder: &Decoder, r#struct: &StructMeta, doc: &mut String) {
+    if r#struct.fields.is_empty() {
+        doc.push_str("No fields.\n");
+        return;
+    }
+
+    doc.push_str("| Tag | Field | Type | Wire type | Constraints | Written by |\n");
+    doc.push_str("|---|---|---|---|---|---|\n");
+
+    for field in r#struct.fields {
+        let wire_type = match field.ty {
+            FieldTypeMeta::Type(ty) => decoder.wire_type(ty, &[]).name().to_string(),
+            FieldTypeMeta::TypeParam(name) => format!("that of `{}`", name),
+        };
+
+        doc.push_str(&format!(
+            "| {} | `{}` | `{}` | {} | {} | {} |\n",
+            field.tag,
+            field.name.rust,
+            field_type_name(field.ty),
+            wire_type,
+            constraints(field),
+            if field.client_writable {
+                "server, clients"
+            } else {
+                "server"
+            }
+        ));
+    }
+}
+
+fn constraints(field: &FieldMeta) -> String {
+    let mut constraints = Vec::new();
+
+    if let Some(max_len) = field.max_len {
+        constraints.push(format!("at most {} long", max_len));
+    }
+
+    if let Some(range) = &field.range {
+        let min = range.min.map(|min| min.to_string()).unwrap_or_default();
+        let max = range.max.map(|max| format!("={}", max)).unwrap_or_default();
+        let clamp = if range.clamp { ", clamped" } else { "" };
+        constraints.push(format!("in `{}..{}`{}", min, max, clamp));
+    }
+
+    constraints.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        steit_derive,
+        test_util::Point,
+        types::{List, Maybe},
+    };
+
+    use super::protocol_doc;
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Hero {
+        #[steit(tag = 0, max_len = 16)]
+        name: String,
+        #[steit(tag = 1, range = "0..=100", clamp)]
+        hp: u8,
+        #[steit(tag = 2)]
+        path: List<Point>,
+        #[steit(tag = 3, client_writable)]
+        action: Maybe<Action>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned, csharp_name = "HeroAction")]
+    enum Action {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1)]
+        Attack {
+            #[steit(tag = 0)]
+            target: u32,
+        },
+    }
+
+    #[test]
+    fn markdown() {
+        let doc = protocol_doc::<Hero>(false);
+
+        for line in [
+            "# Protocol of `Hero`",
+            "## enum `Action`",
+            "Named `HeroAction` in C#.",
+            "| 0 | `Idle` | yes |",
+            "### `Action::Attack`",
+            "| 0 | `target` | `u32` | Varint |  | server |",
+            "## struct `Hero`",
+            "| 0 | `name` | `String` | Sized | at most 16 long | server |",
+            "| 1 | `hp` | `u8` | Varint | in `0..=100`, clamped | server |",
+            "| 3 | `action` | `Maybe<Action>` | Sized |  | server, clients |",
+            "## enum `Maybe<T>`",
+            "| 0 | `value` | `T` | that of `T` |  | server |",
+            "## struct `Point`",
+        ] {
+            assert!(doc.lines().any(|doc_line| doc_line == line), "{}", line);
+        }
+
+        assert!(doc.find("## enum `Action`") < doc.find("## struct `Hero`"));
+        assert!(!protocol_doc::<Hero>(true).contains("`Maybe<T>`"));
+    }
+}
@@ -0,0 +1,334 @@
+use std::{collections::BTreeMap, io};
+
+use crate::ser::Serialize;
+
+use super::{
+    meta::HasMeta,
+    msg::{MessageMeta, StructMeta},
+    path::{bind, resolve},
+    r#type::TypeMeta,
+    value::{type_arg, unsupported, Decoder, Value},
+};
+
+/// Self-describing formats that `dump` can write.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DumpFormat {
+    Cbor,
+    MessagePack,
+}
+
+/// Writes `value` as a tree of maps keyed by field names, for tools which don't know steit.
+///
+/// Enums become single-entry maps from the variant name to its fields,
+/// `Map`s are keyed by their field numbers and empty `Option`s are nulls.
+pub fn dump<T: Serialize + HasMeta>(value: &T, format: DumpFormat) -> io::Result<Vec<u8>> {
+    let decoder = Decoder::new::<T>(false);
+    let value = decoder.decode(T::TYPE, &value.to_bytes())?;
+
+    let mut dumper = Dumper {
+        decoder,
+        format,
+        bytes: Vec::new(),
+    };
+
+    dumper.dump(&value, T::TYPE, &[])?;
+    Ok(dumper.bytes)
+}
+
+struct Dumper {
+    decoder: Decoder,
+    format: DumpFormat,
+    bytes: Vec<u8>,
+}
+
+impl Dumper {
+    // Writes the major type of a CBOR data item with its argument in the shortest form.
+    fn cbor_head(&mut self, major: u8, arg: u64) {
+        let major = major << 5;
+
+        if arg < 24 {
+            self.bytes.push(major | arg as u8);
+        } else if arg <= u8::MAX as u64 {
+            self.bytes.push(major | 24);
+            self.bytes.push(arg as u8);
+        } else if arg <= u16::MAX as u64 {
+            self.bytes.push(major | 25);
+            self.bytes.extend(&(arg as u16).to_be_bytes());
+        } else if arg <= u32::MAX as u64 {
+            self.bytes.push(major | 26);
+            self.bytes.extend(&(arg as u32).to_be_bytes());
+        } else {
+            self.bytes.push(major | 27);
+            self.bytes.extend(&arg.to_be_bytes());
+        }
+    }
+
+    // Writes a MessagePack length, in its fix form if `fix` is given and the length fits.
+    fn msgpack_len(&mut self, fix: Option<(u8, usize)>, markers: [u8; 3], len: usize) {
+        match fix {
+            Some((marker, max)) if len <= max => self.bytes.push(marker | len as u8),
+            _ if len <= u8::MAX as usize && markers[0] != 0 => {
+                self.bytes.push(markers[0]);
+                self.bytes.push(len as u8);
+            }
+            _ if len <= u16::MAX as usize => {
+                self.bytes.push(markers[1]);
+                self.bytes.extend(&(len as u16).to_be_bytes());
+            }
+            _ => {
+                self.bytes.push(markers[2]);
+                self.bytes.extend(&(len as u32).to_be_bytes());
+            }
+        }
+    }
+
+    fn null(&mut self) {
+        match self.format {
+            DumpFormat::Cbor => self.bytes.push(0xf6),
+            DumpFormat::MessagePack => self.bytes.push(0xc0),
+        }
+    }
+
+    fn bool(&mut self, value: bool) {
+        match self.format {
+            DumpFormat::Cbor => self.bytes.push(if value { 0xf5 } else { 0xf4 }),
+            DumpFormat::MessagePack => self.bytes.push(if value { 0xc3 } else { 0xc2 }),
+        }
+    }
+
+    fn uint(&mut self, value: u64) {
+        match self.format {
+            DumpFormat::Cbor => self.cbor_head(0, value),
+            DumpFormat::MessagePack => {
+                if value < 0x80 {
+                    self.bytes.push(value as u8);
+                } else if value <= u8::MAX as u64 {
+                    self.bytes.push(0xcc);
+                    self.bytes.push(value as u8);
+                } else if value <= u16::MAX as u64 {
+                    self.bytes.push(0xcd);
+                    self.bytes.extend(&(value as u16).to_be_bytes());
+                } else if value <= u32::MAX as u64 {
+                    self.bytes.push(0xce);
+                    self.bytes.extend(&(value as u32).to_be_bytes());
+                } else {
+                    self.bytes.push(0xcf);
+                    self.bytes.extend(&value.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    fn int(&mut self, value: i64) {
+        if value >= 0 {
+            return self.uint(value as u64);
+        }
+
+        match self.format {
+            DumpFormat::Cbor => self.cbor_head(1, !value as u64),
+            DumpFormat::MessagePack => {
+                if value >= -32 {
+                    self.bytes.push(value as u8);
+                } else if value >= i8::MIN as i64 {
+                    self.bytes.push(0xd0);
+                    self.bytes.push(value as u8);
+                } else if value >= i16::MIN as i64 {
+                    self.bytes.push(0xd1);
+                    self.bytes.extend(&(value as i16).to_be_bytes());
+                } else if value >= i32::MIN as i64 {
+                    self.bytes.push(0xd2);
+                    self.bytes.extend(&(value as i32).to_be_bytes());
+                } else {
+                    self.bytes.push(0xd3);
+                    self.bytes.extend(&value.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    fn float(&mut self, value: f64) {
+        match self.format {
+            DumpFormat::Cbor => self.bytes.push(0xfb),
+            DumpFormat::MessagePack => self.bytes.push(0xcb),
+        }
+
+        self.bytes.extend(&value.to_be_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        match self.format {
+            DumpFormat::Cbor => self.cbor_head(3, value.len() as u64),
+            DumpFormat::MessagePack => {
+                self.msgpack_len(Some((0xa0, 31)), [0xd9, 0xda, 0xdb], value.len())
+            }
+        }
+
+        self.bytes.extend(value.as_bytes());
+    }
+
+    fn array(&mut self, len: usize) {
+        match self.format {
+            DumpFormat::Cbor => self.cbor_head(4, len as u64),
+            DumpFormat::MessagePack => self.msgpack_len(Some((0x90, 15)), [0, 0xdc, 0xdd], len),
+        }
+    }
+
+    fn map(&mut self, len: usize) {
+        match self.format {
+            DumpFormat::Cbor => self.cbor_head(5, len as u64),
+            DumpFormat::MessagePack => self.msgpack_len(Some((0x80, 15)), [0, 0xde, 0xdf], len),
+        }
+    }
+
+    fn dump_fields(
+        &mut self,
+        fields: &BTreeMap<u32, Value>,
+        r#struct: &'static StructMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<()> {
+        self.map(r#struct.fields.len());
+
+        for field in r#struct.fields {
+            let ty = resolve(field.ty, args).ok_or_else(|| unsupported(field.name.rust))?;
+            self.str(field.name.rust);
+            self.dump(&fields[&field.tag], ty, args)?;
+        }
+
+        Ok(())
+    }
+
+    fn dump(
+        &mut self,
+        value: &Value,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<()> {
+        let (name, type_args) = match ty {
+            TypeMeta::Primitive(name, _) => (name.rust, &[][..]),
+            TypeMeta::Ref(name, type_args) => (name.rust, *type_args),
+        };
+
+        if name == "Box" {
+            return self.dump(value, type_arg(type_args, args)?, args);
+        }
+
+        match value {
+            Value::Bool(value) => self.bool(*value),
+            Value::UInt(value) | Value::Flags(value) => self.uint(*value),
+            Value::Int(value) => self.int(*value),
+            Value::Float(value) => self.float(*value),
+            Value::String(value) => self.str(value),
+
+            Value::List(items) => {
+                let ty = type_arg(type_args, args)?;
+                self.array(items.len());
+
+                for item in items {
+                    self.dump(item, ty, args)?;
+                }
+            }
+
+            Value::Map(entries) => {
+                let ty = type_arg(type_args, args)?;
+                self.map(entries.len());
+
+                for (&key, value) in entries {
+                    self.uint(key as u64);
+                    self.dump(value, ty, args)?;
+                }
+            }
+
+            Value::Option(None) => self.null(),
+            Value::Option(Some(value)) => self.dump(value, type_arg(type_args, args)?, args)?,
+
+            Value::Struct(fields) | Value::Variant(_, fields) => {
+                match (self.decoder.msg(name)?, value) {
+                    (MessageMeta::Struct(r#struct), _) => {
+                        let args = bind(r#struct.type_params, type_args, args);
+                        self.dump_fields(fields, r#struct, &args)?;
+                    }
+
+                    (MessageMeta::Enum(r#enum), Value::Variant(tag, _)) => {
+                        let variant = r#enum
+                            .variants
+                            .iter()
+                            .find(|variant| variant.tag == *tag)
+                            .ok_or_else(|| unsupported(name))?;
+
+                        let args = bind(r#enum.type_params, type_args, args);
+                        self.map(1);
+                        self.str(variant.ty.name.rust);
+                        self.dump_fields(fields, &variant.ty, &args)?;
+                    }
+
+                    _ => return Err(unsupported(name)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::Runtime,
+        steit_derive,
+        test_util::Point,
+        types::{List, Maybe},
+    };
+
+    use super::{dump, DumpFormat};
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Log {
+        #[steit(tag = 0)]
+        ok: bool,
+        #[steit(tag = 1)]
+        points: List<Point>,
+        #[steit(tag = 2)]
+        shift: Maybe<u16>,
+    }
+
+    fn log() -> Log {
+        let mut log = Log::new(Runtime::new());
+        log.ok = true;
+        log.points
+            .push_with(|runtime| Point::new(runtime, 1, -300, 70000));
+        log
+    }
+
+    #[test]
+    fn dump_cbor() {
+        let mut expected = vec![0xa3, 0x62, b'o', b'k', 0xf5];
+        expected.extend(&[0x66, b'p', b'o', b'i', b'n', b't', b's', 0x81]);
+        expected.extend(&[0xa3, 0x61, b'x', 0x01]);
+        expected.extend(&[0x61, b'y', 0x39, 0x01, 0x2b]);
+        expected.extend(&[0x61, b'z', 0x1a, 0x00, 0x01, 0x11, 0x70]);
+        expected.extend(&[0x65, b's', b'h', b'i', b'f', b't']);
+        expected.extend(&[0xa1, 0x64, b'N', b'o', b'n', b'e', 0xa0]);
+
+        assert_eq!(dump(&log(), DumpFormat::Cbor).unwrap(), expected);
+    }
+
+    #[test]
+    fn dump_msgpack() {
+        let mut expected = vec![0x83, 0xa2, b'o', b'k', 0xc3];
+        expected.extend(&[0xa6, b'p', b'o', b'i', b'n', b't', b's', 0x91]);
+        expected.extend(&[0x83, 0xa1, b'x', 0x01]);
+        expected.extend(&[0xa1, b'y', 0xd1, 0xfe, 0xd4]);
+        expected.extend(&[0xa1, b'z', 0xce, 0x00, 0x01, 0x11, 0x70]);
+        expected.extend(&[0xa5, b's', b'h', b'i', b'f', b't']);
+        expected.extend(&[0x81, 0xa4, b'N', b'o', b'n', b'e', 0x80]);
+
+        assert_eq!(dump(&log(), DumpFormat::MessagePack).unwrap(), expected);
+    }
+
+    #[test]
+    fn dump_option() {
+        assert_eq!(dump(&None::<u8>, DumpFormat::Cbor).unwrap(), &[0xf6]);
+        assert_eq!(dump(&Some(-1i8), DumpFormat::MessagePack).unwrap(), &[0xff]);
+    }
+}
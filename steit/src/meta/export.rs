@@ -0,0 +1,215 @@
+use crate::gen::gen_util;
+
+use super::{
+    meta::HasMeta,
+    msg::{FieldMeta, MessageMeta, StructMeta},
+    name::NameMeta,
+    r#type::{FieldTypeMeta, TypeMeta},
+};
+
+/// Renders `T` and the types it refers to in the schema DSL of `include_schema!`,
+/// sorted by name, to bootstrap schema files from derived types.
+///
+/// Generic types and flags have no DSL form, so they are only mentioned in comments.
+pub fn export_schema<T: HasMeta>(skip_builtins: bool) -> String {
+    let msgs = gen_util::collect_meta::<T>(|msg| msg.rust_name().to_string());
+    let mut msgs: Vec<_> = msgs
+        .into_values()
+        .filter(|msg| !skip_builtins || !msg.is_builtin())
+        .collect();
+    msgs.sort_by_key(|msg| msg.rust_name());
+
+    let mut schema = String::new();
+
+    for (index, msg) in msgs.into_iter().enumerate() {
+        if index > 0 {
+            schema.push('\n');
+        }
+
+        match msg {
+            MessageMeta::Struct(r#struct) if r#struct.type_params.is_empty() => {
+                write_name_attrs(r#struct.name, "", &mut schema);
+                schema.push_str(&format!("struct {} {{\n", r#struct.name.rust));
+                write_fields(r#struct, "    ", &mut schema);
+                schema.push_str("}\n");
+            }
+
+            MessageMeta::Enum(r#enum) if r#enum.type_params.is_empty() => {
+                write_name_attrs(r#enum.name, "", &mut schema);
+                schema.push_str(&format!("enum {} {{\n", r#enum.name.rust));
+
+                for variant in r#enum.variants {
+                    let name = variant.ty.name;
+                    write_name_attrs(name, "    ", &mut schema);
+
+                    if variant.is_default && variant.tag != 0 {
+                        schema.push_str("    #[steit(default)]\n");
+                    }
+
+                    if variant.ty.fields.is_empty() {
+                        schema.push_str(&format!("    {} = {},\n", name.rust, variant.tag));
+                    } else {
+                        schema.push_str(&format!("    {} = {} {{\n", name.rust, variant.tag));
+                        write_fields(&variant.ty, "        ", &mut schema);
+                        schema.push_str("    },\n");
+                    }
+                }
+
+                schema.push_str("}\n");
+            }
+
+            msg => {
+                schema.push_str(&format!(
+                    "// `{}` has no schema form, as it is generic or flags.\n",
+                    msg.rust_name()
+                ));
+            }
+        }
+    }
+
+    schema
+}
+
+fn write_name_attrs(name: &NameMeta, indent: &str, schema: &mut String) {
+    if let Some(csharp) = name.csharp {
+        schema.push_str(&format!("{}#[steit(csharp_name = {:?})]\n", indent, csharp));
+    }
+}
+
+fn write_fields(r#struct: &StructMeta, indent: &str, schema: &mut String) {
+    for field in r#struct.fields {
+        write_field_attrs(field, indent, schema);
+
+        schema.push_str(&format!(
+            "{}{}: {} = {},\n",
+            indent,
+            field.name.rust,
+            field_type_name(field.ty),
+            field.tag
+        ));
+    }
+}
+
+fn write_field_attrs(field: &FieldMeta, indent: &str, schema: &mut String) {
+    let mut attrs = Vec::new();
+
+    if let Some(csharp) = field.name.csharp {
+        attrs.push(format!("csharp_name = {:?}", csharp));
+    }
+
+    if let Some(max_len) = field.max_len {
+        attrs.push(format!("max_len = {}", max_len));
+    }
+
+    if let Some(range) = &field.range {
+        let min = range.min.map(|min| min.to_string()).unwrap_or_default();
+
+        attrs.push(match range.max {
+            Some(max) => format!("range = \"{}..={}\"", min, max),
+            None => format!("range = \"{}..\"", min),
+        });
+
+        if range.clamp {
+            attrs.push("clamp".to_string());
+        }
+    }
+
+    if field.client_writable {
+        attrs.push("client_writable".to_string());
+    }
+
+    if !attrs.is_empty() {
+        schema.push_str(&format!("{}#[steit({})]\n", indent, attrs.join(", ")));
+    }
+}
+
+pub(super) fn field_type_name(ty: &FieldTypeMeta) -> String {
+    match ty {
+        FieldTypeMeta::Type(ty) => type_name(ty),
+        FieldTypeMeta::TypeParam(name) => name.to_string(),
+    }
+}
+
+fn type_name(ty: &TypeMeta) -> String {
+    match ty {
+        TypeMeta::Primitive(name, _) => name.rust.to_string(),
+        TypeMeta::Ref(name, []) => name.rust.to_string(),
+        TypeMeta::Ref(name, args) => {
+            let args: Vec<_> = args.iter().map(field_type_name).collect();
+            format!("{}<{}>", name.rust, args.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        steit_derive,
+        test_util::Point,
+        types::{List, Maybe},
+    };
+
+    use super::export_schema;
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Hero {
+        #[steit(tag = 0, max_len = 16)]
+        name: String,
+        #[steit(tag = 1, range = "0..=100", clamp)]
+        hp: u8,
+        #[steit(tag = 2, csharp_name = "Steps")]
+        path: List<Point>,
+        #[steit(tag = 3, client_writable)]
+        action: Maybe<Action>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    enum Action {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1, csharp_name = "Hit")]
+        Attack {
+            #[steit(tag = 0)]
+            target: u32,
+        },
+    }
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            export_schema::<Hero>(false),
+            [
+                "enum Action {",
+                "    Idle = 0,",
+                "    #[steit(csharp_name = \"Hit\")]",
+                "    Attack = 1 {",
+                "        target: u32 = 0,",
+                "    },",
+                "}",
+                "",
+                "struct Hero {",
+                "    #[steit(max_len = 16)]",
+                "    name: String = 0,",
+                "    #[steit(range = \"0..=100\", clamp)]",
+                "    hp: u8 = 1,",
+                "    #[steit(csharp_name = \"Steps\")]",
+                "    path: List<Point> = 2,",
+                "    #[steit(client_writable)]",
+                "    action: Maybe<Action> = 3,",
+                "}",
+                "",
+                "// `Maybe` has no schema form, as it is generic or flags.",
+                "",
+                "struct Point {",
+                "    x: i32 = 0,",
+                "    y: i32 = 1,",
+                "    z: i32 = 2,",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::{collections::BTreeMap, io, marker::PhantomData};
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{LogEntry, LogEntryKind, StringTable},
+    wire_fmt::HasWireType,
+};
+
+use super::{
+    meta::HasMeta,
+    path::describe_typed,
+    r#type::TypeMeta,
+    value::{type_arg, Decoder, Value},
+};
+
+/// A flattened log entry, with its value decoded as JSON.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogRow {
+    pub tick: u64,
+    pub kind: LogEntryKind,
+    pub path: Vec<u32>,
+    /// The path described by schema names, as by `describe_path`.
+    pub names: String,
+    pub value: String,
+}
+
+/// Flattens recorded logs of a root `T` into one row per entry, for analytics.
+///
+/// Logs are pushed in batches, typically one per tick, and must be pushed in order
+/// since interned strings are resolved across batches.
+pub struct LogTable<T: HasMeta> {
+    decoder: Decoder,
+    strings: StringTable,
+    rows: Vec<LogRow>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: HasMeta> Default for LogTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: HasMeta> LogTable<T> {
+    pub fn new() -> Self {
+        Self {
+            decoder: Decoder::new::<T>(false),
+            strings: StringTable::new(),
+            rows: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn rows(&self) -> &[LogRow] {
+        &self.rows
+    }
+
+    pub fn push_batch(&mut self, tick: u64, bytes: &[u8]) -> io::Result<()> {
+        let reader = &mut Reader::new(bytes);
+
+        while !reader.eof()? {
+            let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+
+            if let Some(row) = self.row(tick, entry)? {
+                self.rows.push(row);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn row(&mut self, tick: u64, entry: LogEntry) -> io::Result<Option<LogRow>> {
+        let mut kind = entry.kind();
+
+        let (path, value) = match entry {
+            LogEntry::Intern { index, value, .. } => {
+                self.strings.insert(index, value)?;
+                return Ok(None);
+            }
+
+            LogEntry::UpdateInterned { path, index, .. } => {
+                let value = self.strings.get(index).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("interned string {} not found", index),
+                    )
+                })?;
+
+                // Resolved, so it reads like any other update.
+                kind = LogEntryKind::Update;
+                (path, json_string(value))
+            }
+
+            LogEntry::Update { path, value, .. } | LogEntry::Replace { path, value, .. } => {
+                let value = self.decode(&path, false, &value.into_raw())?;
+                (path, value)
+            }
+
+            LogEntry::ListPush { path, item, .. } => {
+                let value = self.decode(&path, true, &item.into_raw())?;
+                (path, value)
+            }
+
+            LogEntry::ListExtend { path, items, .. } => {
+                let value = self.decode(&path, false, &items.into_raw())?;
+                (path, value)
+            }
+
+            LogEntry::ListPop { path, .. } => (path, "null".to_string()),
+            LogEntry::ListMove { path, from, to, .. } => {
+                (path, format!(r#"{{"from":{},"to":{}}}"#, from, to))
+            }
+            LogEntry::MapRemove { path, key, .. } => (path, key.to_string()),
+        };
+
+        let (names, _) = describe_typed::<T>(&path, self.decoder.msgs());
+
+        Ok(Some(LogRow {
+            tick,
+            kind,
+            names,
+            path,
+            value,
+        }))
+    }
+
+    // Values of unknown types are kept as raw bytes.
+    fn decode(&self, path: &[u32], item: bool, bytes: &[u8]) -> io::Result<String> {
+        let (_, ty) = describe_typed::<T>(path, self.decoder.msgs());
+
+        let ty = ty.and_then(|(ty, args)| {
+            let ty: &'static TypeMeta = match (item, ty) {
+                (false, ty) => ty,
+                (true, TypeMeta::Ref(_, type_args)) => type_arg(type_args, &args).ok()?,
+                (true, TypeMeta::Primitive(..)) => return None,
+            };
+
+            Some((ty, args))
+        });
+
+        let value = match ty {
+            Some((ty, args)) => self.decoder.decode_with(ty, &args, bytes)?,
+            None => Value::List(bytes.iter().map(|&byte| Value::UInt(byte as u64)).collect()),
+        };
+
+        let mut json = String::new();
+        write_json(&value, &mut json);
+        Ok(json)
+    }
+
+    /// Writes rows as CSV with a header, quoting fields as in RFC 4180.
+    pub fn write_csv(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        writeln!(writer, "tick,kind,path,names,value")?;
+
+        for row in &self.rows {
+            let path: Vec<_> = row.path.iter().map(u32::to_string).collect();
+
+            writeln!(
+                writer,
+                "{},{:?},{},{},{}",
+                row.tick,
+                row.kind,
+                path.join("."),
+                csv_field(&row.names),
+                csv_field(&row.value),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub(super) fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+fn write_json_fields(fields: &BTreeMap<u32, Value>, json: &mut String) {
+    json.push('{');
+
+    for (index, (tag, value)) in fields.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+
+        json.push_str(&format!("\"{}\":", tag));
+        write_json(value, json);
+    }
+
+    json.push('}');
+}
+
+// Fields are keyed by their tags, like `Value`.
+pub(super) fn write_json(value: &Value, json: &mut String) {
+    match value {
+        Value::Bool(value) => json.push_str(&value.to_string()),
+        Value::UInt(value) | Value::Flags(value) => json.push_str(&value.to_string()),
+        Value::Int(value) => json.push_str(&value.to_string()),
+        // JSON has no NaN nor infinities.
+        Value::Float(value) if !value.is_finite() => json.push_str("null"),
+        Value::Float(value) => json.push_str(&value.to_string()),
+        Value::String(value) => json.push_str(&json_string(value)),
+
+        Value::List(items) => {
+            json.push('[');
+
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+
+                write_json(item, json);
+            }
+
+            json.push(']');
+        }
+
+        Value::Map(entries) | Value::Struct(entries) => write_json_fields(entries, json),
+        Value::Option(None) => json.push_str("null"),
+        Value::Option(Some(value)) => write_json(value, json),
+
+        Value::Variant(tag, fields) => {
+            json.push_str(&format!("{{\"{}\":", tag));
+            write_json_fields(fields, json);
+            json.push('}');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::{loggers::BufferLogger, LogEntryKind},
+        rt::Runtime,
+        steit_derive,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::LogTable;
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Match {
+        #[steit(tag = 0, string_intern)]
+        map: String,
+        #[steit(tag = 1)]
+        points: List<Point>,
+    }
+
+    #[test]
+    fn rows_and_csv() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut table = LogTable::<Match>::new();
+        let mut game = Match::new(runtime);
+
+        game.set_map("de,dust".to_string());
+        game.points
+            .push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        table
+            .push_batch(0, &logger.lock().unwrap().pluck_bytes())
+            .unwrap();
+
+        game.points[0].set_y(-4);
+        game.points
+            .push_with(|runtime| Point::new(runtime, 0, 0, 0));
+        game.points.move_item(1, 0);
+        game.points.pop();
+        table
+            .push_batch(1, &logger.lock().unwrap().pluck_bytes())
+            .unwrap();
+
+        let rows = table.rows();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[1].kind, LogEntryKind::ListPush);
+        assert_eq!(rows[1].names, "Match.points");
+        assert_eq!(rows[1].value, r#"{"0":1,"1":2,"2":3}"#);
+        assert_eq!(rows[2].names, "Match.points[0].y");
+
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            [
+                "tick,kind,path,names,value",
+                r#"0,Update,0,Match.map,"""de,dust""""#,
+                r#"0,ListPush,1,Match.points,"{""0"":1,""1"":2,""2"":3}""#,
+                "1,Update,1.0.1,Match.points[0].y,-4",
+                r#"1,ListPush,1,Match.points,"{""0"":0,""1"":0,""2"":0}""#,
+                r#"1,ListMove,1,Match.points,"{""from"":1,""to"":0}""#,
+                "1,ListPop,1,Match.points,null",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
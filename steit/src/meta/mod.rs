@@ -1,10 +1,34 @@
+#[cfg(feature = "runtime")]
+mod conformance;
+mod doc;
+#[cfg(any(test, feature = "dump"))]
+mod dump;
+mod export;
+#[cfg(feature = "runtime")]
+mod log_table;
 #[allow(clippy::module_inception)]
 mod meta;
 mod msg;
 mod name;
+mod path;
+mod schema;
+mod snapshot;
 mod r#type;
+mod value;
 
 pub use self::meta::*;
+#[cfg(feature = "runtime")]
+pub use conformance::*;
+pub use doc::*;
+#[cfg(any(test, feature = "dump"))]
+pub use dump::*;
+pub use export::*;
+#[cfg(feature = "runtime")]
+pub use log_table::*;
 pub use msg::*;
 pub use name::*;
+pub use path::*;
 pub use r#type::*;
+pub use schema::*;
+pub use snapshot::*;
+pub use value::*;
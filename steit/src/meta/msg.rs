@@ -4,27 +4,38 @@ use super::{name::NameMeta, r#type::FieldTypeMeta};
 pub enum MessageMeta {
     Struct(StructMeta),
     Enum(EnumMeta),
+    Flags(FlagsMeta),
 }
 
 impl MessageMeta {
     pub fn rust_name(&self) -> &'static str {
         match *self {
             MessageMeta::Struct(StructMeta { name, .. })
-            | MessageMeta::Enum(EnumMeta { name, .. }) => name.rust,
+            | MessageMeta::Enum(EnumMeta { name, .. })
+            | MessageMeta::Flags(FlagsMeta { name, .. }) => name.rust,
         }
     }
 
     pub fn csharp_name(&self) -> String {
         match *self {
             MessageMeta::Struct(StructMeta { name, .. })
-            | MessageMeta::Enum(EnumMeta { name, .. }) => name.csharp(String::from),
+            | MessageMeta::Enum(EnumMeta { name, .. })
+            | MessageMeta::Flags(FlagsMeta { name, .. }) => name.csharp(String::from),
         }
     }
 
     pub fn is_builtin(&self) -> bool {
         match self {
             MessageMeta::Struct(StructMeta { builtin, .. })
-            | MessageMeta::Enum(EnumMeta { builtin, .. }) => *builtin,
+            | MessageMeta::Enum(EnumMeta { builtin, .. })
+            | MessageMeta::Flags(FlagsMeta { builtin, .. }) => *builtin,
+        }
+    }
+
+    pub fn is_component(&self) -> bool {
+        match self {
+            MessageMeta::Struct(StructMeta { component, .. }) => *component,
+            MessageMeta::Enum(_) | MessageMeta::Flags(_) => false,
         }
     }
 }
@@ -35,6 +46,8 @@ pub struct StructMeta {
     pub type_params: &'static [&'static str],
     pub fields: &'static [FieldMeta],
     pub builtin: bool,
+    /// Components are shared by many messages and can be generated once, apart from them.
+    pub component: bool,
 }
 
 #[derive(Debug)]
@@ -49,17 +62,45 @@ pub struct EnumMeta {
 pub struct VariantMeta {
     pub ty: StructMeta,
     pub tag: u32,
+    /// Marked with `#[steit(default)]`, or else having tag 0.
+    pub is_default: bool,
 }
 
 impl VariantMeta {
     pub fn default(&self) -> bool {
-        self.tag == 0
+        self.is_default
     }
 }
 
+#[derive(Debug)]
+pub struct FlagsMeta {
+    pub name: &'static NameMeta,
+    pub flags: &'static [FlagMeta],
+    pub builtin: bool,
+}
+
+#[derive(Debug)]
+pub struct FlagMeta {
+    pub name: &'static NameMeta,
+    pub bit: u32,
+}
+
 #[derive(Debug)]
 pub struct FieldMeta {
     pub name: &'static NameMeta,
     pub ty: &'static FieldTypeMeta,
     pub tag: u32,
+    pub max_len: Option<usize>,
+    pub range: Option<RangeMeta>,
+    /// Clients may write the field and everything under it, see `WritePolicy::client_writable`.
+    pub client_writable: bool,
+}
+
+/// Inclusive bounds of a numeric field.
+#[derive(Debug)]
+pub struct RangeMeta {
+    pub min: Option<i128>,
+    pub max: Option<i128>,
+    /// Out-of-range values are clamped rather than rejected.
+    pub clamp: bool,
 }
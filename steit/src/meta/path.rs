@@ -0,0 +1,485 @@
+use std::{collections::HashMap, io};
+
+use crate::gen::gen_util;
+#[cfg(feature = "runtime")]
+use crate::rt::Path;
+
+use super::{
+    meta::HasMeta,
+    msg::{MessageMeta, StructMeta},
+    r#type::{FieldTypeMeta, TypeMeta},
+};
+
+pub(super) type TypeArgs = Vec<(&'static str, &'static TypeMeta)>;
+
+enum Cursor {
+    Type(&'static TypeMeta, TypeArgs),
+    Variant(&'static StructMeta, TypeArgs),
+}
+
+pub(super) fn resolve(
+    ty: &'static FieldTypeMeta,
+    args: &[(&str, &'static TypeMeta)],
+) -> Option<&'static TypeMeta> {
+    match ty {
+        FieldTypeMeta::Type(ty) => Some(ty),
+        FieldTypeMeta::TypeParam(name) => args
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|&(_, ty)| ty),
+    }
+}
+
+pub(super) fn bind(
+    type_params: &[&'static str],
+    type_args: &'static [FieldTypeMeta],
+    args: &[(&str, &'static TypeMeta)],
+) -> TypeArgs {
+    type_params
+        .iter()
+        .zip(type_args)
+        .filter_map(|(&param, arg)| resolve(arg, args).map(|arg| (param, arg)))
+        .collect()
+}
+
+fn field(
+    r#struct: &'static StructMeta,
+    tag: u32,
+    args: TypeArgs,
+) -> Option<(String, Option<Cursor>)> {
+    let field = r#struct.fields.iter().find(|field| field.tag == tag)?;
+    let cursor = resolve(field.ty, &args).map(|ty| Cursor::Type(ty, args));
+    Some((format!(".{}", field.name.rust), cursor))
+}
+
+fn step(
+    cursor: Cursor,
+    tag: u32,
+    msgs: &HashMap<String, &'static MessageMeta>,
+) -> Option<(String, Option<Cursor>)> {
+    let (ty, args) = match cursor {
+        Cursor::Variant(variant, args) => return field(variant, tag, args),
+        Cursor::Type(ty, args) => (ty, args),
+    };
+
+    let (name, type_args) = match ty {
+        TypeMeta::Ref(name, type_args) => (name, *type_args),
+        TypeMeta::Primitive(..) => return None,
+    };
+
+    match name.rust {
+        "Box" => {
+            let ty = resolve(type_args.first()?, &args)?;
+            step(Cursor::Type(ty, args), tag, msgs)
+        }
+
+        "List" | "Map" | "Vec" => {
+            let cursor = resolve(type_args.last()?, &args).map(|ty| Cursor::Type(ty, args));
+            Some((format!("[{}]", tag), cursor))
+        }
+
+        "Flags" => match resolve(type_args.first()?, &args)? {
+            TypeMeta::Ref(name, _) => match msgs.get(name.rust)? {
+                MessageMeta::Flags(flags) => {
+                    let flag = flags.flags.iter().find(|flag| flag.bit == tag)?;
+                    Some((format!("[{}]", flag.name.rust), None))
+                }
+
+                _ => None,
+            },
+
+            TypeMeta::Primitive(..) => None,
+        },
+
+        _ => match msgs.get(name.rust)? {
+            MessageMeta::Struct(r#struct) => {
+                field(r#struct, tag, bind(r#struct.type_params, type_args, &args))
+            }
+
+            MessageMeta::Enum(r#enum) => {
+                let variant = r#enum.variants.iter().find(|variant| variant.tag == tag)?;
+                let args = bind(r#enum.type_params, type_args, &args);
+                let cursor = Cursor::Variant(&variant.ty, args);
+                Some((format!("::{}", variant.ty.name.rust), Some(cursor)))
+            }
+
+            MessageMeta::Flags(_) => None,
+        },
+    }
+}
+
+/// Describes `path` from the root `T` by schema names, e.g. `Hero.items[2].count`.
+/// Tags which cannot be resolved are kept as numbers.
+pub fn describe_path<T: HasMeta>(path: &[u32]) -> String {
+    let msgs = gen_util::collect_meta::<T>(|msg| msg.rust_name().to_string());
+    describe_typed::<T>(path, &msgs).0
+}
+
+/// Like `describe_path`, also returning the type at `path` if it is known.
+pub(super) fn describe_typed<T: HasMeta>(
+    path: &[u32],
+    msgs: &HashMap<String, &'static MessageMeta>,
+) -> (String, Option<(&'static TypeMeta, TypeArgs)>) {
+    let mut description = T::NAME.rust.to_string();
+    let mut cursor = Some(Cursor::Type(T::TYPE, Vec::new()));
+
+    for &tag in path {
+        match cursor.and_then(|cursor| step(cursor, tag, msgs)) {
+            Some((segment, next)) => {
+                description.push_str(&segment);
+                cursor = next;
+            }
+
+            None => {
+                description.push_str(&format!(".{}", tag));
+                cursor = None;
+            }
+        }
+    }
+
+    match cursor {
+        Some(Cursor::Type(ty, args)) => (description, Some((ty, args))),
+        _ => (description, None),
+    }
+}
+
+// Finds the tag named `segment` at `cursor`, `None` standing for `*` over a collection.
+fn tag_by_name(
+    cursor: &Cursor,
+    segment: &str,
+    msgs: &HashMap<String, &'static MessageMeta>,
+) -> Option<Option<u32>> {
+    if let Ok(tag) = segment.parse() {
+        return Some(Some(tag));
+    }
+
+    let (mut ty, args) = match cursor {
+        Cursor::Variant(variant, _) => {
+            let field = variant
+                .fields
+                .iter()
+                .find(|field| field.name.rust == segment)?;
+            return Some(Some(field.tag));
+        }
+
+        Cursor::Type(ty, args) => (*ty, args),
+    };
+
+    loop {
+        let (name, type_args) = match ty {
+            TypeMeta::Ref(name, type_args) => (name, *type_args),
+            TypeMeta::Primitive(..) => return None,
+        };
+
+        match name.rust {
+            "Box" => ty = resolve(type_args.first()?, args)?,
+            "List" | "Map" | "Vec" if segment == "*" => return Some(None),
+
+            _ => {
+                return match msgs.get(name.rust)? {
+                    MessageMeta::Struct(r#struct) => r#struct
+                        .fields
+                        .iter()
+                        .find(|field| field.name.rust == segment)
+                        .map(|field| Some(field.tag)),
+
+                    MessageMeta::Enum(r#enum) => r#enum
+                        .variants
+                        .iter()
+                        .find(|variant| variant.ty.name.rust == segment)
+                        .map(|variant| Some(variant.tag)),
+
+                    MessageMeta::Flags(_) => None,
+                };
+            }
+        }
+    }
+}
+
+/// A path whose segments may be `*`, matching any item of a collection.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PathPattern {
+    segments: Vec<Option<u32>>,
+}
+
+impl PathPattern {
+    /// Parses a pattern like `players.*.hp` by the schema names of the root `T`.
+    /// Variants are named like fields, as in `weapon.Sword.damage`, and tags can be used as is.
+    pub fn parse<T: HasMeta>(pattern: &str) -> io::Result<Self> {
+        let msgs = gen_util::collect_meta::<T>(|msg| msg.rust_name().to_string());
+        let mut cursor = Some(Cursor::Type(T::TYPE, Vec::new()));
+        let mut segments = Vec::new();
+
+        for segment in pattern.split('.').filter(|segment| !segment.is_empty()) {
+            let tag = cursor
+                .as_ref()
+                .and_then(|cursor| tag_by_name(cursor, segment, &msgs))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("`{}` not found in `{}::{}`", segment, T::NAME.rust, pattern),
+                    )
+                })?;
+
+            segments.push(tag);
+            // Collections step to their items whatever the tag is.
+            cursor = cursor.and_then(|cursor| {
+                step(cursor, tag.unwrap_or(0), &msgs).and_then(|(_, next)| next)
+            });
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// `None` for wildcards.
+    pub fn segments(&self) -> &[Option<u32>] {
+        &self.segments
+    }
+
+    pub fn matches(&self, path: &[u32]) -> bool {
+        path.len() == self.segments.len() && self.matches_prefix(path)
+    }
+
+    /// Whether `path` matches the start of this pattern, like an ancestor being replaced.
+    pub fn matches_prefix(&self, path: &[u32]) -> bool {
+        path.len() <= self.segments.len()
+            && path
+                .iter()
+                .zip(&self.segments)
+                .all(|(tag, segment)| segment.is_none_or(|segment| segment == *tag))
+    }
+
+    /// Patterns of fields marked with `#[steit(client_writable)]` under the root `T`.
+    /// Recursive types are only searched down to their first recurrence.
+    pub fn client_writable<T: HasMeta>() -> Vec<Self> {
+        let mut walk = WritableWalk {
+            msgs: gen_util::collect_meta::<T>(|msg| msg.rust_name().to_string()),
+            prefix: Vec::new(),
+            stack: Vec::new(),
+            patterns: Vec::new(),
+        };
+
+        walk.visit(T::TYPE, &[]);
+        walk.patterns
+    }
+
+    /// Whether `path` is matched by this pattern or leads into a path which is.
+    pub fn covers(&self, path: &[u32]) -> bool {
+        path.get(..self.segments.len())
+            .is_some_and(|path| self.matches(path))
+    }
+}
+
+struct WritableWalk {
+    msgs: HashMap<String, &'static MessageMeta>,
+    prefix: Vec<Option<u32>>,
+    // Messages being visited, to stop at recursive types.
+    stack: Vec<&'static str>,
+    patterns: Vec<PathPattern>,
+}
+
+impl WritableWalk {
+    fn visit(&mut self, ty: &'static TypeMeta, args: &[(&str, &'static TypeMeta)]) {
+        let (name, type_args) = match ty {
+            TypeMeta::Ref(name, type_args) => (name, *type_args),
+            TypeMeta::Primitive(..) => return,
+        };
+
+        match name.rust {
+            "Box" => {
+                if let Some(ty) = type_args.first().and_then(|arg| resolve(arg, args)) {
+                    self.visit(ty, args);
+                }
+            }
+
+            "List" | "Map" | "Vec" => {
+                if let Some(ty) = type_args.last().and_then(|arg| resolve(arg, args)) {
+                    self.prefix.push(None);
+                    self.visit(ty, args);
+                    self.prefix.pop();
+                }
+            }
+
+            _ => {
+                let msg = match self.msgs.get(name.rust) {
+                    Some(msg) if !self.stack.contains(&name.rust) => *msg,
+                    _ => return,
+                };
+
+                self.stack.push(name.rust);
+
+                match msg {
+                    MessageMeta::Struct(r#struct) => {
+                        let args = bind(r#struct.type_params, type_args, args);
+                        self.visit_fields(r#struct, &args);
+                    }
+
+                    MessageMeta::Enum(r#enum) => {
+                        let args = bind(r#enum.type_params, type_args, args);
+
+                        for variant in r#enum.variants {
+                            self.prefix.push(Some(variant.tag));
+                            self.visit_fields(&variant.ty, &args);
+                            self.prefix.pop();
+                        }
+                    }
+
+                    MessageMeta::Flags(_) => (),
+                }
+
+                self.stack.pop();
+            }
+        }
+    }
+
+    fn visit_fields(&mut self, r#struct: &'static StructMeta, args: &[(&str, &'static TypeMeta)]) {
+        for field in r#struct.fields {
+            self.prefix.push(Some(field.tag));
+
+            if field.client_writable {
+                self.patterns.push(PathPattern {
+                    segments: self.prefix.clone(),
+                });
+            } else if let Some(ty) = resolve(field.ty, args) {
+                self.visit(ty, args);
+            }
+
+            self.prefix.pop();
+        }
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<T> From<&Path<T>> for PathPattern {
+    fn from(path: &Path<T>) -> Self {
+        Self {
+            segments: path.segments().iter().copied().map(Some).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        flags, steit_derive,
+        test_util::Point,
+        types::{Flags, List},
+    };
+
+    use super::{describe_path, PathPattern};
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Squad {
+        #[steit(tag = 0)]
+        leader: Point,
+        #[steit(tag = 1)]
+        members: List<Point>,
+        #[steit(tag = 2)]
+        order: Order<Point>,
+        #[steit(tag = 3)]
+        formation: Flags<Formation>,
+    }
+
+    flags! {
+        #[allow(dead_code)]
+        enum Formation {
+            Tight = 0,
+            Hidden = 3,
+        }
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    enum Order<T> {
+        #[steit(tag = 0)]
+        Hold,
+        #[steit(tag = 1)]
+        Move(#[steit(tag = 0)] T),
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Lobby {
+        #[steit(tag = 0)]
+        seats: List<Seat>,
+        #[steit(tag = 1, client_writable)]
+        ready: bool,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Seat {
+        #[steit(tag = 0)]
+        squad: Squad,
+        #[steit(tag = 1, client_writable)]
+        emote: u32,
+        #[steit(tag = 2)]
+        order: Order<Banner>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Banner {
+        #[steit(tag = 0, client_writable)]
+        color: u32,
+    }
+
+    #[test]
+    fn describe_struct() {
+        assert_eq!(describe_path::<Squad>(&[]), "Squad");
+        assert_eq!(describe_path::<Squad>(&[0, 1]), "Squad.leader.y");
+        assert_eq!(describe_path::<Squad>(&[1, 3, 2]), "Squad.members[3].z");
+    }
+
+    #[test]
+    fn describe_flags() {
+        assert_eq!(describe_path::<Squad>(&[3, 3]), "Squad.formation[Hidden]");
+        assert_eq!(describe_path::<Squad>(&[3, 1]), "Squad.formation.1");
+    }
+
+    #[test]
+    fn describe_enum() {
+        assert_eq!(describe_path::<Squad>(&[2, 1]), "Squad.order::Move");
+        assert_eq!(
+            describe_path::<Squad>(&[2, 1, 0, 0]),
+            "Squad.order::Move.f0.x"
+        );
+    }
+
+    #[test]
+    fn describe_unknown() {
+        assert_eq!(describe_path::<Squad>(&[7, 1]), "Squad.7.1");
+        assert_eq!(describe_path::<Squad>(&[0, 0, 5]), "Squad.leader.x.5");
+    }
+
+    #[test]
+    fn parse_pattern() {
+        let pattern = PathPattern::parse::<Squad>("members.*.y").unwrap();
+        assert_eq!(pattern.segments(), [Some(1), None, Some(1)]);
+        assert!(pattern.matches(&[1, 7, 1]));
+        assert!(!pattern.matches(&[1, 7, 2]));
+        assert!(pattern.matches_prefix(&[1, 7]));
+
+        let pattern = PathPattern::parse::<Squad>("order.Move.0.x").unwrap();
+        assert_eq!(pattern.segments(), [Some(2), Some(1), Some(0), Some(0)]);
+
+        assert!(PathPattern::parse::<Squad>("leader.*").is_err());
+        assert!(PathPattern::parse::<Squad>("members.*.w").is_err());
+    }
+
+    #[test]
+    fn client_writable() {
+        let patterns = PathPattern::client_writable::<Lobby>();
+        let segments: Vec<_> = patterns.iter().map(PathPattern::segments).collect();
+
+        assert_eq!(
+            segments,
+            [
+                &[Some(0), None, Some(1)][..],
+                &[Some(0), None, Some(2), Some(1), Some(0), Some(0)],
+                &[Some(1)],
+            ]
+        );
+    }
+}
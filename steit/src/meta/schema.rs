@@ -0,0 +1,310 @@
+use std::io;
+
+use crate::wire_fmt::WireType;
+
+use super::{
+    meta::HasMeta,
+    msg::{MessageMeta, StructMeta},
+    path::{bind, resolve},
+    r#type::TypeMeta,
+    value::{type_arg, unsupported, Decoder},
+};
+
+/// Hashes the wire layout of `T`, which is every tag and wire type reachable from it.
+/// Names are left out, so renaming fields or types keeps the hash.
+///
+/// The hash is stable across builds and platforms, so it can be pinned in code
+/// to catch changes breaking other decoders of `T`.
+pub fn schema_hash<T: HasMeta>() -> u64 {
+    let mut walker = Walker {
+        decoder: Decoder::new::<T>(false),
+        visited: Vec::new(),
+        layout: String::new(),
+    };
+
+    // Types without meta are hashed by name only.
+    let _ = walker.walk(T::TYPE, &[]);
+
+    // FNV-1a
+    walker
+        .layout
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Checks that `T` encodes the same as a protobuf message with the same tags,
+/// with signed integers as `sint32`/`sint64` and lists of numbers as packed repeated fields.
+///
+/// Maps, options, enums, lists of messages and fields tagged 0 have no protobuf counterpart,
+/// so they are rejected.
+pub fn check_protobuf_layout<T: HasMeta>() -> io::Result<()> {
+    let mut walker = Walker {
+        decoder: Decoder::new::<T>(false),
+        visited: Vec::new(),
+        layout: String::new(),
+    };
+
+    walker.check(T::TYPE, &[], T::NAME.rust)
+}
+
+struct Walker {
+    decoder: Decoder,
+    visited: Vec<String>,
+    layout: String,
+}
+
+fn incompatible(name: &str, reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("`{}` is not laid out like protobuf, {}", name, reason),
+    )
+}
+
+impl Walker {
+    fn walk_fields(
+        &mut self,
+        r#struct: &'static StructMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<()> {
+        self.layout.push('{');
+
+        for field in r#struct.fields {
+            let ty = resolve(field.ty, args).ok_or_else(|| unsupported(field.name.rust))?;
+            let wire_type = self.decoder.wire_type(ty, args);
+            self.layout
+                .push_str(&format!("{}:{}=", field.tag, wire_type.value()));
+            self.walk(ty, args)?;
+            self.layout.push(';');
+        }
+
+        self.layout.push('}');
+        Ok(())
+    }
+
+    fn walk(
+        &mut self,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<()> {
+        let (name, type_args) = match ty {
+            TypeMeta::Primitive(name, _) => {
+                self.layout.push_str(name.rust);
+                return Ok(());
+            }
+
+            TypeMeta::Ref(name, type_args) => (name.rust, *type_args),
+        };
+
+        match name {
+            "Box" => self.walk(type_arg(type_args, args)?, args),
+
+            "Vec" | "List" | "Map" | "Option" | "Flags" => {
+                self.layout.push_str(&format!("{}<", name));
+
+                if name != "Flags" {
+                    self.walk(type_arg(type_args, args)?, args)?;
+                }
+
+                self.layout.push('>');
+                Ok(())
+            }
+
+            _ => {
+                let msg = self.decoder.msg(name)?;
+
+                // Recursive types are cut at their first repetition.
+                let key = format!("{}{:?}", name, args);
+
+                if let Some(index) = self.visited.iter().position(|visited| *visited == key) {
+                    self.layout.push_str(&format!("@{}", index));
+                    return Ok(());
+                }
+
+                self.visited.push(key);
+
+                match msg {
+                    MessageMeta::Struct(r#struct) => {
+                        let args = bind(r#struct.type_params, type_args, args);
+                        self.walk_fields(r#struct, &args)
+                    }
+
+                    MessageMeta::Enum(r#enum) => {
+                        let args = bind(r#enum.type_params, type_args, args);
+                        self.layout.push('(');
+
+                        for variant in r#enum.variants {
+                            self.layout.push_str(&variant.tag.to_string());
+
+                            // Only defaults other than tag 0 are marked,
+                            // which keeps hashes of enums without `#[steit(default)]` as they were.
+                            if variant.is_default && variant.tag != 0 {
+                                self.layout.push('*');
+                            }
+
+                            self.walk_fields(&variant.ty, &args)?;
+                        }
+
+                        self.layout.push(')');
+                        Ok(())
+                    }
+
+                    MessageMeta::Flags(_) => Ok(()),
+                }
+            }
+        }
+    }
+
+    fn check(
+        &mut self,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+        field: &str,
+    ) -> io::Result<()> {
+        let (name, type_args) = match ty {
+            TypeMeta::Primitive(..) => return Ok(()),
+            TypeMeta::Ref(name, type_args) => (name.rust, *type_args),
+        };
+
+        match name {
+            "Box" => self.check(type_arg(type_args, args)?, args, field),
+            "Flags" => Ok(()),
+
+            "Vec" | "List" => {
+                let ty = type_arg(type_args, args)?;
+
+                match self.decoder.wire_type(ty, args) {
+                    WireType::Varint | WireType::Fixed32 | WireType::Fixed64 => Ok(()),
+                    WireType::Sized => Err(incompatible(field, "as its items are not numbers")),
+                }
+            }
+
+            "Map" => Err(incompatible(field, "as maps are keyed by tags")),
+            "Option" => Err(incompatible(field, "as options are nested")),
+
+            _ => match self.decoder.msg(name)? {
+                MessageMeta::Struct(r#struct) => {
+                    let key = format!("{}{:?}", name, args);
+
+                    if self.visited.contains(&key) {
+                        return Ok(());
+                    }
+
+                    self.visited.push(key);
+
+                    let args = bind(r#struct.type_params, type_args, args);
+
+                    for field in r#struct.fields {
+                        let ty =
+                            resolve(field.ty, &args).ok_or_else(|| unsupported(field.name.rust))?;
+                        let name = format!("{}.{}", r#struct.name.rust, field.name.rust);
+
+                        if field.tag == 0 {
+                            return Err(incompatible(&name, "as field numbers start at 1"));
+                        }
+
+                        self.check(ty, &args, &name)?;
+                    }
+
+                    Ok(())
+                }
+
+                MessageMeta::Enum(_) => Err(incompatible(field, "as enums have no oneof tags")),
+                MessageMeta::Flags(_) => Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        steit_derive,
+        test_util::Point,
+        types::{List, Map},
+    };
+
+    use super::{check_protobuf_layout, schema_hash};
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Hero {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        position: Point,
+        #[steit(tag = 2)]
+        items: List<u32>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct RenamedHero {
+        #[steit(tag = 0)]
+        title: String,
+        #[steit(tag = 1)]
+        location: Point,
+        #[steit(tag = 2)]
+        bag: List<u32>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct MovedHero {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 3)]
+        position: Point,
+        #[steit(tag = 2)]
+        items: List<u32>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Party {
+        #[steit(tag = 1)]
+        gold: List<u32>,
+        #[steit(tag = 2)]
+        heroes: List<Hero>,
+        #[steit(tag = 3)]
+        scores: Map<u32, u32>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Squad {
+        #[steit(tag = 1)]
+        heroes: List<u32>,
+        #[steit(tag = 2)]
+        scores: Map<u32, u32>,
+    }
+
+    #[test]
+    fn hash_ignores_names() {
+        assert_eq!(schema_hash::<Hero>(), schema_hash::<RenamedHero>());
+        assert_ne!(schema_hash::<Hero>(), schema_hash::<MovedHero>());
+        assert_ne!(schema_hash::<Hero>(), schema_hash::<Point>());
+    }
+
+    #[test]
+    fn protobuf_layout() {
+        let error = check_protobuf_layout::<Hero>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`Hero.name` is not laid out like protobuf, as field numbers start at 1"
+        );
+
+        let error = check_protobuf_layout::<Party>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`Party.heroes` is not laid out like protobuf, as its items are not numbers"
+        );
+
+        let error = check_protobuf_layout::<Squad>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`Squad.scores` is not laid out like protobuf, as maps are keyed by tags"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::{collections::BTreeMap, io};
+
+use crate::{
+    de::{Deserialize, Reader},
+    ser::Serialize,
+    wire_fmt::{WireType, WIRE_TYPE_MASK},
+};
+
+use super::{
+    meta::HasMeta,
+    msg::{MessageMeta, StructMeta},
+    path::{bind, resolve},
+    r#type::TypeMeta,
+    value::{type_arg, unsupported, Decoder, Value},
+};
+
+/// Starts the body of a struct laid out in preorder.
+/// It reads as field 0 with wire type 7, which never occurs in the tagged layout.
+pub(super) const PREORDER_MARKER: u32 = WIRE_TYPE_MASK;
+
+/// How a snapshot is laid out, stored in its first byte.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SnapshotLayout {
+    /// The usual encoding, as produced by `Serialize`.
+    Tagged = 0,
+    /// Structs with every field set are written in preorder, without field tags.
+    /// Other structs are tagged as usual, since their defaults are omitted.
+    /// Decoding it requires the exact schema the snapshot was written with.
+    Compact = 1,
+}
+
+impl SnapshotLayout {
+    pub fn from_value(value: u8) -> io::Result<Self> {
+        match value {
+            0 => Ok(SnapshotLayout::Tagged),
+            1 => Ok(SnapshotLayout::Compact),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown snapshot layout {}", value),
+            )),
+        }
+    }
+}
+
+/// Writes a full snapshot of `value`, headed by its layout.
+pub fn write_snapshot<T: Serialize + HasMeta>(
+    value: &T,
+    layout: SnapshotLayout,
+) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![layout as u8];
+
+    match layout {
+        SnapshotLayout::Tagged => value.steit_serialize(&mut bytes)?,
+        SnapshotLayout::Compact => {
+            let value = Decoder::new::<T>(false).decode(T::TYPE, &value.to_bytes())?;
+            Encoder::new::<T>(true).encode(&value, T::TYPE, &[], &mut bytes)?;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Reads a snapshot written by `write_snapshot` in any layout.
+pub fn read_snapshot<T: Deserialize + HasMeta>(bytes: &[u8]) -> io::Result<T> {
+    let (&layout, body) = bytes
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "missing snapshot header"))?;
+
+    match SnapshotLayout::from_value(layout)? {
+        SnapshotLayout::Tagged => T::deserialize(&mut Reader::new(body)),
+        SnapshotLayout::Compact => {
+            let value = Decoder::new::<T>(true).decode(T::TYPE, body)?;
+            let mut bytes = Vec::new();
+            Encoder::new::<T>(false).encode(&value, T::TYPE, &[], &mut bytes)?;
+            T::deserialize(&mut Reader::new(&*bytes))
+        }
+    }
+}
+
+/// Writes values decoded by `Decoder` back, in either layout.
+struct Encoder {
+    decoder: Decoder,
+}
+
+fn mismatch(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("value does not match the meta of `{}`", name),
+    )
+}
+
+impl Encoder {
+    fn new<T: HasMeta>(compact: bool) -> Self {
+        Self {
+            decoder: Decoder::new::<T>(compact),
+        }
+    }
+
+    fn encode_nested(
+        &self,
+        value: &Value,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+        field_number: Option<u32>,
+        writer: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let wire_type = self.decoder.wire_type(ty, args);
+
+        if let Some(field_number) = field_number {
+            wire_type.tag(field_number)?.steit_serialize(writer)?;
+        }
+
+        match wire_type {
+            WireType::Varint | WireType::Fixed32 | WireType::Fixed64 => {
+                self.encode(value, ty, args, writer)
+            }
+            WireType::Sized => {
+                let mut bytes = Vec::new();
+                self.encode(value, ty, args, &mut bytes)?;
+                (bytes.len() as u32).steit_serialize(writer)?;
+                writer.extend(bytes);
+                Ok(())
+            }
+        }
+    }
+
+    fn encode_fields(
+        &self,
+        fields: &BTreeMap<u32, Value>,
+        r#struct: &'static StructMeta,
+        args: &[(&str, &'static TypeMeta)],
+        writer: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let mut resolved = Vec::with_capacity(r#struct.fields.len());
+        let mut full = !r#struct.fields.is_empty();
+
+        for field in r#struct.fields {
+            let ty = resolve(field.ty, args).ok_or_else(|| unsupported(field.name.rust))?;
+            let value = fields
+                .get(&field.tag)
+                .ok_or_else(|| mismatch(r#struct.name.rust))?;
+            let is_default = *value == self.decoder.default(ty, args)?;
+
+            full &= !is_default;
+            resolved.push((field.tag, ty, value, is_default));
+        }
+
+        if self.decoder.compact() && full {
+            PREORDER_MARKER.steit_serialize(writer)?;
+
+            for (_, ty, value, _) in resolved {
+                self.encode_nested(value, ty, args, None, writer)?;
+            }
+        } else {
+            for (tag, ty, value, is_default) in resolved {
+                if !is_default {
+                    self.encode_nested(value, ty, args, Some(tag), writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode(
+        &self,
+        value: &Value,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+        writer: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let (name, type_args) = match ty {
+            TypeMeta::Primitive(name, _) => {
+                return match value {
+                    Value::Bool(value) => value.steit_serialize(writer),
+                    Value::UInt(value) => value.steit_serialize(writer),
+                    Value::Int(value) => value.steit_serialize(writer),
+                    Value::String(value) => value.steit_serialize(writer),
+                    Value::Float(value) if name.rust == "f32" => {
+                        (*value as f32).steit_serialize(writer)
+                    }
+                    Value::Float(value) => value.steit_serialize(writer),
+                    _ => Err(mismatch(name.rust)),
+                };
+            }
+
+            TypeMeta::Ref(name, type_args) => (name, *type_args),
+        };
+
+        match (name.rust, value) {
+            ("Box", value) => self.encode(value, type_arg(type_args, args)?, args, writer),
+
+            ("Vec", Value::List(items)) | ("List", Value::List(items)) => {
+                let ty = type_arg(type_args, args)?;
+
+                for item in items {
+                    self.encode_nested(item, ty, args, None, writer)?;
+                }
+
+                Ok(())
+            }
+
+            ("Map", Value::Map(entries)) => {
+                let ty = type_arg(type_args, args)?;
+
+                for (&key, value) in entries {
+                    self.encode_nested(value, ty, args, Some(key), writer)?;
+                }
+
+                Ok(())
+            }
+
+            ("Option", Value::Option(option)) => match option {
+                Some(value) => {
+                    let ty = type_arg(type_args, args)?;
+                    self.encode_nested(value, ty, args, None, writer)
+                }
+
+                None => Ok(()),
+            },
+
+            ("Flags", Value::Flags(bits)) => bits.steit_serialize(writer),
+
+            (_, value) => match (self.decoder.msg(name.rust)?, value) {
+                (MessageMeta::Struct(r#struct), Value::Struct(fields)) => {
+                    let args = bind(r#struct.type_params, type_args, args);
+                    self.encode_fields(fields, r#struct, &args, writer)
+                }
+
+                (MessageMeta::Enum(r#enum), Value::Variant(tag, fields)) => {
+                    let variant = r#enum
+                        .variants
+                        .iter()
+                        .find(|variant| variant.tag == *tag)
+                        .ok_or_else(|| mismatch(name.rust))?;
+
+                    let args = bind(r#enum.type_params, type_args, args);
+                    tag.steit_serialize(writer)?;
+                    self.encode_fields(fields, &variant.ty, &args, writer)
+                }
+
+                _ => Err(mismatch(name.rust)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::Runtime,
+        ser::Serialize,
+        steit_derive,
+        test_util::Point,
+        types::{List, Maybe},
+    };
+
+    use super::{read_snapshot, write_snapshot, SnapshotLayout};
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct World {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        points: List<Point>,
+        #[steit(tag = 2)]
+        boss: Maybe<Point>,
+    }
+
+    fn world() -> World {
+        let mut world = World::new(Runtime::new());
+        world.set_name("wild".to_string());
+
+        for x in 1..=3 {
+            world
+                .points
+                .push_with(|runtime| Point::new(runtime, x, -x, x * 2));
+        }
+
+        // Not fully populated, so it stays tagged.
+        world
+            .points
+            .push_with(|runtime| Point::new(runtime, 0, 5, 0));
+
+        let boss = Maybe::some(Runtime::new(), Point::new(Runtime::new(), 7, 7, 7));
+        world.set_boss(boss);
+        world
+    }
+
+    #[test]
+    fn tagged_roundtrip() {
+        let world = world();
+        let bytes = write_snapshot(&world, SnapshotLayout::Tagged).unwrap();
+        assert_eq!(bytes[0], 0);
+        assert_eq!(&bytes[1..], &*world.to_bytes());
+        assert_eq!(read_snapshot::<World>(&bytes).unwrap(), world);
+    }
+
+    #[test]
+    fn compact_roundtrip() {
+        let world = world();
+        let tagged = write_snapshot(&world, SnapshotLayout::Tagged).unwrap();
+        let compact = write_snapshot(&world, SnapshotLayout::Compact).unwrap();
+
+        assert_eq!(compact[0], 1);
+        assert!(compact.len() < tagged.len());
+        assert_eq!(read_snapshot::<World>(&compact).unwrap(), world);
+    }
+
+    #[test]
+    fn compact_preorder_point() {
+        let point = Point::new(Runtime::new(), 1, 2, 3);
+        let bytes = write_snapshot(&point, SnapshotLayout::Compact).unwrap();
+        assert_eq!(bytes, &[1, 7, 2, 4, 6]);
+
+        let point = Point::new(Runtime::new(), 0, 2, 0);
+        let bytes = write_snapshot(&point, SnapshotLayout::Compact).unwrap();
+        assert_eq!(bytes, &[1, 8, 4]);
+    }
+
+    #[test]
+    fn compact_marker_rejected_in_tagged() {
+        assert!(read_snapshot::<Point>(&[0, 7, 2, 4, 6]).is_err());
+        assert!(read_snapshot::<Point>(&[1, 7, 2, 4]).is_err());
+    }
+
+    #[test]
+    fn unknown_layout() {
+        let error = read_snapshot::<Point>(&[2]).unwrap_err();
+        assert_eq!(error.to_string(), "unknown snapshot layout 2");
+        assert!(read_snapshot::<Point>(&[]).is_err());
+    }
+}
@@ -0,0 +1,548 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap},
+    io::{self, Read},
+};
+
+use crate::{
+    de::{self, Deserialize, Reader},
+    gen::gen_util,
+    wire_fmt::{self, WireType},
+};
+
+use super::{
+    meta::HasMeta,
+    msg::{FieldMeta, MessageMeta, RangeMeta, StructMeta},
+    path::{bind, resolve, TypeArgs},
+    r#type::{FieldTypeMeta, TypeMeta},
+    snapshot::PREORDER_MARKER,
+};
+
+/// A value decoded by walking the schema rather than by derived code.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<u32, Value>),
+    Option(Option<Box<Value>>),
+    Struct(BTreeMap<u32, Value>),
+    Variant(u32, BTreeMap<u32, Value>),
+    Flags(u64),
+}
+
+/// Decodes `bytes` as a `T` using only its meta,
+/// following the same merge rules as derived deserializers.
+///
+/// Keys of `Map`s are kept as field numbers,
+/// since their types are not part of the meta.
+pub fn decode_value<T: HasMeta>(bytes: &[u8]) -> io::Result<Value> {
+    Decoder::new::<T>(false).decode(T::TYPE, bytes)
+}
+
+pub(super) struct Decoder {
+    msgs: HashMap<String, &'static MessageMeta>,
+    // Whether structs may be laid out in preorder, see `snapshot`.
+    compact: bool,
+}
+
+pub(super) fn unsupported(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("cannot decode `{}` without its meta", name),
+    )
+}
+
+pub(super) fn type_arg(
+    type_args: &'static [FieldTypeMeta],
+    args: &[(&str, &'static TypeMeta)],
+) -> io::Result<&'static TypeMeta> {
+    type_args
+        .last()
+        .and_then(|ty| resolve(ty, args))
+        .ok_or_else(|| unsupported("type parameter"))
+}
+
+fn constrain(field: &FieldMeta, value: &mut Value) -> io::Result<()> {
+    let name = field.name.rust;
+
+    if let Some(max_len) = field.max_len {
+        let len = match value {
+            Value::String(value) => value.len(),
+            Value::List(items) => items.len(),
+            Value::Map(entries) => entries.len(),
+            _ => return Ok(()),
+        };
+
+        de::validate_max_len(name, len, max_len)?;
+    }
+
+    if let Some(RangeMeta { min, max, clamp }) = field.range {
+        match value {
+            Value::UInt(value) => {
+                *value = de::check_range(name, *value as i128, min, max, clamp)? as u64;
+            }
+
+            Value::Int(value) => {
+                *value = de::check_range(name, *value as i128, min, max, clamp)? as i64;
+            }
+
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+impl Decoder {
+    pub(super) fn new<T: HasMeta>(compact: bool) -> Self {
+        Self {
+            msgs: gen_util::collect_meta::<T>(|msg| msg.rust_name().to_string()),
+            compact,
+        }
+    }
+
+    pub(super) fn decode(&self, ty: &'static TypeMeta, bytes: &[u8]) -> io::Result<Value> {
+        self.decode_with(ty, &[], bytes)
+    }
+
+    pub(super) fn decode_with(
+        &self,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+        bytes: &[u8],
+    ) -> io::Result<Value> {
+        let mut value = self.default(ty, args)?;
+        self.merge(&mut value, ty, args, &mut Reader::new(bytes))?;
+        Ok(value)
+    }
+
+    pub(super) fn msgs(&self) -> &HashMap<String, &'static MessageMeta> {
+        &self.msgs
+    }
+
+    pub(super) fn compact(&self) -> bool {
+        self.compact
+    }
+
+    pub(super) fn msg(&self, name: &str) -> io::Result<&'static MessageMeta> {
+        self.msgs
+            .get(name)
+            .copied()
+            .ok_or_else(|| unsupported(name))
+    }
+
+    pub(super) fn wire_type(
+        &self,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> WireType {
+        match ty {
+            TypeMeta::Primitive(_, wire_type) => *wire_type,
+            TypeMeta::Ref(name, type_args) => match name.rust {
+                "Box" => match type_arg(type_args, args) {
+                    Ok(ty) => self.wire_type(ty, args),
+                    Err(_) => WireType::Sized,
+                },
+
+                "Flags" => WireType::Varint,
+                _ => WireType::Sized,
+            },
+        }
+    }
+
+    fn fields(
+        &self,
+        r#struct: &'static StructMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<BTreeMap<u32, Value>> {
+        let mut fields = BTreeMap::new();
+
+        for field in r#struct.fields {
+            let ty = resolve(field.ty, args).ok_or_else(|| unsupported(field.name.rust))?;
+            fields.insert(field.tag, self.default(ty, args)?);
+        }
+
+        Ok(fields)
+    }
+
+    pub(super) fn default(
+        &self,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<Value> {
+        let (name, type_args) = match ty {
+            TypeMeta::Primitive(name, _) => {
+                return match name.rust {
+                    "bool" => Ok(Value::Bool(false)),
+                    "u8" | "u16" | "u32" | "u64" => Ok(Value::UInt(0)),
+                    "i8" | "i16" | "i32" | "i64" => Ok(Value::Int(0)),
+                    "f32" | "f64" => Ok(Value::Float(0.0)),
+                    "String" => Ok(Value::String(String::new())),
+                    _ => Err(unsupported(name.rust)),
+                };
+            }
+
+            TypeMeta::Ref(name, type_args) => (name, *type_args),
+        };
+
+        match name.rust {
+            "Box" => self.default(type_arg(type_args, args)?, args),
+            "Vec" | "List" => Ok(Value::List(Vec::new())),
+            "Map" => Ok(Value::Map(BTreeMap::new())),
+            "Option" => Ok(Value::Option(None)),
+            "Flags" => Ok(Value::Flags(0)),
+
+            _ => match self.msg(name.rust)? {
+                MessageMeta::Struct(r#struct) => {
+                    let args = bind(r#struct.type_params, type_args, args);
+                    Ok(Value::Struct(self.fields(r#struct, &args)?))
+                }
+
+                MessageMeta::Enum(r#enum) => {
+                    let variant = r#enum
+                        .variants
+                        .iter()
+                        .find(|variant| variant.default())
+                        .ok_or_else(|| unsupported(name.rust))?;
+
+                    let args = bind(r#enum.type_params, type_args, args);
+                    Ok(Value::Variant(
+                        variant.tag,
+                        self.fields(&variant.ty, &args)?,
+                    ))
+                }
+
+                MessageMeta::Flags(_) => Err(unsupported(name.rust)),
+            },
+        }
+    }
+
+    fn merge_nested(
+        &self,
+        value: &mut Value,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+        wire_type: WireType,
+        reader: &mut Reader<&[u8]>,
+    ) -> io::Result<()> {
+        if wire_type != self.wire_type(ty, args) {
+            // Like `Vec<T>`, items of a `Vec` field can come unpacked, one per occurrence.
+            if let (TypeMeta::Ref(name, type_args), Value::List(items)) = (ty, &mut *value) {
+                let item_ty = type_arg(type_args, args)?;
+
+                if name.rust == "Vec" && wire_type == self.wire_type(item_ty, args) {
+                    let mut item = self.default(item_ty, args)?;
+                    self.merge(&mut item, item_ty, args, reader)?;
+                    items.push(item);
+                    return Ok(());
+                }
+            }
+
+            return reader.skip_field(wire_type);
+        }
+
+        match wire_type {
+            WireType::Varint | WireType::Fixed32 | WireType::Fixed64 => {
+                self.merge(value, ty, args, reader)
+            }
+            WireType::Sized => {
+                // Nested bytes are buffered so that the reader type stays the same while recursing.
+                let size = u64::deserialize(reader)?;
+                let mut bytes = Vec::new();
+                reader.by_ref().take(size).read_to_end(&mut bytes)?;
+                self.merge(value, ty, args, &mut Reader::new(&*bytes))
+            }
+        }
+    }
+
+    fn merge_fields(
+        &self,
+        fields: &mut BTreeMap<u32, Value>,
+        r#struct: &'static StructMeta,
+        args: &[(&str, &'static TypeMeta)],
+        reader: &mut Reader<&[u8]>,
+    ) -> io::Result<()> {
+        let mut first = true;
+
+        while !reader.eof()? {
+            let key = u32::deserialize(reader)?;
+
+            if self.compact && first && key == PREORDER_MARKER {
+                return self.merge_preorder(fields, r#struct, args, reader);
+            }
+
+            first = false;
+            let (tag, wire_type) = wire_fmt::parse_tag(key)?;
+
+            match r#struct.fields.iter().find(|field| field.tag == tag) {
+                Some(field) => {
+                    let ty = resolve(field.ty, args).ok_or_else(|| unsupported(field.name.rust))?;
+                    let value = self.field_value(fields, tag, ty, args)?;
+                    self.merge_nested(value, ty, args, wire_type, reader)?;
+                    constrain(field, value)?;
+                }
+
+                None => reader.skip_field(wire_type)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The value of the field with `tag`, which all start as defaults.
+    fn field_value<'a>(
+        &self,
+        fields: &'a mut BTreeMap<u32, Value>,
+        tag: u32,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+    ) -> io::Result<&'a mut Value> {
+        match fields.entry(tag) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(self.default(ty, args)?)),
+        }
+    }
+
+    fn merge_preorder(
+        &self,
+        fields: &mut BTreeMap<u32, Value>,
+        r#struct: &'static StructMeta,
+        args: &[(&str, &'static TypeMeta)],
+        reader: &mut Reader<&[u8]>,
+    ) -> io::Result<()> {
+        for field in r#struct.fields {
+            let ty = resolve(field.ty, args).ok_or_else(|| unsupported(field.name.rust))?;
+            let value = self.field_value(fields, field.tag, ty, args)?;
+            self.merge_nested(value, ty, args, self.wire_type(ty, args), reader)?;
+            constrain(field, value)?;
+        }
+
+        if !reader.eof()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "trailing bytes after the fields of `{}`",
+                    r#struct.name.rust
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn merge(
+        &self,
+        value: &mut Value,
+        ty: &'static TypeMeta,
+        args: &[(&str, &'static TypeMeta)],
+        reader: &mut Reader<&[u8]>,
+    ) -> io::Result<()> {
+        let (name, type_args) = match ty {
+            TypeMeta::Primitive(name, _) => {
+                *value = match name.rust {
+                    "bool" => Value::Bool(bool::deserialize(reader)?),
+                    "u8" => Value::UInt(u8::deserialize(reader)? as u64),
+                    "u16" => Value::UInt(u16::deserialize(reader)? as u64),
+                    "u32" => Value::UInt(u32::deserialize(reader)? as u64),
+                    "u64" => Value::UInt(u64::deserialize(reader)?),
+                    "i8" => Value::Int(i8::deserialize(reader)? as i64),
+                    "i16" => Value::Int(i16::deserialize(reader)? as i64),
+                    "i32" => Value::Int(i32::deserialize(reader)? as i64),
+                    "i64" => Value::Int(i64::deserialize(reader)?),
+                    "f32" => Value::Float(f32::deserialize(reader)? as f64),
+                    "f64" => Value::Float(f64::deserialize(reader)?),
+                    "String" => Value::String(String::deserialize(reader)?),
+                    _ => return Err(unsupported(name.rust)),
+                };
+
+                return Ok(());
+            }
+
+            TypeMeta::Ref(name, type_args) => (name, *type_args),
+        };
+
+        match (name.rust, value) {
+            ("Box", value) => self.merge(value, type_arg(type_args, args)?, args, reader),
+
+            ("Vec", Value::List(items)) | ("List", Value::List(items)) => {
+                let ty = type_arg(type_args, args)?;
+                let wire_type = self.wire_type(ty, args);
+
+                while !reader.eof()? {
+                    let mut item = self.default(ty, args)?;
+                    self.merge_nested(&mut item, ty, args, wire_type, reader)?;
+                    items.push(item);
+                }
+
+                Ok(())
+            }
+
+            ("Map", Value::Map(entries)) => {
+                let ty = type_arg(type_args, args)?;
+
+                while !reader.eof()? {
+                    let (key, wire_type) = reader.read_tag()?;
+
+                    let value = match entries.entry(key) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(self.default(ty, args)?),
+                    };
+
+                    self.merge_nested(value, ty, args, wire_type, reader)?;
+                }
+
+                Ok(())
+            }
+
+            ("Option", Value::Option(option)) => {
+                let ty = type_arg(type_args, args)?;
+                let wire_type = self.wire_type(ty, args);
+
+                while !reader.eof()? {
+                    if option.is_none() {
+                        *option = Some(Box::new(self.default(ty, args)?));
+                    }
+
+                    if let Some(value) = option {
+                        self.merge_nested(value, ty, args, wire_type, reader)?;
+                    }
+                }
+
+                Ok(())
+            }
+
+            ("Flags", value) => {
+                *value = Value::Flags(u64::deserialize(reader)?);
+                Ok(())
+            }
+
+            (_, value) => match (self.msg(name.rust)?, value) {
+                (MessageMeta::Struct(r#struct), Value::Struct(fields)) => {
+                    let args = bind(r#struct.type_params, type_args, args);
+                    self.merge_fields(fields, r#struct, &args, reader)
+                }
+
+                (MessageMeta::Enum(r#enum), value) => {
+                    let tag = u32::deserialize(reader)?;
+
+                    let variant = r#enum
+                        .variants
+                        .iter()
+                        .find(|variant| variant.tag == tag)
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("unknown variant tag {}", tag),
+                            )
+                        })?;
+
+                    let args: TypeArgs = bind(r#enum.type_params, type_args, args);
+
+                    match value {
+                        Value::Variant(current, _) if *current == tag => (),
+                        _ => *value = Value::Variant(tag, self.fields(&variant.ty, &args)?),
+                    }
+
+                    match value {
+                        Value::Variant(_, fields) => {
+                            self.merge_fields(fields, &variant.ty, &args, reader)
+                        }
+
+                        _ => unreachable!(),
+                    }
+                }
+
+                _ => Err(unsupported(name.rust)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        ser::Serialize,
+        state::State,
+        steit_derive,
+        test_util::{Foo, Point},
+        types::{List, Map, Maybe},
+    };
+
+    use super::{decode_value, Value};
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Camp {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        tents: List<Point>,
+        #[steit(tag = 2)]
+        supplies: Map<u32, u8>,
+        #[steit(tag = 3)]
+        watch: Maybe<u16>,
+    }
+
+    fn fields(fields: Vec<(u32, Value)>) -> BTreeMap<u32, Value> {
+        fields.into_iter().collect()
+    }
+
+    fn point(x: i64, y: i64, z: i64) -> Value {
+        Value::Struct(fields(vec![
+            (0, Value::Int(x)),
+            (1, Value::Int(y)),
+            (2, Value::Int(z)),
+        ]))
+    }
+
+    #[test]
+    fn decode_struct() {
+        let mut camp = Camp::new(Default::default());
+        let watch = Maybe::some(camp.watch.runtime().clone(), 7);
+        camp.set_name("north".to_string()).set_watch(watch);
+        camp.tents
+            .push_with(|runtime| Point::new(runtime, 1, -2, 3));
+        camp.supplies.insert(4, 9);
+
+        let expected = Value::Struct(fields(vec![
+            (0, Value::String("north".to_string())),
+            (1, Value::List(vec![point(1, -2, 3)])),
+            (2, Value::Map(fields(vec![(4, Value::UInt(9))]))),
+            (3, Value::Variant(1, fields(vec![(0, Value::UInt(7))]))),
+        ]));
+
+        assert_eq!(decode_value::<Camp>(&camp.to_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn decode_option() {
+        let foo = Some(Foo::new(-1, 2));
+        let expected = Value::Option(Some(Box::new(Value::Struct(fields(vec![
+            (0, Value::Int(-1)),
+            (1, Value::Int(2)),
+        ])))));
+
+        assert_eq!(
+            decode_value::<Option<Foo>>(&foo.to_bytes()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            decode_value::<Option<Foo>>(&[]).unwrap(),
+            Value::Option(None)
+        );
+    }
+
+    #[test]
+    fn decode_defaults() {
+        assert_eq!(decode_value::<Point>(&[]).unwrap(), point(0, 0, 0));
+        assert_eq!(
+            decode_value::<Point>(&[8, 4, 8, 6]).unwrap(),
+            point(0, 3, 0)
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    log::{loggers::NoopLogger, LogEntry, Logger},
+    rt::{lock, Runtime},
+    ser::Serialize,
+    types::RngState,
+};
+
+/// Conditions of a `MockLink`, in ticks.
+#[derive(Clone, Debug)]
+pub struct LinkConfig {
+    seed: u64,
+    latency: u64,
+    jitter: u64,
+    loss: f64,
+    ordered: bool,
+}
+
+impl LinkConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds the randomness of jitter and loss, so runs are reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn latency(mut self, latency: u64) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Adds up to `jitter` ticks to the latency of each packet,
+    /// which reorders packets unless the link is `ordered`.
+    pub fn jitter(mut self, jitter: u64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Drops each packet with probability `loss`.
+    pub fn loss(mut self, loss: f64) -> Self {
+        self.loss = loss;
+        self
+    }
+
+    /// Holds packets back so they are delivered in the order they were sent.
+    pub fn ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            latency: 0,
+            jitter: 0,
+            loss: 0.0,
+            ordered: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct LinkStats {
+    pub sent: u64,
+    pub lost: u64,
+    pub delivered: u64,
+}
+
+struct Packet {
+    deliver_at: u64,
+    seq: u64,
+    bytes: Vec<u8>,
+}
+
+struct Link {
+    config: LinkConfig,
+    rng: RngState,
+    now: u64,
+    next_seq: u64,
+    last_deliver_at: u64,
+    in_flight: Vec<Packet>,
+    stats: LinkStats,
+}
+
+impl Link {
+    fn send(&mut self, bytes: Vec<u8>) {
+        self.stats.sent += 1;
+
+        if self.config.loss > 0.0 && self.rng.next_f64() < self.config.loss {
+            self.stats.lost += 1;
+            return;
+        }
+
+        let jitter = match self.config.jitter {
+            0 => 0,
+            jitter => self.rng.next_below(jitter + 1),
+        };
+
+        let mut deliver_at = self.now + self.config.latency + jitter;
+
+        if self.config.ordered {
+            deliver_at = deliver_at.max(self.last_deliver_at);
+            self.last_deliver_at = deliver_at;
+        }
+
+        self.in_flight.push(Packet {
+            deliver_at,
+            seq: self.next_seq,
+            bytes,
+        });
+
+        self.next_seq += 1;
+    }
+
+    fn deliver(&mut self, until: u64) -> Vec<Vec<u8>> {
+        let (mut due, in_flight) = std::mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition::<Vec<_>, _>(|packet| packet.deliver_at <= until);
+
+        self.in_flight = in_flight;
+        self.stats.delivered += due.len() as u64;
+
+        due.sort_by_key(|packet| (packet.deliver_at, packet.seq));
+        due.into_iter().map(|packet| packet.bytes).collect()
+    }
+}
+
+/// A simulated one-way connection from a server logger to a client,
+/// driven by an explicit clock so tests stay deterministic.
+///
+/// Each call to `send`, or each batch logged through `logger`, makes one packet.
+#[derive(Clone)]
+pub struct MockLink {
+    link: Arc<Mutex<Link>>,
+}
+
+impl MockLink {
+    pub fn new(config: LinkConfig) -> Self {
+        let rng = RngState::with_seed(Runtime::with_logger(NoopLogger::new()), config.seed);
+
+        Self {
+            link: Arc::new(Mutex::new(Link {
+                config,
+                rng,
+                now: 0,
+                next_seq: 0,
+                last_deliver_at: 0,
+                in_flight: Vec::new(),
+                stats: LinkStats::default(),
+            })),
+        }
+    }
+
+    pub fn logger(&self) -> MockLogger {
+        MockLogger { link: self.clone() }
+    }
+
+    pub fn send(&self, bytes: Vec<u8>) {
+        lock(&self.link).send(bytes);
+    }
+
+    pub fn now(&self) -> u64 {
+        lock(&self.link).now
+    }
+
+    /// Advances the clock by one tick and returns the packets arriving by then.
+    pub fn tick(&self) -> Vec<Vec<u8>> {
+        let mut link = lock(&self.link);
+        link.now += 1;
+        let now = link.now;
+        link.deliver(now)
+    }
+
+    /// Returns every packet still in flight, as if enough time had passed.
+    pub fn flush(&self) -> Vec<Vec<u8>> {
+        lock(&self.link).deliver(u64::MAX)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        lock(&self.link).in_flight.len()
+    }
+
+    pub fn stats(&self) -> LinkStats {
+        lock(&self.link).stats
+    }
+}
+
+pub struct MockLogger {
+    link: MockLink,
+}
+
+impl Logger for MockLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.log_multi(vec![entry])
+    }
+
+    fn log_multi(&mut self, entries: Vec<LogEntry>) -> io::Result<()> {
+        let mut bytes = Vec::new();
+
+        for entry in entries {
+            entry.cache_size();
+            entry.serialize_nested(None, false, &mut bytes)?;
+        }
+
+        self.link.send(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    use super::{LinkConfig, MockLink};
+
+    fn send_all(link: &MockLink, count: u8) {
+        for index in 0..count {
+            link.send(vec![index]);
+        }
+    }
+
+    fn arrival_order(link: &MockLink) -> Vec<u8> {
+        let mut order = Vec::new();
+
+        while link.in_flight() > 0 {
+            order.extend(link.tick().into_iter().flatten());
+        }
+
+        order
+    }
+
+    #[test]
+    fn latency() {
+        let link = MockLink::new(LinkConfig::new().latency(2));
+        send_all(&link, 2);
+
+        assert!(link.tick().is_empty());
+        assert_eq!(link.tick(), &[vec![0], vec![1]]);
+        assert_eq!(link.now(), 2);
+    }
+
+    #[test]
+    fn jitter_reorders_deterministically() {
+        let config = LinkConfig::new().seed(3).latency(1).jitter(4);
+
+        let link = MockLink::new(config.clone());
+        send_all(&link, 20);
+        let order = arrival_order(&link);
+
+        let other = MockLink::new(config);
+        send_all(&other, 20);
+
+        assert_ne!(order, (0..20).collect::<Vec<_>>());
+        assert_eq!(order, arrival_order(&other));
+    }
+
+    #[test]
+    fn ordered() {
+        let link = MockLink::new(LinkConfig::new().seed(3).jitter(4).ordered(true));
+        send_all(&link, 20);
+        assert_eq!(arrival_order(&link), (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn loss() {
+        let link = MockLink::new(LinkConfig::new().seed(5).loss(0.5));
+        send_all(&link, 100);
+
+        let stats = link.stats();
+        assert!(stats.lost > 0 && stats.lost < 100);
+        assert_eq!(link.flush().len() as u64, stats.sent - stats.lost);
+        assert_eq!(link.stats().delivered, stats.sent - stats.lost);
+    }
+
+    #[test]
+    fn replicate() {
+        let link = MockLink::new(LinkConfig::new().latency(1).jitter(2).ordered(true));
+        let mut point = Point::empty(Runtime::with_logger(link.logger()));
+        let mut mirror = Point::empty(Runtime::new());
+
+        for step in 0..10 {
+            point.set_x(step).set_y(-step);
+
+            for bytes in link.tick() {
+                replay(&mut mirror, &bytes);
+            }
+        }
+
+        for bytes in link.flush() {
+            replay(&mut mirror, &bytes);
+        }
+
+        assert_eq!(mirror, point);
+    }
+}
@@ -0,0 +1,239 @@
+use std::{collections::HashMap, hash::Hash, io};
+
+use crate::{
+    log::LogEntryKind,
+    wire_fmt::{self, WireType},
+};
+
+/// A log entry with only its kind and path decoded, the rest left as it was received.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RawEntry<'a> {
+    pub kind: u32,
+    /// `None` for entries which apply to the whole stream, like interned strings.
+    pub path: Option<Vec<u32>>,
+    /// The whole entry, including its size prefix.
+    pub bytes: &'a [u8],
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> io::Result<u64> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or_else(|| invalid("truncated varint"))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(invalid("varint overflows u64"))
+}
+
+fn read_sized<'a>(bytes: &'a [u8], pos: &mut usize) -> io::Result<&'a [u8]> {
+    let size = read_varint(bytes, pos)? as usize;
+    let (sized, end) = pos
+        .checked_add(size)
+        .and_then(|end| Some((bytes.get(*pos..end)?, end)))
+        .ok_or_else(|| invalid("truncated sized field"))?;
+
+    *pos = end;
+    Ok(sized)
+}
+
+/// Splits a frame of log entries, decoding only what's needed to route them.
+pub fn split_entries(frame: &[u8]) -> io::Result<Vec<RawEntry<'_>>> {
+    let mut entries = Vec::new();
+    let mut pos = 0;
+
+    while pos < frame.len() {
+        let start = pos;
+        let body = read_sized(frame, &mut pos)?;
+        let mut body_pos = 0;
+        let kind = read_varint(body, &mut body_pos)? as u32;
+        let mut path = Vec::new();
+
+        while body_pos < body.len() {
+            let tag = read_varint(body, &mut body_pos)? as u32;
+
+            match wire_fmt::parse_tag(tag)? {
+                (0, WireType::Sized) => {
+                    let packed = read_sized(body, &mut body_pos)?;
+                    let mut packed_pos = 0;
+
+                    while packed_pos < packed.len() {
+                        path.push(read_varint(packed, &mut packed_pos)? as u32);
+                    }
+                }
+
+                (_, WireType::Varint) => {
+                    read_varint(body, &mut body_pos)?;
+                }
+
+                (_, WireType::Sized) => {
+                    read_sized(body, &mut body_pos)?;
+                }
+
+                (_, wire_type) => {
+                    let size = wire_type.fixed_size().unwrap_or_default() as usize;
+                    body_pos = body_pos
+                        .checked_add(size)
+                        .filter(|&end| end <= body.len())
+                        .ok_or_else(|| invalid("truncated fixed field"))?;
+                }
+            }
+        }
+
+        entries.push(RawEntry {
+            kind,
+            path: if kind == LogEntryKind::Intern as u32 {
+                None
+            } else {
+                Some(path)
+            },
+            bytes: frame
+                .get(start..pos)
+                .ok_or_else(|| invalid("truncated entry"))?,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Fans out log frames from upstream to clients, each subscribed to some subtrees,
+/// without decoding values. Gateways can then scale regardless of the schema.
+///
+/// An entry goes to a client if its path overlaps one of the client's subscriptions,
+/// i.e. one of them is a prefix of the other. Entries without a path go to all clients.
+pub struct Relay<K> {
+    subscriptions: HashMap<K, Vec<Vec<u32>>>,
+}
+
+impl<K> Default for Relay<K> {
+    fn default() -> Self {
+        Self {
+            subscriptions: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> Relay<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends entries under `path` to `client`. An empty path subscribes to everything.
+    pub fn subscribe(&mut self, client: K, path: Vec<u32>) -> &mut Self {
+        self.subscriptions.entry(client).or_default().push(path);
+        self
+    }
+
+    pub fn unsubscribe(&mut self, client: &K) {
+        self.subscriptions.remove(client);
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = &K> {
+        self.subscriptions.keys()
+    }
+
+    /// Splits `frame` into one frame per client, with entries copied in their original order.
+    /// Clients with nothing to receive are left out.
+    pub fn route(&self, frame: &[u8]) -> io::Result<HashMap<K, Vec<u8>>> {
+        let entries = split_entries(frame)?;
+        let mut frames = HashMap::new();
+
+        for (client, paths) in &self.subscriptions {
+            let mut client_frame = Vec::new();
+
+            for entry in &entries {
+                let routed = match &entry.path {
+                    Some(entry_path) => paths.iter().any(|path| overlaps(path, entry_path)),
+                    None => true,
+                };
+
+                if routed {
+                    client_frame.extend_from_slice(entry.bytes);
+                }
+            }
+
+            if !client_frame.is_empty() {
+                frames.insert(client.clone(), client_frame);
+            }
+        }
+
+        Ok(frames)
+    }
+}
+
+fn overlaps(a: &[u32], b: &[u32]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        log::{loggers::BufferLogger, LogEntry, LogEntryKind},
+        rt::{Node, Runtime},
+        ser::Serialize,
+        test_util::{replay, Point},
+    };
+
+    use super::{split_entries, Relay};
+
+    #[test]
+    fn split() {
+        let path = Node::child(&Arc::new(Node::Root), 3);
+        let entries = [
+            LogEntry::new_update(&path, &1337),
+            LogEntry::new_intern(0, "hello"),
+            LogEntry::new_list_move(&Node::Root, 1, 2),
+        ];
+
+        let mut frame = Vec::new();
+
+        for entry in &entries {
+            entry.compute_size_nested(None, false).unwrap();
+            entry.serialize_nested(None, false, &mut frame).unwrap();
+        }
+
+        let raw = split_entries(&frame).unwrap();
+        assert_eq!(raw.len(), 3);
+
+        for (raw, entry) in raw.iter().zip(&entries) {
+            assert_eq!(raw.kind, entry.kind() as u32);
+            assert_eq!(raw.path.as_deref(), entry.path());
+        }
+
+        assert_eq!(raw[1].kind, LogEntryKind::Intern as u32);
+        let joined: Vec<u8> = raw.iter().flat_map(|raw| raw.bytes.to_vec()).collect();
+        assert_eq!(joined, frame);
+    }
+
+    #[test]
+    fn route() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::empty(runtime);
+        point.set_x(1).set_y(2).set_z(3);
+
+        let mut relay = Relay::new();
+        relay.subscribe("xz", vec![0]).subscribe("xz", vec![2]);
+        relay.subscribe("all", vec![]).subscribe("none", vec![3]);
+
+        let frames = relay.route(&logger.lock().unwrap().bytes()).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert!(!frames.contains_key("none"));
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &frames["xz"]);
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, 0, 3));
+
+        assert_eq!(frames["all"], logger.lock().unwrap().bytes());
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    de::Reader,
+    log::{
+        loggers::{BufferLogger, NoopLogger},
+        StringTable,
+    },
+    rt::{lock, Runtime},
+    state::State,
+    types::RngState,
+};
+
+use super::mock::{LinkConfig, MockLink};
+
+type GenOp<T, Op> = Box<dyn Fn(&mut RngState, &T) -> Op>;
+type Apply<T, Op> = Box<dyn Fn(&mut T, &Op)>;
+
+/// Runs randomized operations on a server state and checks that mirrored clients,
+/// each joining from a snapshot and then following the log over its own `MockLink`,
+/// stay convergent by hash.
+///
+/// On divergence, the operations are minimized to a shorter sequence which still diverges.
+pub struct Soak<T, Op> {
+    gen_op: GenOp<T, Op>,
+    apply: Apply<T, Op>,
+    seed: u64,
+    ops: usize,
+    clients: usize,
+    check_every: usize,
+    link: LinkConfig,
+}
+
+#[derive(Debug)]
+pub struct SoakFailure<Op> {
+    /// Minimized operations, replayed from a fresh state.
+    pub ops: Vec<Op>,
+    pub client: usize,
+    pub reason: String,
+}
+
+impl<Op: fmt::Debug> fmt::Display for SoakFailure<Op> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "client {} diverged ({}) after {} operation(s): {:?}",
+            self.client,
+            self.reason,
+            self.ops.len(),
+            self.ops,
+        )
+    }
+}
+
+impl<T: State + Hash, Op: Clone> Soak<T, Op> {
+    pub fn new(
+        gen_op: impl Fn(&mut RngState, &T) -> Op + 'static,
+        apply: impl Fn(&mut T, &Op) + 'static,
+    ) -> Self {
+        Self {
+            gen_op: Box::new(gen_op),
+            apply: Box::new(apply),
+            seed: 0,
+            ops: 10_000,
+            clients: 3,
+            check_every: 100,
+            link: LinkConfig::new().latency(1).jitter(2).ordered(true),
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn ops(mut self, ops: usize) -> Self {
+        self.ops = ops;
+        self
+    }
+
+    /// Client `i` joins after `i / clients` of the operations.
+    pub fn clients(mut self, clients: usize) -> Self {
+        self.clients = clients.max(1);
+        self
+    }
+
+    pub fn check_every(mut self, check_every: usize) -> Self {
+        self.check_every = check_every.max(1);
+        self
+    }
+
+    /// Seeds of client links are derived from the seed of the soak.
+    pub fn link(mut self, link: LinkConfig) -> Self {
+        self.link = link;
+        self
+    }
+
+    pub fn run(&self) -> Result<(), SoakFailure<Op>> {
+        let mut rng = RngState::with_seed(Runtime::with_logger(NoopLogger::new()), self.seed);
+        let mut state = T::with_runtime(Runtime::with_logger(NoopLogger::new()));
+        let mut ops = Vec::with_capacity(self.ops);
+
+        for _ in 0..self.ops {
+            let op = (self.gen_op)(&mut rng, &state);
+            (self.apply)(&mut state, &op);
+            ops.push(op);
+        }
+
+        match self.check(&ops) {
+            Some((client, reason)) => Err(self.minimize(ops, client, reason)),
+            None => Ok(()),
+        }
+    }
+
+    fn minimize(&self, mut ops: Vec<Op>, mut client: usize, mut reason: String) -> SoakFailure<Op> {
+        let mut chunk = ops.len() / 2;
+
+        while chunk > 0 {
+            let mut start = 0;
+
+            while start < ops.len() {
+                let end = (start + chunk).min(ops.len());
+                let candidate: Vec<_> = ops
+                    .iter()
+                    .take(start)
+                    .chain(ops.iter().skip(end))
+                    .cloned()
+                    .collect();
+
+                match self.check(&candidate) {
+                    Some(failure) => {
+                        ops = candidate;
+                        client = failure.0;
+                        reason = failure.1;
+                    }
+
+                    None => start = end,
+                }
+            }
+
+            chunk /= 2;
+        }
+
+        SoakFailure {
+            ops,
+            client,
+            reason,
+        }
+    }
+
+    /// Applies `ops` from a fresh state, returning the first client found divergent.
+    fn check(&self, ops: &[Op]) -> Option<(usize, String)> {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut server = T::with_runtime(runtime);
+        let mut clients: Vec<Client<T>> = Vec::with_capacity(self.clients);
+
+        for (index, op) in ops.iter().enumerate() {
+            while clients.len() < self.clients && clients.len() * ops.len() <= index * self.clients
+            {
+                let id = clients.len();
+                let seed = self.seed.wrapping_add(id as u64 + 1);
+
+                // A snapshot clients can't decode diverges as much as a wrong delta does.
+                let state = match snapshot(&server) {
+                    Ok(state) => state,
+                    Err(error) => return Some((id, format!("snapshot failed: {}", error))),
+                };
+
+                clients.push(Client {
+                    id,
+                    state,
+                    link: MockLink::new(self.link.clone().seed(seed)),
+                    // Joining clients need the strings interned so far along with the snapshot.
+                    strings: lock(&logger).strings().clone(),
+                });
+            }
+
+            (self.apply)(&mut server, op);
+            let bytes = lock(&logger).pluck_bytes();
+
+            for client in &mut clients {
+                if !bytes.is_empty() {
+                    client.link.send(bytes.clone());
+                }
+
+                let packets = client.link.tick();
+
+                if let Err(reason) = client.receive(packets) {
+                    return Some((client.id, reason));
+                }
+            }
+
+            if (index + 1) % self.check_every == 0 || index + 1 == ops.len() {
+                let hash = hash(&server);
+
+                for client in &mut clients {
+                    let packets = client.link.flush();
+
+                    if let Err(reason) = client.receive(packets) {
+                        return Some((client.id, reason));
+                    }
+
+                    if self::hash(&client.state) != hash {
+                        return Some((client.id, "hash mismatch".to_string()));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+struct Client<T> {
+    id: usize,
+    state: T,
+    link: MockLink,
+    strings: StringTable,
+}
+
+impl<T: State> Client<T> {
+    fn receive(&mut self, packets: Vec<Vec<u8>>) -> Result<(), String> {
+        for bytes in packets {
+            self.state
+                .replay_with_strings(&mut Reader::new(&*bytes), &mut self.strings)
+                .map_err(|error| error.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn snapshot<T: State>(state: &T) -> std::io::Result<T> {
+    let mut snapshot = T::with_runtime(Runtime::new());
+    snapshot.merge(&mut Reader::new(&*state.to_bytes()))?;
+    Ok(snapshot)
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{steit_derive, types::List};
+
+    use super::Soak;
+
+    #[steit_derive(Hash, Debug, State)]
+    #[steit(steit_owned)]
+    struct Board {
+        #[steit(tag = 0)]
+        score: i32,
+        #[steit(tag = 1, string_intern)]
+        player: String,
+        #[steit(tag = 2)]
+        moves: List<u32>,
+    }
+
+    #[derive(Clone, PartialEq, Debug)]
+    enum Op {
+        Score(i32),
+        Player(u64),
+        Push(u32),
+        Pop,
+        // Mutates without logging, which clients can't follow
+        Sneak,
+    }
+
+    fn apply(board: &mut Board, op: &Op) {
+        match *op {
+            Op::Score(score) => {
+                board.set_score(score);
+            }
+
+            Op::Player(player) => {
+                board.set_player(format!("player-{}", player));
+            }
+
+            Op::Push(value) => {
+                board.moves.push(value);
+            }
+
+            Op::Pop => {
+                board.moves.pop();
+            }
+
+            Op::Sneak => board.score += 1,
+        }
+    }
+
+    #[test]
+    fn convergent() {
+        let soak = Soak::new(
+            |rng, board: &Board| match rng.next_below(4) {
+                0 => Op::Score(rng.next_u32() as i32),
+                1 => Op::Player(rng.next_below(5)),
+                2 if !board.moves.is_empty() => Op::Pop,
+                _ => Op::Push(rng.next_u32()),
+            },
+            apply,
+        )
+        .seed(11)
+        .ops(2_000)
+        .clients(4);
+
+        soak.run().unwrap();
+    }
+
+    #[test]
+    fn minimize_divergence() {
+        let soak = Soak::new(
+            |rng, _: &Board| match rng.next_below(50) {
+                0 => Op::Sneak,
+                _ => Op::Push(rng.next_u32()),
+            },
+            apply,
+        )
+        .seed(7)
+        .ops(500);
+
+        let failure = soak.run().unwrap_err();
+        assert_eq!(failure.ops, &[Op::Sneak]);
+        assert_eq!(failure.client, 0);
+        assert_eq!(failure.reason, "hash mismatch");
+    }
+
+    #[steit_derive(Hash, Debug, State)]
+    #[steit(steit_owned)]
+    struct Gauge {
+        #[steit(tag = 0, range = "0..=100")]
+        level: u8,
+    }
+
+    #[test]
+    fn snapshot_failure() {
+        // Bypasses the range check of the setter, so snapshots of the gauge fail to decode.
+        let soak = Soak::new(|_, _: &Gauge| (), |gauge, _| gauge.level = 200).clients(2);
+        let (client, reason) = soak.check(&[(), ()]).unwrap();
+        assert_eq!(client, 1);
+        assert!(reason.starts_with("snapshot failed: "), "{}", reason);
+    }
+}
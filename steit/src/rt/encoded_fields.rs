@@ -0,0 +1,184 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    sync::Mutex,
+};
+
+use serde::{Serialize as JsonSerialize, Serializer};
+
+use crate::{
+    ser::{encode_with, encoded_size, write_encoded},
+    wire_fmt::WireType,
+};
+
+use super::expect::lock;
+
+type Encoded = io::Result<(WireType, Vec<u8>)>;
+
+/// Keeps fields encoded by `#[steit(serialize_with = …)]` from computing sizes to serializing,
+/// so custom serializers run once per serialization and their errors reach the caller.
+///
+/// Added to structs with such fields. Like [`SizeCache`], it is always equal to itself.
+///
+/// [`SizeCache`]: struct.SizeCache.html
+#[derive(Default)]
+pub struct EncodedFields {
+    fields: Mutex<Vec<(u32, Encoded)>>,
+}
+
+impl EncodedFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes field `field_number` with `serialize` and keeps it for `serialize_with`.
+    ///
+    /// Returns the size of the field, 0 if `serialize` fails,
+    /// in which case serializing returns the error.
+    pub fn cache_size_with<T: ?Sized>(
+        &self,
+        field_number: u32,
+        value: &T,
+        serialize: impl FnOnce(&T, &mut Vec<u8>) -> io::Result<WireType>,
+    ) -> u32 {
+        let encoded = encode_with(value, serialize);
+
+        let size = match &encoded {
+            Ok((wire_type, bytes)) => encoded_size(field_number, *wire_type, bytes),
+            Err(_) => Ok(0),
+        };
+
+        let mut fields = lock(&self.fields);
+        fields.retain(|(other, _)| *other != field_number);
+
+        match size {
+            Ok(size) => {
+                fields.push((field_number, encoded));
+                size
+            }
+
+            Err(error) => {
+                fields.push((field_number, Err(error)));
+                0
+            }
+        }
+    }
+
+    /// Writes field `field_number` as encoded by `cache_size_with`,
+    /// encoding it now if it wasn't.
+    pub fn serialize_with<T: ?Sized>(
+        &self,
+        field_number: u32,
+        value: &T,
+        serialize: impl FnOnce(&T, &mut Vec<u8>) -> io::Result<WireType>,
+        writer: &mut impl io::Write,
+    ) -> io::Result<()> {
+        let cached = {
+            let mut fields = lock(&self.fields);
+
+            fields
+                .iter()
+                .position(|(other, _)| *other == field_number)
+                .map(|index| fields.swap_remove(index).1)
+        };
+
+        let (wire_type, bytes) = match cached {
+            Some(encoded) => encoded?,
+            None => encode_with(value, serialize)?,
+        };
+
+        write_encoded(field_number, wire_type, &bytes, writer)
+    }
+}
+
+// Encodings are only kept between computing sizes and serializing, so copies start without any.
+impl Clone for EncodedFields {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for EncodedFields {
+    fn eq(&self, _other: &EncodedFields) -> bool {
+        true
+    }
+}
+
+impl Eq for EncodedFields {}
+
+impl Hash for EncodedFields {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl fmt::Debug for EncodedFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("EncodedFields")
+    }
+}
+
+impl JsonSerialize for EncodedFields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+
+    use crate::wire_fmt::WireType;
+
+    use super::EncodedFields;
+
+    fn serialize_counted(
+        value: &(u8, &std::cell::Cell<u32>),
+        writer: &mut Vec<u8>,
+    ) -> io::Result<WireType> {
+        value.1.set(value.1.get() + 1);
+        writer.write_all(&[value.0])?;
+        Ok(WireType::Sized)
+    }
+
+    #[test]
+    fn encode_once() {
+        let calls = std::cell::Cell::new(0);
+        let value = (7, &calls);
+        let fields = EncodedFields::new();
+
+        assert_eq!(fields.cache_size_with(1, &value, serialize_counted), 3);
+
+        let mut bytes = Vec::new();
+        fields
+            .serialize_with(1, &value, serialize_counted, &mut bytes)
+            .unwrap();
+
+        assert_eq!(bytes, &[10, 1, 7]);
+        assert_eq!(calls.get(), 1);
+
+        // Nothing cached anymore, so it is encoded again.
+        bytes.clear();
+        fields
+            .serialize_with(1, &value, serialize_counted, &mut bytes)
+            .unwrap();
+
+        assert_eq!(bytes, &[10, 1, 7]);
+        assert_eq!(calls.get(), 2);
+    }
+
+    fn serialize_failing(_value: &u8, _writer: &mut Vec<u8>) -> io::Result<WireType> {
+        Err(io::Error::other("bad"))
+    }
+
+    #[test]
+    fn error_on_serialize() {
+        let fields = EncodedFields::new();
+        assert_eq!(fields.cache_size_with(1, &0, serialize_failing), 0);
+
+        let error = fields
+            .serialize_with(1, &0, |_, _| Ok(WireType::Varint), &mut Vec::new())
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "bad");
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    fmt, io,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// Handles the result of logging a change which has been applied already,
+/// as in setters, which return `&mut Self` rather than errors.
+///
+/// Panics on errors, unless the `no-panic` feature is enabled, which drops them
+/// and leaves failures to be reported by the logger itself.
+/// Either way, errors never get here once a handler is set with `Runtime::on_log_error`.
+pub fn expect_logged(result: io::Result<()>) {
+    if let Err(error) = result {
+        fail(format_args!("failed to log a change: {}", error));
+    }
+}
+
+/// Like `expect_logged` for serializing into memory, which only fails on invalid states,
+/// like field numbers out of range. With `no-panic`, the default value is returned.
+pub fn expect_written<T: Default>(result: io::Result<T>) -> T {
+    result.unwrap_or_else(|error| {
+        fail(format_args!("failed to serialize: {}", error));
+        T::default()
+    })
+}
+
+/// Handles a value rejected by `#[steit(max_len = …)]` or `#[steit(range = …)]` in setters.
+/// Their `try_set_*` counterparts return such errors instead.
+///
+/// Panics on errors, unless the `no-panic` feature is enabled,
+/// in which case `None` is returned and the setter leaves the field as it was.
+/// Setters of states go through `Runtime::expect_valid`, which reports errors to their tree.
+pub fn expect_valid<T>(result: io::Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            fail(format_args!("{}", error));
+            None
+        }
+    }
+}
+
+/// Locks `mutex` even if it's poisoned, since loggers and states stay usable
+/// after a panic elsewhere, at worst missing the change being made.
+pub(crate) fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Panics with `message`, unless the `no-panic` feature is enabled.
+#[cfg(not(feature = "no-panic"))]
+pub(crate) fn fail(message: fmt::Arguments) {
+    panic!("{}", message);
+}
+
+#[cfg(feature = "no-panic")]
+pub(crate) fn fail(_message: fmt::Arguments) {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::{FaultPattern, FaultyLogger, NoopLogger},
+        rt::Runtime,
+        test_util::Point,
+    };
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "no-panic"),
+        should_panic(expected = "failed to log a change")
+    )]
+    fn failed_setter() {
+        let logger = FaultyLogger::new(NoopLogger::new(), FaultPattern::Always);
+        let mut point = Point::empty(Runtime::with_logger(logger));
+        point.set_x(1);
+        assert_eq!(point.x, 1);
+    }
+}
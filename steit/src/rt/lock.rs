@@ -0,0 +1,177 @@
+use std::{
+    io,
+    ops::{Deref, DerefMut},
+    sync::{Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+use crate::state::State;
+
+use super::{
+    expect::{expect_logged, lock},
+    runtime::Runtime,
+};
+
+/// Shares a state between threads, which mutate it through a `StateGuard`.
+///
+/// Entries logged through a guard are batched and flushed together when it's dropped,
+/// so the log never interleaves mutations made under different guards.
+pub struct StateLock<T: State> {
+    state: Mutex<T>,
+    runtime: Runtime,
+}
+
+impl<T: State> StateLock<T> {
+    pub fn new(state: T) -> Self {
+        let runtime = state.runtime().clone();
+
+        Self {
+            state: Mutex::new(state),
+            runtime,
+        }
+    }
+
+    /// Blocks until no other guard is alive.
+    pub fn lock(&self) -> StateGuard<'_, T> {
+        let state = lock(&self.state);
+        self.runtime.begin_batch();
+
+        StateGuard {
+            state,
+            runtime: &self.runtime,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub struct StateGuard<'a, T: State> {
+    state: MutexGuard<'a, T>,
+    runtime: &'a Runtime,
+}
+
+impl<'a, T: State> StateGuard<'a, T> {
+    /// Flushes entries batched so far.
+    /// Dropping the guard does too, but panics if flushing fails.
+    pub fn commit(&mut self) -> io::Result<()> {
+        let result = self.runtime.end_batch(true);
+        self.runtime.begin_batch();
+        result
+    }
+}
+
+impl<'a, T: State> Deref for StateGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.state
+    }
+}
+
+impl<'a, T: State> DerefMut for StateGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.state
+    }
+}
+
+impl<'a, T: State> Drop for StateGuard<'a, T> {
+    fn drop(&mut self) {
+        // The batch is flushed before the state is unlocked,
+        // but errors are dropped while panicking, as panicking again would abort.
+        let result = self.runtime.end_batch(true);
+
+        if !thread::panicking() {
+            expect_logged(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use crate::{
+        log::loggers::BufferLogger,
+        rt::Runtime,
+        steit_derive,
+        test_util::{replay, Point},
+    };
+
+    use super::StateLock;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Tag {
+        #[steit(tag = 0, string_intern)]
+        name: String,
+    }
+
+    #[test]
+    fn batch_per_guard() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let lock = StateLock::new(Point::empty(runtime));
+
+        {
+            let mut point = lock.lock();
+            point.set_x(1).set_y(2);
+            assert!(logger.lock().unwrap().pluck().is_empty());
+        }
+
+        assert_eq!(logger.lock().unwrap().pluck().len(), 2);
+
+        let mut point = lock.lock();
+        point.set_z(3);
+        point.commit().unwrap();
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+    }
+
+    #[test]
+    fn interned_in_guard() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut tag = Tag::new(runtime);
+        tag.set_name("red".to_string());
+
+        let lock = StateLock::new(tag);
+        lock.lock()
+            .set_name("blue".to_string())
+            .set_name("red".to_string());
+
+        let mut mirror = Tag::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, lock.into_inner());
+        assert_eq!(logger.lock().unwrap().strings().len(), 2);
+    }
+
+    #[test]
+    fn across_threads() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let lock = Arc::new(StateLock::new(Point::empty(runtime)));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let lock = lock.clone();
+
+                thread::spawn(move || {
+                    let mut point = lock.lock();
+                    let x = point.x;
+                    point.set_x(x + 1).set_y(x + 1);
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, Point::new(Runtime::new(), 4, 4, 0));
+
+        let point = Arc::try_unwrap(lock).ok().unwrap().into_inner();
+        assert_eq!(point, mirror);
+    }
+}
@@ -2,39 +2,137 @@ use std::{
     io,
     ops::{Deref, DerefMut},
     sync::{Arc, Mutex},
+    thread::{self, ThreadId},
 };
 
-use crate::log::{LogEntry, Logger};
+use crate::log::{LogEntry, Logger, StringTable};
+
+use super::node::Node;
 
 pub type LoggerHandle<T> = Arc<Mutex<RuntimeLogger<T>>>;
 
+/// Gets errors of the inner logger in place of the code which logged, see `Runtime::on_log_error`.
+pub type LogErrorHandler = Box<dyn FnMut(io::Error) + Send>;
+
 pub trait PausableLogger: Logger {
     fn pause(&mut self) -> u32;
     fn unpause(&mut self) -> u32;
+
+    /// Drops entries logged under `path` by the current thread until `unsilence` is called.
+    fn silence(&mut self, path: Vec<u32>);
+    fn unsilence(&mut self, path: &[u32]);
+
+    /// Holds entries logged under `path` by the current thread until `end_batch` is called.
+    fn begin_batch(&mut self, path: Vec<u32>);
+
+    /// Ends the innermost batch of the current thread at `path`, logging its entries
+    /// together if `commit` is set, into an enclosing batch if there is one,
+    /// or dropping them otherwise.
+    fn end_batch(&mut self, path: &[u32], commit: bool) -> io::Result<()>;
+
+    fn log_update_interned(&mut self, path: &Node<u32>, value: &str) -> io::Result<()>;
+
+    fn set_error_handler(&mut self, handler: Option<LogErrorHandler>);
+
+    /// Passes `error` to the error handler if one is set, or returns it otherwise.
+    fn report_error(&mut self, error: io::Error) -> io::Result<()>;
+}
+
+struct Batch {
+    thread: ThreadId,
+    path: Vec<u32>,
+    entries: Vec<LogEntry>,
 }
 
 pub struct RuntimeLogger<T: Logger> {
     inner: T,
     paused: u32,
+    silenced: Vec<(ThreadId, Vec<u32>)>,
+    batches: Vec<Batch>,
+    strings: StringTable,
+    error_handler: Option<LogErrorHandler>,
 }
 
 impl<T: Logger> RuntimeLogger<T> {
     pub(super) fn new(inner: T) -> Self {
-        Self { inner, paused: 0 }
+        Self {
+            inner,
+            paused: 0,
+            silenced: Vec::new(),
+            batches: Vec::new(),
+            strings: StringTable::new(),
+            error_handler: None,
+        }
     }
 
     pub fn replace(&mut self, inner: T) {
         self.inner = inner;
+        // The new logger starts a new stream, which doesn't know any interned strings yet.
+        self.strings.clear();
+    }
+
+    pub fn strings(&self) -> &StringTable {
+        &self.strings
+    }
+
+    fn is_silenced(&self, path: &[u32]) -> bool {
+        if self.silenced.is_empty() {
+            return false;
+        }
+
+        let thread = thread::current().id();
+
+        self.silenced
+            .iter()
+            .any(|(silenced_thread, silenced_path)| {
+                *silenced_thread == thread && path.starts_with(silenced_path)
+            })
+    }
+
+    fn batch_index(&self, path: &[u32]) -> Option<usize> {
+        if self.batches.is_empty() {
+            return None;
+        }
+
+        let thread = thread::current().id();
+
+        self.batches
+            .iter()
+            .rposition(|batch| batch.thread == thread && path.starts_with(&batch.path))
+    }
+
+    // Entries which are not interned strings go to the batch covering them, if any.
+    fn send(&mut self, entry: LogEntry) -> io::Result<()> {
+        let index = entry.path().and_then(|path| self.batch_index(path));
+
+        if let Some(batch) = index.and_then(|index| self.batches.get_mut(index)) {
+            batch.entries.push(entry);
+            return Ok(());
+        }
+
+        let result = self.inner.log(entry);
+        self.handle(result)
+    }
+
+    fn handle(&mut self, result: io::Result<()>) -> io::Result<()> {
+        match (result, &mut self.error_handler) {
+            (Err(error), Some(handler)) => {
+                handler(error);
+                Ok(())
+            }
+
+            (result, _) => result,
+        }
     }
 }
 
 impl<T: Logger> Logger for RuntimeLogger<T> {
     fn log(&mut self, entry: LogEntry) -> io::Result<()> {
-        if self.paused == 0 {
-            self.inner.log(entry)?;
+        if self.paused > 0 || entry.path().is_some_and(|path| self.is_silenced(path)) {
+            return Ok(());
         }
 
-        Ok(())
+        self.send(entry)
     }
 }
 
@@ -51,6 +149,91 @@ impl<T: Logger> PausableLogger for RuntimeLogger<T> {
 
         self.paused
     }
+
+    fn silence(&mut self, path: Vec<u32>) {
+        self.silenced.push((thread::current().id(), path));
+    }
+
+    fn unsilence(&mut self, path: &[u32]) {
+        let thread = thread::current().id();
+
+        if let Some(index) = self
+            .silenced
+            .iter()
+            .rposition(|(silenced_thread, silenced)| *silenced_thread == thread && silenced == path)
+        {
+            self.silenced.remove(index);
+        }
+    }
+
+    fn begin_batch(&mut self, path: Vec<u32>) {
+        self.batches.push(Batch {
+            thread: thread::current().id(),
+            path,
+            entries: Vec::new(),
+        });
+    }
+
+    fn end_batch(&mut self, path: &[u32], commit: bool) -> io::Result<()> {
+        let thread = thread::current().id();
+
+        let batch = match self
+            .batches
+            .iter()
+            .rposition(|batch| batch.thread == thread && batch.path == path)
+        {
+            Some(index) => self.batches.remove(index),
+            None => return Ok(()),
+        };
+
+        if !commit || batch.entries.is_empty() {
+            return Ok(());
+        }
+
+        let index = self.batch_index(path);
+
+        if let Some(enclosing) = index.and_then(|index| self.batches.get_mut(index)) {
+            enclosing.entries.extend(batch.entries);
+            return Ok(());
+        }
+
+        let result = self.inner.log_multi(batch.entries);
+        self.handle(result)
+    }
+
+    fn log_update_interned(&mut self, path: &Node<u32>, value: &str) -> io::Result<()> {
+        if self.paused > 0 || self.is_silenced(&path.collect_values()) {
+            return Ok(());
+        }
+
+        let index = match self.strings.index_of(value) {
+            Some(index) => index,
+
+            None => {
+                let index = self.strings.len() as u32;
+
+                // The string is only interned once clients have been sent it,
+                // so a failed entry gets the string sent again next time.
+                // It's never batched, so strings stay known when a batch is dropped.
+                if let Err(error) = self.inner.log(LogEntry::new_intern(index, value)) {
+                    return self.handle(Err(error));
+                }
+
+                self.strings.intern(value);
+                index
+            }
+        };
+
+        self.send(LogEntry::new_update_interned(path, index))
+    }
+
+    fn set_error_handler(&mut self, handler: Option<LogErrorHandler>) {
+        self.error_handler = handler;
+    }
+
+    fn report_error(&mut self, error: io::Error) -> io::Result<()> {
+        self.handle(Err(error))
+    }
 }
 
 impl<T: Logger> Deref for RuntimeLogger<T> {
@@ -66,3 +249,82 @@ impl<T: Logger> DerefMut for RuntimeLogger<T> {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        log::loggers::{BufferLogger, FaultPattern, FaultyLogger},
+        rt::Runtime,
+        test_util::{replay, Point},
+    };
+
+    #[test]
+    fn silenced() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::empty(runtime.clone());
+
+        runtime.nested(0).silenced(|| {
+            point.set_x(1).set_y(2);
+            runtime.nested(0).silenced(|| point.set_x(3));
+            point.set_x(4);
+        });
+
+        runtime.silenced(|| point.set_z(5));
+        point.set_x(6);
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().pluck_bytes());
+        assert_eq!(mirror, Point::new(Runtime::new(), 6, 2, 0));
+    }
+
+    #[test]
+    fn batches() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::empty(runtime.clone());
+
+        runtime.begin_batch();
+        point.set_x(1);
+        runtime.nested(1).begin_batch();
+        point.set_y(2);
+        runtime.nested(1).end_batch(true).unwrap();
+        runtime.nested(2).begin_batch();
+        point.set_z(3);
+        runtime.nested(2).end_batch(false).unwrap();
+        assert!(logger.lock().unwrap().pluck().is_empty());
+
+        runtime.end_batch(true).unwrap();
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().pluck_bytes());
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, 2, 0));
+    }
+
+    #[test]
+    fn error_handler() {
+        let logger = FaultyLogger::new(BufferLogger::new(), FaultPattern::At(vec![1]));
+        let (runtime, logger) = Runtime::with_logger_returned(logger);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+
+        runtime.on_log_error({
+            let errors = errors.clone();
+            move |error| errors.lock().unwrap().push(error.to_string())
+        });
+
+        let mut point = Point::empty(runtime.clone());
+        point.set_x(1).set_y(2).set_z(3);
+        assert_eq!(*errors.lock().unwrap(), ["injected failure of entry 1"]);
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(
+            &mut mirror,
+            &logger.lock().unwrap().inner_mut().pluck_bytes(),
+        );
+        assert_eq!(mirror, Point::new(Runtime::new(), 1, 0, 3));
+
+        runtime.clear_log_error_handler();
+        logger.lock().unwrap().set_pattern(FaultPattern::Always);
+        assert!(runtime.log_update(&1).is_err());
+    }
+}
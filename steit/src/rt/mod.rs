@@ -1,9 +1,57 @@
+mod encoded_fields;
+mod expect;
+#[cfg(feature = "runtime")]
+mod lock;
+#[cfg(feature = "runtime")]
 mod logger;
+#[cfg(feature = "runtime")]
 mod node;
+#[cfg(feature = "runtime")]
+mod observer;
+#[cfg(feature = "runtime")]
+mod path;
+#[cfg(feature = "runtime")]
+mod pool;
+#[cfg(feature = "runtime")]
+mod prefab;
+#[cfg(feature = "runtime")]
+mod root_path;
+#[cfg(feature = "runtime")]
+mod roots;
+#[cfg(feature = "runtime")]
 mod runtime;
+#[cfg(feature = "runtime")]
+mod shard;
 mod size_cache;
+mod unknown_fields;
+#[cfg(feature = "runtime")]
+mod write_policy;
 
+pub use encoded_fields::*;
+pub use expect::*;
+#[cfg(feature = "runtime")]
+pub use lock::*;
+#[cfg(feature = "runtime")]
 pub use logger::*;
+#[cfg(feature = "runtime")]
 pub use node::*;
+#[cfg(feature = "runtime")]
+pub use observer::*;
+#[cfg(feature = "runtime")]
+pub use path::*;
+#[cfg(feature = "runtime")]
+pub use pool::*;
+#[cfg(feature = "runtime")]
+pub use prefab::*;
+#[cfg(feature = "runtime")]
+pub use root_path::*;
+#[cfg(feature = "runtime")]
+pub use roots::*;
+#[cfg(feature = "runtime")]
 pub use runtime::*;
+#[cfg(feature = "runtime")]
+pub use shard::*;
 pub use size_cache::*;
+pub use unknown_fields::*;
+#[cfg(feature = "runtime")]
+pub use write_policy::*;
@@ -20,9 +20,15 @@ impl<T> Node<T> {
         }
     }
 
+    /// Panics on the root, unless the `no-panic` feature is enabled, which makes it its own parent.
     pub fn parent(&self) -> Arc<Self> {
-        self.get_parent()
-            .expect("there is no parent node of the root")
+        match self.get_parent() {
+            Some(parent) => parent,
+            #[cfg(not(feature = "no-panic"))]
+            None => panic!("there is no parent node of the root"),
+            #[cfg(feature = "no-panic")]
+            None => Arc::new(Node::Root),
+        }
     }
 
     pub fn get_value(&self) -> Option<&T> {
@@ -32,9 +38,23 @@ impl<T> Node<T> {
         }
     }
 
+    /// Panics on the root, see `get_value`.
+    #[allow(clippy::expect_used)]
     pub fn value(&self) -> &T {
         self.get_value().expect("root node doesn't have any value")
     }
+
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut node = self;
+
+        while let Node::Child { parent, .. } = node {
+            depth += 1;
+            node = parent;
+        }
+
+        depth
+    }
 }
 
 impl<T: Copy> Node<T> {
@@ -49,7 +69,7 @@ impl<T: Copy> Node<T> {
     }
 
     pub fn collect_values(&self) -> Vec<T> {
-        let mut values = Vec::new();
+        let mut values = Vec::with_capacity(self.depth());
         self.collect_values_to(&mut values);
         values
     }
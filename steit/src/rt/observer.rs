@@ -0,0 +1,195 @@
+use crate::{
+    log::{LogEntry, LogEntryKind},
+    meta::PathPattern,
+};
+
+type Callback<'a> = Box<dyn FnMut(&[u32], &LogEntry) + 'a>;
+type BatchCallback<'a> = Box<dyn FnMut(&[LogEntry]) + 'a>;
+
+enum Delivery<'a> {
+    Immediate(Callback<'a>),
+    Batched(BatchCallback<'a>, Vec<LogEntry>),
+}
+
+/// Calls back on log entries changing subscribed paths, so UIs can follow the states they show.
+///
+/// A subscription fires for entries at a path its pattern matches,
+/// and for entries replacing an ancestor of such a path.
+/// Callbacks get the concrete path of the entry, with wildcards resolved.
+#[derive(Default)]
+pub struct Observers<'a> {
+    subscriptions: Vec<(PathPattern, Delivery<'a>)>,
+}
+
+impl<'a> Observers<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to `pattern`, built with `PathPattern::parse` or from a `Path`.
+    pub fn subscribe(
+        &mut self,
+        pattern: impl Into<PathPattern>,
+        callback: impl FnMut(&[u32], &LogEntry) + 'a,
+    ) -> &mut Self {
+        let delivery = Delivery::Immediate(Box::new(callback));
+        self.subscriptions.push((pattern.into(), delivery));
+        self
+    }
+
+    /// Like `subscribe`, but entries are held until `flush`, usually called once per tick.
+    ///
+    /// Updates are coalesced, so only the last one at a path is delivered,
+    /// and it drops those below it. Other entries are delivered in order.
+    pub fn subscribe_batched(
+        &mut self,
+        pattern: impl Into<PathPattern>,
+        callback: impl FnMut(&[LogEntry]) + 'a,
+    ) -> &mut Self {
+        let delivery = Delivery::Batched(Box::new(callback), Vec::new());
+        self.subscriptions.push((pattern.into(), delivery));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    pub fn notify(&mut self, entry: &LogEntry) {
+        let path = match entry.path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        for (pattern, delivery) in &mut self.subscriptions {
+            if !pattern.matches_prefix(path) {
+                continue;
+            }
+
+            match delivery {
+                Delivery::Immediate(callback) => callback(path, entry),
+                Delivery::Batched(_, pending) => {
+                    if matches!(entry.kind(), LogEntryKind::Update | LogEntryKind::Replace) {
+                        pending.retain(|pending| {
+                            !pending.path().unwrap_or_default().starts_with(path)
+                        });
+                    }
+
+                    pending.push(entry.clone());
+                }
+            }
+        }
+    }
+
+    /// Delivers entries held for batched subscriptions, skipping those with none.
+    pub fn flush(&mut self) {
+        for (_, delivery) in &mut self.subscriptions {
+            if let Delivery::Batched(callback, pending) = delivery {
+                if !pending.is_empty() {
+                    callback(pending);
+                    pending.clear();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::BufferLogger,
+        meta::PathPattern,
+        rt::{Path, Runtime},
+        steit_derive,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::Observers;
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    struct Lobby {
+        #[steit(tag = 0)]
+        players: List<Point>,
+        #[steit(tag = 1)]
+        round: u32,
+    }
+
+    #[test]
+    fn wildcard() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut lobby = Lobby::new(runtime);
+        lobby.players.push_with(Point::empty);
+        lobby.players.push_with(Point::empty);
+        lobby.players[1].set_y(3);
+        lobby.players[0].set_x(1).set_y(2);
+        lobby.set_round(1);
+
+        let mut ys = Vec::new();
+        let mut rounds = 0;
+
+        let mut observers = Observers::new();
+        observers
+            .subscribe(
+                PathPattern::parse::<Lobby>("players.*.y").unwrap(),
+                |path, _| ys.push(path.to_vec()),
+            )
+            .subscribe(&Path::<Lobby>::root().child::<u32>(1), |_, _| rounds += 1);
+
+        for entry in logger.lock().unwrap().pluck() {
+            observers.notify(&entry);
+        }
+
+        drop(observers);
+        assert_eq!(ys, [vec![0], vec![0], vec![0, 1, 1], vec![0, 0, 1]]);
+        assert_eq!(rounds, 1);
+    }
+
+    #[test]
+    fn batched() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut lobby = Lobby::new(runtime);
+        lobby.players.push_with(Point::empty);
+
+        let mut batches = Vec::new();
+        let mut observers = Observers::new();
+        observers.subscribe_batched(
+            PathPattern::parse::<Lobby>("players.*.x").unwrap(),
+            |entries| {
+                let paths: Vec<_> = entries
+                    .iter()
+                    .map(|entry| entry.path().unwrap().to_vec())
+                    .collect();
+                batches.push(paths);
+            },
+        );
+
+        lobby.players[0].set_x(1).set_x(2).set_y(3);
+        lobby.players[0].set_x(4);
+
+        for entry in logger.lock().unwrap().pluck() {
+            observers.notify(&entry);
+        }
+
+        observers.flush();
+        observers.flush();
+
+        lobby.players.push_with(Point::empty);
+        lobby.players[1].set_z(5);
+        lobby.set_players(List::new(Runtime::new()));
+
+        for entry in logger.lock().unwrap().pluck() {
+            observers.notify(&entry);
+        }
+
+        observers.flush();
+        drop(observers);
+
+        assert_eq!(batches, [vec![vec![0], vec![0, 0, 0]], vec![vec![0]],]);
+    }
+}
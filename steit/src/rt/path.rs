@@ -0,0 +1,137 @@
+use std::{
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    marker::PhantomData,
+};
+
+use crate::{
+    de::{Deserialize, Reader},
+    ser::Serialize,
+    state::State,
+    types::{List, Map, MapKey},
+};
+
+use super::RootPath;
+
+/// Field numbers from a root state down to a nested `T`, as in paths of log entries.
+///
+/// Types deriving with `#[steit(paths)]` build them field by field,
+/// e.g. `Player::path().inventory().item(3)`, so they follow refactors of the schema.
+pub struct Path<T> {
+    segments: Vec<u32>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Path<T> {
+    pub fn root() -> Self {
+        Self::from_segments(Vec::new())
+    }
+
+    /// Trusts `segments` to lead to a `T`.
+    pub fn from_segments(segments: Vec<u32>) -> Self {
+        Self {
+            segments,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn segments(&self) -> &[u32] {
+        &self.segments
+    }
+
+    pub fn into_segments(self) -> Vec<u32> {
+        self.segments
+    }
+
+    /// Goes down to field `field_number`, which must hold a `U`.
+    pub fn child<U>(&self, field_number: u32) -> Path<U> {
+        let mut segments = self.segments.clone();
+        segments.push(field_number);
+        Path::from_segments(segments)
+    }
+
+    /// Whether `path` is this path or leads into it.
+    pub fn contains(&self, path: &[u32]) -> bool {
+        path.starts_with(&self.segments)
+    }
+
+    /// Qualifies this path with the id of the root state it starts from.
+    pub fn in_root(&self, root_id: u32) -> RootPath<T> {
+        RootPath::new(root_id, self.segments.clone())
+    }
+}
+
+impl<T: State> Path<List<T>> {
+    pub fn item(&self, index: u32) -> Path<T> {
+        self.child(index)
+    }
+}
+
+impl<K: MapKey, V: State> Path<Map<K, V>> {
+    pub fn value(&self, key: &K) -> Path<V> {
+        self.child(key.as_field_number())
+    }
+}
+
+impl<T> Default for Path<T> {
+    fn default() -> Self {
+        Self::root()
+    }
+}
+
+impl<T> Clone for Path<T> {
+    fn clone(&self) -> Self {
+        Self::from_segments(self.segments.clone())
+    }
+}
+
+impl<T> PartialEq for Path<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+impl<T> Eq for Path<T> {}
+
+impl<T> Hash for Path<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.segments.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Path<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Path").field(&self.segments).finish()
+    }
+}
+
+impl<T> AsRef<[u32]> for Path<T> {
+    fn as_ref(&self) -> &[u32] {
+        &self.segments
+    }
+}
+
+/// Paths convert to and from their segments encoded as a packed `Vec<u32>`, as in log entries.
+impl<T> From<&Path<T>> for Vec<u8> {
+    fn from(path: &Path<T>) -> Self {
+        path.segments.to_bytes()
+    }
+}
+
+impl<T> From<Path<T>> for Vec<u8> {
+    fn from(path: Path<T>) -> Self {
+        Self::from(&path)
+    }
+}
+
+impl<T> TryFrom<&[u8]> for Path<T> {
+    type Error = io::Error;
+
+    fn try_from(bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self::from_segments(Vec::deserialize(&mut Reader::new(
+            bytes,
+        ))?))
+    }
+}
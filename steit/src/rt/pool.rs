@@ -0,0 +1,100 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use super::runtime::Runtime;
+
+/// Runtimes of the items of a collection by field number, kept once created
+/// so items removed and added again, like projectiles despawned and respawned,
+/// reuse their path nodes instead of allocating new ones.
+///
+/// It holds as many runtimes as the collection has ever held items,
+/// and is dropped whenever the runtime of the collection changes.
+#[derive(Clone, Default)]
+pub struct RuntimePool {
+    parent: Option<Runtime>,
+    nested: Vec<Runtime>,
+}
+
+impl RuntimePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like `parent.nested(field_number)`, but reusing runtimes made before under `parent`.
+    pub fn nested(&mut self, parent: &Runtime, field_number: u32) -> Runtime {
+        if !matches!(&self.parent, Some(pooled) if pooled.ptr_eq(parent)) {
+            self.parent = Some(parent.clone());
+            self.nested.clear();
+        }
+
+        while self.nested.len() <= field_number as usize {
+            self.nested.push(parent.nested(self.nested.len() as u32));
+        }
+
+        self.nested
+            .get(field_number as usize)
+            .cloned()
+            .unwrap_or_else(|| parent.nested(field_number))
+    }
+}
+
+impl PartialEq for RuntimePool {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for RuntimePool {}
+
+impl Hash for RuntimePool {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl fmt::Debug for RuntimePool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RuntimePool")
+            .field("len", &self.nested.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{rt::Runtime, state::State, test_util::Point, types::List};
+
+    use super::RuntimePool;
+
+    #[test]
+    fn reuse_nested() {
+        let runtime = Runtime::new();
+        let mut pool = RuntimePool::new();
+
+        let first = pool.nested(&runtime, 2);
+        assert_eq!(first.path().collect_values(), [2]);
+        assert!(Arc::ptr_eq(first.path(), pool.nested(&runtime, 2).path()));
+
+        // Runtimes of another parent are never handed out.
+        let other = runtime.nested(5);
+        let nested = pool.nested(&other, 2);
+        assert_eq!(nested.path().collect_values(), [5, 2]);
+        assert!(!Arc::ptr_eq(first.path(), nested.path()));
+    }
+
+    #[test]
+    fn respawn_list_items() {
+        let mut list = List::new(Runtime::new());
+        list.push_with(Point::empty);
+        let path = list[0].runtime().path().clone();
+
+        list.pop();
+        list.push_with(Point::empty);
+        assert!(Arc::ptr_eq(&path, list[0].runtime().path()));
+
+        list.set_runtime(Runtime::new().nested(1));
+        assert_eq!(list[0].runtime().path().collect_values(), [1, 0]);
+    }
+}
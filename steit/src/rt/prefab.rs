@@ -0,0 +1,232 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    marker::PhantomData,
+};
+
+use crate::{
+    de::Reader,
+    meta::{HasMeta, MessageMeta},
+    ser::Serialize,
+    state::State,
+    wire_fmt::WireType,
+};
+
+use super::Runtime;
+
+// Top-level fields of an encoded struct by field number, with their tags.
+// Only the last occurrence of a field is kept, as it's the only one derived types write.
+type Fields = BTreeMap<u32, (WireType, Vec<u8>)>;
+
+fn split_fields(bytes: &[u8]) -> io::Result<Fields> {
+    let mut fields = BTreeMap::new();
+    let mut rest = bytes;
+
+    // Readers peek a byte to check for EOF, so a new one is used per read to keep `rest` exact.
+    while !rest.is_empty() {
+        let start = bytes.len() - rest.len();
+        let (field_number, wire_type) = Reader::new(&mut rest).read_tag()?;
+        Reader::new(&mut rest).skip_field(wire_type)?;
+        let end = bytes.len() - rest.len();
+
+        let field = bytes.get(start..end).unwrap_or_default().to_vec();
+        fields.insert(field_number, (wire_type, field));
+    }
+
+    Ok(fields)
+}
+
+// Encodes the default of a field which is present in a template but omitted by an instance.
+// Zero reads as the default for every wire type, be it a number, an empty message or `None`.
+fn zero_field(field_number: u32, wire_type: WireType) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    wire_type.tag(field_number)?.steit_serialize(&mut bytes)?;
+    let size = wire_type.fixed_size().unwrap_or(1);
+    bytes.resize(bytes.len() + size as usize, 0);
+    Ok(bytes)
+}
+
+/// Templates of entities, so spawning many similar ones only sends what sets them apart.
+///
+/// Templates are sent to clients once, with `template_bytes`, and registered there
+/// with `register_bytes` under the same ids. Instances are then encoded by `encode`
+/// as the top-level fields differing from their template, and rebuilt by `decode`.
+///
+/// Only structs can be templates, as enums have no fields to share before their variant is known.
+pub struct Prefabs<T> {
+    templates: HashMap<u32, (Vec<u8>, Fields)>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Default for Prefabs<T> {
+    fn default() -> Self {
+        Self {
+            templates: HashMap::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: State + HasMeta> Prefabs<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `template` under `id`, replacing any template registered before.
+    pub fn register(&mut self, id: u32, template: &T) -> io::Result<&mut Self> {
+        self.register_bytes(id, template.to_bytes())
+    }
+
+    /// Registers a template received as bytes, checking that it decodes.
+    pub fn register_bytes(&mut self, id: u32, bytes: Vec<u8>) -> io::Result<&mut Self> {
+        if !matches!(T::LINK.msg, Some(MessageMeta::Struct(_))) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{}` is not a struct to be a template", T::NAME.rust),
+            ));
+        }
+
+        T::deserialize(&mut Reader::new(&*bytes))?;
+        let fields = split_fields(&bytes)?;
+        self.templates.insert(id, (bytes, fields));
+        Ok(self)
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.templates.contains_key(&id)
+    }
+
+    /// Bytes of the template registered under `id`, to be sent to clients once.
+    pub fn template_bytes(&self, id: u32) -> Option<&[u8]> {
+        self.templates.get(&id).map(|(bytes, _)| &**bytes)
+    }
+
+    fn template(&self, id: u32) -> io::Result<&(Vec<u8>, Fields)> {
+        self.templates.get(&id).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no template registered under {}", id),
+            )
+        })
+    }
+
+    /// Creates an entity from the template under `id`, changed by `overrides` without logging,
+    /// since the entity gets logged as a whole wherever it's put.
+    pub fn instantiate(
+        &self,
+        id: u32,
+        runtime: Runtime,
+        overrides: impl FnOnce(&mut T),
+    ) -> io::Result<T> {
+        let (bytes, _) = self.template(id)?;
+        let mut value = T::from_bytes(runtime.clone(), bytes)?;
+
+        runtime.pause_logger();
+        overrides(&mut value);
+        runtime.unpause_logger();
+
+        Ok(value)
+    }
+
+    /// Encodes `instance` as the fields differing from the template under `id`.
+    pub fn encode(&self, id: u32, instance: &T) -> io::Result<Vec<u8>> {
+        let (_, template) = self.template(id)?;
+        let mut fields = split_fields(&instance.to_bytes())?;
+
+        for (&field_number, &(wire_type, _)) in template {
+            fields
+                .entry(field_number)
+                .or_insert((wire_type, zero_field(field_number, wire_type)?));
+        }
+
+        let mut bytes = Vec::new();
+
+        for (field_number, (_, field)) in fields {
+            if template.get(&field_number).map(|(_, bytes)| bytes) != Some(&field) {
+                bytes.extend(field);
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Rebuilds an instance encoded by `encode` from the template under `id`.
+    pub fn decode(&self, id: u32, bytes: &[u8], runtime: Runtime) -> io::Result<T> {
+        let (_, template) = self.template(id)?;
+        let mut fields = template.clone();
+        fields.extend(split_fields(bytes)?);
+
+        let bytes: Vec<u8> = fields.into_values().flat_map(|(_, field)| field).collect();
+        T::from_bytes(runtime, &bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::BufferLogger, rt::Runtime, ser::Serialize, steit_derive, test_util::Point,
+        types::List,
+    };
+
+    use super::Prefabs;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unit {
+        #[steit(tag = 0)]
+        kind: String,
+        #[steit(tag = 1)]
+        hp: u32,
+        #[steit(tag = 2)]
+        level: u8,
+        #[steit(tag = 3)]
+        skills: List<u8>,
+        #[steit(tag = 4)]
+        spawn: Point,
+    }
+
+    fn orc() -> Unit {
+        let mut orc = Unit::new(Runtime::new());
+        orc.set_kind("orc".to_string()).set_hp(100).set_level(3);
+        orc.skills.push(1);
+        orc.skills.push(2);
+        orc.spawn.set_x(4);
+        orc
+    }
+
+    #[test]
+    fn instantiate_and_encode() {
+        let mut server = Prefabs::new();
+        server.register(7, &orc()).unwrap();
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut units = List::new(runtime);
+
+        units.push_with(|runtime| {
+            server
+                .instantiate(7, runtime, |unit| {
+                    unit.set_hp(50).set_level(0);
+                    unit.spawn.set_y(2);
+                })
+                .unwrap()
+        });
+
+        let unit = &units[0];
+        assert_eq!((unit.hp, unit.level, unit.skills.len()), (50, 0, 2));
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+
+        let delta = server.encode(7, unit).unwrap();
+        assert!(delta.len() < unit.to_bytes().len());
+
+        let mut client = Prefabs::<Unit>::new();
+        let template = server.template_bytes(7).unwrap().to_vec();
+        client.register_bytes(7, template).unwrap();
+
+        let decoded = client.decode(7, &delta, Runtime::new()).unwrap();
+        assert_eq!(decoded, units[0]);
+
+        let unchanged = server.instantiate(7, Runtime::new(), |_| {}).unwrap();
+        assert!(server.encode(7, &unchanged).unwrap().is_empty());
+        assert!(client.decode(8, &delta, Runtime::new()).is_err());
+    }
+}
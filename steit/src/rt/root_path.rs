@@ -0,0 +1,104 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// Field numbers from the root state `root_id` down to a nested `T`,
+/// laid out like paths of entries logged through `Runtime::root`, the root id first,
+/// so states of one tree can refer to states of another,
+/// e.g. chat messages to players of a match. `Roots::resolve` follows them.
+///
+/// States keep them as `Vec<u32>` fields, see `to_segments` and `from_segments`.
+pub struct RootPath<T> {
+    root_id: u32,
+    path: Vec<u32>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> RootPath<T> {
+    /// Trusts `path` to lead to a `T` in root `root_id`.
+    pub fn new(root_id: u32, path: Vec<u32>) -> Self {
+        Self {
+            root_id,
+            path,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Splits `segments` into the root id and the path in that root,
+    /// or returns `None` if there is no root id.
+    pub fn from_segments(mut segments: Vec<u32>) -> Option<Self> {
+        if segments.is_empty() {
+            return None;
+        }
+
+        let root_id = segments.remove(0);
+        Some(Self::new(root_id, segments))
+    }
+
+    pub fn root_id(&self) -> u32 {
+        self.root_id
+    }
+
+    pub fn path(&self) -> &[u32] {
+        &self.path
+    }
+
+    pub fn to_segments(&self) -> Vec<u32> {
+        let mut segments = Vec::with_capacity(self.path.len() + 1);
+        segments.push(self.root_id);
+        segments.extend_from_slice(&self.path);
+        segments
+    }
+
+    /// Goes down to field `field_number`, which must hold a `U`.
+    pub fn child<U>(&self, field_number: u32) -> RootPath<U> {
+        let mut path = self.path.clone();
+        path.push(field_number);
+        RootPath::new(self.root_id, path)
+    }
+}
+
+impl<T> Clone for RootPath<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.root_id, self.path.clone())
+    }
+}
+
+impl<T> PartialEq for RootPath<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.root_id == other.root_id && self.path == other.path
+    }
+}
+
+impl<T> Eq for RootPath<T> {}
+
+impl<T> Hash for RootPath<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.root_id.hash(state);
+        self.path.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for RootPath<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RootPath")
+            .field("root_id", &self.root_id)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RootPath;
+
+    #[test]
+    fn segments() {
+        let path = RootPath::<()>::new(1, vec![0, 2]).child::<u32>(5);
+        assert_eq!(path.to_segments(), &[1, 0, 2, 5]);
+        assert_eq!(RootPath::from_segments(path.to_segments()), Some(path));
+        assert_eq!(RootPath::<u32>::from_segments(Vec::new()), None);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{collections::HashMap, io};
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{LogEntry, LogEntryKind, StringTable},
+    state::State,
+    wire_fmt::HasWireType,
+};
+
+use super::RootPath;
+
+// Root states are kept type-erased, to replay entries and resolve paths into them.
+trait RootState {
+    fn handle(
+        &mut self,
+        path: Vec<u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<&[u8]>,
+    ) -> io::Result<()>;
+
+    fn serialize_at(&self, path: &[u32], writer: &mut Vec<u8>) -> io::Result<bool>;
+}
+
+impl<T: State> RootState for &mut T {
+    fn handle(
+        &mut self,
+        path: Vec<u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<&[u8]>,
+    ) -> io::Result<()> {
+        State::handle(*self, path.into_iter(), kind, key, reader)
+    }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut Vec<u8>) -> io::Result<bool> {
+        State::serialize_at(*self, path, writer)
+    }
+}
+
+/// Replays log entries of several root states sharing one logger,
+/// dispatching each entry by the root id its path starts with.
+///
+/// Senders get the runtime of each root state with `Runtime::root`.
+#[derive(Default)]
+pub struct Roots<'a> {
+    states: HashMap<u32, Box<dyn RootState + 'a>>,
+}
+
+impl<'a> Roots<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `state` to receive entries of root `root_id`,
+    /// replacing any state registered before under the same id.
+    pub fn add<T: State>(&mut self, root_id: u32, state: &'a mut T) -> &mut Self {
+        self.states.insert(root_id, Box::new(state));
+        self
+    }
+
+    pub fn contains(&self, root_id: u32) -> bool {
+        self.states.contains_key(&root_id)
+    }
+
+    /// Decodes the value `path` refers to, walking the registered root state down to it,
+    /// so references from one tree can be followed into another.
+    /// Returns `None` if there is nothing at the path in that root.
+    pub fn resolve<T: Deserialize>(&self, path: &RootPath<T>) -> io::Result<Option<T>> {
+        let state = self
+            .states
+            .get(&path.root_id())
+            .ok_or_else(|| root_not_found(path.root_id()))?;
+
+        let mut bytes = Vec::new();
+
+        if !state.serialize_at(path.path(), &mut bytes)? {
+            return Ok(None);
+        }
+
+        T::deserialize(&mut Reader::new(&*bytes)).map(Some)
+    }
+
+    pub fn replay(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        self.replay_with_strings(reader, &mut StringTable::new())
+    }
+
+    /// Like `State::replay_with_strings`, the string table is shared by all roots of a stream.
+    pub fn replay_with_strings(
+        &mut self,
+        reader: &mut Reader<impl io::Read>,
+        strings: &mut StringTable,
+    ) -> io::Result<()> {
+        while !reader.eof()? {
+            let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+
+            if let Some((kind, mut path, key, bytes)) = entry.unpack(strings)? {
+                if path.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "expected log entry path to start with a root id",
+                    ));
+                }
+
+                let root_id = path.remove(0);
+
+                let state = self
+                    .states
+                    .get_mut(&root_id)
+                    .ok_or_else(|| root_not_found(root_id))?;
+
+                let bytes = bytes.unwrap_or_default();
+                state.handle(path, kind, key, &mut Reader::new(&*bytes))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn root_not_found(root_id: u32) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("root {} not found", root_id),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        de::Reader,
+        log::loggers::BufferLogger,
+        rt::{Path, RootPath, Runtime},
+        state::State,
+        steit_derive,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::Roots;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Item {
+        #[steit(tag = 0, string_intern)]
+        id: String,
+        #[steit(tag = 1)]
+        count: u32,
+    }
+
+    #[test]
+    fn roots_share_logger() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut lobby = Point::empty(runtime.root(0));
+        let mut chat = Item::new(runtime.root(1));
+
+        lobby.set_x(3);
+        chat.set_id("hello".to_string()).set_count(2);
+        lobby.set_y(-1);
+
+        let bytes = logger.lock().unwrap().bytes();
+        let paths: Vec<_> = logger
+            .lock()
+            .unwrap()
+            .pluck()
+            .iter()
+            .filter_map(|entry| entry.path().map(|path| path.to_vec()))
+            .collect();
+
+        // The string intern entry has no path, hence no root id either.
+        assert_eq!(paths, &[vec![0, 0], vec![1, 0], vec![1, 1], vec![0, 1]]);
+
+        let mut lobby_mirror = Point::empty(Runtime::new());
+        let mut chat_mirror = Item::new(Runtime::new());
+
+        Roots::new()
+            .add(0, &mut lobby_mirror)
+            .add(1, &mut chat_mirror)
+            .replay(&mut Reader::new(&*bytes))
+            .unwrap();
+
+        assert_eq!(lobby_mirror, lobby);
+        assert_eq!(chat_mirror, chat);
+    }
+
+    #[test]
+    fn roots_update_whole_root() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let lobby = Point::new(runtime.root(4), 5, 0, 0);
+        lobby.runtime().log_update(&lobby).unwrap();
+
+        let mut mirror = Point::empty(Runtime::new());
+
+        Roots::new()
+            .add(4, &mut mirror)
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+
+        assert_eq!(mirror.x, 5);
+    }
+
+    #[test]
+    fn roots_resolve() {
+        let mut lobby = Point::new(Runtime::new(), 5, 6, 7);
+        let mut chat = Item::new(Runtime::new());
+        chat.set_count(2);
+
+        let mut squads = List::new(Runtime::new());
+        squads.push(Point::new(Runtime::new(), 1, 2, 3));
+
+        let mut spawn = Some(Box::new(Point::new(Runtime::new(), 4, 0, 0)));
+
+        // A chat message would keep the reference in a `Vec<u32>` field.
+        let segments = RootPath::<Point>::new(0, Vec::new())
+            .child::<i32>(1)
+            .to_segments();
+        assert_eq!(segments, &[0, 1]);
+        let reference = RootPath::<i32>::from_segments(segments).unwrap();
+
+        let mut roots = Roots::new();
+        roots
+            .add(0, &mut lobby)
+            .add(1, &mut chat)
+            .add(2, &mut squads)
+            .add(3, &mut spawn);
+
+        assert_eq!(roots.resolve(&reference).unwrap(), Some(6));
+        assert_eq!(
+            roots.resolve(&RootPath::<u32>::new(1, vec![1])).unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            roots.resolve(&RootPath::<Point>::new(2, vec![0])).unwrap(),
+            Some(Point::new(Runtime::new(), 1, 2, 3))
+        );
+        assert_eq!(
+            roots.resolve(&RootPath::<i32>::new(2, vec![0, 2])).unwrap(),
+            Some(3)
+        );
+        assert_eq!(roots.resolve(&reference.child::<i32>(3)).unwrap(), None);
+        assert_eq!(
+            roots.resolve(&RootPath::<Point>::new(2, vec![1])).unwrap(),
+            None
+        );
+        assert_eq!(
+            roots
+                .resolve(&Path::<Point>::root().child::<i32>(0).in_root(3))
+                .unwrap(),
+            Some(4)
+        );
+        assert!(roots
+            .resolve(&RootPath::<Point>::new(4, Vec::new()))
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "root 1 not found")]
+    fn roots_unknown() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        Point::empty(runtime.root(1)).set_x(1);
+
+        let mut mirror = Point::empty(Runtime::new());
+
+        Roots::new()
+            .add(0, &mut mirror)
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+    }
+}
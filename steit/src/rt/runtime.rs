@@ -8,15 +8,23 @@ use std::{
 use serde::Serialize as JsonSerialize;
 
 use crate::{
-    log::{loggers::BufferLogger, LogEntry, Logger},
+    log::{
+        loggers::{BufferLogger, NoopLogger},
+        LogEntry, Logger,
+    },
     ser::Serialize,
 };
 
 use super::{
+    expect::{expect_valid, lock},
     logger::{LoggerHandle, PausableLogger, RuntimeLogger},
     node::Node,
 };
 
+/// Where a state sits in its tree, with the logger its changes go to.
+///
+/// Paths and loggers are shared through `Arc` and `Mutex`, so runtimes and the states
+/// holding them are `Send + Sync` and can be moved to other threads or tasks.
 #[derive(Clone, JsonSerialize)]
 pub struct Runtime {
     #[serde(skip_serializing)]
@@ -46,6 +54,16 @@ impl Runtime {
         Self::with_logger(BufferLogger::new())
     }
 
+    /// Creates a runtime which discards its logs, for states which are never replicated.
+    pub fn noop() -> Self {
+        Self::with_logger(NoopLogger::new())
+    }
+
+    /// Creates a runtime with a `BufferLogger` preallocated for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_logger(BufferLogger::with_capacity(capacity))
+    }
+
     pub fn with_logger_returned<T: Logger + 'static>(logger: T) -> (Self, LoggerHandle<T>) {
         let logger = Arc::new(Mutex::new(RuntimeLogger::new(logger)));
 
@@ -62,6 +80,31 @@ impl Runtime {
         Self::with_logger_returned(logger).0
     }
 
+    /// Returns a runtime at the same path as this one, logging into `logger` instead.
+    pub fn with_logger_at<T: Logger + 'static>(&self, logger: T) -> (Self, LoggerHandle<T>) {
+        let (runtime, logger) = Self::with_logger_returned(logger);
+
+        (
+            Self {
+                path: self.path.clone(),
+                ..runtime
+            },
+            logger,
+        )
+    }
+
+    /// Returns a runtime for the root state `root_id` among several independent
+    /// state trees logged into the logger of this one.
+    ///
+    /// Paths of its entries are prefixed with `root_id`,
+    /// which `Roots` uses on the receiving side to dispatch them.
+    pub fn root(&self, root_id: u32) -> Self {
+        Self {
+            logger: self.logger.clone(),
+            path: Arc::new(Node::child(&Arc::new(Node::Root), root_id)),
+        }
+    }
+
     pub fn nested(&self, field_number: u32) -> Self {
         Self {
             logger: self.logger.clone(),
@@ -83,13 +126,19 @@ impl Runtime {
         }
     }
 
+    /// Whether `other` shares the logger and path node of this runtime,
+    /// rather than only being equal like all runtimes are.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.logger, &other.logger) && Arc::ptr_eq(&self.path, &other.path)
+    }
+
     pub fn path(&self) -> &Arc<Node<u32>> {
         &self.path
     }
 
     pub fn is_root(&self) -> bool {
         match &*self.path {
-            Node::Root { .. } => true,
+            Node::Root => true,
             Node::Child { .. } => false,
         }
     }
@@ -102,8 +151,9 @@ impl Runtime {
         self.path.get_value().copied()
     }
 
+    /// The field number of this state in its parent, 0 for the root.
     pub fn field_number(&self) -> u32 {
-        *self.path.value()
+        self.get_field_number().unwrap_or_default()
     }
 
     pub fn logger(&self) -> &Arc<Mutex<dyn PausableLogger>> {
@@ -111,24 +161,88 @@ impl Runtime {
     }
 
     pub fn pause_logger(&self) -> u32 {
-        self.logger.lock().unwrap().pause()
+        lock(&self.logger).pause()
     }
 
     pub fn unpause_logger(&self) -> u32 {
-        self.logger.lock().unwrap().unpause()
+        lock(&self.logger).unpause()
+    }
+
+    /// Runs `f` without logging changes made under the path of this runtime by this thread,
+    /// like client-side tweaks of a mirrored state which are only visual.
+    ///
+    /// Unlike `pause_logger`, changes made elsewhere or by other threads are still logged.
+    pub fn silenced<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Unsilence<'a>(&'a Runtime, Vec<u32>);
+
+        impl Drop for Unsilence<'_> {
+            fn drop(&mut self) {
+                lock(&self.0.logger).unsilence(&self.1);
+            }
+        }
+
+        let path = self.path.collect_values();
+        lock(&self.logger).silence(path.clone());
+        let _unsilence = Unsilence(self, path);
+        f()
+    }
+
+    /// Holds entries logged under the path of this runtime by this thread
+    /// until `end_batch` is called, to log them together.
+    ///
+    /// Unlike logging into a separate logger, the batch shares interned strings,
+    /// pausing and silencing with the rest of the tree.
+    pub fn begin_batch(&self) {
+        lock(&self.logger).begin_batch(self.path.collect_values());
+    }
+
+    /// Logs entries held since `begin_batch` together if `commit` is set, or drops them.
+    /// Batches nest, with committed entries going to the enclosing batch.
+    pub fn end_batch(&self, commit: bool) -> io::Result<()> {
+        lock(&self.logger).end_batch(&self.path.collect_values(), commit)
+    }
+
+    /// Routes errors of the logger of this tree to `handler`, which then stand for successes,
+    /// along with values rejected by setters of constrained fields in the tree.
+    ///
+    /// Setters can't return errors, so they panic on them otherwise,
+    /// unless the `no-panic` feature is enabled, which drops them instead.
+    pub fn on_log_error(&self, handler: impl FnMut(io::Error) + Send + 'static) {
+        lock(&self.logger).set_error_handler(Some(Box::new(handler)));
+    }
+
+    pub fn clear_log_error_handler(&self) {
+        lock(&self.logger).set_error_handler(None);
+    }
+
+    /// Like `expect_valid`, but passes rejections to the handler set with `on_log_error` if any,
+    /// so they are still reported with `no-panic`. Setters of states go through it.
+    pub fn expect_valid<T>(&self, result: io::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                // Released first, as `expect_valid` may panic.
+                let reported = lock(&self.logger).report_error(error);
+                expect_valid(reported);
+                None
+            }
+        }
     }
 
     pub fn log(&self, entry: LogEntry) -> io::Result<()> {
-        self.logger.lock().unwrap().log(entry)
+        lock(&self.logger).log(entry)
     }
 
     pub fn log_multi(&self, entries: Vec<LogEntry>) -> io::Result<()> {
-        self.logger.lock().unwrap().log_multi(entries)
+        lock(&self.logger).log_multi(entries)
     }
 
     impl_entry!(entry_update, new_update, value: &impl Serialize);
+    impl_entry!(entry_replace, new_replace, value: &impl Serialize);
     impl_entry!(entry_list_push, new_list_push, item: &impl Serialize);
     impl_entry!(entry_list_pop, new_list_pop);
+    impl_entry!(entry_list_extend, new_list_extend, items: &[impl Serialize]);
+    impl_entry!(entry_list_move, new_list_move, from: u32, to: u32);
     impl_entry!(entry_map_remove, new_map_remove, key: u32);
 
     pub fn entry_update_child(&self, field_number: u32, value: &impl Serialize) -> LogEntry {
@@ -142,9 +256,17 @@ impl Runtime {
         field_number: u32,
         value: &impl Serialize,
     );
+
+    impl_log!(log_replace, entry_replace, value: &impl Serialize);
     impl_log!(log_list_push, entry_list_push, item: &impl Serialize);
     impl_log!(log_list_pop, entry_list_pop);
+    impl_log!(log_list_extend, entry_list_extend, items: &[impl Serialize]);
+    impl_log!(log_list_move, entry_list_move, from: u32, to: u32);
     impl_log!(log_map_remove, entry_map_remove, key: u32);
+
+    pub fn log_update_child_interned(&self, field_number: u32, value: &str) -> io::Result<()> {
+        lock(&self.logger).log_update_interned(&Node::child(&self.path, field_number), value)
+    }
 }
 
 impl PartialEq for Runtime {
@@ -173,3 +295,40 @@ impl fmt::Debug for Runtime {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{
+        log::loggers::BufferLogger,
+        test_util::{replay, Point},
+        types::{List, Map},
+    };
+
+    use super::Runtime;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn send_and_sync() {
+        assert_send_sync::<Runtime>();
+        assert_send_sync::<Point>();
+        assert_send_sync::<List<Point>>();
+        assert_send_sync::<Map<u32, Point>>();
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::empty(runtime);
+
+        let point = thread::spawn(move || {
+            point.set_x(1).set_z(3);
+            point
+        })
+        .join()
+        .unwrap();
+
+        let mut mirror = Point::empty(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, point);
+    }
+}
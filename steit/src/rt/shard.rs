@@ -0,0 +1,179 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use crate::{
+    log::{LogEntry, Logger},
+    state::State,
+};
+
+use super::{expect::lock, logger::LoggerHandle, runtime::Runtime};
+
+struct ShardLogger {
+    tick: Arc<AtomicU64>,
+    entries: Vec<(u64, LogEntry)>,
+}
+
+impl Logger for ShardLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.entries
+            .push((self.tick.load(Ordering::Acquire), entry));
+        Ok(())
+    }
+}
+
+/// Splits a state tree into disjoint subtrees, each logging into its own shard,
+/// so they can be mutated in parallel by worker threads.
+///
+/// Entries keep the paths they would have in the whole tree,
+/// and `flush` merges them by tick, then by shard, into one coherent log.
+///
+/// Shards intern strings separately, so subtrees with interned strings can't be sharded.
+#[derive(Default)]
+pub struct Shards {
+    tick: Arc<AtomicU64>,
+    shards: Vec<LoggerHandle<ShardLogger>>,
+}
+
+impl Shards {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves `subtree` onto a new shard and returns its index.
+    ///
+    /// Subtrees must not overlap, or their entries would be logged twice.
+    pub fn assign(&mut self, subtree: &mut impl State) -> usize {
+        let (runtime, logger) = subtree.runtime().with_logger_at(ShardLogger {
+            tick: self.tick.clone(),
+            entries: Vec::new(),
+        });
+
+        subtree.set_runtime(runtime);
+        self.shards.push(logger);
+        self.shards.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick.load(Ordering::Acquire)
+    }
+
+    /// Starts the next tick, after workers are done with the current one.
+    pub fn advance_tick(&self) -> u64 {
+        self.tick.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    /// Takes entries logged by all shards so far, ordered by tick, then by shard.
+    pub fn pluck(&self) -> Vec<LogEntry> {
+        let mut entries = Vec::new();
+
+        for (index, shard) in self.shards.iter().enumerate() {
+            let shard_entries = std::mem::take(&mut lock(shard).entries);
+
+            entries.extend(
+                shard_entries
+                    .into_iter()
+                    .map(|(tick, entry)| (tick, index, entry)),
+            );
+        }
+
+        // Stable, so entries of a shard stay in the order they were logged.
+        entries.sort_by_key(|&(tick, index, _)| (tick, index));
+        entries.into_iter().map(|(_, _, entry)| entry).collect()
+    }
+
+    /// Logs merged entries of all shards into the logger of `runtime`.
+    pub fn flush(&self, runtime: &Runtime) -> io::Result<()> {
+        runtime.log_multi(self.pluck())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{
+        log::loggers::BufferLogger,
+        rt::Runtime,
+        state::State,
+        test_util::{replay, Point},
+        types::List,
+    };
+
+    use super::Shards;
+
+    #[test]
+    fn parallel_subtrees() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut points = List::new(runtime);
+        points.push(Point::empty(Runtime::new()));
+        points.push(Point::empty(Runtime::new()));
+        logger.lock().unwrap().clear();
+
+        let mut shards = Shards::new();
+
+        for point in points.iter_mut() {
+            shards.assign(point);
+        }
+
+        assert_eq!(shards.len(), 2);
+
+        thread::scope(|scope| {
+            for (index, point) in points.iter_mut().enumerate() {
+                scope.spawn(move || {
+                    point.set_x(index as i32 + 1);
+                });
+            }
+        });
+
+        shards.advance_tick();
+        points.get_mut(0).unwrap().set_y(-1);
+        shards.flush(points.runtime()).unwrap();
+
+        let mut mirror = List::<Point>::new(Runtime::new());
+        mirror.push(Point::empty(Runtime::new()));
+        mirror.push(Point::empty(Runtime::new()));
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+
+        assert_eq!(mirror[0], Point::new(Runtime::new(), 1, -1, 0));
+        assert_eq!(mirror[1], Point::new(Runtime::new(), 2, 0, 0));
+    }
+
+    #[test]
+    fn tick_order() {
+        let mut first = Point::empty(Runtime::new().nested(0));
+        let mut second = Point::empty(Runtime::new().nested(1));
+
+        let mut shards = Shards::new();
+        shards.assign(&mut first);
+        shards.assign(&mut second);
+
+        first.set_x(1);
+        shards.advance_tick();
+        second.set_x(2);
+        first.set_x(3);
+        shards.advance_tick();
+        second.set_x(4);
+
+        let paths: Vec<_> = shards
+            .pluck()
+            .iter()
+            .map(|entry| entry.path().unwrap().to_vec())
+            .collect();
+
+        assert_eq!(paths, [[0, 0], [0, 0], [1, 0], [1, 0]]);
+        assert!(shards.pluck().is_empty());
+    }
+}
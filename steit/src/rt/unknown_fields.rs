@@ -0,0 +1,159 @@
+use std::io::{self, Read};
+
+use serde::Serialize as JsonSerialize;
+
+use crate::{de::Reader, ser::Serialize, wire_fmt::WireType};
+
+/// Fields of a message whose tags are unknown to its type, kept as they were read
+/// to be written back after the known ones.
+///
+/// Added to structs with `#[steit(preserve_unknown)]`, so proxies built against an older schema
+/// can change a message without dropping the fields they don't know about.
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug, JsonSerialize)]
+pub struct UnknownFields {
+    bytes: Vec<u8>,
+}
+
+impl UnknownFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The fields as they were read, tags included.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    /// Reads the value of a field whose tag has just been read, keeping it along with the tag.
+    pub fn merge_field(
+        &mut self,
+        field_number: u32,
+        wire_type: WireType,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        wire_type
+            .tag(field_number)?
+            .steit_serialize(&mut self.bytes)?;
+
+        let size = match wire_type {
+            WireType::Varint => {
+                self.read_varint(reader)?;
+                return Ok(());
+            }
+
+            WireType::Sized => self.read_varint(reader)?,
+            wire_type => wire_type.fixed_size().unwrap_or_default() as u64,
+        };
+
+        let read = reader.by_ref().take(size).read_to_end(&mut self.bytes)?;
+
+        if (read as u64) < size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("field of {} bytes ends after {}", size, read),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Copies the bytes of a varint as they are, returning its value.
+    fn read_varint(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<u64> {
+        let mut value = 0;
+        let mut offset = 0;
+        let mut buf = [0];
+
+        loop {
+            reader.read_exact(&mut buf)?;
+            self.bytes.push(buf[0]);
+
+            if offset < u64::BITS {
+                value |= ((buf[0] & 0x7f) as u64) << offset;
+            }
+
+            if buf[0] & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            offset += 7;
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.bytes.len() as u32
+    }
+
+    pub fn serialize(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        writer.write_all(&self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        de::{Deserialize, Reader},
+        ser::Serialize,
+        steit_derive,
+    };
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    struct Newer {
+        #[steit(tag = 0)]
+        name: String,
+        #[steit(tag = 1)]
+        level: u32,
+        #[steit(tag = 2)]
+        ratio: f64,
+        #[steit(tag = 3)]
+        title: String,
+        #[steit(tag = 4)]
+        weight: f32,
+    }
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(steit_owned, preserve_unknown)]
+    struct Proxy {
+        #[steit(tag = 1)]
+        level: u32,
+    }
+
+    #[test]
+    fn round_trip() {
+        let newer = Newer {
+            name: "Lina".to_string(),
+            level: 300,
+            ratio: 0.5,
+            title: "Slayer".to_string(),
+            weight: 2.0,
+            ..Default::default()
+        };
+
+        let bytes = newer.to_bytes();
+        let mut proxy = Proxy::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(proxy.level, 300);
+        assert!(!proxy.unknown_fields.is_empty());
+
+        proxy.level = 301;
+        let bytes = proxy.to_bytes();
+        assert_eq!(bytes.len() as u32, proxy.compute_size());
+
+        let newer = Newer::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!((&*newer.name, newer.level), ("Lina", 301));
+        assert_eq!(
+            (newer.ratio, &*newer.title, newer.weight),
+            (0.5, "Slayer", 2.0)
+        );
+
+        let error = Proxy::deserialize(&mut Reader::new(&[2 << 3 | 2, 5, 1][..]));
+        assert!(error.is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use std::io;
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{LogEntry, LogEntryKind, StringTable},
+    meta::{HasMeta, PathPattern},
+    state::{check_root, State},
+    wire_fmt::HasWireType,
+};
+
+/// What a `WritePolicy` does with entries writing outside of its writable paths.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rejection {
+    /// Skips the entry and replays the rest.
+    Skip,
+    /// Stops replaying with a `PermissionDenied` error, so the connection can be dropped.
+    Disconnect,
+}
+
+/// An entry rejected by a `WritePolicy`, kept for anti-cheat reviews.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AuditRecord {
+    pub kind: LogEntryKind,
+    pub path: Vec<u32>,
+    /// Value of the entry as received, empty for kinds without one.
+    pub bytes: Vec<u8>,
+}
+
+/// Replays entries sent by clients into server-authoritative states,
+/// rejecting those which write outside of the paths clients are allowed to write.
+pub struct WritePolicy {
+    writable: Vec<PathPattern>,
+    rejection: Rejection,
+    audit: Vec<AuditRecord>,
+}
+
+impl WritePolicy {
+    /// Starts with nothing writable.
+    pub fn new(rejection: Rejection) -> Self {
+        Self {
+            writable: Vec::new(),
+            rejection,
+            audit: Vec::new(),
+        }
+    }
+
+    /// Allows writes to fields of `T` marked with `#[steit(client_writable)]`.
+    pub fn client_writable<T: HasMeta>(rejection: Rejection) -> Self {
+        let mut policy = Self::new(rejection);
+        policy.writable = PathPattern::client_writable::<T>();
+        policy
+    }
+
+    /// Allows writes to paths matched by `pattern` and everything under them.
+    pub fn allow(&mut self, pattern: impl Into<PathPattern>) -> &mut Self {
+        self.writable.push(pattern.into());
+        self
+    }
+
+    pub fn is_writable(&self, path: &[u32]) -> bool {
+        self.writable.iter().any(|pattern| pattern.covers(path))
+    }
+
+    /// Entries rejected so far, oldest first.
+    pub fn audit(&self) -> &[AuditRecord] {
+        &self.audit
+    }
+
+    pub fn take_audit(&mut self) -> Vec<AuditRecord> {
+        std::mem::take(&mut self.audit)
+    }
+
+    pub fn replay<T: State>(
+        &mut self,
+        state: &mut T,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        self.replay_with_strings(state, reader, &mut StringTable::new())
+    }
+
+    /// Like `State::replay_with_strings`, applying entries to writable paths only.
+    /// Rejected entries are recorded in the audit log either way.
+    pub fn replay_with_strings<T: State>(
+        &mut self,
+        state: &mut T,
+        reader: &mut Reader<impl io::Read>,
+        strings: &mut StringTable,
+    ) -> io::Result<()> {
+        check_root(state)?;
+
+        while !reader.eof()? {
+            let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+
+            if let Some((kind, path, key, bytes)) = entry.unpack(strings)? {
+                let bytes = bytes.unwrap_or_default();
+
+                if !self.is_writable(&path) {
+                    let message = format!("path {:?} is not client-writable", path);
+                    self.audit.push(AuditRecord { kind, path, bytes });
+
+                    match self.rejection {
+                        Rejection::Skip => continue,
+                        Rejection::Disconnect => {
+                            return Err(io::Error::new(io::ErrorKind::PermissionDenied, message));
+                        }
+                    }
+                }
+
+                state.handle(path.into_iter(), kind, key, &mut Reader::new(&*bytes))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{
+        de::Reader,
+        log::{loggers::BufferLogger, LogEntryKind},
+        meta::PathPattern,
+        rt::Runtime,
+        ser::Serialize,
+        steit_derive,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::{AuditRecord, Rejection, WritePolicy};
+
+    #[test]
+    fn write_policy_skip() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::empty(runtime);
+        point.set_x(1).set_y(2).set_z(3);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut policy = WritePolicy::new(Rejection::Skip);
+        policy.allow(PathPattern::parse::<Point>("x").unwrap());
+        policy.allow(PathPattern::parse::<Point>("z").unwrap());
+
+        let mut server = Point::empty(Runtime::new());
+        policy
+            .replay(&mut server, &mut Reader::new(&*bytes))
+            .unwrap();
+        assert_eq!(server, Point::new(Runtime::new(), 1, 0, 3));
+
+        assert_eq!(
+            policy.take_audit(),
+            [AuditRecord {
+                kind: LogEntryKind::Update,
+                path: vec![1],
+                bytes: 2i32.to_bytes(),
+            }]
+        );
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Squad {
+        #[steit(tag = 0)]
+        units: List<Point>,
+        #[steit(tag = 1)]
+        size: u8,
+    }
+
+    #[test]
+    fn write_policy_disconnect() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut squad = Squad::new(runtime);
+        squad
+            .units
+            .push_with(|runtime| Point::new(runtime, 10, 0, 0));
+        squad.units[0].set_x(20);
+        squad.set_size(7);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut policy = WritePolicy::new(Rejection::Disconnect);
+        policy.allow(PathPattern::parse::<Squad>("units").unwrap());
+        assert!(policy.is_writable(&[0, 0, 0]));
+
+        let mut server = Squad::new(Runtime::new());
+        let error = policy
+            .replay(&mut server, &mut Reader::new(&*bytes))
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(server.units[0].x, 20);
+        assert_eq!(server.size, 0);
+        assert_eq!(policy.audit().len(), 1);
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Player {
+        #[steit(tag = 0)]
+        gold: u32,
+        #[steit(tag = 1, client_writable)]
+        cursor: Point,
+    }
+
+    #[test]
+    fn write_policy_client_writable() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut player = Player::new(runtime);
+        player.set_gold(999_999);
+        player.cursor.set_x(3).set_y(4);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut policy = WritePolicy::client_writable::<Player>(Rejection::Skip);
+        let mut server = Player::new(Runtime::new());
+        policy
+            .replay(&mut server, &mut Reader::new(&*bytes))
+            .unwrap();
+
+        assert_eq!(server.gold, 0);
+        assert_eq!(server.cursor, Point::new(Runtime::new(), 3, 4, 0));
+        assert_eq!(policy.audit()[0].path, [0]);
+    }
+}
@@ -0,0 +1,4 @@
+//! States and their replication: runtimes, loggers, replay and collections,
+//! on top of the wire format in `wire`. Built with the default `runtime` feature.
+
+pub use crate::{log::*, rt::*, state::*, types::*};
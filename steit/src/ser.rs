@@ -1,8 +1,8 @@
-use std::io;
+use std::{cell::Cell, hash::Hasher, io};
 
 use super::{
-    rt::SizeCache,
-    wire_fmt::{HasWireType, WireType},
+    rt::{expect_written, SizeCache},
+    wire_fmt::{HasWireType, WireKey, WireType},
 };
 
 pub trait Serialize: HasWireType {
@@ -11,6 +11,8 @@ pub trait Serialize: HasWireType {
 
     fn size_cache(&self) -> Option<&SizeCache>;
 
+    // Size caching is driven by generated code, which is free to change how it's done.
+    #[doc(hidden)]
     fn cache_size(&self) -> u32 {
         let size = self.compute_size();
 
@@ -21,6 +23,7 @@ pub trait Serialize: HasWireType {
         size
     }
 
+    #[doc(hidden)]
     fn cached_size(&self) -> u32 {
         match self.size_cache() {
             Some(size_cache) => size_cache.get(),
@@ -52,9 +55,8 @@ pub trait Serialize: HasWireType {
             return Ok(0);
         }
 
-        match Self::WIRE_TYPE {
-            WireType::Varint => (),
-            WireType::Sized => size += size.cache_size(),
+        if Self::WIRE_TYPE == WireType::Sized {
+            size += size.cache_size();
         }
 
         if let Some(field_number) = field_number {
@@ -80,9 +82,8 @@ pub trait Serialize: HasWireType {
             self.tag(field_number)?.serialize_cached(writer)?;
         }
 
-        match Self::WIRE_TYPE {
-            WireType::Varint => (),
-            WireType::Sized => self.cached_size().serialize_cached(writer)?,
+        if Self::WIRE_TYPE == WireType::Sized {
+            self.cached_size().serialize_cached(writer)?;
         }
 
         self.serialize_cached(writer)
@@ -90,7 +91,467 @@ pub trait Serialize: HasWireType {
 
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
-        self.steit_serialize(&mut bytes).unwrap();
+        expect_written(self.steit_serialize(&mut bytes));
         bytes
     }
+
+    /// Like `to_bytes`, with maps anywhere in the value written in `order`.
+    fn to_bytes_in(&self, order: MapOrder) -> Vec<u8> {
+        order.scope(|| self.to_bytes())
+    }
+
+    /// Encodes `to_bytes` as lowercase hex, for logs and text protocols.
+    fn to_hex(&self) -> String {
+        self.to_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Serializes into `writer` while feeding the same bytes to `hasher`,
+    /// so a checksum of the encoding costs no second pass.
+    fn serialize_hashed(
+        &self,
+        writer: &mut impl io::Write,
+        hasher: &mut impl Hasher,
+    ) -> io::Result<()> {
+        self.steit_serialize(&mut HashingWriter::new(writer, hasher))
+    }
+}
+
+/// Passes writes through to `inner`, hashing every byte written.
+///
+/// Bytes arrive in arbitrary chunks, so `H` must hash consecutive writes as one stream,
+/// as `DefaultHasher` does.
+pub struct HashingWriter<W: io::Write, H: Hasher> {
+    inner: W,
+    hasher: H,
+}
+
+impl<W: io::Write, H: Hasher> HashingWriter<W, H> {
+    pub fn new(inner: W, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    pub fn into_inner(self) -> (W, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<W: io::Write, H: Hasher> io::Write for HashingWriter<W, H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.write(buf.get(..len).unwrap_or(buf));
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub(crate) fn encode_with<T: ?Sized>(
+    value: &T,
+    serialize: impl FnOnce(&T, &mut Vec<u8>) -> io::Result<WireType>,
+) -> io::Result<(WireType, Vec<u8>)> {
+    let mut bytes = Vec::new();
+    let wire_type = serialize(value, &mut bytes)?;
+    Ok((wire_type, bytes))
+}
+
+/// Computes the size of a field encoded by a custom serializer,
+/// which writes the field payload and returns the wire type it is encoded with.
+pub fn compute_size_with<T: ?Sized>(
+    field_number: u32,
+    value: &T,
+    serialize: impl FnOnce(&T, &mut Vec<u8>) -> io::Result<WireType>,
+) -> io::Result<u32> {
+    let (wire_type, bytes) = encode_with(value, serialize)?;
+    encoded_size(field_number, wire_type, &bytes)
+}
+
+pub(crate) fn encoded_size(
+    field_number: u32,
+    wire_type: WireType,
+    bytes: &[u8],
+) -> io::Result<u32> {
+    if bytes.is_empty() {
+        return Ok(0);
+    }
+
+    let mut size = bytes.len() as u32;
+
+    if wire_type == WireType::Sized {
+        size += size.compute_size();
+    }
+
+    Ok(size + wire_type.tag(field_number)?.compute_size())
+}
+
+/// Writes a field encoded by a custom serializer, see [`compute_size_with`].
+pub fn serialize_with<T: ?Sized>(
+    field_number: u32,
+    value: &T,
+    serialize: impl FnOnce(&T, &mut Vec<u8>) -> io::Result<WireType>,
+    writer: &mut impl io::Write,
+) -> io::Result<()> {
+    let (wire_type, bytes) = encode_with(value, serialize)?;
+    write_encoded(field_number, wire_type, &bytes, writer)
+}
+
+pub(crate) fn write_encoded(
+    field_number: u32,
+    wire_type: WireType,
+    bytes: &[u8],
+    writer: &mut impl io::Write,
+) -> io::Result<()> {
+    if bytes.is_empty() {
+        return Ok(());
+    }
+
+    wire_type.tag(field_number)?.serialize_cached(writer)?;
+
+    if wire_type == WireType::Sized {
+        (bytes.len() as u32).serialize_cached(writer)?;
+    }
+
+    writer.write_all(bytes)
+}
+
+/// Writes `value` as 4 little-endian bytes, whatever the byte order of the host.
+pub fn put_fixed32(value: u32, writer: &mut impl io::Write) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// Writes `value` as 8 little-endian bytes, see [`put_fixed32`].
+pub fn put_fixed64(value: u64, writer: &mut impl io::Write) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// How floats are turned into bits for encoding and hashing.
+///
+/// IEEE 754 leaves NaN payloads unspecified across platforms and compares `-0.0` equal to `0.0`,
+/// so values equal for game logic can have different bits.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash, Debug)]
+pub enum FloatMode {
+    /// Bits are kept as is, so NaN payloads and `-0.0` survive a round trip.
+    Preserve,
+    /// NaNs become the canonical quiet NaN and `-0.0` becomes `0.0`,
+    /// so values comparing equal, or both NaN, always have the same bits.
+    #[default]
+    Canonical,
+}
+
+impl FloatMode {
+    pub fn f32_bits(self, value: f32) -> u32 {
+        match self {
+            FloatMode::Canonical if value.is_nan() => f32::NAN.to_bits(),
+            FloatMode::Canonical if value == 0.0 => 0,
+            _ => value.to_bits(),
+        }
+    }
+
+    pub fn f64_bits(self, value: f64) -> u64 {
+        match self {
+            FloatMode::Canonical if value.is_nan() => f64::NAN.to_bits(),
+            FloatMode::Canonical if value == 0.0 => 0,
+            _ => value.to_bits(),
+        }
+    }
+
+    pub fn hash_f32(self, value: f32, hasher: &mut impl Hasher) {
+        hasher.write_u32(self.f32_bits(value));
+    }
+
+    pub fn hash_f64(self, value: f64, hasher: &mut impl Hasher) {
+        hasher.write_u64(self.f64_bits(value));
+    }
+}
+
+/// Order in which `Map` entries are written, see `Serialize::to_bytes_in`.
+///
+/// Both decode to equal maps. Only the order of entries in memory, and so of iteration, differs.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash, Debug)]
+pub enum MapOrder {
+    /// Entries in the order they were inserted, which decoding keeps,
+    /// so replicas built from snapshots iterate like their source.
+    #[default]
+    Insertion,
+    /// Entries sorted by key, so equal maps encode to the same bytes
+    /// however they were built, for hashing and persistence.
+    Canonical,
+}
+
+thread_local! {
+    static MAP_ORDER: Cell<MapOrder> = const { Cell::new(MapOrder::Insertion) };
+}
+
+// Restores the order of the enclosing scope, even if serializing panics.
+struct MapOrderGuard(MapOrder);
+
+impl Drop for MapOrderGuard {
+    fn drop(&mut self) {
+        MAP_ORDER.with(|order| order.set(self.0));
+    }
+}
+
+impl MapOrder {
+    /// The order maps are written in on this thread.
+    pub fn current() -> Self {
+        MAP_ORDER.with(Cell::get)
+    }
+
+    /// Runs `f` with maps written in this order on this thread.
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let _guard = MapOrderGuard(MAP_ORDER.with(|order| order.replace(self)));
+        f()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::Hasher,
+        io::{self, Write},
+    };
+
+    use crate::{
+        de::{Deserialize, Reader},
+        rt::{EncodedFields, Runtime, SizeCache},
+        steit_derive,
+        test_util::Point,
+        wire_fmt::WireType,
+    };
+
+    use super::{put_fixed32, put_fixed64, FloatMode, HashingWriter, Serialize};
+
+    #[derive(Default, PartialEq, Debug)]
+    struct Version {
+        major: u8,
+        minor: u8,
+    }
+
+    fn serialize_version(value: &Version, writer: &mut impl io::Write) -> io::Result<WireType> {
+        ((value.major as u16) << 8 | value.minor as u16).steit_serialize(writer)?;
+        Ok(WireType::Varint)
+    }
+
+    fn deserialize_version(
+        wire_type: WireType,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<Version> {
+        assert_eq!(wire_type, WireType::Varint);
+        let value = u16::deserialize(reader)?;
+
+        Ok(Version {
+            major: (value >> 8) as u8,
+            minor: value as u8,
+        })
+    }
+
+    fn serialize_labels(value: &[String], writer: &mut impl io::Write) -> io::Result<WireType> {
+        writer.write_all(value.join(",").as_bytes())?;
+        Ok(WireType::Sized)
+    }
+
+    fn deserialize_labels(
+        wire_type: WireType,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<Vec<String>> {
+        assert_eq!(wire_type, WireType::Sized);
+        let labels = String::deserialize(reader)?;
+        Ok(labels.split(',').map(str::to_string).collect())
+    }
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(steit_owned, no_meta)]
+    struct Release {
+        #[steit(
+            tag = 0,
+            serialize_with = "serialize_version",
+            deserialize_with = "deserialize_version"
+        )]
+        version: Version,
+        #[steit(
+            tag = 1,
+            serialize_with = "serialize_labels",
+            deserialize_with = "deserialize_labels"
+        )]
+        labels: Vec<String>,
+        #[steit(tag = 2)]
+        build: u32,
+    }
+
+    fn serialize_odd(value: &u32, writer: &mut impl io::Write) -> io::Result<WireType> {
+        if value.is_multiple_of(2) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected an odd value",
+            ));
+        }
+
+        value.steit_serialize(writer)?;
+        Ok(WireType::Varint)
+    }
+
+    #[steit_derive(Debug, Serialize)]
+    #[steit(steit_owned, no_meta)]
+    enum Roll {
+        #[steit(tag = 0)]
+        Missed,
+        #[steit(tag = 1)]
+        Odd {
+            #[steit(tag = 0, serialize_with = "serialize_odd")]
+            value: u32,
+        },
+    }
+
+    #[steit_derive(Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    struct Shuffled {
+        #[steit(tag = 2)]
+        c: u8,
+        #[steit(tag = 0)]
+        a: u8,
+        #[steit(tag = 1)]
+        b: u8,
+    }
+
+    #[test]
+    fn serialize_in_tag_order() {
+        let mut shuffled = Shuffled::new();
+        shuffled.a = 1;
+        shuffled.b = 2;
+        shuffled.c = 3;
+        assert_eq!(shuffled.to_bytes(), &[0, 1, 8, 2, 16, 3]);
+    }
+
+    fn release(labels: &[&str]) -> Release {
+        let mut release = Release::new();
+        release.version = Version { major: 1, minor: 2 };
+        release.labels = labels.iter().map(|label| label.to_string()).collect();
+        release.build = 7;
+        release
+    }
+
+    #[test]
+    fn serialize_with() {
+        let release = release(&["a", "b"]);
+        assert_eq!(release.compute_size(), 10);
+        assert_eq!(release.to_bytes(), &[0, 130, 2, 10, 3, 97, 44, 98, 16, 7]);
+    }
+
+    #[test]
+    fn serialize_with_omitted() {
+        let release = release(&[]);
+        assert_eq!(release.compute_size(), 5);
+        assert_eq!(release.to_bytes(), &[0, 130, 2, 16, 7]);
+    }
+
+    #[test]
+    fn serialize_with_error() {
+        let roll = Roll::Odd {
+            value: 3,
+            size_cache: SizeCache::new(),
+            encoded_fields: EncodedFields::new(),
+        };
+
+        assert_eq!(roll.to_bytes(), &[1, 0, 3]);
+
+        let roll = Roll::Odd {
+            value: 4,
+            size_cache: SizeCache::new(),
+            encoded_fields: EncodedFields::new(),
+        };
+
+        let error = roll.steit_serialize(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.to_string(), "expected an odd value");
+    }
+
+    #[test]
+    fn deserialize_with() {
+        let release = release(&["a", "b"]);
+        let bytes = release.to_bytes();
+        let deserialized = Release::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(deserialized, release);
+    }
+
+    #[test]
+    fn fixed_little_endian() {
+        let mut bytes = Vec::new();
+        put_fixed32(0x0403_0201, &mut bytes).unwrap();
+        put_fixed64(0x0807_0605_0403_0201, &mut bytes).unwrap();
+        assert_eq!(bytes, &[1, 2, 3, 4, 1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut reader = Reader::new(&*bytes);
+        assert_eq!(reader.read_fixed32().unwrap(), 0x0403_0201);
+        assert_eq!(reader.read_fixed64().unwrap(), 0x0807_0605_0403_0201);
+        assert!(reader.eof().unwrap());
+    }
+
+    #[test]
+    fn serialize_hashed() {
+        let point = Point::new(Runtime::new(), 1, -2, 3);
+        let mut bytes = Vec::new();
+        let mut hasher = DefaultHasher::new();
+        point.serialize_hashed(&mut bytes, &mut hasher).unwrap();
+
+        let mut expected = DefaultHasher::new();
+        expected.write(&point.to_bytes());
+        assert_eq!(bytes, point.to_bytes());
+        assert_eq!(hasher.finish(), expected.finish());
+    }
+
+    #[test]
+    fn hashing_writer_partial_writes() {
+        let mut buf = [0; 3];
+        let mut writer = HashingWriter::new(&mut buf[..], DefaultHasher::new());
+        assert_eq!(writer.write(&[1, 2, 3, 4]).unwrap(), 3);
+        assert!(writer.write_all(&[5]).is_err());
+
+        let mut expected = DefaultHasher::new();
+        expected.write(&[1, 2, 3]);
+        assert_eq!(writer.hash(), expected.finish());
+    }
+
+    #[test]
+    fn float_modes() {
+        let nan = f32::from_bits(0x7fc0_0001);
+        assert_eq!(FloatMode::Preserve.f32_bits(nan), 0x7fc0_0001);
+        assert_eq!(FloatMode::Canonical.f32_bits(nan), f32::NAN.to_bits());
+        assert_eq!(FloatMode::Canonical.f32_bits(-nan), f32::NAN.to_bits());
+        assert_eq!(FloatMode::Preserve.f64_bits(-0.0), 1 << 63);
+        assert_eq!(FloatMode::Canonical.f64_bits(-0.0), 0);
+        assert_eq!(FloatMode::Canonical.f64_bits(-1.5), (-1.5f64).to_bits());
+
+        let hash = |mode: FloatMode, value: f64| {
+            let mut hasher = DefaultHasher::new();
+            mode.hash_f64(value, &mut hasher);
+            hasher.finish()
+        };
+
+        assert_eq!(
+            hash(FloatMode::Canonical, -0.0),
+            hash(FloatMode::Canonical, 0.0)
+        );
+        assert_ne!(
+            hash(FloatMode::Preserve, -0.0),
+            hash(FloatMode::Preserve, 0.0)
+        );
+    }
+
+    // Only built for big-endian targets, e.g. `cross test --target s390x-unknown-linux-gnu`.
+    #[cfg(target_endian = "big")]
+    #[test]
+    fn fixed_ignores_host_order() {
+        let mut bytes = Vec::new();
+        put_fixed32(1, &mut bytes).unwrap();
+        assert_ne!(bytes, 1u32.to_ne_bytes());
+        assert_eq!(bytes, &[1, 0, 0, 0]);
+    }
 }
@@ -1,10 +1,14 @@
-use std::io;
+use std::{
+    io,
+    panic::{self, AssertUnwindSafe},
+};
 
 use super::{
     de::{Deserialize, Reader},
-    log::{LogEntry, LogEntryKind},
-    rt::Runtime,
+    log::{LogEntry, LogEntryKind, StringTable},
+    rt::{expect_logged, Runtime},
     ser::Serialize,
+    types::KeyIndex,
     wire_fmt::HasWireType,
 };
 
@@ -21,6 +25,16 @@ pub trait State: Serialize + Deserialize {
         reader: &mut Reader<impl io::Read>,
     ) -> io::Result<()>;
 
+    /// Decodes a state written by `Serialize::to_bytes`, logging its changes to `runtime`.
+    fn from_bytes(runtime: Runtime, bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut state = Self::deserialize(&mut Reader::new(bytes))?;
+        state.set_runtime(runtime);
+        Ok(state)
+    }
+
     fn is_root(&self) -> bool {
         self.runtime().is_root()
     }
@@ -29,45 +43,862 @@ pub trait State: Serialize + Deserialize {
         !self.is_root()
     }
 
+    /// Recomputes fields marked with `#[steit(aggregate = …)]` in this and all nested states,
+    /// logging those which changed. Servers should call it on their roots before sending logs.
+    fn refresh_aggregates(&mut self) {}
+
+    /// Runs `#[steit(on_serialize = …)]` hooks in this and all nested states, innermost first.
+    /// Call it on roots before taking snapshots or flushing logs.
+    fn prepare_serialize(&mut self) {}
+
+    /// Recomputes `computed` fields and runs `#[steit(on_deserialize = …)]` hooks
+    /// in this and all nested states, innermost first, like after decoding them.
+    fn finish_deserialize(&mut self) {}
+
+    /// Replaces the whole state with `value`, logged as one `Replace` entry of its path,
+    /// so clients swap the subtree at once, like on match restarts or respawns.
+    ///
+    /// `value` is first finished like a decoded state and gets its aggregates refreshed,
+    /// without logging, so what is logged is what clients end up with.
+    fn replace_with(&mut self, mut value: Self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        let runtime = self.runtime().clone();
+        value.set_runtime(runtime.clone());
+
+        runtime.silenced(|| {
+            value.finish_deserialize();
+            value.refresh_aggregates();
+        });
+
+        expect_logged(runtime.log_replace(&value));
+        *self = value;
+        self
+    }
+
+    /// Replaces the state with its default, like `replace_with`.
+    fn reset(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        let runtime = self.runtime().clone();
+        self.replace_with(Self::with_runtime(runtime))
+    }
+
+    /// Runs `f`, logging its changes as one batch if it succeeds, so clients get them
+    /// in the same frame. If it fails, the state is restored and nothing is logged.
+    ///
+    /// The state is cloned beforehand to be restored, which copies all of it
+    /// but subtrees behind an `Arc`, so keep transactions to small states or put those behind one.
+    fn transaction<R, E>(&mut self, f: impl FnOnce(&mut Self) -> Result<R, E>) -> Result<R, E>
+    where
+        Self: Sized + Clone,
+    {
+        let runtime = self.runtime().clone();
+        let backup = self.clone();
+
+        runtime.begin_batch();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+        let committed = matches!(result, Ok(Ok(_)));
+        let ended = runtime.end_batch(committed);
+
+        match result {
+            Ok(Ok(value)) => {
+                expect_logged(ended);
+                Ok(value)
+            }
+
+            Ok(Err(error)) => {
+                *self = backup;
+                Err(error)
+            }
+
+            Err(panic) => panic::resume_unwind(panic),
+        }
+    }
+
+    /// Validates `items[from..]`, which have just been added to a `List` of `items`,
+    /// adding their keys to `keys`. Only overridden for `Keyed` items.
+    #[doc(hidden)]
+    fn validate_list_items(_items: &[Self], _from: usize, _keys: &mut KeyIndex) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Hash of the key of this item in a `KeyIndex`. Only overridden for `Keyed` items.
+    #[doc(hidden)]
+    fn list_key_hash(&self) -> Option<u64> {
+        None
+    }
+
     fn handle_update(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
         *self = Self::with_runtime(self.runtime().clone());
         self.merge(reader)
     }
 
-    fn replay(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
-        if !self.is_root() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "`replay` can only be called on the root `State` object",
-            ));
+    /// Serializes the value at `path` under this state, walking it like paths of log entries,
+    /// and returns whether there is one. Only that value is serialized.
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        if !path.is_empty() {
+            return Ok(false);
         }
 
+        self.steit_serialize(writer)?;
+        Ok(true)
+    }
+
+    fn replay(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        self.replay_with_strings(reader, &mut StringTable::new())
+    }
+
+    /// Replays log entries using `strings` to resolve interned strings,
+    /// so one table should be kept per stream across calls.
+    fn replay_with_strings(
+        &mut self,
+        reader: &mut Reader<impl io::Read>,
+        strings: &mut StringTable,
+    ) -> io::Result<()> {
+        check_root(self)?;
+
         while !reader.eof()? {
             let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+            apply_entry(self, entry, strings)?;
+        }
 
-            let (kind, path, key, bytes) = unpack_log_entry(entry);
-            let path = path.into_iter();
-            let bytes = bytes.unwrap_or_default();
-            let reader = &mut Reader::new(&*bytes);
+        Ok(())
+    }
 
-            self.handle(path, kind, key, reader)?;
-        }
+    /// Applies one entry, as taken from a `BufferLogger` or a channel,
+    /// to mirror states in-process without serializing entries first.
+    fn replay_entry(&mut self, entry: LogEntry, strings: &mut StringTable) -> io::Result<()> {
+        check_root(self)?;
+        apply_entry(self, entry, strings)
+    }
+}
 
+pub(crate) fn check_root<T: State>(state: &T) -> io::Result<()> {
+    if state.is_root() {
         Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "`replay` can only be called on the root `State` object",
+        ))
     }
 }
 
-fn unpack_log_entry(entry: LogEntry) -> (LogEntryKind, Vec<u32>, Option<u32>, Option<Vec<u8>>) {
-    match entry {
-        LogEntry::Update { path, value, .. } => {
-            (LogEntryKind::Update, path, None, Some(value.into_raw()))
+// Callers check that `state` is a root once, rather than once per entry.
+fn apply_entry<T: State>(
+    state: &mut T,
+    entry: LogEntry,
+    strings: &mut StringTable,
+) -> io::Result<()> {
+    if let Some((kind, path, key, bytes)) = entry.unpack(strings)? {
+        let bytes = bytes.unwrap_or_default();
+        state.handle(path.into_iter(), kind, key, &mut Reader::new(&*bytes))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        de::{Deserialize, Reader},
+        log::{
+            loggers::{BufferLogger, FaultPattern, FaultyLogger},
+            LogEntry, LogEntryKind, Logger, StringTable,
+        },
+        rt::Runtime,
+        ser::Serialize,
+        steit_derive,
+        test_util::{replay, Point},
+        types::{List, Maybe},
+    };
+
+    use super::State;
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unit {
+        #[steit(tag = 0)]
+        hp: u16,
+        #[steit(tag = 1)]
+        alive: bool,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Squad {
+        #[steit(tag = 0)]
+        units: List<Unit>,
+        #[steit(tag = 1, aggregate = "sum(units.hp)")]
+        total_hp: u32,
+        #[steit(tag = 2, aggregate = "count(units.alive)")]
+        alive: u8,
+        #[steit(tag = 3, aggregate = "max(units.hp)")]
+        max_hp: u16,
+        #[steit(tag = 4, aggregate = "count(units)")]
+        size: u8,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Army {
+        #[steit(tag = 0)]
+        squads: List<Squad>,
+        #[steit(tag = 1, aggregate = "sum(squads.total_hp)")]
+        total_hp: u64,
+        #[steit(tag = 2)]
+        reserve: Maybe<Squad>,
+    }
+
+    fn add_unit(squad: &mut Squad, hp: u16, alive: bool) {
+        squad.units.push_with(|runtime| {
+            let mut unit = Unit::new(runtime);
+            unit.hp = hp;
+            unit.alive = alive;
+            unit
+        });
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Hero {
+        #[steit(tag = 0)]
+        attack: u16,
+        #[steit(tag = 1)]
+        level: u16,
+        #[steit(computed = "power")]
+        power: u32,
+    }
+
+    fn power(hero: &Hero) -> u32 {
+        hero.attack as u32 * hero.level as u32 + 1
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, on_deserialize = "Bounds::restore")]
+    struct Bounds {
+        #[steit(tag = 0)]
+        min: i32,
+        #[steit(tag = 1)]
+        max: i32,
+    }
+
+    impl Bounds {
+        fn restore(&mut self) {
+            if self.min > self.max {
+                std::mem::swap(&mut self.min, &mut self.max);
+            }
+        }
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, on_deserialize = "Shape::restore")]
+    enum Shape {
+        #[steit(tag = 0)]
+        Dot {},
+        #[steit(tag = 1)]
+        Line {
+            #[steit(tag = 0)]
+            len: i32,
+        },
+    }
+
+    impl Shape {
+        fn restore(&mut self) {
+            if let Shape::Line { len, .. } = self {
+                *len = len.abs();
+            }
+        }
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, on_serialize = "Body::sync")]
+    struct Body {
+        #[steit(tag = 0)]
+        x: i32,
+        // Moved by the physics engine, then synced into `x`.
+        #[steit(computed = "Body::reset_physics")]
+        physics_x: i32,
+    }
+
+    impl Body {
+        fn reset_physics(&self) -> i32 {
+            self.x
         }
 
-        LogEntry::ListPush { path, item, .. } => {
-            (LogEntryKind::ListPush, path, None, Some(item.into_raw()))
+        fn sync(&mut self) {
+            if self.x != self.physics_x {
+                self.set_x(self.physics_x);
+            }
         }
+    }
+
+    #[steit_derive(Clone, PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Item {
+        #[steit(tag = 0, string_intern)]
+        id: String,
+        #[steit(tag = 1)]
+        count: u32,
+    }
+
+    #[test]
+    fn log_interned_once() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut item = Item::new(runtime);
+
+        item.set_id("sword".to_string())
+            .set_id("shield".to_string())
+            .set_id("sword".to_string());
+
+        let kinds: Vec<_> = logger
+            .lock()
+            .unwrap()
+            .pluck()
+            .iter()
+            .map(|entry| entry.kind())
+            .collect();
+
+        assert_eq!(
+            kinds,
+            &[
+                LogEntryKind::Intern,
+                LogEntryKind::UpdateInterned,
+                LogEntryKind::Intern,
+                LogEntryKind::UpdateInterned,
+                LogEntryKind::UpdateInterned,
+            ],
+        );
+    }
+
+    #[test]
+    fn log_interned_paused() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut item = Item::new(runtime.clone());
+
+        runtime.pause_logger();
+        item.set_id("sword".to_string());
+        runtime.unpause_logger();
+        item.set_id("sword".to_string());
+
+        let kinds: Vec<_> = logger
+            .lock()
+            .unwrap()
+            .pluck()
+            .iter()
+            .map(|entry| entry.kind())
+            .collect();
+
+        assert_eq!(kinds, &[LogEntryKind::Intern, LogEntryKind::UpdateInterned]);
+    }
+
+    #[test]
+    fn replay_interned() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut item = Item::new(runtime);
+        item.set_id("sword".to_string()).set_count(2);
+        item.set_id("shield".to_string())
+            .set_id("sword".to_string());
+
+        let mut mirror = Item::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, item);
+    }
+
+    #[test]
+    fn replay_interned_across_calls() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut item = Item::new(runtime);
+        let mut mirror = Item::new(Runtime::new());
+        let mut strings = StringTable::new();
+
+        item.set_id("sword".to_string());
+        let bytes = logger.lock().unwrap().pluck_bytes();
+        mirror
+            .replay_with_strings(&mut Reader::new(&*bytes), &mut strings)
+            .unwrap();
+
+        item.set_id("shield".to_string())
+            .set_id("sword".to_string());
+        let bytes = logger.lock().unwrap().pluck_bytes();
+        mirror
+            .replay_with_strings(&mut Reader::new(&*bytes), &mut strings)
+            .unwrap();
+
+        assert_eq!(mirror.id, "sword");
+        assert_eq!(strings.len(), 2);
+    }
+
+    #[test]
+    fn log_interned_after_failure() {
+        let logger = FaultyLogger::new(BufferLogger::new(), FaultPattern::At(vec![0]));
+        let (runtime, logger) = Runtime::with_logger_returned(logger);
+        runtime.on_log_error(|_| {});
+
+        let mut item = Item::new(runtime);
+        item.set_id("sword".to_string());
+        item.set_id("sword".to_string());
+
+        let mut mirror = Item::new(Runtime::new());
+        replay(
+            &mut mirror,
+            &logger.lock().unwrap().inner_mut().pluck_bytes(),
+        );
+        assert_eq!(mirror.id, "sword");
+    }
+
+    #[test]
+    fn transaction_commit_and_rollback() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::new(runtime, 1, 1, 1);
+
+        let result: Result<_, ()> = point.transaction(|point| {
+            point.set_x(2).set_y(3);
+            Ok(point.x + point.y)
+        });
+
+        assert_eq!(result, Ok(5));
+        assert_eq!(logger.lock().unwrap().pluck().len(), 2);
+
+        let result: Result<(), _> = point.transaction(|point| {
+            point.set_z(9);
+            Err("out of range")
+        });
+
+        assert_eq!(result, Err("out of range"));
+        assert_eq!((point.x, point.y, point.z), (2, 3, 1));
+        assert!(logger.lock().unwrap().pluck().is_empty());
+
+        point.set_x(4);
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+    }
+
+    #[test]
+    fn transaction_interned() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut item = Item::new(runtime.clone());
+        item.set_id("sword".to_string());
+
+        let result: Result<(), ()> = item.transaction(|item| {
+            item.set_id("shield".to_string())
+                .set_id("sword".to_string());
+            Ok(())
+        });
+        assert!(result.is_ok());
+
+        let result: Result<(), _> = item.transaction(|item| {
+            item.set_id("axe".to_string());
+            Err(())
+        });
+        assert!(result.is_err());
+        item.set_id("axe".to_string());
+
+        runtime.pause_logger();
+        let result: Result<(), ()> = item.transaction(|item| {
+            item.set_count(3);
+            Ok(())
+        });
+        runtime.unpause_logger();
+        assert!(result.is_ok());
+        assert_eq!(logger.lock().unwrap().strings().len(), 3);
+
+        let mut mirror = Item::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!((mirror.id.as_str(), mirror.count), ("axe", 0));
+    }
+
+    #[test]
+    fn from_bytes_logs_to_runtime() {
+        let bytes = Point::new(Runtime::new(), 1, 2, 3).to_bytes();
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut point = Point::from_bytes(runtime, &bytes).unwrap();
+        assert_eq!((point.x, point.y, point.z), (1, 2, 3));
+
+        point.set_x(4);
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+        assert!(Point::from_bytes(Runtime::new(), &[0]).is_err());
+    }
+
+    #[test]
+    fn replace_and_reset() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut army = Army::new(runtime);
+        army.squads.push_with(Squad::new);
+        add_unit(&mut army.squads[0], 30, true);
+        add_unit(&mut army.squads[0], 20, true);
+
+        let mut mirror = Army::new(Runtime::new());
+        let mut strings = StringTable::new();
+
+        for entry in logger.lock().unwrap().pluck() {
+            mirror.replay_entry(entry, &mut strings).unwrap();
+        }
+
+        let mut respawned = Squad::new(Runtime::new());
+        add_unit(&mut respawned, 50, true);
+        army.squads[0].replace_with(respawned);
+
+        // Nested states are logged under the path they were moved to.
+        army.squads[0].units[0].set_hp(45);
+
+        let entries = logger.lock().unwrap().pluck();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind(), LogEntryKind::Replace);
+        assert_eq!(entries[0].path(), Some(&[0, 0][..]));
+        assert_eq!(entries[1].path(), Some(&[0, 0, 0, 0, 0][..]));
+
+        for entry in entries {
+            mirror.replay_entry(entry, &mut strings).unwrap();
+        }
+
+        assert_eq!(mirror, army);
+        assert_eq!(mirror.squads[0].units.len(), 1);
+        assert_eq!(mirror.squads[0].total_hp, 50);
+
+        army.reset();
+
+        for entry in logger.lock().unwrap().pluck() {
+            mirror.replay_entry(entry, &mut strings).unwrap();
+        }
+
+        assert!(mirror.squads.is_empty());
+        assert_eq!(mirror, army);
+    }
+
+    #[test]
+    fn replay_entries_in_process() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut army = Army::new(runtime);
+        army.squads.push_with(Squad::new);
+        add_unit(&mut army.squads[0], 30, true);
+        army.squads[0].units[0].set_hp(12);
+        army.squads[0].units.pop();
+
+        let mut mirror = Army::new(Runtime::new());
+        let mut strings = StringTable::new();
+
+        for entry in logger.lock().unwrap().pluck() {
+            mirror.replay_entry(entry, &mut strings).unwrap();
+        }
+
+        assert_eq!(mirror, army);
+        assert!(mirror.squads[0]
+            .replay_entry(
+                LogEntry::new_list_pop(Runtime::new().nested(0).path()),
+                &mut strings
+            )
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "interned string 0 not found")]
+    fn replay_interned_unknown() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut item = Item::new(runtime);
+        item.set_id("sword".to_string()).set_id("sword".to_string());
+
+        let mut entries = logger.lock().unwrap().pluck();
+        entries.remove(0);
+        logger.lock().unwrap().log_multi(entries).unwrap();
+
+        replay(
+            &mut Item::new(Runtime::new()),
+            &logger.lock().unwrap().bytes(),
+        );
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    enum Stance {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1)]
+        Moving {
+            #[steit(tag = 0)]
+            speed: u32,
+            #[steit(tag = 1)]
+            waypoints: List<u32>,
+        },
+    }
+
+    #[test]
+    fn variant_view_checked() {
+        let mut stance = Stance::new(Runtime::new());
+        assert!(stance.as_moving_mut().is_none());
+
+        stance.set_moving_speed(3);
+        assert_eq!(*stance.as_moving_mut().unwrap().speed(), 3);
+    }
+
+    #[test]
+    fn variant_view_log() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut stance = Stance::new(runtime);
+        stance.set_moving_speed(3);
+        logger.lock().unwrap().clear();
+
+        let mut moving = stance.as_moving_mut().unwrap();
+        moving.set_speed(5);
+        moving.waypoints_mut().push(7);
+
+        let entries = logger.lock().unwrap().pluck();
+        let kinds: Vec<_> = entries.iter().map(|entry| entry.kind()).collect();
+        assert_eq!(kinds, &[LogEntryKind::Update, LogEntryKind::ListPush]);
+    }
+
+    #[test]
+    fn variant_view_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut stance = Stance::new(runtime);
+        stance.set_moving_speed(3);
+
+        let mut moving = stance.as_moving_mut().unwrap();
+        moving.set_speed(5);
+        moving.set_waypoints_with(|runtime| List::from_iter(runtime, vec![1, 2]));
+        moving.waypoints_mut().push(7);
+        assert_eq!(**moving.waypoints(), vec![1, 2, 7]);
+
+        let mut mirror = Stance::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, stance);
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Position {
+        #[steit(tag = 0, skip_unchanged)]
+        x: i32,
+        #[steit(tag = 1)]
+        y: i32,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, skip_unchanged)]
+    enum Heading {
+        #[steit(tag = 0)]
+        Still,
+        #[steit(tag = 1)]
+        Turning {
+            #[steit(tag = 0)]
+            angle: i32,
+        },
+    }
+
+    #[test]
+    fn skip_unchanged_field() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut position = Position::new(runtime);
+        position.set_x(1).set_x(1).set_y(1).set_y(1);
+        position.set_x_with(|_| 1).set_x_with(|_| 2);
+        assert_eq!(logger.lock().unwrap().pluck().len(), 4);
+        assert_eq!(position.x, 2);
+    }
+
+    #[test]
+    fn skip_unchanged_variant() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut heading = Heading::new(runtime);
+
+        // Switching variants is logged even if the field is at its default value.
+        heading.set_turning_angle(0).set_turning_angle(0);
+        assert_eq!(logger.lock().unwrap().pluck().len(), 2);
+
+        heading.set_turning_angle(90);
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Level {
+        #[steit(tag = 0)]
+        id: u32,
+        #[steit(tag = 1, no_log)]
+        checksum: u64,
+    }
+
+    #[test]
+    fn no_log_field() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut level = Level::new(runtime);
+        level.set_checksum(42).set_id(1).set_checksum(43);
+        assert_eq!(logger.lock().unwrap().pluck().len(), 1);
+
+        let snapshot = Level::deserialize(&mut Reader::new(&*level.to_bytes())).unwrap();
+        assert_eq!(snapshot, level);
+    }
+
+    #[test]
+    fn refresh_aggregates() {
+        let mut squad = Squad::new(Runtime::new());
+        add_unit(&mut squad, 10, true);
+        add_unit(&mut squad, 30, false);
+        add_unit(&mut squad, 20, true);
+        squad.refresh_aggregates();
+
+        assert_eq!(
+            (squad.total_hp, squad.alive, squad.max_hp, squad.size),
+            (60, 2, 30, 3)
+        );
+    }
+
+    #[test]
+    fn refresh_nested_aggregates() {
+        let mut army = Army::new(Runtime::new());
+
+        for hp in &[5, 7] {
+            army.squads.push_with(|runtime| {
+                let mut squad = Squad::new(runtime);
+                add_unit(&mut squad, *hp, true);
+                squad
+            });
+        }
+
+        army.refresh_aggregates();
+        assert_eq!(army.total_hp, 12);
+        assert_eq!(army.squads[1].total_hp, 7);
+    }
+
+    #[test]
+    fn refresh_aggregates_log_changes_only() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut squad = Squad::new(runtime);
+        add_unit(&mut squad, 10, true);
+        squad.refresh_aggregates();
+
+        let mut mirror = Squad::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().pluck_bytes());
+        assert_eq!(mirror, squad);
+
+        squad.refresh_aggregates();
+        assert!(logger.lock().unwrap().pluck().is_empty());
+
+        squad.units[0].set_hp(4);
+        squad.refresh_aggregates();
+
+        let entries = logger.lock().unwrap().pluck();
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path().unwrap()).collect();
+        assert_eq!(paths, &[&[0, 0, 0][..], &[1], &[3]]);
+    }
+
+    #[test]
+    fn refresh_aggregates_in_variants() {
+        let mut squad = Squad::new(Runtime::new());
+        add_unit(&mut squad, 3, true);
+
+        let mut army = Army::new(Runtime::new());
+        army.set_reserve(Maybe::some(Runtime::new(), squad));
+        army.refresh_aggregates();
+
+        match &army.reserve {
+            Maybe::Some { value, .. } => assert_eq!(value.total_hp, 3),
+            Maybe::None { .. } => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn computed_on_new() {
+        assert_eq!(Hero::new(Runtime::new()).power, 1);
+    }
+
+    #[test]
+    fn computed_not_serialized() {
+        let mut hero = Hero::new(Runtime::new());
+        hero.set_attack(3).set_level(2);
+        hero.power = 100;
+        assert_eq!(hero.to_bytes(), &[0, 3, 8, 2]);
+
+        let hero = Hero::deserialize(&mut Reader::new(&*hero.to_bytes())).unwrap();
+        assert_eq!(hero.power, 7);
+    }
+
+    #[test]
+    fn computed_on_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut hero = Hero::new(runtime);
+        hero.set_attack(5);
+
+        let mut mirror = Hero::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().pluck_bytes());
+        assert_eq!(mirror.power, 1);
+
+        hero.set_level(3);
+        replay(&mut mirror, &logger.lock().unwrap().pluck_bytes());
+        assert_eq!(mirror.power, 16);
+    }
+
+    #[test]
+    fn on_deserialize_after_merge() {
+        let bounds = Bounds::deserialize(&mut Reader::new(&[0, 10, 8, 2][..])).unwrap();
+        assert_eq!((bounds.min, bounds.max), (1, 5));
+
+        let shape = Shape::deserialize(&mut Reader::new(&[1, 0, 5][..])).unwrap();
+        assert!(matches!(shape, Shape::Line { len: 3, .. }));
+    }
+
+    #[test]
+    fn finish_deserialize_on_replace() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut hero = Hero::new(runtime.clone());
+
+        let mut replacement = Hero::new(Runtime::new());
+        replacement.attack = 4;
+        replacement.level = 2;
+        hero.replace_with(replacement);
+        assert_eq!(hero.power, 9);
+
+        let mut bounds = Bounds::new(runtime);
+        let mut replacement = Bounds::new(Runtime::new());
+        replacement.min = 6;
+        bounds.replace_with(replacement);
+        assert_eq!((bounds.min, bounds.max), (0, 6));
+
+        // Only the replacements are logged, with hooks already run.
+        let entries = logger.lock().unwrap().pluck();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].payload(), Some(&bounds.to_bytes()[..]));
+    }
+
+    #[test]
+    fn on_deserialize_after_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut bounds = Bounds::new(runtime);
+        bounds.set_min(4);
+
+        let mut mirror = Bounds::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!((mirror.min, mirror.max), (0, 4));
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut shape = Shape::new_line(runtime);
+        shape.set_line_len(-2);
+
+        let mut mirror = Shape::new_line(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert!(matches!(mirror, Shape::Line { len: 2, .. }));
+    }
+
+    #[test]
+    fn on_serialize_nested() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut bodies = List::<Body>::new(runtime);
+        bodies.push_with(Body::new);
+        bodies.push_with(Body::new);
+        logger.lock().unwrap().pluck();
+
+        bodies[1].physics_x = 3;
+        assert_eq!(bodies[1].x, 0);
+
+        bodies.prepare_serialize();
+        assert_eq!(bodies[1].x, 3);
 
-        LogEntry::ListPop { path, .. } => (LogEntryKind::ListPop, path, None, None),
-        LogEntry::MapRemove { path, key, .. } => (LogEntryKind::MapRemove, path, Some(key), None),
+        let entries = logger.lock().unwrap().pluck();
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path().unwrap()).collect();
+        assert_eq!(paths, &[&[1, 0]]);
     }
 }
@@ -28,15 +28,11 @@ pub struct Foo(#[steit(tag = 0)] pub i32, #[steit(tag = 1)] pub i32);
 
 impl Foo {
     pub fn new(f0: i32, f1: i32) -> Self {
-        Self {
-            0: f0,
-            1: f1,
-            2: SizeCache::new(),
-        }
+        Self(f0, f1, SizeCache::new())
     }
 }
 
-#[steit_derive(PartialEq, Debug, State)]
+#[steit_derive(Clone, PartialEq, Debug, State)]
 #[steit(steit_owned, ctor_prefix = "empty")]
 pub struct Point {
     #[steit(tag = 0)]
@@ -106,7 +102,7 @@ pub fn assert_deserialize<T: PartialEq + fmt::Debug + Deserialize>(bytes: &[u8],
 }
 
 pub fn assert_ser_de<T: Clone + PartialEq + fmt::Debug + Serialize + Deserialize>(value: T) {
-    assert_eq!(deserialize::<T>(&*serialize(value.clone())), value);
+    assert_eq!(deserialize::<T>(&serialize(value.clone())), value);
 }
 
 pub fn replay<T: State>(value: &mut T, bytes: &[u8]) {
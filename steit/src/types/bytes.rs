@@ -8,7 +8,7 @@ use serde::Serialize as JsonSerialize;
 use crate::{
     de::{Deserialize, Reader},
     meta::{HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::SizeCache,
+    rt::{expect_written, SizeCache},
     ser::Serialize,
     wire_fmt::{HasWireType, WireType},
 };
@@ -23,13 +23,25 @@ impl Bytes {
 
     pub fn from_value(value: &impl Serialize) -> Self {
         let mut bytes = Vec::new();
-        value.steit_serialize(&mut bytes).unwrap();
+        expect_written(value.steit_serialize(&mut bytes));
         Self(bytes)
     }
 
+    pub fn as_raw(&self) -> &[u8] {
+        &self.0
+    }
+
     pub fn into_raw(self) -> Vec<u8> {
         self.0
     }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 impl fmt::Debug for Bytes {
@@ -0,0 +1,282 @@
+use std::{io, marker::PhantomData};
+
+use serde::Serialize as JsonSerialize;
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::LogEntryKind,
+    meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
+    rt::{expect_logged, Runtime, SizeCache},
+    ser::Serialize,
+    state::State,
+    wire_fmt::{HasWireType, WireType},
+};
+
+/// Names a bit of `Flags`, usually implemented with `flags!`.
+pub trait Flag: Copy {
+    fn bit(self) -> u32;
+}
+
+/// A set of `Flag`s serialized as a varint of their bits.
+/// Setting or clearing a bit is logged as an update of that bit alone.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, JsonSerialize)]
+pub struct Flags<E: Flag> {
+    bits: u64,
+    runtime: Runtime,
+    phantom: PhantomData<E>,
+}
+
+impl<E: Flag> Flags<E> {
+    pub fn new(runtime: Runtime) -> Self {
+        Self::from_bits(runtime, 0)
+    }
+
+    pub fn from_bits(runtime: Runtime, bits: u64) -> Self {
+        Self {
+            bits,
+            runtime,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn from_iter(runtime: Runtime, iter: impl IntoIterator<Item = E>) -> Self {
+        let bits = iter.into_iter().fold(0, |bits, flag| bits | mask(flag));
+        Self::from_bits(runtime, bits)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(&self, flag: E) -> bool {
+        self.bits & mask(flag) != 0
+    }
+
+    pub fn insert(&mut self, flag: E) -> &mut Self {
+        self.set(flag, true)
+    }
+
+    pub fn remove(&mut self, flag: E) -> &mut Self {
+        self.set(flag, false)
+    }
+
+    /// Nothing is logged if the bit already has `value`.
+    pub fn set(&mut self, flag: E, value: bool) -> &mut Self {
+        if self.contains(flag) != value {
+            expect_logged(self.runtime.log_update_child(flag.bit(), &value));
+            self.bits ^= mask(flag);
+        }
+
+        self
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        if !self.is_empty() {
+            self.bits = 0;
+            expect_logged(self.runtime.log_update(self));
+        }
+
+        self
+    }
+}
+
+fn mask(flag: impl Flag) -> u64 {
+    1 << flag.bit()
+}
+
+impl<E: Flag> Default for Flags<E> {
+    fn default() -> Self {
+        Self::new(Runtime::default())
+    }
+}
+
+impl<E: Flag> HasWireType for Flags<E> {
+    const WIRE_TYPE: WireType = WireType::Varint;
+}
+
+impl<E: Flag> Serialize for Flags<E> {
+    fn compute_size(&self) -> u32 {
+        self.bits.compute_size()
+    }
+
+    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.bits.serialize_cached(writer)
+    }
+
+    fn size_cache(&self) -> Option<&SizeCache> {
+        None
+    }
+
+    fn is_omissible(&self, _size_hint: Option<u32>) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<E: Flag> Deserialize for Flags<E> {
+    fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        self.bits.merge(reader)
+    }
+}
+
+impl<E: Flag> State for Flags<E> {
+    fn with_runtime(runtime: Runtime) -> Self {
+        Self::new(runtime)
+    }
+
+    fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    fn set_runtime(&mut self, runtime: Runtime) {
+        self.runtime = runtime;
+    }
+
+    fn handle(
+        &mut self,
+        mut path: impl Iterator<Item = u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        if let Some(bit) = path.next() {
+            if bit >= 64 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bit {} out of bounds", bit),
+                ));
+            }
+
+            let mut value = false;
+            value.handle(path, kind, key, reader)?;
+
+            if value {
+                self.bits |= 1 << bit;
+            } else {
+                self.bits &= !(1 << bit);
+            }
+
+            Ok(())
+        } else {
+            match kind {
+                LogEntryKind::Update => self.handle_update(reader),
+
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{:?} is not supported on `Flags`", kind),
+                )),
+            }
+        }
+    }
+}
+
+impl<E: Flag + HasMeta> HasMeta for Flags<E> {
+    const NAME: &'static NameMeta = &NameMeta {
+        rust: "Flags",
+        csharp: Some("StateFlags"),
+    };
+
+    const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[FieldTypeMeta::Type(E::TYPE)]);
+
+    const LINK: &'static MetaLink = &MetaLink {
+        r#type: Self::TYPE,
+        msg: None,
+        links: || &[E::LINK],
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        flags,
+        log::{loggers::BufferLogger, LogEntryKind},
+        meta::{HasMeta, MessageMeta},
+        rt::Runtime,
+        steit_derive,
+        test_util::{assert_serialize, replay},
+    };
+
+    use super::Flags;
+
+    flags! {
+        pub enum Status {
+            Stunned = 0,
+            Silenced = 1,
+            Rooted = 5,
+        }
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unit {
+        #[steit(tag = 0)]
+        hp: u32,
+        #[steit(tag = 1)]
+        status: Flags<Status>,
+    }
+
+    #[test]
+    fn insert_and_contains() {
+        let mut flags = Flags::new(Runtime::new());
+        flags.insert(Status::Stunned).insert(Status::Rooted);
+
+        assert!(flags.contains(Status::Stunned));
+        assert!(!flags.contains(Status::Silenced));
+        assert_eq!(flags.bits(), 0b100001);
+    }
+
+    #[test]
+    fn serialize() {
+        let flags = Flags::from_iter(Runtime::new(), vec![Status::Silenced, Status::Rooted]);
+        assert_serialize(flags, &[34]);
+    }
+
+    #[test]
+    fn log_bits() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut unit = Unit::new(runtime);
+
+        unit.status
+            .insert(Status::Silenced)
+            .insert(Status::Silenced);
+        unit.status.remove(Status::Stunned).remove(Status::Silenced);
+
+        assert_eq!(
+            logger.lock().unwrap().bytes(),
+            &[8, 0, 2, 2, 1, 1, 10, 1, 1, /**/ 8, 0, 2, 2, 1, 1, 10, 1, 0],
+        );
+    }
+
+    #[test]
+    fn replay_bits() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut unit = Unit::new(runtime);
+
+        unit.set_hp(10);
+        unit.status.insert(Status::Stunned).insert(Status::Rooted);
+        unit.status.remove(Status::Stunned);
+
+        let mut mirror = Unit::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, unit);
+
+        unit.status.clear();
+        let kind = logger.lock().unwrap().pluck().pop().unwrap().kind();
+        assert_eq!(kind, LogEntryKind::Update);
+    }
+
+    #[test]
+    fn meta() {
+        match &Status::LINK.msg {
+            Some(MessageMeta::Flags(flags)) => {
+                let bits: Vec<_> = flags.flags.iter().map(|flag| flag.bit).collect();
+                assert_eq!(bits, &[0, 1, 5]);
+            }
+
+            _ => panic!("expected flags meta"),
+        }
+    }
+}
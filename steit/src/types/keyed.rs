@@ -0,0 +1,131 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    iter::FromIterator,
+};
+
+/// Items with a key which must be unique within their `List`,
+/// derived from fields marked with `#[steit(key)]`.
+///
+/// Keys may change through mutable access to items in a list, which re-indexes them
+/// before its next push, extend or replayed entry, rejecting those while two items share a key.
+pub trait Keyed {
+    type Key: Eq + Hash + fmt::Debug;
+
+    fn key(&self) -> Self::Key;
+}
+
+/// Hashes of the keys of the items in a `List`, counted so colliding keys can be told apart
+/// from duplicates by comparing only the items which share a hash.
+#[derive(Clone, Default)]
+pub struct KeyIndex {
+    counts: HashMap<u64, u32>,
+    stale: bool,
+}
+
+impl KeyIndex {
+    pub fn hash_key<K: Hash>(key: &K) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn contains(&self, hash: u64) -> bool {
+        self.counts.contains_key(&hash)
+    }
+
+    pub fn insert(&mut self, hash: u64) {
+        *self.counts.entry(hash).or_insert(0) += 1;
+    }
+
+    pub fn remove(&mut self, hash: u64) {
+        if let Some(count) = self.counts.get_mut(&hash) {
+            *count -= 1;
+
+            if *count == 0 {
+                self.counts.remove(&hash);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.stale = false;
+    }
+
+    /// Marks keys as possibly changed through mutable access to items, unless none are keyed.
+    pub fn touch(&mut self) {
+        if !self.counts.is_empty() {
+            self.stale = true;
+        }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+}
+
+impl FromIterator<u64> for KeyIndex {
+    fn from_iter<I: IntoIterator<Item = u64>>(hashes: I) -> Self {
+        let mut keys = Self::default();
+
+        for hash in hashes {
+            keys.insert(hash);
+        }
+
+        keys
+    }
+}
+
+impl PartialEq for KeyIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for KeyIndex {}
+
+impl Hash for KeyIndex {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl fmt::Debug for KeyIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyIndex")
+            .field("len", &self.counts.len())
+            .field("stale", &self.stale)
+            .finish()
+    }
+}
+
+/// Checks that keys of `items[from..]` are unique among all of `items`,
+/// adding them to `keys`, which holds the keys of `items[..from]`.
+///
+/// On error, `keys` is left as it was.
+pub fn validate_unique_keys<T: Keyed>(
+    items: &[T],
+    from: usize,
+    keys: &mut KeyIndex,
+) -> io::Result<()> {
+    for (index, item) in items.iter().enumerate().skip(from) {
+        let key = item.key();
+        let hash = KeyIndex::hash_key(&key);
+
+        if keys.contains(hash) && items.iter().take(index).any(|other| other.key() == key) {
+            for item in items.iter().take(index).skip(from) {
+                keys.remove(KeyIndex::hash_key(&item.key()));
+            }
+
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("duplicate key {:?}", key),
+            ));
+        }
+
+        keys.insert(hash);
+    }
+
+    Ok(())
+}
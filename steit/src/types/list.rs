@@ -8,20 +8,26 @@ use std::{
 use serde::Serialize as JsonSerialize;
 
 use crate::{
-    de::{Deserialize, Reader},
+    de::{trace_index, Deserialize, Reader},
     log::LogEntryKind,
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::{Runtime, SizeCache},
+    rt::{expect_logged, expect_valid, Runtime, RuntimePool, SizeCache},
     ser::Serialize,
     state::State,
     wire_fmt::{HasWireType, WireType},
 };
 
+use super::keyed::{KeyIndex, Keyed};
+
 #[derive(Clone, PartialEq, Eq, Default, Hash, Debug, JsonSerialize)]
 pub struct List<T: State> {
     items: Vec<T>,
     size_cache: SizeCache,
     runtime: Runtime,
+    #[serde(skip_serializing)]
+    nested: RuntimePool,
+    #[serde(skip_serializing)]
+    keys: KeyIndex,
 }
 
 impl<T: State> Deref for List<T> {
@@ -47,92 +53,299 @@ impl<T: State> List<T> {
     }
 
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.keys.touch();
         self.items.get_mut(index)
     }
 
-    pub fn push(&mut self, mut item: T) -> usize {
-        self.push_with(|runtime| {
+    /// Panics if the key of `item` is already in the list, for `Keyed` items.
+    /// With `no-panic`, `item` is dropped instead and the length of the list is returned,
+    /// which is out of bounds.
+    pub fn push(&mut self, item: T) -> usize {
+        let len = self.items.len();
+        expect_valid(self.try_push(item)).unwrap_or(len)
+    }
+
+    /// Panics like `push`.
+    pub fn push_with(&mut self, get_item: impl FnOnce(Runtime) -> T) -> usize {
+        let len = self.items.len();
+        expect_valid(self.try_push_with(get_item)).unwrap_or(len)
+    }
+
+    /// Like `push`, but returns an error instead of panicking on a duplicate key,
+    /// in which case `item` is not appended.
+    pub fn try_push(&mut self, mut item: T) -> io::Result<usize> {
+        self.try_push_with(|runtime| {
             item.set_runtime(runtime);
             item
         })
     }
 
-    pub fn push_with(&mut self, get_item: impl FnOnce(Runtime) -> T) -> usize {
+    /// Like `push_with`, but returns an error like `try_push`.
+    pub fn try_push_with(&mut self, get_item: impl FnOnce(Runtime) -> T) -> io::Result<usize> {
+        self.refresh_keys()?;
         let field_number = self.items.len() as u32;
 
         self.runtime.pause_logger();
-        let item = get_item(self.runtime.nested(field_number));
+        let item = get_item(self.nested.nested(&self.runtime, field_number));
         self.runtime.unpause_logger();
 
-        self.runtime.log_list_push(&item).unwrap();
         self.items.push(item);
 
-        field_number as usize
+        if let Err(error) =
+            T::validate_list_items(&self.items, field_number as usize, &mut self.keys)
+        {
+            self.items.pop();
+            return Err(error);
+        }
+
+        if let Some(item) = self.items.last() {
+            expect_logged(self.runtime.log_list_push(item));
+        }
+
+        Ok(field_number as usize)
+    }
+
+    /// Appends `items` with a single log entry, returning the range of their indices.
+    ///
+    /// Panics like `push`, in which case no item is appended.
+    /// With `no-panic`, the range is empty instead.
+    pub fn extend(&mut self, items: impl IntoIterator<Item = T>) -> ops::Range<usize> {
+        let from = self.items.len();
+        expect_valid(self.try_extend(items)).unwrap_or(from..from)
+    }
+
+    /// Like `extend`, but returns an error instead of panicking on a duplicate key,
+    /// in which case no item is appended.
+    pub fn try_extend(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+    ) -> io::Result<ops::Range<usize>> {
+        self.refresh_keys()?;
+        let from = self.items.len();
+
+        for mut item in items {
+            let field_number = self.items.len() as u32;
+            item.set_runtime(self.nested.nested(&self.runtime, field_number));
+            self.items.push(item);
+        }
+
+        if let Err(error) = T::validate_list_items(&self.items, from, &mut self.keys) {
+            self.items.truncate(from);
+            return Err(error);
+        }
+
+        if let Some(items) = self.items.get(from..).filter(|items| !items.is_empty()) {
+            expect_logged(self.runtime.log_list_extend(items));
+        }
+
+        Ok(from..self.items.len())
+    }
+
+    pub fn extend_from_slice(&mut self, items: &[T]) -> ops::Range<usize>
+    where
+        T: Clone,
+    {
+        self.extend(items.iter().cloned())
     }
 
     pub fn pop(&mut self) -> Option<T> {
         if !self.items.is_empty() {
-            self.runtime.log_list_pop().unwrap();
-            self.items.pop()
+            expect_logged(self.runtime.log_list_pop());
+            self.pop_silently()
         } else {
             None
         }
     }
 
+    fn pop_silently(&mut self) -> Option<T> {
+        let item = self.items.pop()?;
+        self.unindex_key(&item);
+        Some(item)
+    }
+
+    /// Re-indexes keys which may have changed through mutable access to items,
+    /// failing while two items share a key.
+    fn refresh_keys(&mut self) -> io::Result<()> {
+        if !self.keys.is_stale() {
+            return Ok(());
+        }
+
+        let mut keys = KeyIndex::default();
+        T::validate_list_items(&self.items, 0, &mut keys)?;
+        self.keys = keys;
+        Ok(())
+    }
+
+    fn unindex_key(&mut self, item: &T) {
+        if let Some(hash) = item.list_key_hash() {
+            self.keys.remove(hash);
+        }
+    }
+
     pub fn swap_remove(&mut self, index: usize) -> Option<T> {
-        if index >= self.items.len() {
+        let last_index = self.items.len().checked_sub(1)?;
+
+        if index > last_index {
             return None;
         }
 
-        let last_index = self.items.len() - 1;
-
         if index == last_index {
             return self.pop();
         }
 
         let runtime = &self.runtime;
+        let last = self.items.last()?;
 
-        runtime
-            .log_multi(vec![
-                runtime.entry_list_pop(),
-                runtime.entry_update_child(index as u32, &self.items[last_index]),
-            ])
-            .unwrap();
+        expect_logged(runtime.log_multi(vec![
+            runtime.entry_list_pop(),
+            runtime.entry_update_child(index as u32, last),
+        ]));
 
         let removed = self.items.swap_remove(index);
-        let swapped = &mut self.items[index];
+        self.unindex_key(&removed);
 
-        if swapped.wire_type() == WireType::Sized {
-            let runtime = removed.runtime().sibling(index as u32);
-            swapped.set_runtime(runtime);
+        if let Some(swapped) = self.items.get_mut(index) {
+            if swapped.wire_type() == WireType::Sized {
+                let runtime = removed.runtime().sibling(index as u32);
+                swapped.set_runtime(runtime);
+            }
         }
 
         Some(removed)
     }
 
-    pub fn iter_mut(&mut self) -> slice::IterMut<T> {
+    /// Removes the item at `index`, shifting the items after it,
+    /// logged as moving it to the end and popping it.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let last_index = self.items.len().checked_sub(1)?;
+
+        if index > last_index {
+            return None;
+        }
+
+        if index < last_index {
+            let runtime = &self.runtime;
+
+            expect_logged(runtime.log_multi(vec![
+                runtime.entry_list_move(index as u32, last_index as u32),
+                runtime.entry_list_pop(),
+            ]));
+
+            self.shift(index, last_index);
+            self.pop_silently()
+        } else {
+            self.pop()
+        }
+    }
+
+    /// Moves the item at `from` to `to`, shifting the items in between.
+    ///
+    /// Panics if either index is out of bounds, unless with `no-panic`,
+    /// in which case nothing is moved.
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        expect_valid(self.try_move_item(from, to));
+    }
+
+    /// Like `move_item`, but returns an error instead of panicking.
+    pub fn try_move_item(&mut self, from: usize, to: usize) -> io::Result<()> {
+        let len = self.items.len();
+
+        if from >= len || to >= len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot move item {} to {}", from, to),
+            ));
+        }
+
+        if from != to {
+            expect_logged(self.runtime.log_list_move(from as u32, to as u32));
+            self.shift(from, to);
+        }
+
+        Ok(())
+    }
+
+    fn shift(&mut self, from: usize, to: usize) {
+        let (start, end) = (from.min(to), from.max(to));
+
+        let items = match self.items.get_mut(start..=end) {
+            Some(items) => items,
+            None => return,
+        };
+
+        if from < to {
+            items.rotate_left(1);
+        } else {
+            items.rotate_right(1);
+        }
+
+        for (index, item) in (start..).zip(items) {
+            item.set_runtime(self.nested.nested(&self.runtime, index as u32));
+        }
+    }
+
+    /// Stable sorts items by `key` without logging, for states which keep lists sorted.
+    pub(super) fn sort_silently_by_key<K: Ord>(&mut self, key: impl FnMut(&T) -> K) {
+        self.items.sort_by_key(key);
+        self.set_runtime(self.runtime.clone());
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.keys.touch();
         self.items.iter_mut()
     }
 }
 
+impl<T: State + Keyed> List<T> {
+    pub fn position_by_key(&self, key: &T::Key) -> Option<usize> {
+        if !self.keys.is_stale() && !self.keys.contains(KeyIndex::hash_key(key)) {
+            return None;
+        }
+
+        self.items.iter().position(|item| &item.key() == key)
+    }
+
+    pub fn contains_key(&self, key: &T::Key) -> bool {
+        self.position_by_key(key).is_some()
+    }
+
+    pub fn get_by_key(&self, key: &T::Key) -> Option<&T> {
+        self.position_by_key(key)
+            .and_then(|index| self.items.get(index))
+    }
+
+    pub fn get_by_key_mut(&mut self, key: &T::Key) -> Option<&mut T> {
+        let index = self.position_by_key(key)?;
+        self.get_mut(index)
+    }
+}
+
+// Like `Vec`, indexing panics out of bounds, even with `no-panic`, which `get` is for.
 impl<T: State> ops::Index<usize> for List<T> {
     type Output = T;
 
+    #[allow(clippy::indexing_slicing)]
     fn index(&self, index: usize) -> &Self::Output {
         &self.items[index]
     }
 }
 
 impl<T: State> ops::IndexMut<usize> for List<T> {
+    #[allow(clippy::indexing_slicing)]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.keys.touch();
         &mut self.items[index]
     }
 }
 
 impl<T: State> FromIterator<T> for List<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        let keys = items.iter().filter_map(T::list_key_hash).collect();
+
         Self {
-            items: iter.into_iter().collect(),
+            items,
+            keys,
             ..Default::default()
         }
     }
@@ -160,6 +373,7 @@ impl<T: State> HasWireType for List<T> {
     const WIRE_TYPE: WireType = WireType::Sized;
 }
 
+// Items are written in index order.
 impl<T: State> Serialize for List<T> {
     fn compute_size(&self) -> u32 {
         self.items.compute_size()
@@ -176,16 +390,18 @@ impl<T: State> Serialize for List<T> {
 
 impl<T: State> Deserialize for List<T> {
     fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
-        let mut field_number = self.items.len() as u32;
+        self.refresh_keys()?;
+        let from = self.items.len();
+        let mut field_number = from as u32;
 
         while !reader.eof()? {
-            let mut item = T::with_runtime(self.runtime.nested(field_number));
+            let mut item = T::with_runtime(self.nested.nested(&self.runtime, field_number));
+            trace_index(item.merge_nested(T::WIRE_TYPE, reader), field_number)?;
             field_number += 1;
-            item.merge_nested(T::WIRE_TYPE, reader)?;
             self.items.push(item);
         }
 
-        Ok(())
+        T::validate_list_items(&self.items, from, &mut self.keys)
     }
 }
 
@@ -200,12 +416,30 @@ impl<T: State> State for List<T> {
 
     fn set_runtime(&mut self, runtime: Runtime) {
         for (field_number, item) in self.items.iter_mut().enumerate() {
-            item.set_runtime(runtime.nested(field_number as u32))
+            item.set_runtime(self.nested.nested(&runtime, field_number as u32))
         }
 
         self.runtime = runtime;
     }
 
+    fn refresh_aggregates(&mut self) {
+        for item in &mut self.items {
+            item.refresh_aggregates();
+        }
+    }
+
+    fn prepare_serialize(&mut self) {
+        for item in &mut self.items {
+            item.prepare_serialize();
+        }
+    }
+
+    fn finish_deserialize(&mut self) {
+        for item in &mut self.items {
+            item.finish_deserialize();
+        }
+    }
+
     fn handle(
         &mut self,
         mut path: impl Iterator<Item = u32>,
@@ -213,9 +447,20 @@ impl<T: State> State for List<T> {
         key: Option<u32>,
         reader: &mut Reader<impl io::Read>,
     ) -> io::Result<()> {
+        self.refresh_keys()?;
+
         if let Some(field_number) = path.next() {
             if let Some(item) = self.items.get_mut(field_number as usize) {
-                item.handle(path, kind, key, reader)
+                // Replayed entries may replace a whole item, like the one moved by `swap_remove`.
+                let hash = item.list_key_hash();
+                let handled = item.handle(path, kind, key, reader);
+
+                if item.list_key_hash() != hash {
+                    self.keys.touch();
+                    handled.and(self.refresh_keys())
+                } else {
+                    handled
+                }
             } else {
                 Err(io::Error::new(
                     io::ErrorKind::InvalidData,
@@ -228,15 +473,48 @@ impl<T: State> State for List<T> {
 
                 LogEntryKind::ListPush => {
                     let field_number = self.items.len() as u32;
-                    let mut item = T::with_runtime(self.runtime.nested(field_number));
+                    let mut item = T::with_runtime(self.nested.nested(&self.runtime, field_number));
                     item.merge(reader)?;
                     self.items.push(item);
+
+                    let validated =
+                        T::validate_list_items(&self.items, field_number as usize, &mut self.keys);
+
+                    if validated.is_err() {
+                        self.items.pop();
+                    }
+
+                    validated
+                }
+
+                LogEntryKind::ListExtend => {
+                    let from = self.items.len();
+                    let merged = self.merge(reader);
+
+                    if merged.is_err() {
+                        self.items.truncate(from);
+                    }
+
+                    merged
+                }
+
+                LogEntryKind::ListMove => {
+                    let from = u32::deserialize(reader)? as usize;
+                    let to = u32::deserialize(reader)? as usize;
+
+                    if from >= self.items.len() || to >= self.items.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("cannot move item {} to {}", from, to),
+                        ));
+                    }
+
+                    self.shift(from, to);
                     Ok(())
                 }
 
                 LogEntryKind::ListPop => {
-                    if !self.items.is_empty() {
-                        self.items.remove(self.items.len() - 1);
+                    if self.pop_silently().is_some() {
                         Ok(())
                     } else {
                         Err(io::Error::new(
@@ -256,6 +534,19 @@ impl<T: State> State for List<T> {
             }
         }
     }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        match path.split_first() {
+            Some((field_number, path)) => match self.items.get(*field_number as usize) {
+                Some(item) => item.serialize_at(path, writer),
+                None => Ok(false),
+            },
+            None => {
+                self.steit_serialize(writer)?;
+                Ok(true)
+            }
+        }
+    }
 }
 
 impl<T: State + HasMeta> HasMeta for List<T> {
@@ -276,14 +567,43 @@ impl<T: State + HasMeta> HasMeta for List<T> {
 #[cfg(test)]
 mod tests {
     use crate::{
+        de::{Deserialize, Reader},
         log::loggers::BufferLogger,
         rt::{LoggerHandle, Runtime},
         state::State,
+        steit_derive,
         test_util::{assert_serialize, merge, replay, Point},
     };
 
     use super::List;
 
+    #[steit_derive(Clone, PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Unit {
+        #[steit(tag = 0, key)]
+        id: u32,
+        #[steit(tag = 1)]
+        hp: i32,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Slot {
+        #[steit(tag = 0, key)]
+        row: u8,
+        #[steit(tag = 1, key)]
+        column: u8,
+    }
+
+    fn push_unit(list: &mut List<Unit>, id: u32, hp: i32) {
+        list.push_with(|runtime| {
+            let mut unit = Unit::new(runtime);
+            unit.id = id;
+            unit.hp = hp;
+            unit
+        });
+    }
+
     fn list_with_logger<T: State>() -> (List<T>, LoggerHandle<BufferLogger>) {
         let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
         let list = List::new(runtime);
@@ -298,7 +618,7 @@ mod tests {
     fn push_and_get() {
         let mut list = list();
         list.push(1);
-        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.first(), Some(&1));
     }
 
     #[test]
@@ -325,13 +645,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extend_and_check_log() {
+        let (mut list, logger) = list_with_logger();
+        list.push(1);
+        assert_eq!(list.extend(vec![2, 3]), 1..3);
+        assert_eq!(&**list, &[1, 2, 3]);
+
+        assert_eq!(
+            logger.lock().unwrap().bytes(),
+            &[4, 8, 10, 1, 2, /**/ 5, 10, 10, 2, 4, 6],
+        );
+    }
+
+    #[test]
+    fn extend_empty_no_log() {
+        let (mut list, logger) = list_with_logger::<i32>();
+        assert_eq!(list.extend(vec![]), 0..0);
+        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+    }
+
+    #[test]
+    fn extend_from_slice_runtime() {
+        let mut list = list();
+        push_unit(&mut list, 1, 10);
+        list.extend_from_slice(&[Unit::new(Runtime::new())]);
+        assert_eq!(list[1].runtime().field_number(), 1);
+    }
+
     #[test]
     fn remove_and_get() {
         let mut list = list();
         list.push(0);
         list.push(1);
         list.swap_remove(0);
-        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.first(), Some(&1));
     }
 
     #[test]
@@ -349,6 +697,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn remove_ordered_and_replay() {
+        let (mut list, logger) = list_with_logger();
+
+        for x in 0..4 {
+            list.push_with(|runtime| Point::new(runtime, x, 0, 0));
+        }
+
+        assert_eq!(list.remove(1).map(|point| point.x), Some(1));
+        assert_eq!(list.remove(2).map(|point| point.x), Some(3));
+        assert!(list.remove(2).is_none());
+        assert_eq!(
+            list.iter().map(|point| point.x).collect::<Vec<_>>(),
+            &[0, 2]
+        );
+        assert_eq!(list[1].runtime().field_number(), 1);
+
+        let mut mirror = List::<Point>::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, list);
+    }
+
     #[test]
     fn iter() {
         let mut list = list();
@@ -424,7 +794,7 @@ mod tests {
     fn replay_push_no_log() {
         let (mut list, logger) = list_with_logger();
         replay(&mut list, &[4, 8, 10, 1, 1]);
-        assert_eq!(list.get(0), Some(&-1));
+        assert_eq!(list.first(), Some(&-1));
         assert_eq!(logger.lock().unwrap().bytes(), &[]);
     }
 
@@ -436,6 +806,43 @@ mod tests {
         assert_eq!(list.get(1), Some(&Point::new(Runtime::new(), -2, 1, 5)));
     }
 
+    #[test]
+    fn replay_extend() {
+        let (mut list, logger) = list_with_logger();
+        list.push_with(|runtime| Point::new(runtime, -1, -1, -1));
+        list.extend(vec![
+            Point::new(Runtime::new(), 2, 2, 2),
+            Point::new(Runtime::new(), 3, 3, 3),
+        ]);
+
+        let mut mirror = List::<Point>::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, list);
+        assert_eq!(mirror[2].runtime().field_number(), 2);
+    }
+
+    #[test]
+    fn move_and_replay() {
+        let (mut list, logger) = list_with_logger();
+
+        for x in 0..4 {
+            list.push_with(|runtime| Point::new(runtime, x, 0, 0));
+        }
+
+        list.move_item(0, 2);
+        list.move_item(3, 1);
+        assert_eq!(
+            list.iter().map(|point| point.x).collect::<Vec<_>>(),
+            &[1, 3, 2, 0]
+        );
+        assert_eq!(list[3].runtime().field_number(), 3);
+
+        let mut mirror = List::<Point>::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, list);
+        assert_eq!(mirror[1].runtime().field_number(), 1);
+    }
+
     #[test]
     #[should_panic(expected = "index 0 out of bounds")]
     fn replay_update_out_of_bounds() {
@@ -447,7 +854,7 @@ mod tests {
         let mut list = list();
         list.push(0);
         replay(&mut list, &[7, 0, 2, 1, 0, 10, 1, 1]);
-        assert_eq!(list.get(0), Some(&-1));
+        assert_eq!(list.first(), Some(&-1));
     }
 
     #[test]
@@ -455,7 +862,7 @@ mod tests {
         let mut list = list();
         list.push_with(|runtime| Point::new(runtime, -1, -1, -1));
         replay(&mut list, &[8, 0, 2, 2, 0, 2, 10, 1, 100]);
-        assert_eq!(list.get(0), Some(&Point::new(Runtime::new(), -1, -1, 50)));
+        assert_eq!(list.first(), Some(&Point::new(Runtime::new(), -1, -1, 50)));
     }
 
     #[test]
@@ -469,7 +876,219 @@ mod tests {
             &[1, 9, /**/ 12, 0, 2, 1, 0, 10, 6, 0, 4, 8, 4, 16, 4],
         );
 
-        assert_eq!(list.get(0), Some(&Point::new(Runtime::new(), 2, 2, 2)));
+        assert_eq!(list.first(), Some(&Point::new(Runtime::new(), 2, 2, 2)));
         assert_eq!(list.get(1), None);
     }
+
+    #[test]
+    fn get_by_key() {
+        let mut list = list();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 3, 20);
+
+        assert_eq!(list.position_by_key(&3), Some(1));
+        assert_eq!(list.get_by_key(&7).map(|unit| unit.hp), Some(10));
+        assert!(!list.contains_key(&1));
+
+        list.get_by_key_mut(&3).unwrap().set_hp(25);
+        assert_eq!(list[1].hp, 25);
+    }
+
+    #[test]
+    fn get_by_composite_key() {
+        let mut list = list();
+        list.push_with(|runtime| {
+            let mut slot = Slot::new(runtime);
+            slot.set_row(1).set_column(2);
+            slot
+        });
+
+        assert!(list.contains_key(&(1, 2)));
+        assert!(!list.contains_key(&(2, 1)));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "no-panic"), should_panic(expected = "duplicate key 7"))]
+    fn push_duplicate_key() {
+        let mut list = list();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 7, 20);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "no-panic"),
+        should_panic(expected = "cannot move item 0 to 2")
+    )]
+    fn move_item_out_of_bounds() {
+        let mut list = list();
+        list.push(1);
+        list.push(2);
+        list.move_item(0, 2);
+        assert_eq!(*list, vec![1, 2]);
+    }
+
+    #[test]
+    fn try_push_duplicate_key() {
+        let (mut list, logger) = list_with_logger();
+        push_unit(&mut list, 7, 10);
+        logger.lock().unwrap().clear();
+
+        let mut unit = Unit::new(Runtime::new());
+        unit.id = 7;
+        let error = list.try_push(unit).unwrap_err();
+
+        assert_eq!(error.to_string(), "duplicate key 7");
+        assert_eq!(list.len(), 1);
+        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+    }
+
+    #[test]
+    fn push_key_after_remove() {
+        let mut list = list();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 3, 20);
+        push_unit(&mut list, 5, 30);
+        list.swap_remove(0);
+        list.remove(0);
+
+        assert!(!list.contains_key(&7));
+        assert!(!list.contains_key(&5));
+        assert_eq!(list.position_by_key(&3), Some(0));
+
+        push_unit(&mut list, 7, 40);
+        assert_eq!(list.position_by_key(&7), Some(1));
+    }
+
+    #[test]
+    fn replay_swap_remove_keys() {
+        let (mut list, logger) = list_with_logger();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 3, 20);
+        list.swap_remove(0);
+
+        let mut mirror = List::<Unit>::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+
+        assert_eq!(mirror.position_by_key(&3), Some(0));
+        assert!(!mirror.contains_key(&7));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "no-panic"), should_panic(expected = "duplicate key 0"))]
+    fn extend_duplicate_key() {
+        let mut list = list();
+        push_unit(&mut list, 7, 10);
+
+        let units = vec![Unit::new(Runtime::new()), Unit::new(Runtime::new())];
+        assert_eq!(list.extend(units), 1..1);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn try_extend_duplicate_key() {
+        let (mut list, logger) = list_with_logger();
+        push_unit(&mut list, 7, 10);
+        logger.lock().unwrap().clear();
+
+        let units = vec![Unit::new(Runtime::new()), Unit::new(Runtime::new())];
+        let error = list.try_extend(units).unwrap_err();
+
+        assert_eq!(error.to_string(), "duplicate key 0");
+        assert_eq!(list.len(), 1);
+        assert!(!list.contains_key(&0));
+        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+
+        let mut unit = Unit::new(Runtime::new());
+        unit.id = 3;
+        assert_eq!(list.try_extend(vec![unit]).unwrap(), 1..2);
+    }
+
+    #[test]
+    fn change_key_in_place() {
+        let mut list = list();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 3, 20);
+
+        list.get_by_key_mut(&3).unwrap().set_id(5);
+        assert_eq!(list.position_by_key(&5), Some(1));
+        assert!(!list.contains_key(&3));
+
+        push_unit(&mut list, 3, 30);
+        assert_eq!(list.position_by_key(&3), Some(2));
+    }
+
+    #[test]
+    fn change_key_in_place_to_duplicate() {
+        let (mut list, logger) = list_with_logger();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 3, 20);
+        list[1].set_id(7);
+        logger.lock().unwrap().clear();
+
+        let mut unit = Unit::new(Runtime::new());
+        unit.id = 9;
+        let error = list.try_push(unit.clone()).unwrap_err();
+
+        assert_eq!(error.to_string(), "duplicate key 7");
+        assert_eq!(list.len(), 2);
+        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+
+        for unit in &mut list {
+            if unit.hp == 20 {
+                unit.set_id(3);
+            }
+        }
+
+        assert_eq!(list.try_push(unit).unwrap(), 2);
+        assert_eq!(list.position_by_key(&3), Some(1));
+    }
+
+    #[test]
+    fn replay_change_key_to_duplicate() {
+        let (mut list, logger) = list_with_logger();
+        push_unit(&mut list, 7, 10);
+        push_unit(&mut list, 3, 20);
+
+        let mut mirror = List::<Unit>::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        logger.lock().unwrap().clear();
+
+        list[1].set_id(7);
+        let bytes = logger.lock().unwrap().bytes();
+        assert!(mirror.replay(&mut Reader::new(&*bytes)).is_err());
+        assert_eq!(mirror.position_by_key(&7), Some(0));
+    }
+
+    #[test]
+    fn merge_duplicate_key() {
+        let bytes = [4, 0, 7, 8, 2, /**/ 2, 0, 7];
+        let error = List::<Unit>::deserialize(&mut Reader::new(&bytes[..])).unwrap_err();
+        assert_eq!(error.to_string(), "duplicate key 7");
+    }
+
+    #[test]
+    fn replay_duplicate_key() {
+        let (mut list, logger) = list_with_logger();
+        push_unit(&mut list, 7, 10);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut mirror = List::<Unit>::new(Runtime::new());
+        replay(&mut mirror, &bytes);
+        assert!(mirror.replay(&mut Reader::new(&*bytes)).is_err());
+        assert_eq!(mirror.len(), 1);
+    }
+
+    #[test]
+    fn replay_extend_duplicate_key() {
+        let (mut list, logger) = list_with_logger::<Unit>();
+        list.extend(vec![Unit::new(Runtime::new())]);
+        let bytes = logger.lock().unwrap().bytes();
+
+        let mut mirror = List::<Unit>::new(Runtime::new());
+        replay(&mut mirror, &bytes);
+        assert!(mirror.replay(&mut Reader::new(&*bytes)).is_err());
+        assert_eq!(mirror.len(), 1);
+    }
 }
@@ -4,7 +4,7 @@ use super::key::MapKey;
 
 pub struct MapIter<'a, K: MapKey, V: 'a> {
     inner: Box<dyn Iterator<Item = (&'a u32, &'a V)> + 'a>,
-    _marker: PhantomData<*const K>,
+    _marker: PhantomData<fn() -> K>,
 }
 
 impl<'a, K: MapKey, V> MapIter<'a, K, V> {
@@ -20,16 +20,17 @@ impl<'a, K: MapKey, V> Iterator for MapIter<'a, K, V> {
     type Item = (K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(&field_number, value)| {
-            let key = K::try_from_field_number(field_number).unwrap();
-            (key, value)
+        // Keys of entries are checked as they are added, so none are skipped.
+        self.inner.by_ref().find_map(|(&field_number, value)| {
+            let key = K::try_from_field_number(field_number).ok()?;
+            Some((key, value))
         })
     }
 }
 
 pub struct MapIterMut<'a, K: MapKey, V: 'a> {
     inner: Box<dyn Iterator<Item = (&'a u32, &'a mut V)> + 'a>,
-    _marker: PhantomData<*const K>,
+    _marker: PhantomData<fn() -> K>,
 }
 
 impl<'a, K: MapKey, V> MapIterMut<'a, K, V> {
@@ -45,9 +46,10 @@ impl<'a, K: MapKey, V> Iterator for MapIterMut<'a, K, V> {
     type Item = (K, &'a mut V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(&field_number, value)| {
-            let key = K::try_from_field_number(field_number).unwrap();
-            (key, value)
+        // Keys of entries are checked as they are added, so none are skipped.
+        self.inner.by_ref().find_map(|(&field_number, value)| {
+            let key = K::try_from_field_number(field_number).ok()?;
+            Some((key, value))
         })
     }
 }
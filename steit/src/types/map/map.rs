@@ -4,13 +4,13 @@ use indexmap::map::IndexMap;
 use serde::Serialize as JsonSerialize;
 
 use crate::{
-    de::{Deserialize, Reader},
+    de::{trace_index, Deserialize, Reader},
     log::LogEntryKind,
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
-    rt::{Runtime, SizeCache},
-    ser::Serialize,
+    rt::{expect_logged, expect_written, Runtime, SizeCache},
+    ser::{MapOrder, Serialize},
     state::State,
-    wire_fmt::{self, HasWireType, WireType},
+    wire_fmt::{HasWireType, WireType},
 };
 
 use super::{
@@ -24,7 +24,7 @@ pub struct Map<K: MapKey, V: State> {
     entries: IndexMap<u32, V>,
     size_cache: SizeCache,
     runtime: Runtime,
-    _marker: PhantomData<*const K>,
+    _marker: PhantomData<fn() -> K>,
 }
 
 impl<K: MapKey, V: State> Map<K, V> {
@@ -50,6 +50,10 @@ impl<K: MapKey, V: State> Map<K, V> {
         self.entries.contains_key(&key.as_field_number())
     }
 
+    pub(crate) fn contains_field_number(&self, field_number: u32) -> bool {
+        self.entries.contains_key(&field_number)
+    }
+
     pub fn get(&self, key: &K) -> Option<&V> {
         self.entries.get(&key.as_field_number())
     }
@@ -58,9 +62,20 @@ impl<K: MapKey, V: State> Map<K, V> {
         self.entries.get_mut(&key.as_field_number())
     }
 
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts `value` under `key`, moving it to the path of the entry
+    /// so that changes made through `get_mut` are logged there.
+    pub fn insert(&mut self, key: K, mut value: V) -> Option<V> {
         let field_number = key.as_field_number();
-        self.runtime.log_update_child(field_number, &value).unwrap();
+        value.set_runtime(self.runtime.nested(field_number));
+        expect_logged(self.runtime.log_update_child(field_number, &value));
         self.entries.insert(field_number, value)
     }
 
@@ -72,30 +87,35 @@ impl<K: MapKey, V: State> Map<K, V> {
         self.insert(key, value)
     }
 
+    /// Logs nothing if there is no entry under `key`.
     pub fn remove(&mut self, key: &K) -> Option<V> {
         let field_number = key.as_field_number();
-        self.runtime.log_map_remove(field_number).unwrap();
-        self.entries.remove(&field_number)
+        let removed = self.entries.remove(&field_number)?;
+        expect_logged(self.runtime.log_map_remove(field_number));
+        Some(removed)
     }
 
-    pub fn iter(&self) -> MapIter<K, V> {
+    pub fn iter(&self) -> MapIter<'_, K, V> {
         MapIter::new(self.entries.iter())
     }
 
-    pub fn iter_mut(&mut self) -> MapIterMut<K, V> {
+    pub fn iter_mut(&mut self) -> MapIterMut<'_, K, V> {
         MapIterMut::new(self.entries.iter_mut())
     }
 }
 
+// Like `HashMap`, indexing panics on missing keys, even with `no-panic`, which `get` is for.
 impl<K: MapKey, V: State> ops::Index<&K> for Map<K, V> {
     type Output = V;
 
+    #[allow(clippy::expect_used)]
     fn index(&self, index: &K) -> &Self::Output {
         self.get(index).expect("no entry found for key")
     }
 }
 
 impl<K: MapKey, V: State> ops::IndexMut<&K> for Map<K, V> {
+    #[allow(clippy::expect_used)]
     fn index_mut(&mut self, index: &K) -> &mut Self::Output {
         self.get_mut(index).expect("no entry found for key")
     }
@@ -152,20 +172,30 @@ impl<K: MapKey, V: State> HasWireType for Map<K, V> {
     const WIRE_TYPE: WireType = WireType::Sized;
 }
 
+// Entries are written in insertion order, or sorted by key under `MapOrder::Canonical`.
 impl<K: MapKey, V: State> Serialize for Map<K, V> {
     fn compute_size(&self) -> u32 {
         let mut size = 0;
 
         for (&field_number, value) in &self.entries {
-            size += value.compute_size_nested(field_number, false).unwrap();
+            size += expect_written(value.compute_size_nested(field_number, false));
         }
 
         size
     }
 
     fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
-        for (&field_number, value) in &self.entries {
-            value.serialize_nested(field_number, false, writer)?;
+        if MapOrder::current() == MapOrder::Canonical {
+            let mut entries: Vec<_> = self.entries.iter().collect();
+            entries.sort_unstable_by_key(|&(&field_number, _)| field_number);
+
+            for (&field_number, value) in entries {
+                value.serialize_nested(field_number, false, writer)?;
+            }
+        } else {
+            for (&field_number, value) in &self.entries {
+                value.serialize_nested(field_number, false, writer)?;
+            }
         }
 
         Ok(())
@@ -179,15 +209,14 @@ impl<K: MapKey, V: State> Serialize for Map<K, V> {
 impl<K: MapKey, V: State> Deserialize for Map<K, V> {
     fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
         while !reader.eof()? {
-            let field_number = u32::deserialize(reader)?;
-            wire_fmt::validate_field_number(field_number)?;
+            let (field_number, wire_type) = reader.read_tag()?;
             K::try_from_field_number(field_number)?;
 
             if let Some(value) = self.entries.get_mut(&field_number) {
-                value.merge_nested(V::WIRE_TYPE, reader)?;
+                trace_index(value.merge_nested(wire_type, reader), field_number)?;
             } else {
                 let mut value = V::with_runtime(self.runtime.nested(field_number));
-                value.merge_nested(V::WIRE_TYPE, reader)?;
+                trace_index(value.merge_nested(wire_type, reader), field_number)?;
                 self.entries.insert(field_number, value);
             }
         }
@@ -213,6 +242,24 @@ impl<K: MapKey, V: State> State for Map<K, V> {
         self.runtime = runtime;
     }
 
+    fn refresh_aggregates(&mut self) {
+        for value in self.entries.values_mut() {
+            value.refresh_aggregates();
+        }
+    }
+
+    fn prepare_serialize(&mut self) {
+        for value in self.entries.values_mut() {
+            value.prepare_serialize();
+        }
+    }
+
+    fn finish_deserialize(&mut self) {
+        for value in self.entries.values_mut() {
+            value.finish_deserialize();
+        }
+    }
+
     fn handle(
         &mut self,
         mut path: impl Iterator<Item = u32>,
@@ -225,7 +272,7 @@ impl<K: MapKey, V: State> State for Map<K, V> {
                 value.handle(path, kind, key, reader)
             } else if kind == LogEntryKind::Update && path.next().is_none() {
                 let mut value = V::with_runtime(self.runtime.nested(field_number));
-                value.merge_nested(V::WIRE_TYPE, reader)?;
+                value.merge(reader)?;
                 self.entries.insert(field_number, value);
                 Ok(())
             } else {
@@ -263,6 +310,19 @@ impl<K: MapKey, V: State> State for Map<K, V> {
             }
         }
     }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        match path.split_first() {
+            Some((field_number, path)) => match self.entries.get(field_number) {
+                Some(value) => value.serialize_at(path, writer),
+                None => Ok(false),
+            },
+            None => {
+                self.steit_serialize(writer)?;
+                Ok(true)
+            }
+        }
+    }
 }
 
 impl<K: MapKey, V: State + HasMeta> HasMeta for Map<K, V> {
@@ -285,8 +345,10 @@ mod tests {
     use std::io;
 
     use crate::{
+        de::{Deserialize, Reader},
         log::loggers::BufferLogger,
         rt::{LoggerHandle, Runtime},
+        ser::{MapOrder, Serialize},
         state::State,
         test_util::{assert_serialize, merge, replay, Point},
     };
@@ -389,6 +451,24 @@ mod tests {
         assert_eq!(logger.lock().unwrap().bytes(), &[3, 12, 8, 2]);
     }
 
+    #[test]
+    fn insert_moves_runtime() {
+        let (mut map, logger) = map_with_logger();
+        map.insert(4u32, Point::new(Runtime::new(), 1, 2, 3));
+        map.get_mut(&4).unwrap().set_y(5);
+        map.remove(&9);
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[&4].runtime().field_number(), 4);
+
+        let mut mirror = Map::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, map);
+
+        map.remove(&4);
+        assert!(map.is_empty());
+    }
+
     #[test]
     fn iter() {
         let mut map = map();
@@ -449,13 +529,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serialize_canonical() {
+        let mut shuffled = map();
+        shuffled.insert(3u8, 30);
+        shuffled.insert(1, 10);
+
+        let mut sorted = map();
+        sorted.insert(1u8, 10);
+        sorted.insert(3, 30);
+
+        assert_eq!(shuffled.to_bytes(), [24, 60, 8, 20]);
+        assert_eq!(shuffled.to_bytes_in(MapOrder::Canonical), [8, 20, 24, 60]);
+        assert_eq!(
+            shuffled.to_bytes_in(MapOrder::Canonical),
+            sorted.to_bytes_in(MapOrder::Canonical)
+        );
+        assert_eq!(MapOrder::current(), MapOrder::Insertion);
+
+        let mut points = map();
+        points.insert(2u8, Point::new(Runtime::new(), 1, 2, 3));
+        points.insert(1, Point::new(Runtime::new(), 4, 5, 6));
+
+        let bytes = points.to_bytes_in(MapOrder::Canonical);
+        let decoded = Map::<u8, Point>::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(decoded, points);
+        assert_eq!(
+            decoded.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+
     #[test]
     fn merge_no_log() {
         let (mut map, logger) = map_with_logger();
         map.insert(1u16, 10);
         map.insert(2, 20);
         logger.lock().unwrap().clear();
-        merge(&mut map, &[3, 60]);
+        merge(&mut map, &[24, 60]);
         assert_eq!(map.get(&3), Some(&30));
         assert_eq!(logger.lock().unwrap().bytes(), &[]);
     }
@@ -465,14 +576,14 @@ mod tests {
         let mut map = map();
         map.insert_with(1u8, |runtime| Point::new(runtime, -1, -1, -1));
         map.insert_with(10, |runtime| Point::new(runtime, 2, 2, 2));
-        merge(&mut map, &[10, 2, 8, 5]);
+        merge(&mut map, &[82, 2, 8, 5]);
         assert_eq!(map.get(&10), Some(&Point::new(Runtime::new(), 2, -3, 2)));
     }
 
     #[test]
     fn merge_push_new() {
         let mut map = map();
-        merge(&mut map, &[2, 2, 16, 7]);
+        merge(&mut map, &[18, 2, 16, 7]);
         assert_eq!(map.get(&2u8), Some(&Point::new(Runtime::new(), 0, 0, -4)));
     }
 
@@ -38,14 +38,6 @@ impl<T: State> Maybe<T> {
             None => Self::none(runtime),
         }
     }
-
-    pub fn is_some(&self) -> bool {
-        matches!(self, Maybe::Some { .. })
-    }
-
-    pub fn is_none(&self) -> bool {
-        !self.is_some()
-    }
 }
 
 impl<T: State + Copy> Maybe<T> {
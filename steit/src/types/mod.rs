@@ -1,9 +1,29 @@
 mod bytes;
+#[cfg(feature = "runtime")]
+mod flags;
+mod keyed;
+#[cfg(feature = "runtime")]
 mod list;
+#[cfg(feature = "runtime")]
 mod map;
+#[cfg(feature = "runtime")]
 mod maybe;
+#[cfg(feature = "runtime")]
+mod rng;
+#[cfg(feature = "runtime")]
+mod sorted_view;
 
 pub use bytes::*;
+#[cfg(feature = "runtime")]
+pub use flags::*;
+pub use keyed::*;
+#[cfg(feature = "runtime")]
 pub use list::*;
+#[cfg(feature = "runtime")]
 pub use map::*;
+#[cfg(feature = "runtime")]
 pub use maybe::*;
+#[cfg(feature = "runtime")]
+pub use rng::*;
+#[cfg(feature = "runtime")]
+pub use sorted_view::*;
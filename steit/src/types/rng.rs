@@ -0,0 +1,120 @@
+use serde::Serialize as JsonSerialize;
+
+use crate::{rt::Runtime, steit_derive};
+
+const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A counter-based random stream (SplitMix64) whose whole state is a seed and
+/// the number of values drawn, so it replicates and checkpoints like any other state.
+#[steit_derive(Clone, PartialEq, Eq, Hash, Debug, State, JsonSerialize)]
+#[steit(steit_owned)]
+pub struct RngState {
+    #[steit(tag = 0, no_getter)]
+    seed: u64,
+    #[steit(tag = 1, no_getter)]
+    counter: u64,
+}
+
+impl RngState {
+    pub fn with_seed(runtime: Runtime, seed: u64) -> Self {
+        let mut rng = Self::new(runtime);
+        rng.seed = seed;
+        rng
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Returns the value `offset` draws ahead without advancing the stream.
+    pub fn peek(&self, offset: u64) -> u64 {
+        let index = self.counter.wrapping_add(offset).wrapping_add(1);
+        mix(self.seed.wrapping_add(index.wrapping_mul(GAMMA)))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let value = self.peek(0);
+        self.advance(1);
+        value
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a value in `0..bound`, or 0 if `bound` is 0.
+    ///
+    /// A value is drawn either way, so streams stay in step whatever bounds they get.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Returns a value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Skips `count` values, logging a single update.
+    pub fn advance(&mut self, count: u64) -> &mut Self {
+        self.set_counter(self.counter.wrapping_add(count))
+    }
+
+    pub fn reseed(&mut self, seed: u64) -> &mut Self {
+        self.set_seed(seed).set_counter(0)
+    }
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{log::loggers::BufferLogger, rt::Runtime, test_util::replay};
+
+    use super::RngState;
+
+    #[test]
+    fn splitmix64() {
+        let mut rng = RngState::with_seed(Runtime::new(), 1234567);
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+        assert_eq!(rng.counter(), 2);
+    }
+
+    #[test]
+    fn peek_and_advance() {
+        let mut rng = RngState::with_seed(Runtime::new(), 42);
+        let third = rng.peek(2);
+        rng.advance(2);
+        assert_eq!(rng.next_u64(), third);
+    }
+
+    #[test]
+    fn next_below() {
+        let mut rng = RngState::with_seed(Runtime::new(), 7);
+        assert!((0..100).all(|_| rng.next_below(6) < 6));
+        assert_eq!(rng.next_below(0), 0);
+        assert_eq!(rng.counter(), 101);
+    }
+
+    #[test]
+    fn replay_stream() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut rng = RngState::new(runtime);
+        rng.reseed(99);
+        rng.next_u64();
+        rng.next_u32();
+
+        let mut mirror = RngState::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(mirror, rng);
+        assert_eq!(mirror.next_u64(), rng.next_u64());
+    }
+}
@@ -0,0 +1,385 @@
+use std::{io, ops::Deref};
+
+use serde::Serialize as JsonSerialize;
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::LogEntryKind,
+    meta::{HasMeta, MetaLink, NameMeta, TypeMeta},
+    rt::{expect_valid, Runtime, SizeCache},
+    ser::Serialize,
+    state::State,
+    wire_fmt::{HasWireType, WireType},
+};
+
+use super::list::List;
+
+/// Items which can be ordered in a `SortedView`.
+pub trait SortKey {
+    type Key: Ord;
+
+    fn sort_key(&self) -> Self::Key;
+}
+
+/// A `List` kept in ascending order of `SortKey`, with equal keys in insertion order.
+///
+/// Reordering is logged as `ListMove` entries, so clients can mirror it with a plain `List`.
+#[derive(Clone, PartialEq, Eq, Default, Hash, Debug, JsonSerialize)]
+pub struct SortedView<T: State + SortKey> {
+    list: List<T>,
+}
+
+impl<T: State + SortKey> Deref for SortedView<T> {
+    type Target = List<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.list
+    }
+}
+
+impl<T: State + SortKey> SortedView<T> {
+    pub fn new(runtime: Runtime) -> Self {
+        Self {
+            list: List::new(runtime),
+        }
+    }
+
+    /// Returns the index the item ends up at.
+    pub fn insert(&mut self, mut item: T) -> usize {
+        self.insert_with(|runtime| {
+            item.set_runtime(runtime);
+            item
+        })
+    }
+
+    pub fn insert_with(&mut self, get_item: impl FnOnce(Runtime) -> T) -> usize {
+        let index = self.list.push_with(get_item);
+
+        let to = match self.list.get(index) {
+            Some(item) => self.insertion_point(index, &item.sort_key()),
+            None => return index,
+        };
+
+        self.list.move_item(index, to);
+        to
+    }
+
+    /// Updates the item at `index` with `update`, then moves it to keep the order.
+    /// Returns the index the item ends up at.
+    ///
+    /// Panics if `index` is out of bounds, unless with `no-panic`,
+    /// in which case `index` is returned.
+    pub fn update(&mut self, index: usize, update: impl FnOnce(&mut T)) -> usize {
+        expect_valid(self.try_update(index, update)).unwrap_or(index)
+    }
+
+    /// Like `update`, but returns an error instead of panicking.
+    pub fn try_update(&mut self, index: usize, update: impl FnOnce(&mut T)) -> io::Result<usize> {
+        let key = match self.list.get_mut(index) {
+            Some(item) => {
+                update(item);
+                item.sort_key()
+            }
+
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("index {} out of bounds", index),
+                ))
+            }
+        };
+
+        let before = self.insertion_point(index, &key);
+
+        let to = if before < index {
+            before
+        } else {
+            index
+                + self.list.get(index + 1..).map_or(0, |items| {
+                    items.partition_point(|item| item.sort_key() < key)
+                })
+        };
+
+        self.list.try_move_item(index, to)?;
+        Ok(to)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.list.len() {
+            return None;
+        }
+
+        self.list.move_item(index, self.list.len() - 1);
+        self.list.pop()
+    }
+
+    /// Number of items ordered strictly before `key`,
+    /// which is the index an item with this key would be inserted at if there were no ties.
+    pub fn rank(&self, key: &T::Key) -> usize {
+        self.list.partition_point(|item| &item.sort_key() < key)
+    }
+
+    /// Items with keys in `min..=max`, in order.
+    pub fn range(&self, min: &T::Key, max: &T::Key) -> &[T] {
+        let end = self.list.partition_point(|item| &item.sort_key() <= max);
+        let start = self.rank(min).min(end);
+        self.list.get(start..end).unwrap_or_default()
+    }
+
+    /// The first `n` items, or all of them if there are fewer.
+    pub fn top(&self, n: usize) -> &[T] {
+        self.list.get(..n.min(self.list.len())).unwrap_or_default()
+    }
+
+    /// Index among the items before `index` which an item with `key` goes at,
+    /// after those with equal keys.
+    fn insertion_point(&self, index: usize, key: &T::Key) -> usize {
+        self.list.get(..index).map_or(index, |items| {
+            items.partition_point(|item| &item.sort_key() <= key)
+        })
+    }
+
+    /// Moves items back in order after their keys were changed in place, logging the moves.
+    fn reorder(&mut self) {
+        for index in 1..self.list.len() {
+            if let Some(item) = self.list.get(index) {
+                let to = self.insertion_point(index, &item.sort_key());
+                self.list.move_item(index, to);
+            }
+        }
+    }
+
+    fn sort(&mut self) {
+        self.list.sort_silently_by_key(T::sort_key);
+    }
+}
+
+impl<T: State + SortKey> HasWireType for SortedView<T> {
+    const WIRE_TYPE: WireType = WireType::Sized;
+}
+
+impl<T: State + SortKey> Serialize for SortedView<T> {
+    fn compute_size(&self) -> u32 {
+        self.list.compute_size()
+    }
+
+    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.list.serialize_cached(writer)
+    }
+
+    fn size_cache(&self) -> Option<&SizeCache> {
+        self.list.size_cache()
+    }
+}
+
+impl<T: State + SortKey> Deserialize for SortedView<T> {
+    fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        self.list.merge(reader)?;
+        self.sort();
+        Ok(())
+    }
+}
+
+impl<T: State + SortKey> State for SortedView<T> {
+    fn with_runtime(runtime: Runtime) -> Self {
+        Self::new(runtime)
+    }
+
+    fn runtime(&self) -> &Runtime {
+        self.list.runtime()
+    }
+
+    fn set_runtime(&mut self, runtime: Runtime) {
+        self.list.set_runtime(runtime);
+    }
+
+    // Keys may depend on aggregates, so items are moved back in order afterwards.
+    fn refresh_aggregates(&mut self) {
+        self.list.refresh_aggregates();
+        self.reorder();
+    }
+
+    fn prepare_serialize(&mut self) {
+        self.list.prepare_serialize();
+        self.reorder();
+    }
+
+    fn finish_deserialize(&mut self) {
+        self.list.finish_deserialize();
+        self.sort();
+    }
+
+    // Moves are logged along with the changes causing them, so replayed items are not reordered,
+    // except after the whole view gets replaced.
+    fn handle(
+        &mut self,
+        path: impl Iterator<Item = u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        let mut path = path.peekable();
+        let is_update = path.peek().is_none() && kind == LogEntryKind::Update;
+
+        self.list.handle(path, kind, key, reader)?;
+
+        if is_update {
+            self.sort();
+        }
+
+        Ok(())
+    }
+
+    fn serialize_at(&self, path: &[u32], writer: &mut impl io::Write) -> io::Result<bool> {
+        self.list.serialize_at(path, writer)
+    }
+}
+
+// Encoded as a `List`, which is what clients see.
+impl<T: State + SortKey + HasMeta> HasMeta for SortedView<T> {
+    const NAME: &'static NameMeta = List::<T>::NAME;
+    const TYPE: &'static TypeMeta = List::<T>::TYPE;
+    const LINK: &'static MetaLink = List::<T>::LINK;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use crate::{
+        de::{Deserialize, Reader},
+        log::loggers::BufferLogger,
+        rt::{LoggerHandle, Runtime},
+        ser::Serialize,
+        state::State,
+        steit_derive,
+        test_util::replay,
+        types::List,
+    };
+
+    use super::{SortKey, SortedView};
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Player {
+        #[steit(tag = 0)]
+        id: u32,
+        #[steit(tag = 1)]
+        score: u32,
+    }
+
+    // Highest scores first.
+    impl SortKey for Player {
+        type Key = Reverse<u32>;
+
+        fn sort_key(&self) -> Self::Key {
+            Reverse(self.score)
+        }
+    }
+
+    fn player(id: u32, score: u32) -> Player {
+        let mut player = Player::new(Runtime::new());
+        player.set_id(id).set_score(score);
+        player
+    }
+
+    fn board() -> (SortedView<Player>, LoggerHandle<BufferLogger>) {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut board = SortedView::new(runtime);
+
+        for (id, score) in &[(1, 30), (2, 50), (3, 10), (4, 30)] {
+            board.insert(player(*id, *score));
+        }
+
+        (board, logger)
+    }
+
+    fn ids(players: &[Player]) -> Vec<u32> {
+        players.iter().map(|player| player.id).collect()
+    }
+
+    #[test]
+    fn insert_in_order() {
+        let (board, _) = board();
+        assert_eq!(ids(&board), &[2, 1, 4, 3]);
+        assert_eq!(board[3].runtime().field_number(), 3);
+    }
+
+    #[test]
+    fn update_moves() {
+        let (mut board, _) = board();
+
+        assert_eq!(
+            board.update(3, |player| {
+                player.set_score(40);
+            }),
+            1
+        );
+        assert_eq!(ids(&board), &[2, 3, 1, 4]);
+
+        assert_eq!(
+            board.update(0, |player| {
+                player.set_score(0);
+            }),
+            3
+        );
+        assert_eq!(ids(&board), &[3, 1, 4, 2]);
+
+        assert_eq!(
+            board.update(1, |player| {
+                player.set_id(5);
+            }),
+            1
+        );
+        assert_eq!(ids(&board), &[3, 5, 4, 2]);
+    }
+
+    #[test]
+    fn try_update_out_of_bounds() {
+        let (mut board, _) = board();
+        let error = board.try_update(4, |_| {}).unwrap_err();
+        assert_eq!(error.to_string(), "index 4 out of bounds");
+        assert_eq!(ids(&board), &[2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn remove() {
+        let (mut board, _) = board();
+        assert_eq!(board.remove(1).map(|player| player.id), Some(1));
+        assert_eq!(ids(&board), &[2, 4, 3]);
+        assert!(board.remove(3).is_none());
+    }
+
+    #[test]
+    fn rank_queries() {
+        let (board, _) = board();
+        assert_eq!(board.rank(&Reverse(50)), 0);
+        assert_eq!(board.rank(&Reverse(30)), 1);
+        assert_eq!(board.rank(&Reverse(20)), 3);
+        assert_eq!(ids(board.range(&Reverse(40), &Reverse(10))), &[1, 4, 3]);
+        assert_eq!(ids(board.top(2)), &[2, 1]);
+        assert_eq!(ids(board.top(10)).len(), 4);
+    }
+
+    #[test]
+    fn mirror_with_list() {
+        let (mut board, logger) = board();
+        board.update(2, |player| {
+            player.set_score(60);
+        });
+        board.remove(3);
+        board.insert(player(5, 20));
+
+        let mut mirror = List::<Player>::new(Runtime::new());
+        replay(&mut mirror, &logger.lock().unwrap().bytes());
+        assert_eq!(ids(&mirror), ids(&board));
+    }
+
+    #[test]
+    fn merge_sorts() {
+        let list = List::from_iter(Runtime::new(), vec![player(1, 10), player(2, 20)]);
+        let board = SortedView::<Player>::deserialize(&mut Reader::new(&*list.to_bytes())).unwrap();
+        assert_eq!(ids(&board), &[2, 1]);
+        assert_eq!(board[1].runtime().field_number(), 1);
+    }
+}
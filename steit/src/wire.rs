@@ -0,0 +1,13 @@
+//! The wire format alone, for tools and relays which read and write values but never hold states.
+//!
+//! Everything here is also in `ser`, `de` and `wire_fmt`, and doesn't touch runtimes or loggers,
+//! so code using only this module keeps working if the wire format moves to a crate of its own.
+//! Turning off the default `runtime` feature builds only this part of the crate.
+
+pub use crate::{
+    de::*,
+    rt::SizeCache,
+    ser::*,
+    types::Bytes,
+    wire_fmt::{self, HasWireType, WireKey, WireType},
+};
@@ -9,17 +9,42 @@ pub const WIRE_TYPE_MASK: u32 = (1u32 << WIRE_TYPE_BITS) - 1;
 /// Maximum possible field number.
 pub const FIELD_NUMBER_MAX: u32 = 0x1fffffff;
 
+/// How a field is laid out after its tag, which is all a reader needs to skip it.
+///
+/// Values take the low `WIRE_TYPE_BITS` of tags, so there is room for 8 wire types,
+/// some of which are left unused for compatibility with protobuf (3 and 4, its groups)
+/// or reserved (7, the preorder marker of compact snapshots).
+///
+/// Adding a wire type is a breaking change to the format for readers, which reject
+/// unknown ones as they can't skip them. So a new type must be given an unused value,
+/// taught to every runtime, and only written once all readers of a payload support it.
+/// Existing types never change their value or layout, nor do existing fields change their type.
+/// Adding a variant is a breaking change to this enum as well, for code matching on it.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum WireType {
     Varint = 0,
+    /// 8 little-endian bytes, like `f64`.
+    Fixed64 = 1,
     Sized = 2,
+    /// 4 little-endian bytes, like `f32`.
+    Fixed32 = 5,
 }
 
 impl WireType {
+    /// Every wire type, in the order of their values.
+    pub const ALL: [WireType; 4] = [
+        WireType::Varint,
+        WireType::Fixed64,
+        WireType::Sized,
+        WireType::Fixed32,
+    ];
+
     pub fn from_value(value: u32) -> io::Result<Self> {
         match value {
             0 => Ok(WireType::Varint),
+            1 => Ok(WireType::Fixed64),
             2 => Ok(WireType::Sized),
+            5 => Ok(WireType::Fixed32),
             _ => Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("illegal wire type {}", value),
@@ -34,6 +59,25 @@ impl WireType {
     pub fn tag(self, field_number: u32) -> io::Result<u32> {
         tag(field_number, self)
     }
+
+    /// Name of the variant, shared by runtimes in other languages.
+    pub fn name(self) -> &'static str {
+        match self {
+            WireType::Varint => "Varint",
+            WireType::Fixed64 => "Fixed64",
+            WireType::Sized => "Sized",
+            WireType::Fixed32 => "Fixed32",
+        }
+    }
+
+    /// Number of bytes taken by values of fixed wire types.
+    pub fn fixed_size(self) -> Option<u32> {
+        match self {
+            WireType::Fixed32 => Some(4),
+            WireType::Fixed64 => Some(8),
+            WireType::Varint | WireType::Sized => None,
+        }
+    }
 }
 
 pub trait HasWireType {
@@ -42,9 +86,24 @@ pub trait HasWireType {
     fn wire_type(&self) -> WireType {
         Self::WIRE_TYPE
     }
+}
 
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Tags of fields holding a value, implemented for every `HasWireType`.
+///
+/// It's sealed, as encoders and decoders rely on tags always matching `WIRE_TYPE`.
+pub trait WireKey: sealed::Sealed {
+    fn tag(&self, field_number: u32) -> io::Result<u32>;
+}
+
+impl<T: HasWireType + ?Sized> sealed::Sealed for T {}
+
+impl<T: HasWireType + ?Sized> WireKey for T {
     fn tag(&self, field_number: u32) -> io::Result<u32> {
-        tag(field_number, Self::WIRE_TYPE)
+        tag(field_number, T::WIRE_TYPE)
     }
 }
 
@@ -73,3 +132,31 @@ pub fn tag(field_number: u32, wire_type: WireType) -> io::Result<u32> {
     validate_field_number(field_number)?;
     Ok(field_number << WIRE_TYPE_BITS | wire_type.value() as u32)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_tag, tag, WireType, WIRE_TYPE_MASK};
+
+    #[test]
+    fn catalogue() {
+        for value in 0..=WIRE_TYPE_MASK {
+            if let Ok(wire_type) = WireType::from_value(value) {
+                assert_eq!(wire_type.value() as u32, value);
+                assert!(WireType::ALL.contains(&wire_type));
+            }
+        }
+
+        for wire_type in WireType::ALL {
+            assert_eq!(
+                WireType::from_value(wire_type.value() as u32).unwrap(),
+                wire_type
+            );
+            assert_eq!(
+                parse_tag(tag(9, wire_type).unwrap()).unwrap(),
+                (9, wire_type)
+            );
+        }
+
+        assert!(WireType::from_value(7).is_err());
+    }
+}